@@ -0,0 +1,87 @@
+-- Full-text index over posts. The trigram tokenizer matches CJK text without
+-- word segmentation; rowid is the post id so results join straight back to posts.
+CREATE VIRTUAL TABLE IF NOT EXISTS posts_fts USING fts5(
+    title,
+    content,
+    tags,
+    category,
+    tokenize = 'trigram'
+);
+
+INSERT INTO posts_fts (rowid, title, content, tags, category)
+SELECT p.id,
+       p.title,
+       p.content,
+       COALESCE((SELECT group_concat(t.name, ' ')
+                 FROM post_tags pt INNER JOIN tags t ON t.id = pt.tag_id
+                 WHERE pt.post_id = p.id), ''),
+       COALESCE((SELECT c.name FROM categories c WHERE c.id = p.category_id), '')
+FROM posts p;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_insert AFTER INSERT ON posts
+BEGIN
+    INSERT INTO posts_fts (rowid, title, content, tags, category)
+    VALUES (
+        new.id,
+        new.title,
+        new.content,
+        '',
+        COALESCE((SELECT name FROM categories WHERE id = new.category_id), '')
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_update
+AFTER UPDATE OF title, content, category_id ON posts
+BEGIN
+    UPDATE posts_fts
+    SET title = new.title,
+        content = new.content,
+        category = COALESCE((SELECT name FROM categories WHERE id = new.category_id), '')
+    WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_delete AFTER DELETE ON posts
+BEGIN
+    DELETE FROM posts_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_tag_link AFTER INSERT ON post_tags
+BEGIN
+    UPDATE posts_fts
+    SET tags = COALESCE((SELECT group_concat(t.name, ' ')
+                         FROM post_tags pt INNER JOIN tags t ON t.id = pt.tag_id
+                         WHERE pt.post_id = new.post_id), '')
+    WHERE rowid = new.post_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_tag_unlink AFTER DELETE ON post_tags
+BEGIN
+    UPDATE posts_fts
+    SET tags = COALESCE((SELECT group_concat(t.name, ' ')
+                         FROM post_tags pt INNER JOIN tags t ON t.id = pt.tag_id
+                         WHERE pt.post_id = old.post_id), '')
+    WHERE rowid = old.post_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_tag_rename AFTER UPDATE OF name ON tags
+BEGIN
+    UPDATE posts_fts
+    SET tags = COALESCE((SELECT group_concat(t.name, ' ')
+                         FROM post_tags pt INNER JOIN tags t ON t.id = pt.tag_id
+                         WHERE pt.post_id = posts_fts.rowid), '')
+    WHERE rowid IN (SELECT post_id FROM post_tags WHERE tag_id = new.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_category_rename AFTER UPDATE OF name ON categories
+BEGIN
+    UPDATE posts_fts
+    SET category = new.name
+    WHERE rowid IN (SELECT id FROM posts WHERE category_id = new.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_fts_after_category_delete AFTER DELETE ON categories
+BEGIN
+    UPDATE posts_fts
+    SET category = ''
+    WHERE rowid IN (SELECT id FROM posts WHERE category_id = old.id);
+END;
@@ -0,0 +1,27 @@
+-- Every save of a post is snapshotted here so edits can be diffed and restored.
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    post_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    cover_url TEXT,
+    category_id INTEGER,
+    tag_ids TEXT NOT NULL DEFAULT '[]', -- JSON array of tag ids
+    status INTEGER NOT NULL,
+    post_images TEXT,
+    pdf_url TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_revisions_post_id
+ON post_revisions(post_id, id DESC);
+
+-- Seed one revision per existing post so the current text is already recoverable.
+INSERT INTO post_revisions (post_id, title, content, cover_url, category_id, tag_ids, status, post_images, pdf_url, created_at)
+SELECT p.id, p.title, p.content, p.cover_url, p.category_id,
+       (SELECT json_group_array(tag_id)
+        FROM (SELECT pt.tag_id FROM post_tags pt WHERE pt.post_id = p.id ORDER BY pt.tag_id)),
+       p.status, p.post_images, p.pdf_url, COALESCE(p.updated_at, CURRENT_TIMESTAMP)
+FROM posts p
+WHERE p.status != 2;
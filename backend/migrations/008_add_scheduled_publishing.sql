@@ -0,0 +1,8 @@
+-- Scheduled posts (status = 4) go live at publish_at; the column is only set while a post is scheduled.
+ALTER TABLE posts ADD COLUMN publish_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_posts_status_publish_at
+ON posts(status, publish_at);
+
+-- Revisions keep the schedule so restoring a scheduled revision restores its publish time.
+ALTER TABLE post_revisions ADD COLUMN publish_at DATETIME;
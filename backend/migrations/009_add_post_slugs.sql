@@ -0,0 +1,13 @@
+-- Human-readable article URLs. Existing posts get their slugs generated at startup
+-- (transliteration needs Rust), so the column stays nullable.
+ALTER TABLE posts ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_posts_slug ON posts(slug);
+
+-- Slugs a post used before being renamed; old links 301 to the current slug.
+CREATE TABLE IF NOT EXISTS post_slug_redirects (
+    slug TEXT PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+-- Trash bin: deleting a post keeps it (status = 2) with the status to restore and when it was trashed.
+ALTER TABLE posts ADD COLUMN previous_status INTEGER;
+ALTER TABLE posts ADD COLUMN deleted_at DATETIME;
+
+-- Posts deleted before the trash existed start their retention period from their last update.
+UPDATE posts SET deleted_at = COALESCE(updated_at, CURRENT_TIMESTAMP) WHERE status = 2;
+
+CREATE INDEX IF NOT EXISTS idx_posts_status_deleted_at
+ON posts(status, deleted_at);
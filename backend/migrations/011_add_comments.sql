@@ -0,0 +1,45 @@
+-- 原生评论：文章评论（post_id 非空）和留言板（post_id 为空）共用一张表。
+CREATE TABLE IF NOT EXISTS comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    post_id INTEGER,
+    parent_id INTEGER,
+    author_name TEXT NOT NULL,
+    author_email TEXT,
+    author_url TEXT,
+    -- 头像：邮箱 SHA-256（Gravatar）；导入的 GitHub 用户直接用其头像地址
+    email_hash TEXT,
+    avatar_url TEXT,
+    content TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'rejected', 'spam')),
+    -- 命中的垃圾评论规则（JSON 数组），给审核时参考
+    spam_reasons TEXT NOT NULL DEFAULT '[]',
+    ip TEXT,
+    user_agent TEXT,
+    source TEXT NOT NULL DEFAULT 'native' CHECK (source IN ('native', 'giscus')),
+    -- 导入来源里的评论 id，重复导入时跳过
+    external_id TEXT UNIQUE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES comments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_comments_thread
+ON comments(post_id, status, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_comments_status_created
+ON comments(status, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_comments_parent
+ON comments(parent_id);
+
+-- 被封禁的评论者：按 IP 或邮箱哈希
+CREATE TABLE IF NOT EXISTS comment_bans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL CHECK (kind IN ('ip', 'email')),
+    value TEXT NOT NULL,
+    reason TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (kind, value)
+);
@@ -0,0 +1,29 @@
+-- Cookie-free page analytics. Views are aggregated per UTC day and scope
+-- ('site', 'section:<name>', 'post:<id>'); raw hits are never stored.
+CREATE TABLE IF NOT EXISTS analytics_daily (
+    day TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    post_id INTEGER,
+    views INTEGER NOT NULL DEFAULT 0,
+    visitors INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (day, scope),
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_analytics_daily_post ON analytics_daily(post_id, day);
+
+-- Visitor hashes seen today, used only to count uniques. The hash salt rotates
+-- daily, so rows from previous days are useless and get pruned on flush.
+CREATE TABLE IF NOT EXISTS analytics_visitors (
+    day TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    visitor TEXT NOT NULL,
+    PRIMARY KEY (day, scope, visitor)
+);
+
+CREATE TABLE IF NOT EXISTS analytics_referrers (
+    day TEXT NOT NULL,
+    host TEXT NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (day, host)
+);
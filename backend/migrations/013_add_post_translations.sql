@@ -0,0 +1,11 @@
+-- 文章译文：posts 里的正文视为默认语言（zh），其它语言的标题和正文放这里。
+CREATE TABLE IF NOT EXISTS post_translations (
+    post_id INTEGER NOT NULL,
+    locale TEXT NOT NULL CHECK (locale IN ('en')),
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (post_id, locale),
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,21 @@
+-- 系列文章：一个系列按 position 排好若干篇文章，一篇文章最多属于一个系列。
+CREATE TABLE IF NOT EXISTS series (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    slug TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS series_posts (
+    series_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL UNIQUE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (series_id, post_id),
+    FOREIGN KEY (series_id) REFERENCES series(id) ON DELETE CASCADE,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_series_posts_order
+ON series_posts(series_id, position);
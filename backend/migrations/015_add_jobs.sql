@@ -0,0 +1,31 @@
+-- 后台任务队列：耗时的工作不再占着请求，由进程内的 worker 从这里领取执行。
+-- 进程重启后 running 的任务回到 queued 重新执行。
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- 公开轮询用的随机令牌，避免按自增 id 枚举别人的任务
+    token TEXT NOT NULL UNIQUE,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'cancelled')),
+    -- 0 到 1
+    progress REAL NOT NULL DEFAULT 0,
+    progress_message TEXT,
+    result TEXT,
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    -- 最早可执行时间；失败重试时按退避推后
+    run_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    cancel_requested INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at DATETIME,
+    finished_at DATETIME,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_queue
+ON jobs(status, run_at);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_created
+ON jobs(created_at DESC);
@@ -0,0 +1,5 @@
+-- 同一种任务、同样参数的请求在一段时间内复用已有任务（排队中、执行中或刚成功的）。
+ALTER TABLE jobs ADD COLUMN dedup_key TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_dedup
+ON jobs(kind, dedup_key, id DESC);
@@ -0,0 +1,33 @@
+-- 书籍阅读进度和划线笔记。站点只有一位读者（站长），进度按文件保存一份。
+CREATE TABLE IF NOT EXISTS book_reading_progress (
+    book_file_id INTEGER PRIMARY KEY,
+    book_id INTEGER NOT NULL,
+    -- EPUB 是 epubcfi(...)，PDF 是 page:N
+    locator TEXT NOT NULL,
+    -- 0 到 100
+    percentage REAL NOT NULL DEFAULT 0 CHECK (percentage BETWEEN 0 AND 100),
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (book_id) REFERENCES books(id) ON DELETE CASCADE,
+    FOREIGN KEY (book_file_id) REFERENCES book_files(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS book_highlights (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    book_id INTEGER NOT NULL,
+    book_file_id INTEGER NOT NULL,
+    -- EPUB 是 CFI range，PDF 是 page:N
+    locator TEXT NOT NULL,
+    -- 划线所在章节，导出笔记时用来分组
+    chapter TEXT NOT NULL DEFAULT '',
+    text TEXT NOT NULL,
+    note TEXT NOT NULL DEFAULT '',
+    color TEXT NOT NULL DEFAULT 'yellow'
+        CHECK (color IN ('yellow', 'green', 'blue', 'pink', 'purple')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (book_id) REFERENCES books(id) ON DELETE CASCADE,
+    FOREIGN KEY (book_file_id) REFERENCES book_files(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_highlights_book
+ON book_highlights(book_id, book_file_id, id);
@@ -0,0 +1,2 @@
+-- 上传完成时从 PDF 页面树读出的页数；EPUB 没有固定页数，留空。
+ALTER TABLE book_files ADD COLUMN page_count INTEGER CHECK (page_count IS NULL OR page_count > 0);
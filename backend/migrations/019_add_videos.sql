@@ -0,0 +1,24 @@
+-- 上传到 R2 的视频。分片上传完成时写入，时长、分辨率和封面帧由后台任务用 ffprobe/ffmpeg 补上。
+CREATE TABLE IF NOT EXISTS videos (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    r2_key TEXT NOT NULL UNIQUE,
+    public_url TEXT NOT NULL UNIQUE,
+    content_type TEXT NOT NULL,
+    file_size INTEGER NOT NULL CHECK (file_size > 0),
+    -- 秒
+    duration REAL,
+    width INTEGER,
+    height INTEGER,
+    codec TEXT,
+    poster_url TEXT,
+    probe_status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (probe_status IN ('pending', 'ready', 'failed')),
+    probe_error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_videos_created
+ON videos(created_at DESC, id DESC);
@@ -14,6 +14,7 @@ pub mod constants {
     pub const DEFAULT_UPLOAD_DIR: &str = "uploads";
     pub const DEFAULT_BLOG_DATA_DIR: &str = "data";
     pub const DEFAULT_DEEPSEEK_API_URL: &str = "https://api.deepseek.com";
+    pub const DEFAULT_DEEPSEEK_MODEL: &str = "deepseek-chat";
 
     /// Bearer token前缀
     pub const BEARER_PREFIX: &str = "Bearer ";
@@ -56,6 +57,7 @@ pub struct Config {
     pub ai: AiConfig,
     pub cors: CorsConfig,
     pub storage: StorageConfig,
+    pub opds: OpdsConfig,
     pub s3: S3Config,
 }
 
@@ -97,6 +99,7 @@ pub struct ServerConfig {
 pub struct AiConfig {
     pub deepseek_api_key: String,
     pub deepseek_api_url: String,
+    pub deepseek_model: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,6 +107,16 @@ pub struct CorsConfig {
     pub origins: Vec<String>,
 }
 
+/// OPDS 书库目录里私密书籍的访问方式；都不设置时目录只对管理员会话显示私密书籍。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpdsConfig {
+    /// HTTP Basic 认证，用户名和密码都设置才启用
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// 填在阅读器 App 目录地址里的访问令牌（`?token=`）
+    pub token: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
     pub upload_dir: String,
@@ -179,6 +192,9 @@ impl Config {
         let deepseek_api_url = env::var("DEEPSEEK_API_URL")
             .unwrap_or_else(|_| constants::DEFAULT_DEEPSEEK_API_URL.to_string());
 
+        let deepseek_model = env::var("DEEPSEEK_MODEL")
+            .unwrap_or_else(|_| constants::DEFAULT_DEEPSEEK_MODEL.to_string());
+
         // CORS 配置：开发模式允许所有来源，生产模式需要明确配置
         let cors_origins = if environment.is_development() {
             // 开发模式：如果设置了 CORS_ORIGINS 就使用，否则允许所有（通过空列表表示）
@@ -258,6 +274,7 @@ impl Config {
             ai: AiConfig {
                 deepseek_api_key,
                 deepseek_api_url,
+                deepseek_model,
             },
             cors: CorsConfig {
                 origins: cors_origins,
@@ -268,10 +285,22 @@ impl Config {
                 max_file_size,
             },
             s3,
+            opds: OpdsConfig {
+                username: non_empty_env("OPDS_USERNAME"),
+                password: non_empty_env("OPDS_PASSWORD"),
+                token: non_empty_env("OPDS_TOKEN"),
+            },
         })
     }
 }
 
+fn non_empty_env(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
 fn load_google_auth_config() -> Option<GoogleAuthConfig> {
     let client_id = env::var("GOOGLE_CLIENT_ID").ok()?;
     let client_secret = env::var("GOOGLE_CLIENT_SECRET").ok()?;
@@ -2,10 +2,12 @@ pub mod category_repository;
 pub mod download_repository;
 pub mod music_repository;
 pub mod post_repository;
+pub mod post_revision_repository;
 pub mod tag_repository;
 
 pub use category_repository::CategoryRepository;
 pub use download_repository::DownloadRepository;
 pub use music_repository::MusicRepository;
 pub use post_repository::PostRepository;
+pub use post_revision_repository::PostRevisionRepository;
 pub use tag_repository::TagRepository;
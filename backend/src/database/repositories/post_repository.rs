@@ -1,9 +1,12 @@
 use crate::database::DatabasePool;
 use crate::models::{
     AdjacentPost, AdjacentPosts, CreatePostRequest, Post, PostListQuery, PostStatus,
-    PostWithDetails, UpdatePostRequest,
+    PostWithDetails, TrashedPost, UpdatePostRequest,
+};
+use crate::utils::{
+    error::Result,
+    text::{truncate_safely, SUMMARY_CHARS},
 };
-use crate::utils::{error::Result, text::truncate_safely};
 use sqlx::Row;
 use std::collections::HashMap;
 
@@ -24,9 +27,10 @@ impl PostRepository {
 
         let row = sqlx::query!(
             r#"
-            INSERT INTO posts (title, cover_url, content, category_id, status, post_images, pdf_url)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            RETURNING id, title, cover_url, content, category_id, status, post_images, pdf_url, created_at, updated_at
+            INSERT INTO posts (title, cover_url, content, category_id, status, post_images, pdf_url, publish_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id, slug, title, cover_url, content, category_id, status, post_images, pdf_url,
+                      publish_at as "publish_at?: chrono::DateTime<chrono::Utc>", created_at, updated_at
             "#,
             request.title,
             request.cover_url,
@@ -34,13 +38,15 @@ impl PostRepository {
             request.category_id,
             status_i32,
             post_images_json,
-            request.pdf_url
+            request.pdf_url,
+            request.publish_at
         )
         .fetch_one(&mut **tx)
         .await?;
 
         Ok(Post {
             id: row.id,
+            slug: row.slug,
             title: row.title,
             cover_url: row.cover_url,
             content: row.content,
@@ -49,6 +55,7 @@ impl PostRepository {
             status: row.status as i32,
             post_images: row.post_images,
             pdf_url: row.pdf_url,
+            publish_at: row.publish_at,
             tags: Vec::new(), // 单独创建时不获取标签
             created_at: row.created_at.unwrap().and_utc(),
             updated_at: row.updated_at.unwrap().and_utc(),
@@ -58,7 +65,8 @@ impl PostRepository {
     pub async fn get_by_id(pool: &DatabasePool, id: i64) -> Result<Option<Post>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, title, cover_url, content, category_id, status, post_images, pdf_url, created_at, updated_at
+            SELECT id, slug, title, cover_url, content, category_id, status, post_images, pdf_url,
+                   publish_at as "publish_at?: chrono::DateTime<chrono::Utc>", created_at, updated_at
             FROM posts
             WHERE id = ? AND status != ?
             "#,
@@ -70,6 +78,7 @@ impl PostRepository {
 
         Ok(row.map(|row| Post {
             id: row.id,
+            slug: row.slug,
             title: row.title,
             cover_url: row.cover_url,
             content: row.content,
@@ -78,6 +87,7 @@ impl PostRepository {
             status: row.status as i32,
             post_images: row.post_images,
             pdf_url: row.pdf_url,
+            publish_at: row.publish_at,
             tags: Vec::new(), // 单独查询时不获取标签
             created_at: row.created_at.unwrap().and_utc(),
             updated_at: row.updated_at.unwrap().and_utc(),
@@ -90,7 +100,8 @@ impl PostRepository {
     ) -> Result<Option<Post>> {
         let row = sqlx::query!(
             r#"
-            SELECT p.id, p.title, p.cover_url, p.content, p.category_id, p.status, p.post_images, p.pdf_url,
+            SELECT p.id, p.slug, p.title, p.cover_url, p.content, p.category_id, p.status, p.post_images, p.pdf_url,
+                   p.publish_at as "publish_at?: chrono::DateTime<chrono::Utc>",
                    p.created_at, p.updated_at, c.name as category_name
             FROM posts p
             LEFT JOIN categories c ON p.category_id = c.id
@@ -127,6 +138,7 @@ impl PostRepository {
 
             Ok(Some(Post {
                 id: row.id,
+                slug: row.slug,
                 title: row.title,
                 cover_url: row.cover_url,
                 content: row.content,
@@ -135,6 +147,7 @@ impl PostRepository {
                 status: row.status as i32,
                 post_images: row.post_images,
                 pdf_url: row.pdf_url,
+                publish_at: row.publish_at,
                 tags, // 包含完整的标签列表
                 created_at: row.created_at.unwrap().and_utc(),
                 updated_at: row.updated_at.unwrap().and_utc(),
@@ -161,7 +174,7 @@ impl PostRepository {
 
         let newer = sqlx::query_as::<_, AdjacentPost>(
             r#"
-            SELECT id, title
+            SELECT id, slug, title
             FROM posts
             WHERE status = ?
               AND (created_at > ? OR (created_at = ? AND id > ?))
@@ -178,7 +191,7 @@ impl PostRepository {
 
         let older = sqlx::query_as::<_, AdjacentPost>(
             r#"
-            SELECT id, title
+            SELECT id, slug, title
             FROM posts
             WHERE status = ?
               AND (created_at < ? OR (created_at = ? AND id < ?))
@@ -193,7 +206,11 @@ impl PostRepository {
         .fetch_optional(pool)
         .await?;
 
-        Ok(Some(AdjacentPosts { newer, older }))
+        Ok(Some(AdjacentPosts {
+            newer,
+            older,
+            series: None,
+        }))
     }
 
     pub async fn list_with_complete_info(
@@ -260,8 +277,8 @@ impl PostRepository {
 
         // Get posts with category names
         let posts_query = format!(
-            "SELECT p.id, p.title, p.cover_url, substr(p.content, 1, 400) AS content,
-                    p.category_id, p.status, p.post_images, p.pdf_url,
+            "SELECT p.id, p.slug, p.title, p.cover_url, substr(p.content, 1, 400) AS content,
+                    p.category_id, p.status, p.post_images, p.pdf_url, p.publish_at,
                     p.created_at, p.updated_at, c.name as category_name
              FROM posts p
              LEFT JOIN categories c ON p.category_id = c.id
@@ -308,10 +325,11 @@ impl PostRepository {
 
             // 获取完整内容并生成摘要（列表接口只返回摘要）
             let full_content: String = row.get("content");
-            let content_summary = truncate_safely(&full_content, 200);
+            let content_summary = truncate_safely(&full_content, SUMMARY_CHARS);
 
             let post = Post {
                 id: post_id,
+                slug: row.get("slug"),
                 title: row.get("title"),
                 cover_url: row.get("cover_url"),
                 content: content_summary,                // 列表接口返回摘要
@@ -320,6 +338,7 @@ impl PostRepository {
                 status: row.get::<i32, _>("status"),
                 post_images: row.get("post_images"),
                 pdf_url: row.get("pdf_url"),
+                publish_at: row.get("publish_at"),
                 tags, // 使用上面查询的标签列表
                 created_at: row
                     .get::<Option<chrono::NaiveDateTime>, _>("created_at")
@@ -396,6 +415,45 @@ impl PostRepository {
         Ok((posts, total))
     }
 
+    /// 订阅源用：已发布文章的完整正文（列表接口只有摘要），可按分类或标签过滤。
+    pub async fn list_published_for_feed(
+        pool: &DatabasePool,
+        category_id: Option<i64>,
+        tag_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Post>> {
+        let mut posts = sqlx::query_as::<_, Post>(
+            r#"
+            SELECT p.id, p.slug, p.title, p.cover_url, p.content, c.name AS category_name,
+                   p.category_id, p.status, p.post_images, p.pdf_url, p.publish_at,
+                   p.created_at, p.updated_at
+            FROM posts p
+            LEFT JOIN categories c ON p.category_id = c.id
+            WHERE p.status = ?
+              AND (? IS NULL OR p.category_id = ?)
+              AND (? IS NULL OR EXISTS (
+                    SELECT 1 FROM post_tags pt WHERE pt.post_id = p.id AND pt.tag_id = ?))
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(PostStatus::Published as i32)
+        .bind(category_id)
+        .bind(category_id)
+        .bind(tag_id)
+        .bind(tag_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        let post_ids = posts.iter().map(|post| post.id).collect::<Vec<_>>();
+        let mut tags_by_post = Self::load_tags_for_posts(pool, &post_ids).await?;
+        for post in &mut posts {
+            post.tags = tags_by_post.remove(&post.id).unwrap_or_default();
+        }
+        Ok(posts)
+    }
+
     pub async fn update(
         pool: &DatabasePool,
         id: i64,
@@ -407,18 +465,121 @@ impl PostRepository {
         Ok(post)
     }
 
+    /// 移入回收站：记住删除前的状态，恢复时还原。
     pub async fn delete(pool: &DatabasePool, id: i64) -> Result<bool> {
         let result = sqlx::query!(
-            "UPDATE posts SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            r#"
+            UPDATE posts
+            SET previous_status = status,
+                status = ?,
+                deleted_at = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND status != ?
+            "#,
             PostStatus::Deleted as i32,
-            id
+            id,
+            PostStatus::Deleted as i32
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_trashed(
+        pool: &DatabasePool,
+        page: u32,
+        page_size: u32,
+        retention_days: i64,
+    ) -> Result<(Vec<TrashedPost>, i64)> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM posts WHERE status = ?")
+            .bind(PostStatus::Deleted as i32)
+            .fetch_one(pool)
+            .await?;
+        let posts = sqlx::query_as::<_, TrashedPost>(
+            r#"
+            SELECT id, slug, title, cover_url, category_id, previous_status,
+                   COALESCE(deleted_at, updated_at) AS deleted_at,
+                   datetime(COALESCE(deleted_at, updated_at), ?) AS purge_at,
+                   created_at, updated_at
+            FROM posts
+            WHERE status = ?
+            ORDER BY deleted_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(format!("+{retention_days} days"))
+        .bind(PostStatus::Deleted as i32)
+        .bind(page_size as i64)
+        .bind((page.max(1) - 1) as i64 * page_size as i64)
+        .fetch_all(pool)
+        .await?;
+        Ok((posts, total))
+    }
+
+    /// 回收站中的文章（含正文），彻底删除前用来收集要清理的资源。
+    pub async fn get_trashed(pool: &DatabasePool, id: i64) -> Result<Option<Post>> {
+        sqlx::query_as::<_, Post>(
+            r#"
+            SELECT id, slug, title, cover_url, content, NULL AS category_name, category_id, status,
+                   post_images, pdf_url, publish_at, created_at, updated_at
+            FROM posts
+            WHERE id = ? AND status = ?
+            "#,
         )
+        .bind(id)
+        .bind(PostStatus::Deleted as i32)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 从回收站恢复；删除前的状态未知（旧数据）时恢复为草稿。
+    pub async fn restore_from_trash(pool: &DatabasePool, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE posts
+            SET status = COALESCE(previous_status, ?),
+                previous_status = NULL,
+                deleted_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND status = ?
+            "#,
+        )
+        .bind(PostStatus::Draft as i32)
+        .bind(id)
+        .bind(PostStatus::Deleted as i32)
         .execute(pool)
         .await?;
+        Ok(result.rows_affected() > 0)
+    }
 
+    /// 彻底删除回收站中的文章；标签关联、修订和旧 slug 由外键级联删除。
+    pub async fn purge(pool: &DatabasePool, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM posts WHERE id = ? AND status = ?")
+            .bind(id)
+            .bind(PostStatus::Deleted as i32)
+            .execute(pool)
+            .await?;
         Ok(result.rows_affected() > 0)
     }
 
+    pub async fn list_trashed_before(
+        pool: &DatabasePool,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<i64>> {
+        sqlx::query_scalar(
+            "SELECT id FROM posts
+             WHERE status = ? AND julianday(COALESCE(deleted_at, updated_at)) <= julianday(?)
+             ORDER BY id ASC",
+        )
+        .bind(PostStatus::Deleted as i32)
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await
+        .map_err(Into::into)
+    }
+
     pub async fn update_in_tx(
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
         id: i64,
@@ -426,7 +587,9 @@ impl PostRepository {
     ) -> Result<Option<Post>> {
         // Get current post data
         let current = sqlx::query!(
-            "SELECT title, cover_url, content, category_id, status, post_images, pdf_url FROM posts WHERE id = ?",
+            r#"SELECT title, cover_url, content, category_id, status, post_images, pdf_url,
+                      publish_at as "publish_at?: chrono::DateTime<chrono::Utc>"
+               FROM posts WHERE id = ?"#,
             id
         )
         .fetch_optional(&mut **tx)
@@ -449,6 +612,7 @@ impl PostRepository {
                 }
             };
             let pdf_url = request.pdf_url.resolve(current.pdf_url);
+            let publish_at = request.publish_at.resolve(current.publish_at);
 
             let row = sqlx::query!(
                 r#"
@@ -460,9 +624,11 @@ impl PostRepository {
                     status = ?,
                     post_images = ?,
                     pdf_url = ?,
+                    publish_at = ?,
                     updated_at = CURRENT_TIMESTAMP
                 WHERE id = ?
-                RETURNING id, title, cover_url, content, category_id, status, post_images, pdf_url, created_at, updated_at
+                RETURNING id, slug, title, cover_url, content, category_id, status, post_images, pdf_url,
+                          publish_at as "publish_at?: chrono::DateTime<chrono::Utc>", created_at, updated_at
                 "#,
                 title,
                 cover_url,
@@ -471,6 +637,7 @@ impl PostRepository {
                 status,
                 post_images,
                 pdf_url,
+                publish_at,
                 id
             )
             .fetch_one(&mut **tx)
@@ -478,6 +645,7 @@ impl PostRepository {
 
             Ok(Some(Post {
                 id: row.id.unwrap(),
+                slug: row.slug,
                 title: row.title,
                 cover_url: row.cover_url,
                 content: row.content,
@@ -486,6 +654,7 @@ impl PostRepository {
                 status: row.status as i32,
                 post_images: row.post_images,
                 pdf_url: row.pdf_url,
+                publish_at: row.publish_at,
                 tags: Vec::new(), // 事务中更新时不重新获取标签
                 created_at: row.created_at.unwrap().and_utc(),
                 updated_at: row.updated_at.unwrap().and_utc(),
@@ -494,4 +663,91 @@ impl PostRepository {
             Ok(None)
         }
     }
+
+    /// 把到期的定时文章改为已发布，返回发布的文章 id。
+    ///
+    /// 文章日期（`created_at`）改为计划发布时间，列表排序和 RSS 的 pubDate 因此与上线时间一致。
+    pub async fn publish_due_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<i64>> {
+        sqlx::query_scalar(
+            r#"
+            UPDATE posts
+            SET status = ?,
+                created_at = datetime(publish_at),
+                publish_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE status = ? AND julianday(publish_at) <= julianday(?)
+            RETURNING id
+            "#,
+        )
+        .bind(PostStatus::Published as i32)
+        .bind(PostStatus::Scheduled as i32)
+        .bind(now)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 当前占用该 slug 的文章 id（包括已删除的文章，唯一索引覆盖全部行）。
+    pub async fn slug_owner_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        slug: &str,
+    ) -> Result<Option<i64>> {
+        sqlx::query_scalar("SELECT id FROM posts WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// 设置文章 slug；旧 slug 记入跳转表，新 slug 若曾是跳转则由当前文章接管。
+    pub async fn set_slug_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        id: i64,
+        slug: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO post_slug_redirects (slug, post_id)
+             SELECT slug, id FROM posts WHERE id = ? AND slug IS NOT NULL AND slug != ?",
+        )
+        .bind(id)
+        .bind(slug)
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query("DELETE FROM post_slug_redirects WHERE slug = ?")
+            .bind(slug)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query("UPDATE posts SET slug = ? WHERE id = ?")
+            .bind(slug)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    /// 按 slug 查文章 id：先查当前 slug，再查改名前的旧 slug。
+    pub async fn find_id_by_slug(pool: &DatabasePool, slug: &str) -> Result<Option<i64>> {
+        sqlx::query_scalar(
+            "SELECT id, 0 AS priority FROM posts WHERE slug = ?
+             UNION ALL
+             SELECT post_id, 1 AS priority FROM post_slug_redirects WHERE slug = ?
+             ORDER BY priority
+             LIMIT 1",
+        )
+        .bind(slug)
+        .bind(slug)
+        .fetch_optional(pool)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn list_missing_slugs(pool: &DatabasePool) -> Result<Vec<(i64, String)>> {
+        sqlx::query_as("SELECT id, title FROM posts WHERE slug IS NULL ORDER BY id ASC")
+            .fetch_all(pool)
+            .await
+            .map_err(Into::into)
+    }
 }
@@ -0,0 +1,74 @@
+use crate::database::DatabasePool;
+use crate::models::PostRevision;
+use crate::utils::error::Result;
+
+const REVISION_COLUMNS: &str = "id, post_id, title, content, cover_url, category_id, tag_ids, status, post_images, pdf_url, publish_at, created_at";
+
+pub struct PostRevisionRepository;
+
+impl PostRevisionRepository {
+    /// 把文章当前状态（含标签）写成一条修订，须在保存文章的同一事务内调用。
+    pub async fn snapshot_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        post_id: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO post_revisions (post_id, title, content, cover_url, category_id, tag_ids, status, post_images, pdf_url, publish_at)
+            SELECT p.id, p.title, p.content, p.cover_url, p.category_id,
+                   (SELECT json_group_array(tag_id)
+                    FROM (SELECT pt.tag_id FROM post_tags pt WHERE pt.post_id = p.id ORDER BY pt.tag_id)),
+                   p.status, p.post_images, p.pdf_url, p.publish_at
+            FROM posts p
+            WHERE p.id = ?
+            "#,
+        )
+        .bind(post_id)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_for_post(pool: &DatabasePool, post_id: i64) -> Result<Vec<PostRevision>> {
+        let sql = format!(
+            "SELECT {REVISION_COLUMNS} FROM post_revisions WHERE post_id = ? ORDER BY id DESC"
+        );
+        sqlx::query_as::<_, PostRevision>(&sql)
+            .bind(post_id)
+            .fetch_all(pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get(
+        pool: &DatabasePool,
+        post_id: i64,
+        revision_id: i64,
+    ) -> Result<Option<PostRevision>> {
+        let sql =
+            format!("SELECT {REVISION_COLUMNS} FROM post_revisions WHERE id = ? AND post_id = ?");
+        sqlx::query_as::<_, PostRevision>(&sql)
+            .bind(revision_id)
+            .bind(post_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// 只保留最新的 `keep` 条修订，返回被删除的修订（调用方据此清理不再引用的资源）。
+    pub async fn prune(pool: &DatabasePool, post_id: i64, keep: i64) -> Result<Vec<PostRevision>> {
+        let sql = format!(
+            "DELETE FROM post_revisions
+             WHERE post_id = ?
+               AND id NOT IN (SELECT id FROM post_revisions WHERE post_id = ? ORDER BY id DESC LIMIT ?)
+             RETURNING {REVISION_COLUMNS}"
+        );
+        sqlx::query_as::<_, PostRevision>(&sql)
+            .bind(post_id)
+            .bind(post_id)
+            .bind(keep)
+            .fetch_all(pool)
+            .await
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,28 @@
+//! 编辑器的 AI 写作助手。结果以 SSE 推送：若干 `delta`（增量文本），
+//! 最后一个 `done`（完整结果）或 `error`。EventSource 不能 POST，前端用 fetch 读流。
+
+use crate::models::{AiAssistRequest, AiTask};
+use crate::services::Services;
+use crate::utils::error::Result;
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use std::convert::Infallible;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+
+pub async fn assist(
+    State(services): State<Services>,
+    Path(task): Path<AiTask>,
+    Json(request): Json<AiAssistRequest>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let events = services.ai.assist(task, request).await?;
+    let stream = ReceiverStream::new(events).map(|event| {
+        Ok(Event::default()
+            .event(event.name())
+            .json_data(&event)
+            .unwrap_or_else(|_| Event::default().event("error")))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
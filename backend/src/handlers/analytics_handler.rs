@@ -0,0 +1,100 @@
+//! 访问统计：前端在每次路由切换时用 `navigator.sendBeacon` 上报，不设 Cookie；
+//! 后台查看热门文章、来源和按天曲线。
+
+use crate::models::{
+    AnalyticsQuery, ApiResponse, DailyViews, PageViewBeacon, ReferrerStat, SectionStat,
+    TimeseriesQuery, TopPost,
+};
+use crate::services::analytics_service::{is_bot, referrer_host};
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
+use crate::utils::rate_limit::{client_ip, FixedWindowLimiter};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 每 IP 每分钟最多记 60 次浏览，超出的静默丢弃，防止刷量。
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const RATE_LIMIT: u32 = 60;
+
+static RATE: LazyLock<FixedWindowLimiter> =
+    LazyLock::new(|| FixedWindowLimiter::new(RATE_WINDOW, RATE_LIMIT));
+
+/// sendBeacon 发的是 `text/plain`（免 CORS 预检），所以按字符串收再解析 JSON。
+/// 无论是否记录都回 204，爬虫和开启 DNT / GPC 的访客不统计。
+pub async fn collect(
+    State(services): State<Services>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<StatusCode> {
+    let beacon: PageViewBeacon = serde_json::from_str(&body)
+        .map_err(|_| AppError::BadRequest("Invalid page view payload".to_string()))?;
+    let header_value = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let opted_out = header_value("dnt") == Some("1") || header_value("sec-gpc") == Some("1");
+    let user_agent = header_value(header::USER_AGENT.as_str()).unwrap_or_default();
+    if opted_out || is_bot(user_agent) {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    let ip = client_ip(&headers);
+    if !RATE.check(&ip) {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    let referrer = beacon
+        .referrer
+        .as_deref()
+        .and_then(|referrer| referrer_host(referrer, header_value(header::HOST.as_str())));
+    services
+        .analytics
+        .record(&beacon.path, referrer, &ip, user_agent, Utc::now());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn top_posts(
+    State(services): State<Services>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Json<ApiResponse<Vec<TopPost>>>> {
+    Ok(Json(ApiResponse::success(
+        services
+            .analytics
+            .top_posts(query.days, query.limit)
+            .await?,
+    )))
+}
+
+pub async fn top_referrers(
+    State(services): State<Services>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Json<ApiResponse<Vec<ReferrerStat>>>> {
+    Ok(Json(ApiResponse::success(
+        services
+            .analytics
+            .top_referrers(query.days, query.limit)
+            .await?,
+    )))
+}
+
+pub async fn sections(
+    State(services): State<Services>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Json<ApiResponse<Vec<SectionStat>>>> {
+    Ok(Json(ApiResponse::success(
+        services.analytics.sections(query.days).await?,
+    )))
+}
+
+pub async fn timeseries(
+    State(services): State<Services>,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<ApiResponse<Vec<DailyViews>>>> {
+    Ok(Json(ApiResponse::success(
+        services
+            .analytics
+            .timeseries(query.post_id, query.section.as_deref(), query.days)
+            .await?,
+    )))
+}
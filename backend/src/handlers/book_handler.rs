@@ -1,14 +1,27 @@
-use crate::models::{ApiResponse, CreateBookFile, CreateBookRequest, UpdateBookRequest};
+use crate::config::Config;
+use crate::middleware::auth::is_admin;
+use crate::models::{
+    ApiResponse, BookBadgeQuery, BookFile, BookFileLink, BookFileLinkRequest, BookHighlight,
+    BookImportQuery, BookImportReport, BookStats, CreateBookFile, CreateBookRequest,
+    CreateHighlightRequest, HighlightListQuery, ReadingProgress, ReadingReport,
+    SaveReadingProgressRequest, SignedFileQuery, UpdateBookRequest, UpdateHighlightRequest,
+};
+use crate::routes::AppState;
 use crate::services::Services;
 use crate::utils::error::AppError;
+use crate::utils::rate_limit::client_ip;
+use crate::utils::reading_history::{self, HistorySource};
+use crate::utils::signed_url;
 use crate::utils::{CompletedVideoPart, R2Storage, VideoMultipartSession};
 use axum::{
     body::Body,
-    extract::{Path, State},
-    http::{header, StatusCode},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::Response,
     Json,
 };
+use axum_extra::extract::Multipart;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -38,11 +51,17 @@ pub struct AbortBookUploadRequest {
 #[derive(Debug, Serialize)]
 pub struct CompleteBookUploadResponse {
     file: crate::models::BookFile,
+    book: crate::models::Book,
+    /// 提取元数据的后台任务令牌，结果是 `ExtractedBookMetadata`；格式不支持或文件太大时为空
+    metadata_job: Option<String>,
 }
 
+/// 读书记录导出一般只有几百 KB，几千本书也到不了这个大小。
+const MAX_IMPORT_FILE_SIZE: usize = 5 * 1024 * 1024;
+
 pub async fn list_public(
     State(services): State<Services>,
-) -> crate::utils::error::Result<Json<ApiResponse<Vec<crate::models::Book>>>> {
+) -> crate::utils::error::Result<Json<ApiResponse<Vec<crate::models::PublicBook>>>> {
     Ok(Json(ApiResponse::success(
         services.book.list_public().await?,
     )))
@@ -56,35 +75,175 @@ pub async fn list_admin(
     )))
 }
 
+/// 客户端发来的这些头原样转发给 R2，由 R2 处理 Range 和条件请求。
+const FORWARDED_REQUEST_HEADERS: [header::HeaderName; 4] = [
+    header::RANGE,
+    header::IF_RANGE,
+    header::IF_NONE_MATCH,
+    header::IF_MODIFIED_SINCE,
+];
+
+/// R2 响应里需要回传给客户端的头。
+const FORWARDED_RESPONSE_HEADERS: [header::HeaderName; 5] = [
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::ACCEPT_RANGES,
+    header::ETAG,
+    header::LAST_MODIFIED,
+];
+
+/// 在线阅读的链接要覆盖一次完整的阅读（PDF 阅读器会持续发 Range 请求），下载链接只需几分钟。
+const READ_LINK_TTL_SECONDS: u32 = 4 * 60 * 60;
+pub(super) const DOWNLOAD_LINK_TTL_SECONDS: u32 = 15 * 60;
+
+/// 签名链接的密钥由 JWT 密钥派生，改动链接方案不会牵扯到登录令牌。
+const LINK_KEY_PURPOSE: &str = "book-file-links";
+
+pub(super) fn link_key(config: &Config) -> Vec<u8> {
+    signed_url::derive_key(&config.jwt.secret, LINK_KEY_PURPOSE)
+}
+
+pub(super) fn link_resource(book_id: i64, file_id: i64, download: bool) -> String {
+    let disposition = if download { "attachment" } else { "inline" };
+    format!("book-file:{book_id}:{file_id}:{disposition}")
+}
+
+pub(super) fn content_disposition(file: &BookFile, download: bool) -> String {
+    if download {
+        format!(
+            "attachment; filename*=UTF-8''{}",
+            urlencoding::encode(&file.file_name)
+        )
+    } else {
+        "inline".to_string()
+    }
+}
+
+/// POST /api/books/:book_id/files/:file_id/link —— 按书籍的开关签发有时效的链接。
+/// 公开书籍返回后端签名地址；私密书籍只对管理员签发 R2 预签名地址，字节不经过后端。
+pub async fn create_file_link(
+    State(app_state): State<AppState>,
+    Path((book_id, file_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    Json(request): Json<BookFileLinkRequest>,
+) -> crate::utils::error::Result<Json<ApiResponse<BookFileLink>>> {
+    let (book, file) = app_state.services.book.get_file(book_id, file_id).await?;
+    let ttl = if request.download {
+        DOWNLOAD_LINK_TTL_SECONDS
+    } else {
+        READ_LINK_TTL_SECONDS
+    };
+    let expires_at = Utc::now() + Duration::seconds(ttl.into());
+
+    if !book.is_public {
+        if !is_admin(&headers, &app_state.config) {
+            return Err(AppError::NotFound("Book not found".to_string()));
+        }
+        let url = app_state.r2_storage.presign_download(
+            &file.r2_key,
+            ttl,
+            &content_disposition(&file, request.download),
+        )?;
+        return Ok(Json(ApiResponse::success(BookFileLink { url, expires_at })));
+    }
+    if request.download && !book.download_enabled {
+        return Err(AppError::Forbidden(
+            "Downloads are disabled for this book".to_string(),
+        ));
+    }
+
+    let expires = expires_at.timestamp();
+    let ip = request.bind_ip.then(|| client_ip(&headers));
+    let signature = signed_url::sign(
+        &link_key(&app_state.config),
+        &link_resource(book_id, file_id, request.download),
+        expires,
+        ip.as_deref(),
+    );
+    let url = format!(
+        "/api/books/{book_id}/files/{file_id}/content?expires={expires}&download={}&ip={}&sig={signature}",
+        request.download,
+        request.bind_ip
+    );
+    Ok(Json(ApiResponse::success(BookFileLink { url, expires_at })))
+}
+
+/// 只接受 `create_file_link` 签发的链接；签发后书籍被设为私密或关闭下载也会立即失效。
 pub async fn read_file(
-    State(services): State<Services>,
+    State(app_state): State<AppState>,
     Path((book_id, file_id)): Path<(i64, i64)>,
+    Query(query): Query<SignedFileQuery>,
+    headers: HeaderMap,
 ) -> crate::utils::error::Result<Response> {
-    let file = services.book.get_public_file(book_id, file_id).await?;
-    let upstream = reqwest::Client::new()
-        .get(&file.file_url)
+    let (Some(expires), Some(signature)) = (query.expires, query.sig.as_deref()) else {
+        return Err(AppError::Forbidden("A signed link is required".to_string()));
+    };
+    let ip = query.ip.then(|| client_ip(&headers));
+    signed_url::verify(
+        &link_key(&app_state.config),
+        &link_resource(book_id, file_id, query.download),
+        expires,
+        ip.as_deref(),
+        signature,
+        Utc::now().timestamp(),
+    )?;
+    let (book, file) = app_state.services.book.get_file(book_id, file_id).await?;
+    if !book.is_public {
+        return Err(AppError::NotFound("Book not found".to_string()));
+    }
+    if query.download && !book.download_enabled {
+        return Err(AppError::Forbidden(
+            "Downloads are disabled for this book".to_string(),
+        ));
+    }
+    let mut request = reqwest::Client::new().get(&file.file_url);
+    for name in FORWARDED_REQUEST_HEADERS {
+        if let Some(value) = headers.get(&name) {
+            request = request.header(name.as_str(), value.as_bytes());
+        }
+    }
+    let upstream = request
         .send()
         .await
         .map_err(|error| AppError::Internal(format!("Could not fetch book file: {error}")))?;
 
-    if !upstream.status().is_success() {
+    let status = StatusCode::from_u16(upstream.status().as_u16())
+        .map_err(|error| AppError::Internal(format!("Invalid upstream status: {error}")))?;
+    if !matches!(
+        status,
+        StatusCode::OK
+            | StatusCode::PARTIAL_CONTENT
+            | StatusCode::NOT_MODIFIED
+            | StatusCode::RANGE_NOT_SATISFIABLE
+    ) {
         return Err(AppError::Internal(format!(
             "Book storage returned {}",
             upstream.status()
         )));
     }
 
-    let content_length = upstream.content_length();
     let mut response = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, file.mime_type)
-        .header(header::CACHE_CONTROL, "public, max-age=3600")
-        .header(header::CONTENT_DISPOSITION, "inline");
-    if let Some(length) = content_length {
-        response = response.header(header::CONTENT_LENGTH, length);
+        .status(status)
+        .header(header::CACHE_CONTROL, "private, max-age=3600");
+    if matches!(status, StatusCode::OK | StatusCode::PARTIAL_CONTENT) {
+        response = response
+            .header(
+                header::CONTENT_DISPOSITION,
+                content_disposition(&file, query.download),
+            )
+            .header(header::CONTENT_TYPE, file.mime_type);
     }
+    for name in FORWARDED_RESPONSE_HEADERS {
+        if let Some(value) = upstream.headers().get(name.as_str()) {
+            response = response.header(name, value.as_bytes());
+        }
+    }
+    let body = match status {
+        StatusCode::OK | StatusCode::PARTIAL_CONTENT => Body::from_stream(upstream.bytes_stream()),
+        _ => Body::empty(),
+    };
     response
-        .body(Body::from_stream(upstream.bytes_stream()))
+        .body(body)
         .map_err(|error| AppError::Internal(format!("Could not stream book file: {error}")))
 }
 
@@ -115,6 +274,123 @@ pub async fn delete_book(
     Ok(Json(ApiResponse::success(())))
 }
 
+/// 从 Goodreads / 豆瓣导出的 CSV 批量导入读书记录，表单字段为 `file`。
+pub async fn import_history(
+    State(services): State<Services>,
+    Query(query): Query<BookImportQuery>,
+    mut multipart: Multipart,
+) -> crate::utils::error::Result<Json<ApiResponse<BookImportReport>>> {
+    let source = query
+        .source
+        .as_deref()
+        .filter(|source| !source.is_empty())
+        .map(HistorySource::parse)
+        .transpose()?;
+    let mut data = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|error| AppError::BadRequest(format!("Invalid multipart body: {error}")))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|error| AppError::BadRequest(format!("Invalid multipart body: {error}")))?
+        {
+            if bytes.len() + chunk.len() > MAX_IMPORT_FILE_SIZE {
+                return Err(AppError::BadRequest(format!(
+                    "Import files are limited to {} MB",
+                    MAX_IMPORT_FILE_SIZE / 1024 / 1024
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        data = Some(bytes);
+        break;
+    }
+    let data = data.ok_or_else(|| AppError::BadRequest("No file provided".to_string()))?;
+    let csv = String::from_utf8(data)
+        .map_err(|_| AppError::BadRequest("The CSV file must be UTF-8 encoded".to_string()))?;
+    let (source, records) = reading_history::parse(&csv, source)?;
+    Ok(Json(ApiResponse::success(
+        services
+            .book
+            .import_history(source, records, query.dry_run, query.is_public)
+            .await?,
+    )))
+}
+
+pub async fn stats_public(
+    State(services): State<Services>,
+) -> crate::utils::error::Result<Json<ApiResponse<BookStats>>> {
+    Ok(Json(ApiResponse::success(services.book.stats(true).await?)))
+}
+
+pub async fn stats_admin(
+    State(services): State<Services>,
+) -> crate::utils::error::Result<Json<ApiResponse<BookStats>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.stats(false).await?,
+    )))
+}
+
+pub async fn report_public(
+    State(services): State<Services>,
+    Path(year): Path<i32>,
+) -> crate::utils::error::Result<Json<ApiResponse<ReadingReport>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.report(true, year).await?,
+    )))
+}
+
+pub async fn report_admin(
+    State(services): State<Services>,
+    Path(year): Path<i32>,
+) -> crate::utils::error::Result<Json<ApiResponse<ReadingReport>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.report(false, year).await?,
+    )))
+}
+
+/// GET /api/books/stats/badge.svg?year= —— 读完了多少本书的徽章，只统计公开的书。
+pub async fn stats_badge(
+    State(services): State<Services>,
+    Query(query): Query<BookBadgeQuery>,
+) -> crate::utils::error::Result<Response> {
+    let stats = services.book.stats(true).await?;
+    let (label, message) = match query.year {
+        Some(year) => {
+            let finished = stats
+                .years
+                .iter()
+                .find(|count| count.year == year)
+                .map_or(0, |count| count.finished);
+            (format!("books read in {year}"), finished.to_string())
+        }
+        None => ("books read".to_string(), stats.finished.to_string()),
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")
+        .header(header::CACHE_CONTROL, "public, max-age=3600")
+        .body(Body::from(badge_svg(&label, &message)))
+        .map_err(|error| AppError::Internal(format!("Could not build badge: {error}")))
+}
+
+/// shields.io 风格的扁平徽章。文字宽度按 11px Verdana 粗略估算。
+fn badge_svg(label: &str, message: &str) -> String {
+    let text_width = |text: &str| text.chars().count() as u32 * 7 + 10;
+    let (label_width, message_width) = (text_width(label), text_width(message));
+    let width = label_width + message_width;
+    let (label_x, message_x) = (label_width * 5, label_width * 10 + message_width * 5);
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="#4c8c6b"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="110"><text x="{label_x}" y="140" transform="scale(.1)">{label}</text><text x="{message_x}" y="140" transform="scale(.1)">{message}</text></g></svg>"##
+    )
+}
+
 pub async fn begin_file_upload(
     State(storage): State<Arc<R2Storage>>,
     State(services): State<Services>,
@@ -165,8 +441,22 @@ pub async fn complete_file_upload(
             mime_type: request.content_type,
         })
         .await?;
+
+    // 元数据在后台任务里提取，前端用令牌轮询 /api/jobs/:token 拿结果
+    let metadata_job = match services.book_metadata.enqueue(&file).await {
+        Ok(job) => job.map(|job| job.token),
+        Err(error) => {
+            tracing::warn!(
+                "Failed to queue metadata extraction for {}: {error}",
+                file.r2_key
+            );
+            None
+        }
+    };
     Ok(Json(ApiResponse::success(CompleteBookUploadResponse {
+        book: services.book.get(book_id, false).await?,
         file,
+        metadata_job,
     })))
 }
 
@@ -187,3 +477,93 @@ pub async fn delete_file(
     services.book.delete_file(file_id).await?;
     Ok(Json(ApiResponse::success(())))
 }
+
+pub async fn list_progress(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> crate::utils::error::Result<Json<ApiResponse<Vec<ReadingProgress>>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.list_progress(id).await?,
+    )))
+}
+
+/// PUT /api/admin/books/:id/files/:file_id/progress —— 阅读器翻页时调用。
+pub async fn save_progress(
+    State(services): State<Services>,
+    Path((id, file_id)): Path<(i64, i64)>,
+    Json(request): Json<SaveReadingProgressRequest>,
+) -> crate::utils::error::Result<Json<ApiResponse<ReadingProgress>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.save_progress(id, file_id, request).await?,
+    )))
+}
+
+pub async fn list_highlights(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Query(query): Query<HighlightListQuery>,
+) -> crate::utils::error::Result<Json<ApiResponse<Vec<BookHighlight>>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.list_highlights(id, query.file_id).await?,
+    )))
+}
+
+pub async fn create_highlight(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Json(request): Json<CreateHighlightRequest>,
+) -> crate::utils::error::Result<Json<ApiResponse<BookHighlight>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.create_highlight(id, request).await?,
+    )))
+}
+
+pub async fn update_highlight(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateHighlightRequest>,
+) -> crate::utils::error::Result<Json<ApiResponse<BookHighlight>>> {
+    Ok(Json(ApiResponse::success(
+        services.book.update_highlight(id, request).await?,
+    )))
+}
+
+pub async fn delete_highlight(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> crate::utils::error::Result<Json<ApiResponse<()>>> {
+    services.book.delete_highlight(id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// GET /api/admin/books/:id/notes.md —— 划线和批注导出成 Markdown。
+pub async fn export_notes(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> crate::utils::error::Result<Response> {
+    let (book, markdown) = services.book.export_notes(id).await?;
+    let disposition = format!(
+        "attachment; filename=\"book-{id}-notes.md\"; filename*=UTF-8''{}.md",
+        urlencoding::encode(&book.record.title)
+    );
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/markdown; charset=utf-8")
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(Body::from(markdown))
+        .map_err(|error| AppError::Internal(format!("Could not build notes response: {error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_widths_follow_text_length() {
+        let svg = badge_svg("books read in 2024", "12");
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        // 18 个字符的标签 136px，2 个字符的数字 24px
+        assert!(svg.contains(r#"width="160""#));
+        assert!(svg.contains(r#"<rect x="136" width="24""#));
+        assert!(svg.contains(">12</text>"));
+    }
+}
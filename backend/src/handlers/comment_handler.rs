@@ -0,0 +1,144 @@
+//! 原生评论：文章评论和留言板（替代 giscus，评论者不需要 GitHub 账号）。
+//! 新评论先过垃圾评论规则，再进审核队列；同一邮箱有过通过的评论则直接展示。
+
+use crate::models::{
+    ApiListResponse, ApiResponse, BanCommenterRequest, Comment, CommentListQuery, CommentSubmitted,
+    CreateCommentRequest, GiscusExport, GiscusImportSummary, PublicComment,
+};
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
+use crate::utils::rate_limit::{client_ip, FixedWindowLimiter};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    Json,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 每 IP 每 10 分钟最多 5 条评论，正常讨论足够，刷屏会被卡住。
+const RATE_WINDOW: Duration = Duration::from_secs(600);
+const RATE_LIMIT: u32 = 5;
+
+static RATE: LazyLock<FixedWindowLimiter> =
+    LazyLock::new(|| FixedWindowLimiter::new(RATE_WINDOW, RATE_LIMIT));
+
+pub async fn list_post_comments(
+    State(services): State<Services>,
+    Path(post_id): Path<i64>,
+) -> Result<Json<ApiResponse<Vec<PublicComment>>>> {
+    Ok(Json(ApiResponse::success(
+        services.comment.list_thread(Some(post_id)).await?,
+    )))
+}
+
+pub async fn list_guestbook(
+    State(services): State<Services>,
+) -> Result<Json<ApiResponse<Vec<PublicComment>>>> {
+    Ok(Json(ApiResponse::success(
+        services.comment.list_thread(None).await?,
+    )))
+}
+
+pub async fn create_post_comment(
+    State(services): State<Services>,
+    Path(post_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<Json<ApiResponse<CommentSubmitted>>> {
+    submit(&services, Some(post_id), &headers, request).await
+}
+
+pub async fn create_guestbook_comment(
+    State(services): State<Services>,
+    headers: HeaderMap,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<Json<ApiResponse<CommentSubmitted>>> {
+    submit(&services, None, &headers, request).await
+}
+
+async fn submit(
+    services: &Services,
+    post_id: Option<i64>,
+    headers: &HeaderMap,
+    request: CreateCommentRequest,
+) -> Result<Json<ApiResponse<CommentSubmitted>>> {
+    let ip = client_ip(headers);
+    if !RATE.check(&ip) {
+        return Err(AppError::TooManyRequests(
+            "Too many comments, please try again later".to_string(),
+        ));
+    }
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(300).collect());
+    Ok(Json(ApiResponse::success(
+        services
+            .comment
+            .submit(post_id, request, &ip, user_agent)
+            .await?,
+    )))
+}
+
+pub async fn list_admin(
+    State(services): State<Services>,
+    Query(query): Query<CommentListQuery>,
+) -> Result<Json<ApiListResponse<Comment>>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+    let (comments, total) = services
+        .comment
+        .list_admin(query.status.as_deref(), page, page_size)
+        .await?;
+    Ok(Json(ApiListResponse::success(
+        comments, total, page, page_size,
+    )))
+}
+
+pub async fn approve(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Comment>>> {
+    Ok(Json(ApiResponse::success(
+        services.comment.approve(id).await?,
+    )))
+}
+
+pub async fn reject(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Comment>>> {
+    Ok(Json(ApiResponse::success(
+        services.comment.reject(id).await?,
+    )))
+}
+
+pub async fn ban(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    request: Option<Json<BanCommenterRequest>>,
+) -> Result<Json<ApiResponse<Comment>>> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    Ok(Json(ApiResponse::success(
+        services.comment.ban(id, request).await?,
+    )))
+}
+
+pub async fn delete_comment(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<()>>> {
+    services.comment.delete(id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// 导入 giscus / GitHub Discussions 的 JSON 导出，可重复执行。
+pub async fn import_giscus(
+    State(services): State<Services>,
+    Json(export): Json<GiscusExport>,
+) -> Result<Json<ApiResponse<GiscusImportSummary>>> {
+    Ok(Json(ApiResponse::success(
+        services.comment.import_giscus(export).await?,
+    )))
+}
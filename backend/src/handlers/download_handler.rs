@@ -1,18 +1,24 @@
 use crate::models::{ApiListResponse, ApiResponse, CreateDownloadRequest, DownloadListQuery};
 use crate::routes::AppState;
 use crate::services::Services;
+use crate::utils::http_range::{self, RangeOutcome, Validators};
 use crate::utils::DOCUMENT_TYPES;
 use axum::{
     body::Body,
     extract::{Path, Query, State},
     http::{
-        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
-        StatusCode,
+        header::{
+            ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+            LAST_MODIFIED,
+        },
+        HeaderMap, StatusCode,
     },
     response::{Json, Response},
 };
 use axum_extra::extract::Multipart;
+use std::io::SeekFrom;
 use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
 
 pub async fn upload_file(
@@ -69,9 +75,11 @@ pub async fn upload_file(
     Ok(Json(ApiResponse::bad_request("No file provided")))
 }
 
+/// 支持 Range / If-Range / If-None-Match / If-Modified-Since，方便断点续传。
 pub async fn download_file(
     State(app_state): State<AppState>,
     Path(id): Path<i64>,
+    headers: HeaderMap,
 ) -> Result<Response<Body>, StatusCode> {
     match app_state.services.download.get_download(id).await {
         Ok(Some(download)) => {
@@ -84,28 +92,63 @@ pub async fn download_file(
                 return Err(StatusCode::NOT_FOUND);
             }
 
-            match File::open(&file_path).await {
-                Ok(file) => {
-                    let stream = ReaderStream::new(file);
-                    let body = Body::from_stream(stream);
+            let (mut file, metadata) = match File::open(&file_path).await {
+                Ok(file) => match file.metadata().await {
+                    Ok(metadata) => (file, metadata),
+                    Err(e) => {
+                        tracing::error!("Failed to stat file: {}", e);
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                },
+                Err(e) => {
+                    tracing::error!("Failed to open file: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+
+            let size = metadata.len();
+            let validators = Validators::from_metadata(&metadata);
+            let outcome = http_range::evaluate(&headers, &validators, size);
+            let mut response = Response::builder()
+                .header(ACCEPT_RANGES, "bytes")
+                .header(ETAG, &validators.etag);
+            if let Some(last_modified) = validators.last_modified_header() {
+                response = response.header(LAST_MODIFIED, last_modified);
+            }
+            if let Some(content_range) = http_range::content_range(outcome, size) {
+                response = response.header(CONTENT_RANGE, content_range);
+            }
 
-                    let response = Response::builder()
-                        .status(StatusCode::OK)
+            let response = match outcome {
+                RangeOutcome::NotModified => response
+                    .status(StatusCode::NOT_MODIFIED)
+                    .body(Body::empty()),
+                RangeOutcome::Unsatisfiable => response
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .body(Body::empty()),
+                RangeOutcome::Full | RangeOutcome::Partial { .. } => {
+                    let (status, length) = match outcome {
+                        RangeOutcome::Partial { start, end } => {
+                            if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+                                tracing::error!("Failed to seek file: {}", e);
+                                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                            }
+                            (StatusCode::PARTIAL_CONTENT, end - start + 1)
+                        }
+                        _ => (StatusCode::OK, size),
+                    };
+                    response
+                        .status(status)
                         .header(CONTENT_TYPE, download.file_type)
+                        .header(CONTENT_LENGTH, length)
                         .header(
                             CONTENT_DISPOSITION,
                             format!("attachment; filename=\"{}\"", download.file_name),
                         )
-                        .body(body)
-                        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-                    Ok(response)
-                }
-                Err(e) => {
-                    tracing::error!("Failed to open file: {}", e);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(Body::from_stream(ReaderStream::new(file.take(length))))
                 }
-            }
+            };
+            response.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
         }
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
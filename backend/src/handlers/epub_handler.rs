@@ -0,0 +1,154 @@
+//! 按章节读 EPUB：服务端解开 R2 上的文件，阅读器先拿书脊和目录，再逐章加载。
+//!
+//! GET .../epub 返回书脊和目录；.../epub/chapters/*path 返回清洗过的章节正文；
+//! .../epub/resources/*path 返回图片、样式表和字体。公开书籍要带 `create_file_link` 签发的阅读链接
+//! 参数（expires/ip/sig），返回的章节和资源地址都带着同一组参数；私密书籍只对管理员开放。
+//! 解开的文件缓存在内存里，同一本书的后续请求不再访问 R2。
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::Response,
+    Json,
+};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use super::book_handler::{link_key, link_resource};
+use crate::middleware::auth::is_admin;
+use crate::models::{ApiResponse, SignedFileQuery};
+use crate::routes::AppState;
+use crate::utils::epub_reader::{
+    EpubArchive, EpubCache, EpubChapterContent, EpubContents, EpubLinks,
+};
+use crate::utils::error::{AppError, Result};
+use crate::utils::rate_limit::client_ip;
+use crate::utils::signed_url;
+use chrono::Utc;
+
+/// 超过这个大小的 EPUB 让阅读器整本下载，不在服务端解开。
+const MAX_EPUB_SIZE: i64 = 100 * 1024 * 1024;
+const CACHE_CAPACITY: usize = 256 * 1024 * 1024;
+const CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+/// 章节和资源跟着文件记录走，重新上传会换新的文件 id。
+const CACHE_CONTROL: &str = "private, max-age=3600";
+
+static CACHE: LazyLock<EpubCache> = LazyLock::new(|| EpubCache::new(CACHE_CAPACITY, CACHE_TTL));
+
+fn base_path(book_id: i64, file_id: i64) -> String {
+    format!("/api/books/{book_id}/files/{file_id}/epub")
+}
+
+/// 校验访问权限并打开 EPUB，同时返回要加在章节、资源链接后面的签名参数。
+async fn open(
+    app_state: &AppState,
+    headers: &HeaderMap,
+    query: &SignedFileQuery,
+    book_id: i64,
+    file_id: i64,
+) -> Result<(Arc<EpubArchive>, String)> {
+    let (book, file) = app_state.services.book.get_file(book_id, file_id).await?;
+    let link_query = match (query.expires, query.sig.as_deref()) {
+        (Some(expires), Some(signature)) if book.is_public => {
+            let ip = query.ip.then(|| client_ip(headers));
+            signed_url::verify(
+                &link_key(&app_state.config),
+                &link_resource(book_id, file_id, false),
+                expires,
+                ip.as_deref(),
+                signature,
+                Utc::now().timestamp(),
+            )?;
+            format!("expires={expires}&ip={}&sig={signature}", query.ip)
+        }
+        // 私密书籍不签发后端链接，管理员凭登录状态读
+        _ if is_admin(headers, &app_state.config) => String::new(),
+        _ if book.is_public => {
+            return Err(AppError::Forbidden("A signed link is required".to_string()))
+        }
+        _ => return Err(AppError::NotFound("Book not found".to_string())),
+    };
+    if file.format != "epub" {
+        return Err(AppError::BadRequest(
+            "Only EPUB files can be read by chapter".to_string(),
+        ));
+    }
+    let key = format!("{}:{}", file.id, file.r2_key);
+    let archive = CACHE
+        .get_or_load(&key, || async {
+            if file.file_size > MAX_EPUB_SIZE {
+                return Err(AppError::BadRequest(
+                    "This EPUB is too large to be read by chapter".to_string(),
+                ));
+            }
+            let data = app_state
+                .r2_storage
+                .get_object(&file.r2_key, MAX_EPUB_SIZE as u64)
+                .await?;
+            tokio::task::spawn_blocking(move || EpubArchive::open(data))
+                .await
+                .map_err(|error| AppError::Internal(format!("EPUB task failed: {error}")))?
+        })
+        .await?;
+    Ok((archive, link_query))
+}
+
+/// GET /api/books/:book_id/files/:file_id/epub
+pub async fn contents(
+    State(app_state): State<AppState>,
+    Path((book_id, file_id)): Path<(i64, i64)>,
+    Query(query): Query<SignedFileQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<EpubContents>>> {
+    let (archive, link_query) = open(&app_state, &headers, &query, book_id, file_id).await?;
+    Ok(Json(ApiResponse::success(archive.contents(EpubLinks {
+        base: &base_path(book_id, file_id),
+        query: &link_query,
+    }))))
+}
+
+/// GET /api/books/:book_id/files/:file_id/epub/chapters/*path
+pub async fn chapter(
+    State(app_state): State<AppState>,
+    Path((book_id, file_id, path)): Path<(i64, i64, String)>,
+    Query(query): Query<SignedFileQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<EpubChapterContent>>> {
+    let (archive, link_query) = open(&app_state, &headers, &query, book_id, file_id).await?;
+    let base = base_path(book_id, file_id);
+    let chapter = tokio::task::spawn_blocking(move || {
+        let links = EpubLinks {
+            base: &base,
+            query: &link_query,
+        };
+        archive.chapter(&path, links)
+    })
+    .await
+    .map_err(|error| AppError::Internal(format!("EPUB task failed: {error}")))??;
+    Ok(Json(ApiResponse::success(chapter)))
+}
+
+/// GET /api/books/:book_id/files/:file_id/epub/resources/*path
+pub async fn resource(
+    State(app_state): State<AppState>,
+    Path((book_id, file_id, path)): Path<(i64, i64, String)>,
+    Query(query): Query<SignedFileQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let (archive, _) = open(&app_state, &headers, &query, book_id, file_id).await?;
+    let (data, media_type) = tokio::task::spawn_blocking(move || archive.resource(&path))
+        .await
+        .map_err(|error| AppError::Internal(format!("EPUB task failed: {error}")))??;
+    Response::builder()
+        .header(header::CONTENT_TYPE, media_type)
+        .header(header::CACHE_CONTROL, CACHE_CONTROL)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        // SVG 图片里可能有脚本，直接打开时也不让它执行
+        .header(
+            header::CONTENT_SECURITY_POLICY,
+            "sandbox; default-src 'none'",
+        )
+        .body(Body::from(data))
+        .map_err(|error| AppError::Internal(format!("Could not build EPUB resource: {error}")))
+}
@@ -0,0 +1,68 @@
+//! 文章导出端点：前台只能导出已发布的文章，管理端可按任意组合批量导出。
+
+use crate::models::EpubExportRequest;
+use crate::services::export_service::EpubFile;
+use crate::services::Services;
+use crate::utils::error::Result;
+use axum::{
+    extract::{Json, Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+fn epub_response(file: EpubFile) -> Response {
+    // filename 给老客户端兜底，filename* 带上中文书名
+    let disposition = format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}.epub",
+        file.file_name,
+        urlencoding::encode(&file.title)
+    );
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                crate::utils::epub::EPUB_MEDIA_TYPE.to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        file.data,
+    )
+        .into_response()
+}
+
+/// GET /api/post/:id/export.epub
+pub async fn post_epub(State(services): State<Services>, Path(id): Path<i64>) -> Result<Response> {
+    let request = EpubExportRequest {
+        post_ids: vec![id],
+        ..Default::default()
+    };
+    Ok(epub_response(
+        services.export.export_epub(request, true).await?,
+    ))
+}
+
+/// GET /api/series/:key/export.epub —— key 可以是 id 或 slug。
+pub async fn series_epub(
+    State(services): State<Services>,
+    Path(key): Path<String>,
+) -> Result<Response> {
+    let detail = services.series.get(&key, true).await?;
+    let request = EpubExportRequest {
+        series_id: Some(detail.series.id),
+        ..Default::default()
+    };
+    Ok(epub_response(
+        services.export.export_epub(request, true).await?,
+    ))
+}
+
+/// POST /api/admin/export/epub
+pub async fn bulk_epub(
+    State(services): State<Services>,
+    Json(request): Json<EpubExportRequest>,
+) -> Result<Response> {
+    let published_only = !request.include_unpublished;
+    Ok(epub_response(
+        services.export.export_epub(request, published_only).await?,
+    ))
+}
@@ -0,0 +1,627 @@
+//! 订阅源:RSS 2.0、Atom 1.0、JSON Feed 1.1。
+//!
+//! 全站:/rss.xml、/atom.xml、/feed.json;按分类/标签:/category/:id/rss.xml、
+//! /tag/:id/atom.xml 等。由 seo_handler::spa_fallback 分发(nginx 已把这些不存在的
+//! 文件反代到后端)。条目带渲染后的完整正文 HTML、封面 enclosure 和更新时间;
+//! 响应带 ETag/Last-Modified,阅读器的条件请求命中时返回 304。
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::events::{BytesCData, BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use sha2::{Digest, Sha256};
+use std::io;
+
+use super::seo_handler::{abs_url, article_path, excerpt, DEFAULT_DESC, SITE, SITE_NAME};
+use crate::models::post::Post;
+use crate::routes::AppState;
+use crate::services::RenderService;
+
+const FEED_LIMIT: i64 = 50;
+const SUMMARY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    fn from_file(name: &str) -> Option<Self> {
+        match name {
+            "rss.xml" => Some(Self::Rss),
+            "atom.xml" => Some(Self::Atom),
+            "feed.json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn file(self) -> &'static str {
+        match self {
+            Self::Rss => "rss.xml",
+            Self::Atom => "atom.xml",
+            Self::Json => "feed.json",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Rss => "application/rss+xml; charset=utf-8",
+            Self::Atom => "application/atom+xml; charset=utf-8",
+            Self::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedScope {
+    Site,
+    Category(i64),
+    Tag(i64),
+}
+
+/// 识别订阅源路径;不是订阅源返回 None。
+fn parse_feed_path(path: &str) -> Option<(FeedScope, FeedFormat)> {
+    let path = path.strip_prefix('/')?;
+    let segments: Vec<&str> = path.split('/').collect();
+    match segments.as_slice() {
+        [file] => Some((FeedScope::Site, FeedFormat::from_file(file)?)),
+        [kind, id, file] => {
+            let format = FeedFormat::from_file(file)?;
+            let id = id.parse::<i64>().ok()?;
+            match *kind {
+                "category" => Some((FeedScope::Category(id), format)),
+                "tag" => Some((FeedScope::Tag(id), format)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 查出来、还没渲染的订阅源;条件请求先拿它算 ETag,命中 304 就不必渲染正文。
+struct FeedSource {
+    title: String,
+    home_url: String,
+    feed_url: String,
+    posts: Vec<Post>,
+}
+
+impl FeedSource {
+    fn updated(&self) -> Option<DateTime<Utc>> {
+        self.posts.iter().map(|post| post.updated_at).max()
+    }
+
+    /// 由订阅源地址、标题和各条目的 id、更新时间、分类标签算出;正文变了 updated_at 一定会变。
+    fn etag(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.feed_url.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.title.as_bytes());
+        for post in &self.posts {
+            hasher.update([0]);
+            hasher.update(post.id.to_be_bytes());
+            hasher.update(post.updated_at.timestamp_micros().to_be_bytes());
+            for name in post
+                .category_name
+                .iter()
+                .chain(post.tags.iter().map(|tag| &tag.name))
+            {
+                hasher.update(name.as_bytes());
+                hasher.update([1]);
+            }
+        }
+        format!("\"{}\"", &hex::encode(hasher.finalize())[..32])
+    }
+
+    fn into_feed(self, render: &RenderService) -> Feed {
+        Feed {
+            updated: self.updated(),
+            entries: self
+                .posts
+                .iter()
+                .map(|post| FeedEntry::from_post(post, render))
+                .collect(),
+            title: self.title,
+            description: DEFAULT_DESC.to_string(),
+            home_url: self.home_url,
+            feed_url: self.feed_url,
+        }
+    }
+}
+
+struct Feed {
+    title: String,
+    description: String,
+    home_url: String,
+    feed_url: String,
+    /// 条目里最新的 updated_at;空订阅源为 None。
+    updated: Option<DateTime<Utc>>,
+    entries: Vec<FeedEntry>,
+}
+
+struct FeedEntry {
+    /// 基于数字 id 的稳定标识,改 slug 不会让阅读器重复推送。
+    id: String,
+    url: String,
+    title: String,
+    summary: String,
+    content_html: String,
+    image: Option<String>,
+    categories: Vec<String>,
+    published: DateTime<Utc>,
+    updated: DateTime<Utc>,
+}
+
+impl FeedEntry {
+    fn from_post(post: &Post, render: &RenderService) -> Self {
+        let mut categories: Vec<String> = post.category_name.iter().cloned().collect();
+        categories.extend(post.tags.iter().map(|tag| tag.name.clone()));
+        Self {
+            id: format!("{SITE}/article/{}", post.id),
+            url: format!("{SITE}{}", article_path(post)),
+            title: post.title.clone(),
+            summary: excerpt(&post.content, SUMMARY_CHARS),
+            content_html: render.render_post_absolute(post, SITE).html.clone(),
+            image: post.cover_url.as_deref().map(abs_url),
+            categories,
+            published: post.created_at,
+            updated: post.updated_at,
+        }
+    }
+}
+
+pub(super) fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+pub(super) fn image_mime(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+pub(super) fn text_element<W: io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    text: &str,
+) -> io::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(text))?;
+    Ok(())
+}
+
+fn write_rss<W: io::Write>(writer: &mut Writer<W>, feed: &Feed) -> io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("rss")
+        .with_attributes([
+            ("version", "2.0"),
+            ("xmlns:atom", "http://www.w3.org/2005/Atom"),
+            ("xmlns:content", "http://purl.org/rss/1.0/modules/content/"),
+        ])
+        .write_inner_content(|w| {
+            w.create_element("channel").write_inner_content(|w| {
+                text_element(w, "title", &feed.title)?;
+                text_element(w, "link", &feed.home_url)?;
+                text_element(w, "description", &feed.description)?;
+                text_element(w, "language", "zh-CN")?;
+                if let Some(updated) = feed.updated {
+                    text_element(w, "lastBuildDate", &updated.to_rfc2822())?;
+                }
+                w.create_element("atom:link")
+                    .with_attributes([
+                        ("href", feed.feed_url.as_str()),
+                        ("rel", "self"),
+                        ("type", "application/rss+xml"),
+                    ])
+                    .write_empty()?;
+                for entry in &feed.entries {
+                    w.create_element("item").write_inner_content(|w| {
+                        text_element(w, "title", &entry.title)?;
+                        text_element(w, "link", &entry.url)?;
+                        w.create_element("guid")
+                            .with_attribute(("isPermaLink", "true"))
+                            .write_text_content(BytesText::new(&entry.id))?;
+                        text_element(w, "pubDate", &entry.published.to_rfc2822())?;
+                        for category in &entry.categories {
+                            text_element(w, "category", category)?;
+                        }
+                        text_element(w, "description", &entry.summary)?;
+                        w.create_element("content:encoded")
+                            .write_inner_content(|w| write_cdata(w, &entry.content_html))?;
+                        if let Some(image) = &entry.image {
+                            let mime = image_mime(image);
+                            w.create_element("enclosure")
+                                .with_attributes([
+                                    ("url", image.as_str()),
+                                    ("length", "0"),
+                                    ("type", mime.as_str()),
+                                ])
+                                .write_empty()?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+/// CDATA 里不能出现 `]]>`,由 quick-xml 拆成多段。
+fn write_cdata<W: io::Write>(writer: &mut Writer<W>, text: &str) -> io::Result<()> {
+    for part in BytesCData::escaped(text) {
+        writer.write_event(Event::CData(part))?;
+    }
+    Ok(())
+}
+
+fn write_atom<W: io::Write>(writer: &mut Writer<W>, feed: &Feed) -> io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("feed")
+        .with_attributes([
+            ("xmlns", "http://www.w3.org/2005/Atom"),
+            ("xml:lang", "zh-CN"),
+        ])
+        .write_inner_content(|w| {
+            text_element(w, "id", &feed.feed_url)?;
+            text_element(w, "title", &feed.title)?;
+            text_element(w, "subtitle", &feed.description)?;
+            text_element(w, "updated", &rfc3339(feed.updated.unwrap_or_default()))?;
+            w.create_element("link")
+                .with_attributes([("rel", "alternate"), ("href", feed.home_url.as_str())])
+                .write_empty()?;
+            w.create_element("link")
+                .with_attributes([
+                    ("rel", "self"),
+                    ("type", "application/atom+xml"),
+                    ("href", feed.feed_url.as_str()),
+                ])
+                .write_empty()?;
+            w.create_element("author")
+                .write_inner_content(|w| text_element(w, "name", "chuyi"))?;
+            for entry in &feed.entries {
+                w.create_element("entry").write_inner_content(|w| {
+                    text_element(w, "id", &entry.id)?;
+                    text_element(w, "title", &entry.title)?;
+                    w.create_element("link")
+                        .with_attributes([("rel", "alternate"), ("href", entry.url.as_str())])
+                        .write_empty()?;
+                    text_element(w, "published", &rfc3339(entry.published))?;
+                    text_element(w, "updated", &rfc3339(entry.updated))?;
+                    for category in &entry.categories {
+                        w.create_element("category")
+                            .with_attribute(("term", category.as_str()))
+                            .write_empty()?;
+                    }
+                    text_element(w, "summary", &entry.summary)?;
+                    w.create_element("content")
+                        .with_attribute(("type", "html"))
+                        .write_text_content(BytesText::new(&entry.content_html))?;
+                    if let Some(image) = &entry.image {
+                        let mime = image_mime(image);
+                        w.create_element("link")
+                            .with_attributes([
+                                ("rel", "enclosure"),
+                                ("type", mime.as_str()),
+                                ("href", image.as_str()),
+                            ])
+                            .write_empty()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+    Ok(())
+}
+
+fn json_feed(feed: &Feed) -> serde_json::Value {
+    let items: Vec<serde_json::Value> = feed
+        .entries
+        .iter()
+        .map(|entry| {
+            let mut item = serde_json::json!({
+                "id": entry.id,
+                "url": entry.url,
+                "title": entry.title,
+                "content_html": entry.content_html,
+                "summary": entry.summary,
+                "date_published": rfc3339(entry.published),
+                "date_modified": rfc3339(entry.updated),
+                "tags": entry.categories,
+            });
+            if let Some(image) = &entry.image {
+                item["image"] = serde_json::Value::String(image.clone());
+                item["attachments"] = serde_json::json!([{
+                    "url": image,
+                    "mime_type": image_mime(image),
+                }]);
+            }
+            item
+        })
+        .collect();
+    serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": feed.title,
+        "home_page_url": feed.home_url,
+        "feed_url": feed.feed_url,
+        "description": feed.description,
+        "language": "zh-CN",
+        "authors": [{ "name": "chuyi" }],
+        "items": items,
+    })
+}
+
+fn render(feed: &Feed, format: FeedFormat) -> String {
+    match format {
+        FeedFormat::Json => json_feed(feed).to_string(),
+        FeedFormat::Rss | FeedFormat::Atom => {
+            let mut writer = Writer::new(Vec::new());
+            let written = match format {
+                FeedFormat::Rss => write_rss(&mut writer, feed),
+                _ => write_atom(&mut writer, feed),
+            };
+            written.expect("writing into a Vec cannot fail");
+            String::from_utf8(writer.into_inner()).expect("feed XML is UTF-8")
+        }
+    }
+}
+
+/// If-None-Match 优先;没有它时才看 If-Modified-Since(HTTP 日期精度为秒)。
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(value) = value.to_str() else {
+            return false;
+        };
+        return value
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+    let (Some(since), Some(last_modified)) = (
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok()),
+        last_modified,
+    ) else {
+        return false;
+    };
+    DateTime::parse_from_rfc2822(since)
+        .map(|since| last_modified.timestamp() <= since.timestamp())
+        .unwrap_or(false)
+}
+
+async fn load_feed(
+    state: &AppState,
+    scope: FeedScope,
+    format: FeedFormat,
+) -> Result<Option<FeedSource>, StatusCode> {
+    let internal = |err: crate::utils::error::AppError| {
+        tracing::error!("Failed to build feed: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let (title, home_url, prefix, category_id, tag_id) = match scope {
+        FeedScope::Site => (
+            SITE_NAME.to_string(),
+            SITE.to_string(),
+            String::new(),
+            None,
+            None,
+        ),
+        FeedScope::Category(id) => {
+            let Some(category) = state
+                .services
+                .category
+                .get_category(id)
+                .await
+                .map_err(internal)?
+            else {
+                return Ok(None);
+            };
+            (
+                format!("{} · {SITE_NAME}", category.name),
+                format!("{SITE}/articles"),
+                format!("/category/{id}"),
+                Some(id),
+                None,
+            )
+        }
+        FeedScope::Tag(id) => {
+            let Some(tag) = state.services.tag.get_tag(id).await.map_err(internal)? else {
+                return Ok(None);
+            };
+            (
+                format!("{} · {SITE_NAME}", tag.name),
+                format!("{SITE}/articles"),
+                format!("/tag/{id}"),
+                None,
+                Some(id),
+            )
+        }
+    };
+    let posts = state
+        .services
+        .post
+        .list_feed_posts(category_id, tag_id, FEED_LIMIT)
+        .await
+        .map_err(internal)?;
+    Ok(Some(FeedSource {
+        title,
+        home_url,
+        feed_url: format!("{SITE}{prefix}/{}", format.file()),
+        posts,
+    }))
+}
+
+/// 处理订阅源请求;`path` 不是订阅源时返回 None,交回 SPA 兜底。
+pub(crate) async fn serve(state: &AppState, path: &str, headers: &HeaderMap) -> Option<Response> {
+    let (scope, format) = parse_feed_path(path)?;
+    let source = match load_feed(state, scope, format).await {
+        Ok(Some(source)) => source,
+        Ok(None) => return Some(StatusCode::NOT_FOUND.into_response()),
+        Err(status) => return Some(status.into_response()),
+    };
+    let etag = source.etag();
+    let updated = source.updated();
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("hex ETag is a valid header"),
+    );
+    if let Some(updated) = updated {
+        response_headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&http_date(updated)).expect("HTTP date is a valid header"),
+        );
+    }
+    if is_not_modified(headers, &etag, updated) {
+        return Some((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+    let body = render(&source.into_feed(&state.services.render), format);
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    Some((response_headers, body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_feed() -> Feed {
+        let time = Utc.with_ymd_and_hms(2025, 3, 1, 8, 30, 0).unwrap();
+        Feed {
+            title: "t".to_string(),
+            description: "d".to_string(),
+            home_url: SITE.to_string(),
+            feed_url: format!("{SITE}/atom.xml"),
+            updated: Some(time),
+            entries: vec![FeedEntry {
+                id: format!("{SITE}/article/1"),
+                url: format!("{SITE}/article/hello"),
+                title: "Hello & <world>".to_string(),
+                summary: "s".to_string(),
+                content_html: "<p>a ]]> b</p>".to_string(),
+                image: Some(format!("{SITE}/uploads/cover.webp")),
+                categories: vec!["rust".to_string()],
+                published: time,
+                updated: time,
+            }],
+        }
+    }
+
+    #[test]
+    fn feed_paths_are_parsed_by_scope_and_format() {
+        assert_eq!(
+            parse_feed_path("/atom.xml"),
+            Some((FeedScope::Site, FeedFormat::Atom))
+        );
+        assert_eq!(
+            parse_feed_path("/category/3/rss.xml"),
+            Some((FeedScope::Category(3), FeedFormat::Rss))
+        );
+        assert_eq!(
+            parse_feed_path("/tag/7/feed.json"),
+            Some((FeedScope::Tag(7), FeedFormat::Json))
+        );
+        assert_eq!(parse_feed_path("/tag/x/atom.xml"), None);
+        assert_eq!(parse_feed_path("/article/rss.xml"), None);
+    }
+
+    #[test]
+    fn xml_feeds_escape_text_and_carry_enclosures() {
+        let feed = sample_feed();
+        let atom = render(&feed, FeedFormat::Atom);
+        assert!(atom.contains("<title>Hello &amp; &lt;world&gt;</title>"));
+        assert!(atom.contains("<updated>2025-03-01T08:30:00Z</updated>"));
+        assert!(atom.contains(r#"rel="enclosure" type="image/webp""#));
+
+        let rss = render(&feed, FeedFormat::Rss);
+        assert!(rss.contains("<![CDATA[<p>a ]]]]><![CDATA[>"));
+        assert!(rss.contains(r#"<enclosure url="https://blog.chuyi.uk/uploads/cover.webp" length="0" type="image/webp"/>"#));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&feed, FeedFormat::Json)).unwrap();
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(json["items"][0]["date_modified"], "2025-03-01T08:30:00Z");
+    }
+
+    #[test]
+    fn etag_follows_entry_updates_without_rendering() {
+        let time = Utc.with_ymd_and_hms(2025, 3, 1, 8, 30, 0).unwrap();
+        let post = Post {
+            id: 1,
+            slug: Some("hello".to_string()),
+            title: "Hello".to_string(),
+            cover_url: None,
+            content: "body".to_string(),
+            category_name: Some("rust".to_string()),
+            category_id: Some(1),
+            status: 1,
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tags: Vec::new(),
+            created_at: time,
+            updated_at: time,
+        };
+        let source = |post: Post| FeedSource {
+            title: "t".to_string(),
+            home_url: SITE.to_string(),
+            feed_url: format!("{SITE}/atom.xml"),
+            posts: vec![post],
+        };
+        let original = source(post.clone()).etag();
+        assert_eq!(source(post.clone()).etag(), original);
+
+        let edited = Post {
+            updated_at: time + chrono::Duration::seconds(1),
+            ..post.clone()
+        };
+        assert_ne!(source(edited).etag(), original);
+        let recategorized = Post {
+            category_name: Some("go".to_string()),
+            ..post
+        };
+        assert_ne!(source(recategorized).etag(), original);
+    }
+
+    #[test]
+    fn conditional_headers_match_etag_or_last_modified() {
+        let updated = Utc.with_ymd_and_hms(2025, 3, 1, 8, 30, 0).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, "W/\"abc\", \"def\"".parse().unwrap());
+        assert!(is_not_modified(&headers, "\"def\"", Some(updated)));
+        assert!(!is_not_modified(&headers, "\"xyz\"", Some(updated)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            http_date(updated).parse().unwrap(),
+        );
+        assert!(is_not_modified(&headers, "\"xyz\"", Some(updated)));
+        assert!(!is_not_modified(
+            &headers,
+            "\"xyz\"",
+            Some(updated + chrono::Duration::seconds(1))
+        ));
+    }
+}
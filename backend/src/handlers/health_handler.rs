@@ -1,11 +1,17 @@
+use crate::handlers::mail_handler;
+use crate::middleware::metrics::prometheus_handle;
 use crate::routes::AppState;
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::OnceLock;
-use std::time::Instant;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use sysinfo::{Disks, System};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -70,6 +76,11 @@ fn get_uptime_seconds() -> u64 {
         .unwrap_or(0)
 }
 
+/// 由指标中间件在每个请求结束时调用。
+pub fn record_request() {
+    REQUEST_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
 fn get_request_count() -> u64 {
     REQUEST_COUNT.load(Ordering::Relaxed)
 }
@@ -86,6 +97,63 @@ pub async fn health_check() -> Result<Json<HealthStatus>, StatusCode> {
     Ok(Json(health_status))
 }
 
+/// 上传目录大小的缓存：遍历目录开销不小，不必每次抓取都算。
+static UPLOAD_DIR_SIZE: Mutex<Option<(Instant, u64)>> = Mutex::new(None);
+const UPLOAD_DIR_SIZE_TTL: Duration = Duration::from_secs(300);
+
+async fn upload_dir_size(upload_dir: &str) -> u64 {
+    if let Ok(cached) = UPLOAD_DIR_SIZE.lock() {
+        if let Some((at, size)) = *cached {
+            if at.elapsed() < UPLOAD_DIR_SIZE_TTL {
+                return size;
+            }
+        }
+    }
+    let path = PathBuf::from(upload_dir);
+    let size = tokio::task::spawn_blocking(move || calculate_dir_size(&path))
+        .await
+        .unwrap_or(0);
+    if let Ok(mut cached) = UPLOAD_DIR_SIZE.lock() {
+        *cached = Some((Instant::now(), size));
+    }
+    size
+}
+
+/// Prometheus scrape endpoint (admin-protected). 请求计数和耗时由中间件实时记录，
+/// 连接池、工具并发槽位和上传目录这类瞬时值在抓取时采样。
+pub async fn prometheus_metrics(State(app_state): State<AppState>) -> Response {
+    let pool = app_state.database.pool();
+    let idle = pool.num_idle() as f64;
+    metrics::gauge!("db_pool_connections", pool.size() as f64 - idle, "state" => "active");
+    metrics::gauge!("db_pool_connections", idle, "state" => "idle");
+    metrics::gauge!(
+        "tool_jobs_in_use",
+        app_state
+            .services
+            .gitbook2epub
+            .jobs_in_use()
+            .await
+            .unwrap_or_default() as f64,
+        "tool" => "gitbook2epub"
+    );
+    metrics::gauge!(
+        "tool_jobs_in_use",
+        mail_handler::jobs_in_use() as f64,
+        "tool" => "mail"
+    );
+    metrics::gauge!(
+        "upload_dir_bytes",
+        upload_dir_size(&app_state.config.storage.upload_dir).await as f64
+    );
+    metrics::gauge!("process_uptime_seconds", get_uptime_seconds() as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus_handle().render(),
+    )
+        .into_response()
+}
+
 /// Detailed health check endpoint
 pub async fn detailed_health_check(
     State(app_state): State<AppState>,
@@ -359,6 +427,13 @@ pub async fn get_dashboard_stats(
         .await
         .unwrap_or((0,));
 
+    let total_views = app_state
+        .services
+        .analytics
+        .total_views()
+        .await
+        .unwrap_or(0);
+
     // Get recent posts (latest 5)
     let recent_posts_rows: Vec<(i64, String, DateTime<Utc>, i32)> = sqlx::query_as(
         "SELECT id, title, created_at, status FROM posts WHERE status != 2 ORDER BY created_at DESC LIMIT 5"
@@ -392,7 +467,7 @@ pub async fn get_dashboard_stats(
     let disk_usage = get_directory_size(upload_dir);
 
     let stats = DashboardStats {
-        total_views: 0, // Placeholder - would need view tracking
+        total_views,
         total_posts: total_posts.0,
         total_categories: total_categories.0,
         total_tags: total_tags.0,
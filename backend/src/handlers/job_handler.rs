@@ -0,0 +1,74 @@
+use crate::models::{
+    ApiListResponse, ApiResponse, EnqueueJobRequest, Job, JobListQuery, PublicJob,
+};
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+pub async fn list(
+    State(services): State<Services>,
+    Query(query): Query<JobListQuery>,
+) -> Result<Json<ApiListResponse<Job>>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+    let (jobs, total) = services.jobs.list(&query, page, page_size).await?;
+    Ok(Json(ApiListResponse::success(jobs, total, page, page_size)))
+}
+
+pub async fn enqueue(
+    State(services): State<Services>,
+    Json(request): Json<EnqueueJobRequest>,
+) -> Result<Json<ApiResponse<Job>>> {
+    Ok(Json(ApiResponse::success(
+        services.jobs.enqueue(request.kind, request.payload).await?,
+    )))
+}
+
+pub async fn get(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Job>>> {
+    Ok(Json(ApiResponse::success(services.jobs.get(id).await?)))
+}
+
+pub async fn cancel(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Job>>> {
+    Ok(Json(ApiResponse::success(services.jobs.cancel(id).await?)))
+}
+
+/// GET /api/jobs/:token —— 入队时拿到的令牌就是查询凭据。
+pub async fn status(
+    State(services): State<Services>,
+    Path(token): Path<String>,
+) -> Result<Json<ApiResponse<PublicJob>>> {
+    Ok(Json(ApiResponse::success(
+        services.jobs.get_by_token(&token).await?.into(),
+    )))
+}
+
+/// 只有成功结束的任务才有结果。
+pub async fn result(
+    State(services): State<Services>,
+    Path(token): Path<String>,
+) -> Result<Json<ApiResponse<serde_json::Value>>> {
+    let job = services.jobs.get_by_token(&token).await?;
+    if job.status != crate::models::JOB_SUCCEEDED {
+        return Err(AppError::BadRequest(format!("Job is {}", job.status)));
+    }
+    Ok(Json(ApiResponse::success(job.result)))
+}
+
+pub async fn cancel_by_token(
+    State(services): State<Services>,
+    Path(token): Path<String>,
+) -> Result<Json<ApiResponse<PublicJob>>> {
+    let job = services.jobs.get_by_token(&token).await?;
+    Ok(Json(ApiResponse::success(
+        services.jobs.cancel(job.id).await?.into(),
+    )))
+}
@@ -21,14 +21,21 @@ use axum::{
 use mailparse::ParsedMail;
 use native_tls::TlsConnector;
 use serde::Deserialize;
-use std::collections::HashMap;
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
-use std::sync::{LazyLock, Mutex};
-use std::time::{Duration, Instant};
+use std::sync::LazyLock;
+use std::time::Duration;
 use tokio::sync::Semaphore;
 
+use crate::utils::rate_limit::{client_ip, FixedWindowLimiter};
+
 /// 同时只允许少量 IMAP 连接，保护小内存机器、并限制滥用速率。
-static MAIL_SEM: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(3));
+const MAIL_SLOTS: usize = 3;
+static MAIL_SEM: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(MAIL_SLOTS));
+
+/// 正在进行的 IMAP 读取数（指标用）。
+pub(crate) fn jobs_in_use() -> usize {
+    MAIL_SLOTS - MAIL_SEM.available_permits()
+}
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
 const IO_TIMEOUT: Duration = Duration::from_secs(25);
@@ -45,9 +52,8 @@ const MAX_BODY_BYTES: usize = 200 * 1024;
 const RATE_WINDOW: Duration = Duration::from_secs(600);
 const RATE_LIMIT: u32 = 30;
 
-/// 每 IP 的（窗口起点, 已用次数）。内存态，重启即清零；超量时整体修剪过期项。
-static RATE: LazyLock<Mutex<HashMap<String, (Instant, u32)>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+static RATE: LazyLock<FixedWindowLimiter> =
+    LazyLock::new(|| FixedWindowLimiter::new(RATE_WINDOW, RATE_LIMIT));
 
 #[derive(Deserialize)]
 pub struct ListReq {
@@ -147,48 +153,6 @@ fn imap_host(email: &str) -> Option<&'static str> {
     Some(host)
 }
 
-/// 取真实访客 IP：站点在 Cloudflare + nginx 后面，套接字对端是本机，
-/// 所以优先信 Cloudflare 注入的 `CF-Connecting-IP`，退而取 `X-Forwarded-For` 首段。
-fn client_ip(headers: &HeaderMap) -> String {
-    if let Some(ip) = headers
-        .get("cf-connecting-ip")
-        .and_then(|v| v.to_str().ok())
-    {
-        let t = ip.trim();
-        if !t.is_empty() {
-            return t.to_string();
-        }
-    }
-    if let Some(xff) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
-        if let Some(first) = xff.split(',').next() {
-            let t = first.trim();
-            if !t.is_empty() {
-                return t.to_string();
-            }
-        }
-    }
-    "unknown".to_string()
-}
-
-/// 固定窗口限流。返回 true 表示放行；false 表示该 IP 本窗口内已超限。
-fn rate_ok(ip: &str) -> bool {
-    let now = Instant::now();
-    let mut map = match RATE.lock() {
-        Ok(m) => m,
-        Err(p) => p.into_inner(), // 锁中毒也继续（限流非关键路径）
-    };
-    // 修剪过期项，避免 map 无限增长。
-    if map.len() > 4096 {
-        map.retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
-    }
-    let entry = map.entry(ip.to_string()).or_insert((now, 0));
-    if now.duration_since(entry.0) >= RATE_WINDOW {
-        *entry = (now, 0);
-    }
-    entry.1 += 1;
-    entry.1 <= RATE_LIMIT
-}
-
 /// 把 imap 的错误翻译成对用户友好、且不泄露内部细节的中文提示。
 fn friendly_imap_err(err: &imap::Error) -> String {
     match err {
@@ -382,7 +346,7 @@ where
     if imap_host(&email).is_none() {
         return json_err(StatusCode::BAD_REQUEST, "暂不支持该邮箱服务商。");
     }
-    if !rate_ok(&ip) {
+    if !RATE.check(&ip) {
         return json_err(StatusCode::TOO_MANY_REQUESTS, "尝试过于频繁，请稍后再试。");
     }
 
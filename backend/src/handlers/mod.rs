@@ -1,17 +1,28 @@
 pub mod about_handler;
+pub mod ai_handler;
+pub mod analytics_handler;
 pub mod auth_handler;
 pub mod book_handler;
 pub mod category_handler;
 pub mod changelog_handler;
+pub mod comment_handler;
 pub mod download_handler;
+pub mod epub_handler;
+pub mod export_handler;
+pub mod feed_handler;
 pub mod health_handler;
+pub mod job_handler;
 pub mod mail_handler;
 pub mod music_handler;
+pub mod opds_handler;
 pub mod pdf_handler;
 pub mod post_handler;
 pub mod quant_handler;
 pub mod resource_handler;
+pub mod search_handler;
 pub mod seo_handler;
+pub mod series_handler;
 pub mod tag_handler;
 pub mod tools_handler;
+pub mod translation_handler;
 pub mod video_handler;
@@ -0,0 +1,680 @@
+//! OPDS 1.2 书库目录（Atom），给 KOReader 等阅读器 App 用。
+//!
+//! /api/opds 是导航源：全部书籍和按阅读状态分的书架；/api/opds/books 是获取源，条目带
+//! 封面和下载链接，支持 OpenSearch（/api/opds/opensearch.xml）按书名或作者搜索。
+//! 匿名访问只列公开书籍，且只有开启了下载的书给出下载链接；带上 HTTP Basic 认证、
+//! `?token=` 访问令牌或管理员会话时列出全部书籍和文件。下载链接跳转到短时效的 R2 预签名地址。
+//! 凭据错误按 IP 计数，超过上限后一段时间内带凭据的请求一律返回 429。
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use serde::Deserialize;
+use std::io;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::book_handler::{content_disposition, DOWNLOAD_LINK_TTL_SECONDS};
+use super::feed_handler::{image_mime, rfc3339, text_element};
+use super::seo_handler::{abs_url, SITE, SITE_NAME};
+use crate::config::OpdsConfig;
+use crate::middleware::auth::{is_admin, secure_eq};
+use crate::models::{Book, BookFile};
+use crate::routes::AppState;
+use crate::utils::error::{AppError, Result};
+use crate::utils::rate_limit::{client_ip, FixedWindowLimiter};
+
+const NAVIGATION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+const ACQUISITION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+const OPENSEARCH_TYPE: &str = "application/opensearchdescription+xml";
+const ROOT_PATH: &str = "/api/opds";
+const BOOKS_PATH: &str = "/api/opds/books";
+const OPENSEARCH_PATH: &str = "/api/opds/opensearch.xml";
+
+/// 每个 IP 在窗口内允许的认证失败次数。
+const AUTH_FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+const AUTH_FAILURE_LIMIT: u32 = 10;
+
+static AUTH_FAILURES: LazyLock<FixedWindowLimiter> =
+    LazyLock::new(|| FixedWindowLimiter::new(AUTH_FAILURE_WINDOW, AUTH_FAILURE_LIMIT));
+
+/// 导航源里的书架顺序。
+const SHELVES: [(&str, &str); 4] = [
+    ("reading", "在读"),
+    ("want_to_read", "想读"),
+    ("finished", "读完"),
+    ("paused", "暂停"),
+];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OpdsQuery {
+    token: Option<String>,
+    status: Option<String>,
+    q: Option<String>,
+}
+
+/// 谁在看目录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Viewer {
+    /// 能看到私密书籍、下载全部文件
+    owner: bool,
+    /// 用 `?token=` 认证时，目录里的每个链接都带上它
+    token: Option<String>,
+}
+
+impl Viewer {
+    fn url(&self, path: &str, params: &[(&str, &str)]) -> String {
+        let mut query: Vec<String> = params
+            .iter()
+            .map(|(name, value)| format!("{name}={}", urlencoding::encode(value)))
+            .collect();
+        if let Some(token) = &self.token {
+            query.push(format!("token={}", urlencoding::encode(token)));
+        }
+        if query.is_empty() {
+            format!("{SITE}{path}")
+        } else {
+            format!("{SITE}{path}?{}", query.join("&"))
+        }
+    }
+}
+
+/// 凭据给了但不对时返回 `None`（回 401），没给凭据就是匿名读者。
+/// `admin` 是请求是否带着管理员会话或管理 token。
+fn authorize(
+    headers: &HeaderMap,
+    token: Option<&str>,
+    config: &OpdsConfig,
+    admin: bool,
+) -> Option<Viewer> {
+    let owner = |token: Option<String>| Some(Viewer { owner: true, token });
+    if let Some(token) = token {
+        let expected = config.token.as_deref()?;
+        return secure_eq(token, expected).then(|| owner(Some(token.to_string())))?;
+    }
+    if admin {
+        return owner(None);
+    }
+    match headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(value) => basic_auth_matches(value, config).then(|| owner(None))?,
+        None => Some(Viewer {
+            owner: false,
+            token: None,
+        }),
+    }
+}
+
+fn basic_auth_matches(value: &str, config: &OpdsConfig) -> bool {
+    let (Some(username), Some(password)) = (&config.username, &config.password) else {
+        return false;
+    };
+    let Some(encoded) = value.strip_prefix("Basic ") else {
+        return false;
+    };
+    let Some(decoded) = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+    else {
+        return false;
+    };
+    decoded
+        .split_once(':')
+        .is_some_and(|(user, pass)| secure_eq(user, username) & secure_eq(pass, password))
+}
+
+/// `Ok(None)` 表示凭据不对（回 401）；失败太多次的 IP 直接 429。
+fn viewer(app_state: &AppState, headers: &HeaderMap, query: &OpdsQuery) -> Result<Option<Viewer>> {
+    let has_credentials = query.token.is_some() || headers.contains_key(header::AUTHORIZATION);
+    let ip = client_ip(headers);
+    if has_credentials && AUTH_FAILURES.exceeded(&ip) {
+        return Err(too_many_failures());
+    }
+    let viewer = authorize(
+        headers,
+        query.token.as_deref(),
+        &app_state.config.opds,
+        is_admin(headers, &app_state.config),
+    );
+    if viewer.is_none() && !AUTH_FAILURES.check(&ip) {
+        return Err(too_many_failures());
+    }
+    Ok(viewer)
+}
+
+fn too_many_failures() -> AppError {
+    AppError::TooManyRequests(
+        "Too many failed sign-in attempts, please try again later".to_string(),
+    )
+}
+
+fn challenge() -> Result<Response> {
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header(
+            header::WWW_AUTHENTICATE,
+            format!("Basic realm=\"{SITE_NAME} OPDS\", charset=\"UTF-8\""),
+        )
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from("Invalid OPDS credentials"))
+        .map_err(|error| AppError::Internal(format!("Could not build OPDS response: {error}")))
+}
+
+fn xml_response(content_type: &str, body: String) -> Result<Response> {
+    Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            format!("{content_type}; charset=utf-8"),
+        )
+        // 同一个地址匿名和登录后看到的内容不同
+        .header(header::CACHE_CONTROL, "private, no-cache")
+        .header(header::VARY, "Authorization, Cookie")
+        .body(Body::from(body))
+        .map_err(|error| AppError::Internal(format!("Could not build OPDS response: {error}")))
+}
+
+/// GET /api/opds —— 导航源。
+pub async fn root(
+    State(app_state): State<AppState>,
+    Query(query): Query<OpdsQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Some(viewer) = viewer(&app_state, &headers, &query)? else {
+        return challenge();
+    };
+    let books = app_state
+        .services
+        .book
+        .catalog(!viewer.owner, None, None)
+        .await?;
+    xml_response(NAVIGATION_TYPE, render_navigation(&viewer, &books))
+}
+
+/// GET /api/opds/books?status=&q= —— 获取源。
+pub async fn books(
+    State(app_state): State<AppState>,
+    Query(query): Query<OpdsQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Some(viewer) = viewer(&app_state, &headers, &query)? else {
+        return challenge();
+    };
+    let status = query.status.as_deref().filter(|status| !status.is_empty());
+    let search = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let books = app_state
+        .services
+        .book
+        .catalog(!viewer.owner, status, search)
+        .await?;
+
+    let mut params = Vec::new();
+    if let Some(status) = status {
+        params.push(("status", status));
+    }
+    if let Some(search) = search {
+        params.push(("q", search));
+    }
+    let title = match (status, search) {
+        (_, Some(search)) => format!("搜索：{search}"),
+        (Some(status), None) => shelf_label(status).to_string(),
+        (None, None) => "全部书籍".to_string(),
+    };
+    xml_response(
+        ACQUISITION_TYPE,
+        render_acquisition(&viewer, &title, &params, &books),
+    )
+}
+
+/// GET /api/opds/opensearch.xml
+pub async fn opensearch(
+    State(app_state): State<AppState>,
+    Query(query): Query<OpdsQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Some(viewer) = viewer(&app_state, &headers, &query)? else {
+        return challenge();
+    };
+    xml_response(OPENSEARCH_TYPE, render_opensearch(&viewer))
+}
+
+/// GET /api/opds/books/:book_id/files/:file_id —— 跳转到 R2 预签名下载地址。
+pub async fn download(
+    State(app_state): State<AppState>,
+    Path((book_id, file_id)): Path<(i64, i64)>,
+    Query(query): Query<OpdsQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Some(viewer) = viewer(&app_state, &headers, &query)? else {
+        return challenge();
+    };
+    let (book, file) = app_state.services.book.get_file(book_id, file_id).await?;
+    if !viewer.owner {
+        if !book.is_public {
+            return Err(AppError::NotFound("Book not found".to_string()));
+        }
+        if !book.download_enabled {
+            return Err(AppError::Forbidden(
+                "Downloads are disabled for this book".to_string(),
+            ));
+        }
+    }
+    let url = app_state.r2_storage.presign_download(
+        &file.r2_key,
+        DOWNLOAD_LINK_TTL_SECONDS,
+        &content_disposition(&file, true),
+    )?;
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header(header::LOCATION, url)
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::empty())
+        .map_err(|error| AppError::Internal(format!("Could not build OPDS response: {error}")))
+}
+
+fn shelf_label(status: &str) -> &'static str {
+    SHELVES
+        .iter()
+        .find(|(value, _)| *value == status)
+        .map_or("书架", |(_, label)| label)
+}
+
+/// 阅读器按 type 决定怎么打开文件，R2 上的 MIME 对 mobi/azw3 只是 octet-stream。
+fn ebook_mime(file: &BookFile) -> &str {
+    match file.format.as_str() {
+        "epub" => "application/epub+zip",
+        "pdf" => "application/pdf",
+        "mobi" => "application/x-mobipocket-ebook",
+        "azw3" => "application/vnd.amazon.ebook",
+        _ => &file.mime_type,
+    }
+}
+
+fn latest(books: &[&Book]) -> DateTime<Utc> {
+    books
+        .iter()
+        .map(|book| book.record.updated_at)
+        .max()
+        .unwrap_or_default()
+}
+
+fn render_xml(write: impl FnOnce(&mut Writer<Vec<u8>>) -> io::Result<()>) -> String {
+    let mut writer = Writer::new(Vec::new());
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .and_then(|_| write(&mut writer))
+        .expect("writing into a Vec cannot fail");
+    String::from_utf8(writer.into_inner()).expect("OPDS XML is UTF-8")
+}
+
+fn link<W: io::Write>(
+    writer: &mut Writer<W>,
+    rel: &str,
+    link_type: &str,
+    href: &str,
+) -> io::Result<()> {
+    writer
+        .create_element("link")
+        .with_attributes([("rel", rel), ("type", link_type), ("href", href)])
+        .write_empty()?;
+    Ok(())
+}
+
+/// 每个源都有的 self / start / search 链接和标题信息。
+/// `id` 用不带 token 的地址，换 token 不会让阅读器当成另一个目录。
+fn write_feed_head<W: io::Write>(
+    writer: &mut Writer<W>,
+    viewer: &Viewer,
+    title: &str,
+    (path, params): (&str, &[(&str, &str)]),
+    self_type: &str,
+    updated: DateTime<Utc>,
+) -> io::Result<()> {
+    let anonymous = Viewer {
+        owner: false,
+        token: None,
+    };
+    text_element(writer, "id", &anonymous.url(path, params))?;
+    text_element(writer, "title", title)?;
+    text_element(writer, "updated", &rfc3339(updated))?;
+    writer
+        .create_element("author")
+        .write_inner_content(|w| text_element(w, "name", SITE_NAME))?;
+    link(writer, "self", self_type, &viewer.url(path, params))?;
+    link(
+        writer,
+        "start",
+        NAVIGATION_TYPE,
+        &viewer.url(ROOT_PATH, &[]),
+    )?;
+    link(
+        writer,
+        "search",
+        OPENSEARCH_TYPE,
+        &viewer.url(OPENSEARCH_PATH, &[]),
+    )?;
+    Ok(())
+}
+
+fn render_navigation(viewer: &Viewer, books: &[Book]) -> String {
+    let all: Vec<&Book> = books.iter().collect();
+    let mut sections = vec![(
+        "all".to_string(),
+        "全部书籍".to_string(),
+        viewer.url(BOOKS_PATH, &[]),
+        all.clone(),
+    )];
+    for (status, label) in SHELVES {
+        let shelf: Vec<&Book> = all
+            .iter()
+            .copied()
+            .filter(|book| book.record.reading_status == status)
+            .collect();
+        sections.push((
+            status.to_string(),
+            label.to_string(),
+            viewer.url(BOOKS_PATH, &[("status", status)]),
+            shelf,
+        ));
+    }
+
+    render_xml(|writer| {
+        writer
+            .create_element("feed")
+            .with_attributes([
+                ("xmlns", "http://www.w3.org/2005/Atom"),
+                ("xml:lang", "zh-CN"),
+            ])
+            .write_inner_content(|w| {
+                write_feed_head(
+                    w,
+                    viewer,
+                    &format!("{SITE_NAME} · 书库"),
+                    (ROOT_PATH, &[]),
+                    NAVIGATION_TYPE,
+                    latest(&all),
+                )?;
+                for (key, label, url, shelf) in &sections {
+                    w.create_element("entry").write_inner_content(|w| {
+                        text_element(w, "id", &format!("{SITE}{BOOKS_PATH}#{key}"))?;
+                        text_element(w, "title", label)?;
+                        text_element(w, "updated", &rfc3339(latest(shelf)))?;
+                        w.create_element("content")
+                            .with_attribute(("type", "text"))
+                            .write_text_content(BytesText::new(&format!("{} 本", shelf.len())))?;
+                        link(w, "subsection", ACQUISITION_TYPE, url)
+                    })?;
+                }
+                Ok(())
+            })?;
+        Ok(())
+    })
+}
+
+fn render_acquisition(
+    viewer: &Viewer,
+    title: &str,
+    params: &[(&str, &str)],
+    books: &[Book],
+) -> String {
+    let all: Vec<&Book> = books.iter().collect();
+    render_xml(|writer| {
+        writer
+            .create_element("feed")
+            .with_attributes([
+                ("xmlns", "http://www.w3.org/2005/Atom"),
+                ("xmlns:opds", "http://opds-spec.org/2010/catalog"),
+                ("xml:lang", "zh-CN"),
+            ])
+            .write_inner_content(|w| {
+                write_feed_head(
+                    w,
+                    viewer,
+                    title,
+                    (BOOKS_PATH, params),
+                    ACQUISITION_TYPE,
+                    latest(&all),
+                )?;
+                link(w, "up", NAVIGATION_TYPE, &viewer.url(ROOT_PATH, &[]))?;
+                for book in books {
+                    write_book_entry(w, viewer, book)?;
+                }
+                Ok(())
+            })?;
+        Ok(())
+    })
+}
+
+fn write_book_entry<W: io::Write>(
+    writer: &mut Writer<W>,
+    viewer: &Viewer,
+    book: &Book,
+) -> io::Result<()> {
+    let record = &book.record;
+    writer.create_element("entry").write_inner_content(|w| {
+        text_element(w, "id", &format!("{SITE}/books/{}", record.id))?;
+        text_element(w, "title", &record.title)?;
+        if !record.author.is_empty() {
+            w.create_element("author")
+                .write_inner_content(|w| text_element(w, "name", &record.author))?;
+        }
+        text_element(w, "updated", &rfc3339(record.updated_at))?;
+        w.create_element("category")
+            .with_attributes([
+                ("term", record.reading_status.as_str()),
+                ("label", shelf_label(&record.reading_status)),
+            ])
+            .write_empty()?;
+        if !record.description.trim().is_empty() {
+            w.create_element("summary")
+                .with_attribute(("type", "text"))
+                .write_text_content(BytesText::new(&record.description))?;
+        }
+        if let Some(cover) = record.cover_url.as_deref().map(abs_url) {
+            let mime = image_mime(&cover);
+            link(w, "http://opds-spec.org/image", &mime, &cover)?;
+            link(w, "http://opds-spec.org/image/thumbnail", &mime, &cover)?;
+        }
+        if viewer.owner || record.download_enabled {
+            for file in &book.files {
+                let href = viewer.url(
+                    &format!("{BOOKS_PATH}/{}/files/{}", record.id, file.id),
+                    &[],
+                );
+                let length = file.file_size.to_string();
+                let format = file.format.to_uppercase();
+                w.create_element("link")
+                    .with_attributes([
+                        ("rel", "http://opds-spec.org/acquisition"),
+                        ("type", ebook_mime(file)),
+                        ("href", href.as_str()),
+                        ("title", format.as_str()),
+                        ("length", length.as_str()),
+                    ])
+                    .write_empty()?;
+            }
+        }
+        if record.is_public {
+            link(
+                w,
+                "alternate",
+                "text/html",
+                &format!("{SITE}/books/{}/read", record.id),
+            )?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn render_opensearch(viewer: &Viewer) -> String {
+    // {searchTerms} 由阅读器替换，不能被百分号编码
+    let base = viewer.url(BOOKS_PATH, &[]);
+    let separator = if base.contains('?') { '&' } else { '?' };
+    let template = format!("{base}{separator}q={{searchTerms}}");
+    render_xml(|writer| {
+        writer
+            .create_element("OpenSearchDescription")
+            .with_attribute(("xmlns", "http://a9.com/-/spec/opensearch/1.1/"))
+            .write_inner_content(|w| {
+                text_element(w, "ShortName", "书库")?;
+                text_element(
+                    w,
+                    "Description",
+                    &format!("在 {SITE_NAME} 的书库里按书名或作者搜索"),
+                )?;
+                text_element(w, "InputEncoding", "UTF-8")?;
+                text_element(w, "OutputEncoding", "UTF-8")?;
+                w.create_element("Url")
+                    .with_attributes([("type", ACQUISITION_TYPE), ("template", template.as_str())])
+                    .write_empty()?;
+                Ok(())
+            })?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BookRecord;
+    use axum::http::HeaderValue;
+    use chrono::TimeZone;
+
+    fn book(id: i64, status: &str, is_public: bool, download_enabled: bool) -> Book {
+        let time = Utc.with_ymd_and_hms(2025, 3, 1, 8, 30, 0).unwrap();
+        Book {
+            record: BookRecord {
+                id,
+                title: format!("Book & {id}"),
+                author: "作者".to_string(),
+                description: String::new(),
+                cover_url: Some("/uploads/covers/a.webp".to_string()),
+                reading_status: status.to_string(),
+                progress: 0,
+                rating: None,
+                notes: String::new(),
+                started_at: None,
+                finished_at: None,
+                is_public,
+                download_enabled,
+                created_at: time,
+                updated_at: time + chrono::Duration::days(id),
+            },
+            files: vec![BookFile {
+                id: id * 10,
+                book_id: id,
+                format: "epub".to_string(),
+                file_url: format!("https://assets.example.com/books/{id}.epub"),
+                r2_key: format!("books/{id}/a.epub"),
+                file_name: "a.epub".to_string(),
+                file_size: 2048,
+                mime_type: "application/epub+zip".to_string(),
+                page_count: None,
+                created_at: time,
+            }],
+        }
+    }
+
+    fn anonymous() -> Viewer {
+        Viewer {
+            owner: false,
+            token: None,
+        }
+    }
+
+    #[test]
+    fn credentials_select_the_viewer() {
+        let config = OpdsConfig {
+            username: Some("reader".to_string()),
+            password: Some("secret".to_string()),
+            token: Some("app-token".to_string()),
+        };
+        let basic = |credentials: &str| {
+            let mut headers = HeaderMap::new();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+            headers.insert(
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {encoded}")).unwrap(),
+            );
+            headers
+        };
+
+        let empty = HeaderMap::new();
+        assert_eq!(authorize(&empty, None, &config, false), Some(anonymous()));
+        assert!(authorize(&empty, None, &config, true).is_some_and(|v| v.owner));
+        assert_eq!(
+            authorize(&empty, Some("app-token"), &config, false),
+            Some(Viewer {
+                owner: true,
+                token: Some("app-token".to_string())
+            })
+        );
+        assert_eq!(authorize(&empty, Some("wrong"), &config, false), None);
+        assert!(authorize(&basic("reader:secret"), None, &config, false).is_some_and(|v| v.owner));
+        assert_eq!(authorize(&basic("reader:nope"), None, &config, false), None);
+
+        let unconfigured = OpdsConfig::default();
+        assert_eq!(
+            authorize(&basic("reader:secret"), None, &unconfigured, false),
+            None
+        );
+        assert_eq!(
+            authorize(&empty, Some("app-token"), &unconfigured, false),
+            None
+        );
+    }
+
+    #[test]
+    fn navigation_feed_lists_shelves_with_counts() {
+        let books = vec![
+            book(1, "reading", true, false),
+            book(2, "finished", true, true),
+        ];
+        let xml = render_navigation(&anonymous(), &books);
+        assert!(xml.contains(r#"<link rel="subsection" type="application/atom+xml;profile=opds-catalog;kind=acquisition" href="https://blog.chuyi.uk/api/opds/books?status=reading"/>"#));
+        assert!(xml.contains("<title>全部书籍</title>"));
+        assert!(xml.contains(r#"<content type="text">2 本</content>"#));
+        assert!(xml.contains(r#"<content type="text">0 本</content>"#));
+        assert!(xml.contains(r#"rel="search" type="application/opensearchdescription+xml""#));
+        assert!(xml.contains("<updated>2025-03-03T08:30:00Z</updated>"));
+    }
+
+    #[test]
+    fn acquisition_links_follow_download_switch_and_viewer() {
+        let books = vec![
+            book(1, "reading", true, false),
+            book(2, "finished", true, true),
+        ];
+        let xml = render_acquisition(&anonymous(), "全部书籍", &[], &books);
+        assert!(xml.contains("<title>Book &amp; 1</title>"));
+        assert!(xml.contains(r#"<link rel="http://opds-spec.org/image/thumbnail" type="image/webp" href="https://blog.chuyi.uk/uploads/covers/a.webp"/>"#));
+        assert!(!xml.contains("/api/opds/books/1/files/10"));
+        assert!(xml.contains(r#"<link rel="http://opds-spec.org/acquisition" type="application/epub+zip" href="https://blog.chuyi.uk/api/opds/books/2/files/20" title="EPUB" length="2048"/>"#));
+
+        let owner = Viewer {
+            owner: true,
+            token: Some("a b".to_string()),
+        };
+        let xml = render_acquisition(&owner, "在读", &[("status", "reading")], &books);
+        assert!(xml.contains("/api/opds/books/1/files/10?token=a%20b"));
+        assert!(xml.contains("<id>https://blog.chuyi.uk/api/opds/books?status=reading</id>"));
+        assert!(xml.contains(
+            r#"href="https://blog.chuyi.uk/api/opds/books?status=reading&amp;token=a%20b"/>"#
+        ));
+
+        let search = render_opensearch(&owner);
+        assert!(search.contains(
+            r#"template="https://blog.chuyi.uk/api/opds/books?token=a%20b&amp;q={searchTerms}""#
+        ));
+    }
+}
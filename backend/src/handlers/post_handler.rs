@@ -1,9 +1,12 @@
 use crate::models::{
-    ApiListResponse, ApiResponse, CreatePostRequest, FileUploadResponse, PostListQuery, PostStatus,
-    UpdatePostRequest, UpdatePostTagsRequest,
+    ApiListResponse, ApiResponse, CreatePostRequest, FileUploadResponse, LangQuery, Post,
+    PostDetail, PostListQuery, PostRevision, PostRevisionDiff, PostRevisionSummary, PostStatus,
+    RevisionDiffQuery, TrashListQuery, TrashedPost, UpdatePostRequest, UpdatePostTagsRequest,
+    DEFAULT_LOCALE,
 };
 use crate::routes::AppState;
 use crate::services::Services;
+use crate::utils::error::{AppError, Result as AppResult};
 use crate::utils::{FileHandler, IMAGE_TYPES};
 use axum::{
     extract::{Path, Query, State},
@@ -19,6 +22,7 @@ pub async fn create_post(
 ) -> Result<Json<ApiResponse<crate::models::Post>>, StatusCode> {
     match services.post.create_post(request).await {
         Ok(post) => Ok(Json(ApiResponse::success(post))),
+        Err(AppError::BadRequest(message)) => Ok(Json(ApiResponse::bad_request(&message))),
         Err(e) => {
             tracing::error!("Failed to create post: {}", e);
             Ok(Json(ApiResponse::internal_error("Failed to create post")))
@@ -29,10 +33,20 @@ pub async fn create_post(
 pub async fn get_post(
     State(services): State<Services>,
     Path(id): Path<i64>,
-) -> (StatusCode, Json<ApiResponse<crate::models::Post>>) {
+    Query(lang): Query<LangQuery>,
+) -> (StatusCode, Json<ApiResponse<PostDetail>>) {
     match services.post.get_post_detail(id).await {
         Ok(Some(post)) if post.status == PostStatus::Published as i32 => {
-            (StatusCode::OK, Json(ApiResponse::success(post)))
+            match localized_detail(&services, post, lang).await {
+                Ok(detail) => (StatusCode::OK, Json(ApiResponse::success(detail))),
+                Err(e) => {
+                    tracing::error!("Failed to localize post: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::internal_error("Failed to get post")),
+                    )
+                }
+            }
         }
         Ok(None) | Ok(Some(_)) => (
             StatusCode::NOT_FOUND,
@@ -48,11 +62,85 @@ pub async fn get_post(
     }
 }
 
+/// 按 slug 获取已发布文章；旧 slug 也能查到，返回数据里的 `slug` 是当前值。
+pub async fn get_post_by_slug(
+    State(services): State<Services>,
+    Path(slug): Path<String>,
+    Query(lang): Query<LangQuery>,
+) -> (StatusCode, Json<ApiResponse<PostDetail>>) {
+    match services.post.get_post_by_slug(&slug).await {
+        Ok(Some(post)) if post.status == PostStatus::Published as i32 => {
+            match localized_detail(&services, post, lang).await {
+                Ok(detail) => (StatusCode::OK, Json(ApiResponse::success(detail))),
+                Err(e) => {
+                    tracing::error!("Failed to localize post: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::internal_error("Failed to get post")),
+                    )
+                }
+            }
+        }
+        Ok(None) | Ok(Some(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::not_found("Post not found")),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to get post by slug: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::internal_error("Failed to get post")),
+            )
+        }
+    }
+}
+
+/// 按 `?lang=` 换上译文并渲染；没有这个语言的译文时返回原文。
+async fn localized_detail(
+    services: &Services,
+    mut post: Post,
+    lang: LangQuery,
+) -> AppResult<PostDetail> {
+    let available_locales = services.translation.locales(post.id).await?;
+    let requested = lang.locale();
+    let translation = if requested == DEFAULT_LOCALE {
+        None
+    } else {
+        services.translation.find(post.id, requested).await?
+    };
+    let (rendered, locale) = match &translation {
+        Some(translation) => {
+            translation.apply_to(&mut post);
+            (services.render.render_translation(translation), requested)
+        }
+        None => (services.render.render_post(&post), DEFAULT_LOCALE),
+    };
+    Ok(PostDetail {
+        post,
+        rendered: rendered.as_ref().clone(),
+        locale,
+        available_locales,
+    })
+}
+
+/// 属于系列的文章返回系列内的上一篇/下一篇，其余按发布时间。
 pub async fn get_adjacent_posts(
     State(services): State<Services>,
     Path(id): Path<i64>,
 ) -> (StatusCode, Json<ApiResponse<crate::models::AdjacentPosts>>) {
-    match services.post.get_adjacent_posts(id).await {
+    let result: AppResult<_> = async {
+        let Some(mut posts) = services.post.get_adjacent_posts(id).await? else {
+            return Ok(None);
+        };
+        if let Some(navigation) = services.series.navigation(id).await? {
+            posts.older = navigation.previous;
+            posts.newer = navigation.next;
+            posts.series = Some(navigation.series);
+        }
+        Ok(Some(posts))
+    }
+    .await;
+    match result {
         Ok(Some(posts)) => (StatusCode::OK, Json(ApiResponse::success(posts))),
         Ok(None) => (
             StatusCode::NOT_FOUND,
@@ -71,11 +159,22 @@ pub async fn get_adjacent_posts(
 pub async fn list_posts(
     State(services): State<Services>,
     Query(mut query): Query<PostListQuery>,
+    Query(lang): Query<LangQuery>,
 ) -> Result<Json<ApiListResponse<crate::models::Post>>, StatusCode> {
     query.status = Some(PostStatus::Published);
     let (page, page_size) = normalize_pagination(&mut query);
 
-    match services.post.list_posts(query).await {
+    let result: AppResult<_> = async {
+        let (mut posts, total) = services.post.list_posts(query).await?;
+        let locale = lang.locale();
+        services
+            .translation
+            .localize(posts.iter_mut().collect(), locale)
+            .await?;
+        Ok((posts, total))
+    }
+    .await;
+    match result {
         Ok((posts, total)) => Ok(Json(ApiListResponse::success(
             posts, total, page, page_size,
         ))),
@@ -89,9 +188,10 @@ pub async fn list_posts(
 pub async fn list_posts_with_details(
     State(services): State<Services>,
     Query(mut query): Query<PostListQuery>,
+    Query(lang): Query<LangQuery>,
 ) -> Result<Json<ApiListResponse<crate::models::PostWithDetails>>, StatusCode> {
     query.status = Some(PostStatus::Published);
-    list_posts_with_details_inner(services, query).await
+    list_posts_with_details_inner(services, query, lang).await
 }
 
 pub async fn admin_get_post(
@@ -112,16 +212,30 @@ pub async fn admin_list_posts_with_details(
     State(services): State<Services>,
     Query(query): Query<PostListQuery>,
 ) -> Result<Json<ApiListResponse<crate::models::PostWithDetails>>, StatusCode> {
-    list_posts_with_details_inner(services, query).await
+    list_posts_with_details_inner(services, query, LangQuery::default()).await
 }
 
 async fn list_posts_with_details_inner(
     services: Services,
     mut query: PostListQuery,
+    lang: LangQuery,
 ) -> Result<Json<ApiListResponse<crate::models::PostWithDetails>>, StatusCode> {
     let (page, page_size) = normalize_pagination(&mut query);
 
-    match services.post.list_posts_with_details(query).await {
+    let result: AppResult<_> = async {
+        let (mut posts, total) = services.post.list_posts_with_details(query).await?;
+        let locale = lang.locale();
+        services
+            .translation
+            .localize(
+                posts.iter_mut().map(|details| &mut details.post).collect(),
+                locale,
+            )
+            .await?;
+        Ok((posts, total))
+    }
+    .await;
+    match result {
         Ok((posts, total)) => Ok(Json(ApiListResponse::success(
             posts, total, page, page_size,
         ))),
@@ -151,6 +265,7 @@ pub async fn update_post(
     match services.post.update_post(id, request).await {
         Ok(Some(post)) => Ok(Json(ApiResponse::success(post))),
         Ok(None) => Ok(Json(ApiResponse::not_found("Post not found"))),
+        Err(AppError::BadRequest(message)) => Ok(Json(ApiResponse::bad_request(&message))),
         Err(e) => {
             tracing::error!("Failed to update post: {}", e);
             Ok(Json(ApiResponse::internal_error("Failed to update post")))
@@ -334,3 +449,72 @@ pub async fn update_post_tags(
         }
     }
 }
+
+pub async fn list_post_revisions(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<ApiResponse<Vec<PostRevisionSummary>>>> {
+    Ok(Json(ApiResponse::success(
+        services.post.list_revisions(id).await?,
+    )))
+}
+
+pub async fn get_post_revision(
+    State(services): State<Services>,
+    Path((id, revision_id)): Path<(i64, i64)>,
+) -> AppResult<Json<ApiResponse<PostRevision>>> {
+    Ok(Json(ApiResponse::success(
+        services.post.get_revision(id, revision_id).await?,
+    )))
+}
+
+pub async fn diff_post_revisions(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> AppResult<Json<ApiResponse<PostRevisionDiff>>> {
+    Ok(Json(ApiResponse::success(
+        services
+            .post
+            .diff_revisions(id, query.from, query.to)
+            .await?,
+    )))
+}
+
+pub async fn restore_post_revision(
+    State(services): State<Services>,
+    Path((id, revision_id)): Path<(i64, i64)>,
+) -> AppResult<Json<ApiResponse<crate::models::Post>>> {
+    Ok(Json(ApiResponse::success(
+        services.post.restore_revision(id, revision_id).await?,
+    )))
+}
+
+pub async fn list_trash(
+    State(services): State<Services>,
+    Query(query): Query<TrashListQuery>,
+) -> AppResult<Json<ApiListResponse<TrashedPost>>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+    let (posts, total) = services.post.list_trash(page, page_size).await?;
+    Ok(Json(ApiListResponse::success(
+        posts, total, page, page_size,
+    )))
+}
+
+pub async fn restore_post_from_trash(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<ApiResponse<crate::models::Post>>> {
+    Ok(Json(ApiResponse::success(
+        services.post.restore_from_trash(id).await?,
+    )))
+}
+
+pub async fn purge_post(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<ApiResponse<()>>> {
+    services.post.purge_post(id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
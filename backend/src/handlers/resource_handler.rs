@@ -1,7 +1,6 @@
-use crate::models::ApiResponse;
+use crate::models::{ApiResponse, Job, JobKind};
 use crate::services::resource_service::{ResourceStats, StaticResource};
 use crate::services::Services;
-use crate::utils::OptimizeResult;
 use axum::{
     extract::{Query, State},
     http::StatusCode,
@@ -79,22 +78,18 @@ pub async fn delete_resource(
     }
 }
 
-/// Optimize all images (batch convert to WebP)
+/// Optimize all images (batch convert to WebP) in a background job; poll the returned job for progress
 pub async fn optimize_all_images(
     State(services): State<Services>,
-) -> Result<Json<ApiResponse<OptimizeResult>>, StatusCode> {
-    match services.resource.optimize_all_images().await {
-        Ok(result) => {
-            tracing::info!(
-                "Image optimization complete: {} converted, {} skipped, {} failed",
-                result.converted,
-                result.skipped,
-                result.failed
-            );
-            Ok(Json(ApiResponse::success(result)))
-        }
+) -> Result<Json<ApiResponse<Job>>, StatusCode> {
+    match services
+        .jobs
+        .enqueue(JobKind::OptimizeImages, serde_json::Value::Null)
+        .await
+    {
+        Ok(job) => Ok(Json(ApiResponse::success(job))),
         Err(e) => {
-            tracing::error!("Failed to optimize images: {}", e);
+            tracing::error!("Failed to enqueue image optimization: {}", e);
             Ok(Json(ApiResponse::internal_error(
                 "Failed to optimize images",
             )))
@@ -0,0 +1,21 @@
+use crate::models::{ApiListResponse, SearchHit, SearchQuery};
+use crate::services::Services;
+use crate::utils::error::Result;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+/// GET /api/search?q=...&page=&page_size= —— 已发布文章的全文检索（bm25 排序 + 高亮摘要）。
+pub async fn search_posts(
+    State(services): State<Services>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ApiListResponse<SearchHit>>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(10).clamp(1, 50);
+    let (hits, total) = services
+        .search
+        .search_published(&query.q, page, page_size)
+        .await?;
+    Ok(Json(ApiListResponse::success(hits, total, page, page_size)))
+}
@@ -1,5 +1,5 @@
 //! 动态 SEO:为爬虫/社交分享在服务端把每个页面的 <head> meta 注入进 SPA 外壳,
-//! 并提供动态 sitemap.xml。面向 Google/Bing 为主——它们能跑 JS,所以这里**只注入
+//! 并提供动态 sitemap.xml(订阅源见 feed_handler)。面向 Google/Bing 为主——它们能跑 JS,所以这里**只注入
 //! meta**(标题/描述/OG/Twitter/canonical/JSON-LD),不渲染正文。
 //!
 //! nginx 把「文件不存在」的文档请求(SPA 路由)和 /sitemap.xml 反代到这里:
@@ -9,17 +9,19 @@
 
 use axum::{
     extract::State,
-    http::{header, StatusCode, Uri},
+    http::{header, HeaderMap, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
 use std::sync::LazyLock;
 
 use crate::models::post::{Post, PostListQuery, PostStatus};
+use crate::models::{Locale, PostSeriesInfo, SeriesDetail, DEFAULT_LOCALE};
 use crate::routes::AppState;
+use crate::utils::markdown;
 
-const SITE: &str = "https://blog.chuyi.uk";
-const SITE_NAME: &str = "chuyi's blog";
-const DEFAULT_DESC: &str = "chuyi's blog —— 全栈开发、技术笔记与思考。";
+pub(crate) const SITE: &str = "https://blog.chuyi.uk";
+pub(crate) const SITE_NAME: &str = "chuyi's blog";
+pub(crate) const DEFAULT_DESC: &str = "chuyi's blog —— 全栈开发、技术笔记与思考。";
 
 /// 构建好的 dist/index.html 路径(prod);可用环境变量覆盖。每次请求读取,
 /// **不缓存**:每次部署都会换带哈希的新 bundle,缓存旧外壳会指向已删除的 js。
@@ -30,6 +32,8 @@ fn dist_index_path() -> String {
 
 static TITLE_RE: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"(?is)<title>.*?</title>").unwrap());
+static BARE_URL_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"https?://\S+").unwrap());
 static DESC_RE: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r#"(?is)<meta\s+name=["']description["'][^>]*>"#).unwrap());
 
@@ -40,41 +44,21 @@ fn esc(s: &str) -> String {
         .replace('"', "&quot;")
 }
 
-fn cdata(s: &str) -> String {
-    s.replace("]]>", "]]]]><![CDATA[>")
-}
-
 /// Markdown/HTML → 纯文本摘要(给 description 用)。
-fn excerpt(content: &str, max: usize) -> String {
-    let mut t = content.to_string();
-    // 代码块、图片、链接(保留链接文字)、行内代码、标题/强调标记、HTML 标签
-    for (re, rep) in [
-        (r"(?s)```.*?```", ""),
-        (r"!\[[^\]]*\]\([^)]*\)", ""),
-        (r"\[([^\]]+)\]\([^)]*\)", "$1"),
-        (r"`([^`]+)`", "$1"),
-        (r"(?m)^#{1,6}\s+", ""),
-        (r"[*_>#~]", ""),
-        (r"<[^>]+>", ""),
-        (r"https?://\S+", ""),
-        (r"\s+", " "),
-    ] {
-        t = regex::Regex::new(re)
-            .unwrap()
-            .replace_all(&t, rep)
-            .into_owned();
-    }
-    let t = t.trim();
+pub(crate) fn excerpt(content: &str, max: usize) -> String {
+    let text = markdown::plain_text(content);
+    let text = BARE_URL_RE.replace_all(&text, "");
+    let t = text.split_whitespace().collect::<Vec<_>>().join(" ");
     let chars: Vec<char> = t.chars().collect();
     if chars.len() > max {
         format!("{}…", chars[..max].iter().collect::<String>().trim_end())
     } else {
-        t.to_string()
+        t
     }
 }
 
 /// 封面/图片转绝对 URL(R2 已是 http;/uploads 等相对路径补上站点前缀)。
-fn abs_url(u: &str) -> String {
+pub(crate) fn abs_url(u: &str) -> String {
     if u.starts_with("http://") || u.starts_with("https://") {
         u.to_string()
     } else if u.starts_with('/') {
@@ -94,6 +78,8 @@ struct Meta {
     robots: &'static str,
     status: StatusCode,
     article: Option<ArticleMeta>,
+    /// 各语言版本的地址；只有一个语言时为空，不输出 hreflang
+    alternates: Vec<(Locale, String)>,
 }
 
 struct ArticleMeta {
@@ -113,14 +99,15 @@ fn website_jsonld() -> String {
     .to_string()
 }
 
-fn article_jsonld(p: &Post, url: &str, image: &Option<String>) -> String {
+fn article_jsonld(article: &ArticleContext, url: &str, image: &Option<String>) -> String {
+    let p = &article.post;
     let keywords: Vec<&str> = p.tags.iter().map(|tag| tag.name.as_str()).collect();
     let mut v = serde_json::json!({
         "@context": "https://schema.org",
         "@type": "Article",
         "headline": p.title,
         "description": excerpt(&p.content, 150),
-        "inLanguage": "zh-CN",
+        "inLanguage": article.locale.hreflang(),
         "wordCount": p.content.chars().count(),
         "datePublished": p.created_at.to_rfc3339(),
         "dateModified": p.updated_at.to_rfc3339(),
@@ -135,9 +122,71 @@ fn article_jsonld(p: &Post, url: &str, image: &Option<String>) -> String {
     if let Some(img) = image {
         v["image"] = serde_json::Value::String(img.clone());
     }
+    if let Some(series) = &article.series {
+        v["isPartOf"] = serde_json::json!({
+            "@type": "CreativeWorkSeries",
+            "name": series.title,
+            "url": format!("{SITE}{}", series_path(&series.slug)),
+        });
+        v["position"] = serde_json::Value::from(series.part);
+    }
     v.to_string()
 }
 
+fn series_path(slug: &str) -> String {
+    format!("/series/{slug}")
+}
+
+/// 系列页：列出已发布的各篇，JSON-LD 用 CreativeWorkSeries。
+fn series_meta(detail: &SeriesDetail) -> Meta {
+    let url = format!("{SITE}{}", series_path(&detail.series.slug));
+    let description = match excerpt(&detail.series.description, 150) {
+        text if text.is_empty() => format!(
+            "「{}」系列，共 {} 篇。",
+            detail.series.title,
+            detail.parts.len()
+        ),
+        text => text,
+    };
+    let parts: Vec<serde_json::Value> = detail
+        .parts
+        .iter()
+        .map(|part| {
+            let path = match &part.slug {
+                Some(slug) => format!("/article/{slug}"),
+                None => format!("/article/{}", part.id),
+            };
+            serde_json::json!({
+                "@type": "Article",
+                "headline": part.title,
+                "url": format!("{SITE}{path}"),
+                "position": part.part,
+            })
+        })
+        .collect();
+    let jsonld = serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "CreativeWorkSeries",
+        "name": detail.series.title,
+        "description": description,
+        "url": url,
+        "hasPart": parts,
+    })
+    .to_string();
+    Meta {
+        title: format!("{} · 系列 · {SITE_NAME}", detail.series.title),
+        description,
+        url,
+        image: None,
+        og_type: "website",
+        jsonld,
+        robots: "index,follow",
+        status: StatusCode::OK,
+        article: None,
+        alternates: Vec::new(),
+    }
+}
+
 /// 静态路由的标题/描述。
 fn static_meta(path: &str) -> Meta {
     let (title, desc, robots, status) = match path {
@@ -230,49 +279,131 @@ fn static_meta(path: &str) -> Meta {
         robots,
         status,
         article: None,
+        alternates: Vec::new(),
     }
 }
 
-async fn build_meta(state: &AppState, path: &str) -> Meta {
-    // 文章详情页 /article/:id
-    if let Some(rest) = path.strip_prefix("/article/") {
-        if let Ok(id) = rest.trim_end_matches('/').parse::<i64>() {
-            if let Ok(Some(post)) = state.services.post.get_post_detail(id).await {
-                if post.status != PostStatus::Published as i32 {
-                    return static_meta(path);
-                }
-                let url = format!("{SITE}/article/{id}");
-                let image = post.cover_url.as_deref().map(abs_url);
-                let desc = {
-                    let e = excerpt(&post.content, 150);
-                    if e.is_empty() {
-                        DEFAULT_DESC.to_string()
-                    } else {
-                        e
-                    }
-                };
-                let jsonld = article_jsonld(&post, &url, &image);
-                let article = ArticleMeta {
-                    published_at: post.created_at.to_rfc3339(),
-                    modified_at: post.updated_at.to_rfc3339(),
-                    section: post.category_name.clone(),
-                    tags: post.tags.iter().map(|tag| tag.name.clone()).collect(),
-                };
-                return Meta {
-                    title: format!("{} · {SITE_NAME}", post.title),
-                    description: desc,
-                    url,
-                    image,
-                    og_type: "article",
-                    jsonld,
-                    robots: "index,follow",
-                    status: StatusCode::OK,
-                    article: Some(article),
-                };
-            }
+/// 文章的规范路径：有 slug 用 slug，否则退回数字 id。
+pub(crate) fn article_path(post: &Post) -> String {
+    match &post.slug {
+        Some(slug) => format!("/article/{slug}"),
+        None => format!("/article/{}", post.id),
+    }
+}
+
+/// 文章某个语言版本的绝对地址：原文是规范路径，译文加 `?lang=`。
+fn article_url(post: &Post, locale: Locale) -> String {
+    if locale == DEFAULT_LOCALE {
+        format!("{SITE}{}", article_path(post))
+    } else {
+        format!("{SITE}{}?lang={}", article_path(post), locale.as_str())
+    }
+}
+
+/// hreflang 备选地址；没有译文时返回空。
+fn alternate_urls(post: &Post, locales: &[Locale]) -> Vec<(Locale, String)> {
+    if locales.len() < 2 {
+        return Vec::new();
+    }
+    locales
+        .iter()
+        .map(|locale| (*locale, article_url(post, *locale)))
+        .collect()
+}
+
+/// 查询串里的 `lang=`，不认识的语言按原文处理。
+fn requested_locale(query: Option<&str>) -> Locale {
+    query
+        .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("lang=")))
+        .and_then(Locale::parse)
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+/// 文章页的上下文：`post` 已换上 `locale` 对应的译文。
+struct ArticleContext {
+    post: Post,
+    locale: Locale,
+    /// 这篇文章的全部语言版本
+    locales: Vec<Locale>,
+    series: Option<PostSeriesInfo>,
+}
+
+/// 译文页换上译文的标题和正文，没有这个语言的译文就按原文输出；顺带查出所属系列。
+async fn article_context(state: &AppState, mut post: Post, query: Option<&str>) -> ArticleContext {
+    let services = &state.services;
+    let locales = services
+        .translation
+        .locales(post.id)
+        .await
+        .unwrap_or_else(|_| vec![DEFAULT_LOCALE]);
+    let mut locale = DEFAULT_LOCALE;
+    let requested = requested_locale(query);
+    if requested != DEFAULT_LOCALE {
+        if let Ok(Some(found)) = services.translation.find(post.id, requested).await {
+            found.apply_to(&mut post);
+            locale = requested;
         }
     }
-    static_meta(path)
+    let series = services
+        .series
+        .navigation(post.id)
+        .await
+        .ok()
+        .flatten()
+        .map(|navigation| navigation.series);
+    ArticleContext {
+        post,
+        locale,
+        locales,
+        series,
+    }
+}
+
+/// `/article/:key` 里的 key 可以是数字 id、当前 slug 或改名前的旧 slug；只返回已发布文章。
+async fn find_article(state: &AppState, key: &str) -> Option<Post> {
+    let post = match key.parse::<i64>() {
+        Ok(id) => state.services.post.get_post_detail(id).await,
+        Err(_) => state.services.post.get_post_by_slug(key).await,
+    };
+    post.ok()
+        .flatten()
+        .filter(|post| post.status == PostStatus::Published as i32)
+}
+
+fn build_meta(path: &str, article: Option<&ArticleContext>) -> Meta {
+    let Some(context) = article else {
+        return static_meta(path);
+    };
+    let post = &context.post;
+    let url = article_url(post, context.locale);
+    let image = post.cover_url.as_deref().map(abs_url);
+    let desc = {
+        let e = excerpt(&post.content, 150);
+        if e.is_empty() {
+            DEFAULT_DESC.to_string()
+        } else {
+            e
+        }
+    };
+    let jsonld = article_jsonld(context, &url, &image);
+    let article = ArticleMeta {
+        published_at: post.created_at.to_rfc3339(),
+        modified_at: post.updated_at.to_rfc3339(),
+        section: post.category_name.clone(),
+        tags: post.tags.iter().map(|tag| tag.name.clone()).collect(),
+    };
+    Meta {
+        title: format!("{} · {SITE_NAME}", post.title),
+        description: desc,
+        url,
+        image,
+        og_type: "article",
+        jsonld,
+        robots: "index,follow",
+        status: StatusCode::OK,
+        article: Some(article),
+        alternates: alternate_urls(post, &context.locales),
+    }
 }
 
 fn inject(html: &str, m: &Meta) -> String {
@@ -295,14 +426,37 @@ fn inject(html: &str, m: &Meta) -> String {
         "<meta data-rh=\"true\" name=\"robots\" content=\"{}\">",
         m.robots
     ));
-    h.push_str(&format!(
-        "<link data-rh=\"true\" rel=\"alternate\" type=\"application/rss+xml\" title=\"{}\" href=\"{SITE}/rss.xml\">",
-        esc(SITE_NAME)
-    ));
+    for (feed_type, file) in [
+        ("application/rss+xml", "rss.xml"),
+        ("application/atom+xml", "atom.xml"),
+        ("application/feed+json", "feed.json"),
+    ] {
+        h.push_str(&format!(
+            "<link data-rh=\"true\" rel=\"alternate\" type=\"{feed_type}\" title=\"{}\" href=\"{SITE}/{file}\">",
+            esc(SITE_NAME)
+        ));
+    }
     h.push_str(&format!(
         "<link data-rh=\"true\" rel=\"canonical\" href=\"{}\">",
         esc(&m.url)
     ));
+    for (locale, url) in &m.alternates {
+        h.push_str(&format!(
+            "<link data-rh=\"true\" rel=\"alternate\" hreflang=\"{}\" href=\"{}\">",
+            locale.hreflang(),
+            esc(url)
+        ));
+    }
+    if let Some((_, url)) = m
+        .alternates
+        .iter()
+        .find(|(locale, _)| *locale == DEFAULT_LOCALE)
+    {
+        h.push_str(&format!(
+            "<link data-rh=\"true\" rel=\"alternate\" hreflang=\"x-default\" href=\"{}\">",
+            esc(url)
+        ));
+    }
     h.push_str(&format!(
         "<meta data-rh=\"true\" property=\"og:type\" content=\"{}\">",
         m.og_type
@@ -390,9 +544,15 @@ async fn sitemap(state: &AppState) -> Response {
         .await
         .map(|(p, _)| p)
         .unwrap_or_default();
+    let translations = state
+        .services
+        .translation
+        .published_locales()
+        .await
+        .unwrap_or_default();
 
     let mut xml = String::from(
-        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">",
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">",
     );
     for (loc, freq) in [
         ("/", "daily"),
@@ -409,13 +569,40 @@ async fn sitemap(state: &AppState) -> Response {
             "<url><loc>{SITE}{loc}</loc><changefreq>{freq}</changefreq></url>"
         ));
     }
-    for p in posts {
+    for series in state.services.series.list(true).await.unwrap_or_default() {
         xml.push_str(&format!(
-            "<url><loc>{SITE}/article/{}</loc><lastmod>{}</lastmod><changefreq>weekly</changefreq></url>",
-            p.id,
-            p.updated_at.format("%Y-%m-%d")
+            "<url><loc>{SITE}{}</loc><lastmod>{}</lastmod><changefreq>weekly</changefreq></url>",
+            esc(&series_path(&series.slug)),
+            series.updated_at.format("%Y-%m-%d")
         ));
     }
+    for p in posts {
+        // 每个语言版本一条 <url>，都带上完整的 xhtml:link 备选列表
+        let mut versions = vec![(DEFAULT_LOCALE, p.updated_at)];
+        versions.extend(translations.get(&p.id).into_iter().flatten().copied());
+        let locales: Vec<Locale> = versions.iter().map(|(locale, _)| *locale).collect();
+        let mut links = String::new();
+        for (locale, url) in alternate_urls(&p, &locales) {
+            links.push_str(&format!(
+                "<xhtml:link rel=\"alternate\" hreflang=\"{}\" href=\"{}\"/>",
+                locale.hreflang(),
+                esc(&url)
+            ));
+            if locale == DEFAULT_LOCALE {
+                links.push_str(&format!(
+                    "<xhtml:link rel=\"alternate\" hreflang=\"x-default\" href=\"{}\"/>",
+                    esc(&url)
+                ));
+            }
+        }
+        for (locale, updated_at) in versions {
+            xml.push_str(&format!(
+                "<url><loc>{}</loc>{links}<lastmod>{}</lastmod><changefreq>weekly</changefreq></url>",
+                esc(&article_url(&p, locale)),
+                updated_at.format("%Y-%m-%d")
+            ));
+        }
+    }
     xml.push_str("</urlset>");
     (
         [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
@@ -424,52 +611,6 @@ async fn sitemap(state: &AppState) -> Response {
         .into_response()
 }
 
-async fn rss(state: &AppState) -> Response {
-    let query = PostListQuery {
-        page: Some(1),
-        page_size: Some(50),
-        category_id: None,
-        status: Some(PostStatus::Published),
-        search: None,
-        tag_id: None,
-    };
-    let posts = state
-        .services
-        .post
-        .list_posts(query)
-        .await
-        .map(|(posts, _)| posts)
-        .unwrap_or_default();
-
-    let mut xml = format!(
-        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
-         <rss version=\"2.0\"><channel>\
-         <title>{}</title><link>{SITE}</link><description>{}</description>\
-         <language>zh-CN</language><atom:link xmlns:atom=\"http://www.w3.org/2005/Atom\" \
-         href=\"{SITE}/rss.xml\" rel=\"self\" type=\"application/rss+xml\"/>",
-        esc(SITE_NAME),
-        esc(DEFAULT_DESC),
-    );
-
-    for post in posts {
-        let url = format!("{SITE}/article/{}", post.id);
-        xml.push_str(&format!(
-            "<item><title>{}</title><link>{url}</link><guid isPermaLink=\"true\">{url}</guid>\
-             <pubDate>{}</pubDate><description><![CDATA[{}]]></description></item>",
-            esc(&post.title),
-            post.created_at.to_rfc2822(),
-            cdata(&post.content),
-        ));
-    }
-    xml.push_str("</channel></rss>");
-
-    (
-        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
-        xml,
-    )
-        .into_response()
-}
-
 fn robots() -> Response {
     let body = format!(
         "User-agent: *\nAllow: /\nDisallow: /admin\nDisallow: /tools/mailbox\n\
@@ -478,24 +619,59 @@ fn robots() -> Response {
     ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response()
 }
 
-/// SPA 兜底:sitemap/robots 直接给,其余路径返回注入了 meta 的 index.html 外壳。
-pub async fn spa_fallback(State(state): State<AppState>, uri: Uri) -> Response {
+/// SPA 兜底:sitemap/robots/订阅源直接给,其余路径返回注入了 meta 的 index.html 外壳。
+pub async fn spa_fallback(State(state): State<AppState>, uri: Uri, headers: HeaderMap) -> Response {
     let path = uri.path().to_string();
     if path == "/sitemap.xml" {
         return sitemap(&state).await;
     }
-    if path == "/rss.xml" {
-        return rss(&state).await;
+    if let Some(response) = super::feed_handler::serve(&state, &path, &headers).await {
+        return response;
     }
     if path == "/robots.txt" {
         return robots();
     }
+    // 文章页:数字 id 和旧 slug 统一 301 到当前 slug,保证只有一个规范 URL。
+    let article = match path.strip_prefix("/article/") {
+        Some(key) => find_article(&state, key.trim_end_matches('/')).await,
+        None => None,
+    };
+    if let Some(post) = &article {
+        let canonical = article_path(post);
+        if path.trim_end_matches('/') != canonical {
+            let location = match uri.query() {
+                Some(query) => format!("{canonical}?{query}"),
+                None => canonical,
+            };
+            return (
+                StatusCode::MOVED_PERMANENTLY,
+                [(header::LOCATION, location)],
+            )
+                .into_response();
+        }
+    }
     let html = match tokio::fs::read_to_string(dist_index_path()).await {
         Ok(h) => h,
         // 读不到外壳:交给 nginx error_page 兜底回静态 index.html
         Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     };
-    let meta = build_meta(&state, &path).await;
+    let series = match path.strip_prefix("/series/") {
+        Some(key) => state
+            .services
+            .series
+            .get(key.trim_end_matches('/'), true)
+            .await
+            .ok(),
+        None => None,
+    };
+    let meta = match (article, series) {
+        (Some(post), _) => {
+            let context = article_context(&state, post, uri.query()).await;
+            build_meta(&path, Some(&context))
+        }
+        (None, Some(series)) => series_meta(&series),
+        (None, None) => build_meta(&path, None),
+    };
     (
         meta.status,
         [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
@@ -516,4 +692,67 @@ mod tests {
         assert_eq!(meta.robots, "noindex,nofollow");
         assert!(meta.title.starts_with("Reader"));
     }
+
+    #[test]
+    fn translated_articles_link_every_language_version() {
+        let now = chrono::Utc::now();
+        let post = Post {
+            id: 7,
+            slug: Some("hello-world".to_string()),
+            title: "Hello world".to_string(),
+            cover_url: None,
+            content: "English body".to_string(),
+            category_name: None,
+            category_id: None,
+            status: PostStatus::Published as i32,
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tags: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let context = ArticleContext {
+            post,
+            locale: Locale::En,
+            locales: vec![Locale::Zh, Locale::En],
+            series: Some(PostSeriesInfo {
+                id: 1,
+                slug: "rust-101".to_string(),
+                title: "Rust 101".to_string(),
+                part: 2,
+                total: 3,
+            }),
+        };
+        let meta = build_meta("/article/hello-world", Some(&context));
+        let html = inject("<html><head><title>x</title></head></html>", &meta);
+        assert!(html.contains(&format!(
+            "rel=\"canonical\" href=\"{SITE}/article/hello-world?lang=en\""
+        )));
+        assert!(html.contains(&format!(
+            "hreflang=\"zh-CN\" href=\"{SITE}/article/hello-world\""
+        )));
+        assert!(html.contains(&format!(
+            "hreflang=\"x-default\" href=\"{SITE}/article/hello-world\""
+        )));
+        assert!(html.contains(r#""inLanguage":"en""#));
+
+        assert!(html.contains(&format!(
+            r#""isPartOf":{{"@type":"CreativeWorkSeries","name":"Rust 101","url":"{SITE}/series/rust-101"}}"#
+        )));
+        assert!(html.contains(r#""position":2"#));
+
+        let untranslated = ArticleContext {
+            locale: Locale::Zh,
+            locales: vec![Locale::Zh],
+            series: None,
+            ..context
+        };
+        let meta = build_meta("/article/hello-world", Some(&untranslated));
+        assert!(meta.alternates.is_empty());
+        assert!(!meta.jsonld.contains("isPartOf"));
+        assert_eq!(requested_locale(Some("utm=x&lang=EN")), Locale::En);
+        assert_eq!(requested_locale(Some("lang=fr")), DEFAULT_LOCALE);
+    }
 }
@@ -0,0 +1,82 @@
+use crate::models::{
+    ApiResponse, CreateSeriesRequest, Series, SeriesDetail, SetSeriesPostsRequest,
+    UpdateSeriesRequest,
+};
+use crate::services::Services;
+use crate::utils::error::Result;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+pub async fn list_public(
+    State(services): State<Services>,
+) -> Result<Json<ApiResponse<Vec<Series>>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.list(true).await?,
+    )))
+}
+
+/// `key` 是系列 id 或 slug；只返回已发布的文章。
+pub async fn get_public(
+    State(services): State<Services>,
+    Path(key): Path<String>,
+) -> Result<Json<ApiResponse<SeriesDetail>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.get(&key, true).await?,
+    )))
+}
+
+pub async fn list_admin(
+    State(services): State<Services>,
+) -> Result<Json<ApiResponse<Vec<Series>>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.list(false).await?,
+    )))
+}
+
+pub async fn get_admin(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<SeriesDetail>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.get(&id.to_string(), false).await?,
+    )))
+}
+
+pub async fn create(
+    State(services): State<Services>,
+    Json(request): Json<CreateSeriesRequest>,
+) -> Result<Json<ApiResponse<SeriesDetail>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.create(request).await?,
+    )))
+}
+
+pub async fn update(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateSeriesRequest>,
+) -> Result<Json<ApiResponse<SeriesDetail>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.update(id, request).await?,
+    )))
+}
+
+pub async fn delete_series(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<()>>> {
+    services.series.delete(id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+pub async fn set_posts(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Json(request): Json<SetSeriesPostsRequest>,
+) -> Result<Json<ApiResponse<SeriesDetail>>> {
+    Ok(Json(ApiResponse::success(
+        services.series.set_posts(id, request).await?,
+    )))
+}
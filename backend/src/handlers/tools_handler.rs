@@ -1,43 +1,17 @@
-//! 在线工具端点：调用 Python 脚本完成任务后返回结果。
+//! 在线工具端点。转换类工具耗时较长，提交后作为后台任务排队执行，客户端轮询进度、
+//! 完成后再下载结果。
 
+use crate::models::{ApiResponse, Gitbook2EpubJob, Gitbook2EpubRequest};
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
 use axum::{
     body::Body,
-    extract::Json,
-    http::{header, StatusCode},
+    extract::{Json, Path, State},
+    http::header,
     response::{IntoResponse, Response},
 };
-use serde::Deserialize;
 use std::net::{IpAddr, ToSocketAddrs};
-use std::process::Stdio;
-use std::sync::LazyLock;
-use std::time::Duration;
-use tokio::process::Command;
-use tokio::sync::Semaphore;
-
-/// 同时只允许一个转换任务（保护小内存机器，防止 OOM 拖垮博客）。
-static JOB_SEM: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(1));
-
-// GNU `timeout` 杀掉整个进程组（含 pandoc 孙进程），是真正的时限。
-const SCRIPT_TIMEOUT_SECS: u64 = 85;
-// tokio 兜底（万一 `timeout` 本身卡住），整体仍留在 Cloudflare 100s 以内。
-const HARD_TIMEOUT_SECS: u64 = 95;
-
-#[derive(Deserialize)]
-pub struct Gitbook2EpubRequest {
-    url: String,
-    /// 是否下载并内嵌图片。默认 false：图片是这台小内存机器的主要内存压力来源，
-    /// 默认跳过可保证转换又快又稳，需要图文版时再显式开启。
-    #[serde(default)]
-    include_images: bool,
-}
-
-fn json_err(code: StatusCode, msg: &str) -> Response {
-    (
-        code,
-        Json(serde_json::json!({ "code": code.as_u16(), "message": msg, "data": null })),
-    )
-        .into_response()
-}
+use tokio_util::io::ReaderStream;
 
 /// SSRF 防护：只允许指向公网的 http/https 链接。
 fn is_public_http_url(raw: &str) -> bool {
@@ -100,121 +74,60 @@ fn ip_is_public(ip: IpAddr) -> bool {
     }
 }
 
-/// POST /api/tools/gitbook2epub  body: { "url": "..." }
-/// 成功返回 epub 二进制（下载）；失败返回 JSON。
-pub async fn gitbook2epub(Json(req): Json<Gitbook2EpubRequest>) -> Response {
+/// POST /api/tools/gitbook2epub  body: { "url": "...", "include_images": false }
+/// 返回任务 id 和排队位置；同样的链接和选项会复用进行中或刚完成的任务。
+pub async fn gitbook2epub(
+    State(services): State<Services>,
+    Json(req): Json<Gitbook2EpubRequest>,
+) -> Result<Json<ApiResponse<Gitbook2EpubJob>>> {
     let url = req.url.trim().to_string();
     if url.len() > 2048 || !is_public_http_url(&url) {
-        return json_err(
-            StatusCode::BAD_REQUEST,
-            "无效或不被允许的链接（必须是公网 http/https 在线书地址）",
-        );
-    }
-
-    // 单任务串行：占不到就直接拒绝
-    let _permit = match JOB_SEM.try_acquire() {
-        Ok(p) => p,
-        Err(_) => {
-            return json_err(
-                StatusCode::TOO_MANY_REQUESTS,
-                "服务器正在转换其它任务，请稍后再试",
-            )
-        }
-    };
-
-    let script = std::env::var("GITBOOK2EPUB_SCRIPT")
-        .unwrap_or_else(|_| "/var/www/blog/backend/tools/gitbook2epub/gitbook2epub.py".to_string());
-    let workdir = std::env::temp_dir();
-    let job_id = uuid::Uuid::new_v4();
-    let out = workdir.join(format!("gb2epub-{job_id}.epub"));
-    // 默认缓存目录在脚本旁边（生产环境只读），必须显式指向可写的临时目录。
-    let cache = workdir.join(format!("gb2epub-cache-{job_id}"));
-
-    // 通过 GNU `timeout` 启动：到点用 SIGKILL 杀掉整个进程组，连带 pandoc 孙进程，
-    // 避免超时后残留进程在小内存机器上继续吃内存。
-    let mut cmd = Command::new("timeout");
-    cmd.arg("-s")
-        .arg("KILL")
-        .arg("-k")
-        .arg("5")
-        .arg(SCRIPT_TIMEOUT_SECS.to_string())
-        .arg("python3")
-        .arg(&script)
-        .arg(&url)
-        .arg("-o")
-        .arg(&out)
-        .arg("--no-auto-install") // 服务器上绝不自动 pip 安装/下载浏览器
-        .arg("--cache")
-        .arg(&cache)
-        .current_dir(&workdir)
-        .kill_on_drop(true)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::piped());
-    if !req.include_images {
-        cmd.arg("--no-images"); // 默认纯文字，省内存、避免拖垮小机器
+        return Err(AppError::BadRequest(
+            "无效或不被允许的链接（必须是公网 http/https 在线书地址）".to_string(),
+        ));
     }
+    let job = services
+        .gitbook2epub
+        .submit(Gitbook2EpubRequest {
+            url,
+            include_images: req.include_images,
+        })
+        .await?;
+    Ok(Json(ApiResponse::success(
+        services.gitbook2epub.describe(job).await?,
+    )))
+}
 
-    let result = tokio::time::timeout(Duration::from_secs(HARD_TIMEOUT_SECS), cmd.output()).await;
-
-    let cleanup = || async {
-        let _ = tokio::fs::remove_file(&out).await;
-        let _ = tokio::fs::remove_dir_all(&cache).await;
-    };
+/// GET /api/tools/gitbook2epub/:job_id —— 轮询进度。
+pub async fn gitbook2epub_status(
+    State(services): State<Services>,
+    Path(job_id): Path<String>,
+) -> Result<Json<ApiResponse<Gitbook2EpubJob>>> {
+    let job = services.gitbook2epub.get(&job_id).await?;
+    Ok(Json(ApiResponse::success(
+        services.gitbook2epub.describe(job).await?,
+    )))
+}
 
-    match result {
-        Err(_) => {
-            cleanup().await;
-            json_err(
-                StatusCode::GATEWAY_TIMEOUT,
-                "转换超时（书太大或站点太慢），请换更小的书或稍后再试",
-            )
-        }
-        Ok(Err(e)) => {
-            tracing::error!("gitbook2epub 启动失败: {}", e);
-            cleanup().await;
-            json_err(StatusCode::INTERNAL_SERVER_ERROR, "服务器无法启动转换")
-        }
-        Ok(Ok(output)) => {
-            // GNU timeout 在超时杀掉命令时自身以 124 退出。
-            if output.status.code() == Some(124) {
-                cleanup().await;
-                return json_err(
-                    StatusCode::GATEWAY_TIMEOUT,
-                    "转换超时（书太大或站点太慢），请换更小的书或稍后再试",
-                );
-            }
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                tracing::warn!("gitbook2epub 失败: {}", stderr.trim());
-                let hint = if stderr.contains("pandoc") {
-                    "服务器缺少 pandoc"
-                } else if stderr.contains("SSRFError") || stderr.contains("blocked") {
-                    "该链接指向了不被允许的地址"
-                } else {
-                    "转换失败，请确认链接是可访问的 gitbook / bookdown 在线书"
-                };
-                cleanup().await;
-                return json_err(StatusCode::BAD_GATEWAY, hint);
-            }
-            match tokio::fs::read(&out).await {
-                Ok(bytes) => {
-                    cleanup().await;
-                    Response::builder()
-                        .status(StatusCode::OK)
-                        .header(header::CONTENT_TYPE, "application/epub+zip")
-                        .header(
-                            header::CONTENT_DISPOSITION,
-                            "attachment; filename=\"book.epub\"",
-                        )
-                        .body(Body::from(bytes))
-                        .unwrap()
-                }
-                Err(_) => {
-                    cleanup().await;
-                    json_err(StatusCode::INTERNAL_SERVER_ERROR, "转换完成但读取文件失败")
-                }
-            }
-        }
-    }
+/// GET /api/tools/gitbook2epub/:job_id/download —— 下载 epub，过期后 404。
+pub async fn gitbook2epub_download(
+    State(services): State<Services>,
+    Path(job_id): Path<String>,
+) -> Result<Response> {
+    let job = services.gitbook2epub.get(&job_id).await?;
+    let (path, file_name) = services.gitbook2epub.result_file(&job).await?;
+    let file = tokio::fs::File::open(&path).await?;
+    let size = file.metadata().await?.len();
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/epub+zip".to_string()),
+            (header::CONTENT_LENGTH, size.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
 }
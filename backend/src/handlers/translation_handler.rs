@@ -0,0 +1,48 @@
+//! 文章译文的管理接口。原文语言（zh）就是文章本身，不在这里编辑。
+
+use crate::models::{ApiResponse, Locale, PostTranslation, UpsertTranslationRequest};
+use crate::services::Services;
+use crate::utils::error::Result;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+pub async fn list_translations(
+    State(services): State<Services>,
+    Path(post_id): Path<i64>,
+) -> Result<Json<ApiResponse<Vec<PostTranslation>>>> {
+    Ok(Json(ApiResponse::success(
+        services.translation.list(post_id).await?,
+    )))
+}
+
+pub async fn get_translation(
+    State(services): State<Services>,
+    Path((post_id, locale)): Path<(i64, Locale)>,
+) -> Result<Json<ApiResponse<PostTranslation>>> {
+    Ok(Json(ApiResponse::success(
+        services.translation.get(post_id, locale).await?,
+    )))
+}
+
+pub async fn upsert_translation(
+    State(services): State<Services>,
+    Path((post_id, locale)): Path<(i64, Locale)>,
+    Json(request): Json<UpsertTranslationRequest>,
+) -> Result<Json<ApiResponse<PostTranslation>>> {
+    Ok(Json(ApiResponse::success(
+        services
+            .translation
+            .upsert(post_id, locale, request)
+            .await?,
+    )))
+}
+
+pub async fn delete_translation(
+    State(services): State<Services>,
+    Path((post_id, locale)): Path<(i64, Locale)>,
+) -> Result<Json<ApiResponse<()>>> {
+    services.translation.delete(post_id, locale).await?;
+    Ok(Json(ApiResponse::success(())))
+}
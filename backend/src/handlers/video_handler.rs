@@ -1,7 +1,12 @@
-use crate::models::ApiResponse;
-use crate::utils::error::Result;
+use crate::models::{ApiResponse, CreateVideo, Job, UpdateVideoRequest, Video};
+use crate::services::resource_service::UsageRef;
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
 use crate::utils::{CompletedVideoPart, R2Storage, VideoMultipartSession};
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -17,6 +22,12 @@ pub struct CompleteVideoUploadRequest {
     key: String,
     upload_id: String,
     parts: Vec<CompletedVideoPart>,
+    file_name: String,
+    content_type: String,
+    file_size: i64,
+    /// 不传时用文件名
+    #[serde(default)]
+    title: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +39,15 @@ pub struct AbortVideoUploadRequest {
 #[derive(Debug, Serialize)]
 pub struct CompleteVideoUploadResponse {
     public_url: String,
+    video: Video,
+}
+
+/// 视频库列表项：视频本身加上嵌入它的文章。
+#[derive(Debug, Serialize)]
+pub struct VideoEntry {
+    #[serde(flatten)]
+    video: Video,
+    used_by: Vec<UsageRef>,
 }
 
 pub async fn begin_video_upload(
@@ -40,15 +60,32 @@ pub async fn begin_video_upload(
     Ok(Json(ApiResponse::success(session)))
 }
 
+/// 合并分片后登记到视频库，元数据和封面帧由后台任务补上。
 pub async fn complete_video_upload(
     State(storage): State<Arc<R2Storage>>,
+    State(services): State<Services>,
     Json(request): Json<CompleteVideoUploadRequest>,
 ) -> Result<Json<ApiResponse<CompleteVideoUploadResponse>>> {
+    if !request.key.starts_with("videos/") {
+        return Err(AppError::BadRequest("Invalid video object key".to_string()));
+    }
     let public_url = storage
         .complete_upload(&request.key, &request.upload_id, &request.parts)
         .await?;
+    let video = services
+        .video
+        .create(CreateVideo {
+            title: request.title,
+            file_name: request.file_name,
+            r2_key: request.key,
+            public_url: public_url.clone(),
+            content_type: request.content_type,
+            file_size: request.file_size,
+        })
+        .await?;
     Ok(Json(ApiResponse::success(CompleteVideoUploadResponse {
         public_url,
+        video,
     })))
 }
 
@@ -61,3 +98,59 @@ pub async fn abort_video_upload(
         .await?;
     Ok(Json(ApiResponse::success(())))
 }
+
+/// GET /api/admin/videos
+pub async fn list(State(services): State<Services>) -> Result<Json<ApiResponse<Vec<VideoEntry>>>> {
+    let mut usage = services.resource.video_usage().await?;
+    let videos = services
+        .video
+        .list()
+        .await?
+        .into_iter()
+        .map(|video| VideoEntry {
+            used_by: usage.remove(&video.id).unwrap_or_default(),
+            video,
+        })
+        .collect();
+    Ok(Json(ApiResponse::success(videos)))
+}
+
+pub async fn update(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateVideoRequest>,
+) -> Result<Json<ApiResponse<Video>>> {
+    Ok(Json(ApiResponse::success(
+        services.video.update(id, request).await?,
+    )))
+}
+
+/// 还有文章嵌入时拒绝删除，和静态资源的规则一致。
+pub async fn delete_video(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<()>>> {
+    if services
+        .resource
+        .video_usage()
+        .await?
+        .get(&id)
+        .is_some_and(|posts| !posts.is_empty())
+    {
+        return Err(AppError::BadRequest(
+            "Cannot delete a video that is embedded in posts".to_string(),
+        ));
+    }
+    services.video.delete(id).await?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// POST /api/admin/videos/:id/probe —— 重新读取元数据和封面帧。
+pub async fn reprobe(
+    State(services): State<Services>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<Job>>> {
+    Ok(Json(ApiResponse::success(
+        services.video.reprobe(id).await?,
+    )))
+}
@@ -41,9 +41,7 @@ pub async fn admin_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response> {
-    if has_emergency_token(&headers, &app_state.config.jwt.admin_token)
-        || authorized_session(&headers, &app_state.config).is_ok()
-    {
+    if is_admin(&headers, &app_state.config) {
         return Ok(next.run(request).await);
     }
 
@@ -52,6 +50,12 @@ pub async fn admin_middleware(
     ))
 }
 
+/// 公开路由里判断请求是否来自管理员（会话 cookie 或应急 token）。
+pub fn is_admin(headers: &HeaderMap, config: &Config) -> bool {
+    has_emergency_token(headers, &config.jwt.admin_token)
+        || authorized_session(headers, config).is_ok()
+}
+
 pub fn issue_session_token(identity: &AdminIdentity, secret: &str) -> Result<String> {
     let issued_at = unix_timestamp()?;
     let claims = AdminSessionClaims {
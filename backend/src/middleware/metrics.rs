@@ -0,0 +1,92 @@
+//! Prometheus 指标：每个请求按路由模板、方法和状态码类别计数并记录耗时。
+//! 路由用 `MatchedPath`（如 `/api/post/get/:id`），避免按真实路径打标签撑爆基数。
+
+use crate::handlers::health_handler;
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+pub const REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+
+/// 博客接口大多是毫秒级，长尾是转换工具和邮箱读取（几十秒）。
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+fn builder() -> PrometheusBuilder {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(REQUEST_DURATION_METRIC.to_string()),
+            LATENCY_BUCKETS,
+        )
+        .expect("latency buckets are not empty")
+}
+
+/// 安装全局 recorder 并返回渲染用的 handle，可重复调用。
+/// 全局 recorder 已被别处占用时（例如测试）退回一个不接全局宏的 recorder。
+pub fn prometheus_handle() -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        builder()
+            .install_recorder()
+            .unwrap_or_else(|_| builder().build_recorder().handle())
+    })
+}
+
+pub fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "fallback".to_string());
+    let method = request.method().to_string();
+
+    let response = next.run(request).await;
+
+    health_handler::record_request();
+    let labels = [
+        ("route", route),
+        ("method", method),
+        (
+            "status",
+            status_class(response.status().as_u16()).to_string(),
+        ),
+    ];
+    metrics::counter!("http_requests_total", 1, &labels);
+    metrics::histogram!(
+        REQUEST_DURATION_METRIC,
+        start.elapsed().as_secs_f64(),
+        &labels
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_are_grouped_by_class() {
+        assert_eq!(status_class(204), "2xx");
+        assert_eq!(status_class(304), "3xx");
+        assert_eq!(status_class(429), "4xx");
+        assert_eq!(status_class(503), "5xx");
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// 编辑器里的 AI 写作助手能做的事，对应 `/api/admin/ai/:task`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AiTask {
+    Summary,
+    SeoDescription,
+    Tags,
+    Title,
+    Translate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiLanguage {
+    Zh,
+    En,
+}
+
+/// 直接传编辑器里的内容（可能还没保存）。
+#[derive(Debug, Deserialize)]
+pub struct AiAssistRequest {
+    #[serde(default)]
+    pub title: String,
+    pub content: String,
+    /// 仅 translate 需要
+    pub target_language: Option<AiLanguage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagSuggestion {
+    /// 已有标签的 id；None 表示建议新建
+    pub id: Option<i64>,
+    pub name: String,
+}
+
+/// 推给编辑器的 SSE 事件，事件名即 `type`。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AiStreamEvent {
+    Delta {
+        text: String,
+    },
+    Done {
+        text: String,
+        /// tags 任务：解析并匹配已有标签后的结果
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tags: Option<Vec<TagSuggestion>>,
+        /// title 任务：每行一个候选标题
+        #[serde(skip_serializing_if = "Option::is_none")]
+        titles: Option<Vec<String>>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl AiStreamEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Delta { .. } => "delta",
+            Self::Done { .. } => "done",
+            Self::Error { .. } => "error",
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// 前端 `navigator.sendBeacon` 上报的一次页面浏览。
+#[derive(Debug, Deserialize)]
+pub struct PageViewBeacon {
+    pub path: String,
+    /// 进入站点时的 `document.referrer`，站内跳转不带。
+    pub referrer: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalyticsQuery {
+    /// 统计最近多少天（含今天），默认 30，最多 365
+    pub days: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeseriesQuery {
+    /// 指定文章；和 `section` 都不传时是全站
+    pub post_id: Option<i64>,
+    pub section: Option<String>,
+    pub days: Option<u32>,
+}
+
+/// `visitors` 是每天独立访客数之和：访客标识每天轮换，跨天无法去重。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TopPost {
+    pub post_id: i64,
+    pub title: String,
+    pub slug: Option<String>,
+    pub views: i64,
+    pub visitors: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReferrerStat {
+    pub host: String,
+    pub views: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SectionStat {
+    pub section: String,
+    pub views: i64,
+    pub visitors: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct DailyViews {
+    /// UTC 日期，`YYYY-MM-DD`
+    pub day: String,
+    pub views: i64,
+    pub visitors: i64,
+}
@@ -37,9 +37,159 @@ pub struct BookFile {
     pub file_name: String,
     pub file_size: i64,
     pub mime_type: String,
+    /// 从 PDF 读出的页数，EPUB 和旧文件为空
+    pub page_count: Option<i64>,
     pub created_at: DateTime<Utc>,
 }
 
+/// 公开书单里的书：文件只给出格式、文件名和大小，不暴露 R2 地址和 key，
+/// 读取和下载都要通过 `POST /api/books/:book_id/files/:file_id/link` 签发的链接。
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicBook {
+    #[serde(flatten)]
+    pub record: BookRecord,
+    pub files: Vec<PublicBookFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicBookFile {
+    pub id: i64,
+    pub book_id: i64,
+    pub format: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub page_count: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Book> for PublicBook {
+    fn from(book: Book) -> Self {
+        Self {
+            record: book.record,
+            files: book.files.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<BookFile> for PublicBookFile {
+    fn from(file: BookFile) -> Self {
+        Self {
+            id: file.id,
+            book_id: file.book_id,
+            format: file.format,
+            file_name: file.file_name,
+            file_size: file.file_size,
+            mime_type: file.mime_type,
+            page_count: file.page_count,
+            created_at: file.created_at,
+        }
+    }
+}
+
+/// 某个文件读到哪里。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReadingProgress {
+    pub book_file_id: i64,
+    pub book_id: i64,
+    /// EPUB 是 `epubcfi(...)`，PDF 是 `page:N`
+    pub locator: String,
+    /// 0 到 100
+    pub percentage: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveReadingProgressRequest {
+    pub locator: String,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BookHighlight {
+    pub id: i64,
+    pub book_id: i64,
+    pub book_file_id: i64,
+    /// EPUB 是 CFI range，PDF 是 `page:N`
+    pub locator: String,
+    pub chapter: String,
+    pub text: String,
+    pub note: String,
+    pub color: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateHighlightRequest {
+    pub file_id: i64,
+    pub locator: String,
+    #[serde(default)]
+    pub chapter: String,
+    pub text: String,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default = "default_highlight_color")]
+    pub color: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateHighlightRequest {
+    pub note: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HighlightListQuery {
+    pub file_id: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BookFileLinkRequest {
+    /// 下载（attachment）还是在线阅读（inline）
+    #[serde(default)]
+    pub download: bool,
+    /// 把链接绑定到当前客户端 IP
+    #[serde(default)]
+    pub bind_ip: bool,
+}
+
+/// `extract_book_metadata` 任务的参数。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractBookMetadataPayload {
+    pub book_id: i64,
+    pub file_id: i64,
+}
+
+/// 上传完成后从文件里读出的元数据，空书籍字段已按它自动填写，标题等留给管理员决定是否采用。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractedBookMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub page_count: Option<i64>,
+    /// 提取出的封面保存后的地址
+    pub cover_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BookFileLink {
+    /// 公开书籍是后端的签名地址（相对路径），私密书籍是 R2 预签名地址
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 签名链接上的查询参数。
+#[derive(Debug, Default, Deserialize)]
+pub struct SignedFileQuery {
+    pub expires: Option<i64>,
+    #[serde(default)]
+    pub download: bool,
+    #[serde(default)]
+    pub ip: bool,
+    pub sig: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateBookRequest {
     pub title: String,
@@ -79,6 +229,104 @@ pub struct UpdateBookRequest {
     pub download_enabled: Option<bool>,
 }
 
+/// 导入 Goodreads / 豆瓣读书记录时的查询参数。
+#[derive(Debug, Default, Deserialize)]
+pub struct BookImportQuery {
+    /// 只报告会怎么导入，不写数据库
+    #[serde(default)]
+    pub dry_run: bool,
+    /// `goodreads` 或 `douban`，不传时按表头识别
+    pub source: Option<String>,
+    /// 新建的书是否公开，默认不公开
+    #[serde(default)]
+    pub is_public: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BookImportReport {
+    pub dry_run: bool,
+    pub source: String,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub items: Vec<BookImportItem>,
+}
+
+/// 导入文件里每一行的处理结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct BookImportItem {
+    pub line: usize,
+    /// `create`、`update` 或 `skip`
+    pub action: String,
+    pub title: String,
+    pub author: String,
+    /// 已有的书，或非 dry run 时新建的书
+    pub book_id: Option<i64>,
+    /// 更新了哪些字段
+    pub changes: Vec<String>,
+    /// 跳过的原因
+    pub reason: Option<String>,
+}
+
+/// 书架的汇总统计，公开接口只统计公开的书。
+#[derive(Debug, Clone, Serialize)]
+pub struct BookStats {
+    pub total: i64,
+    pub want_to_read: i64,
+    pub reading: i64,
+    pub finished: i64,
+    pub paused: i64,
+    /// 所有打过分的书的平均分
+    pub average_rating: Option<f64>,
+    /// 同时有开始和完成日期的书，从开始到读完的平均天数（当天读完算 1 天）
+    pub average_days_to_finish: Option<f64>,
+    /// 按完成日期统计，新的年份在前
+    pub years: Vec<YearlyReadingCount>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct YearlyReadingCount {
+    pub year: i32,
+    pub finished: i64,
+    /// 1 到 12 月每月读完的本数
+    pub months: Vec<i64>,
+}
+
+/// 某一年的读书报告。
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadingReport {
+    pub year: i32,
+    pub finished: i64,
+    pub months: Vec<i64>,
+    pub average_rating: Option<f64>,
+    pub average_days_to_finish: Option<f64>,
+    /// 有页数的 PDF 加起来的页数
+    pub pages_read: i64,
+    pub fastest: Option<ReportBook>,
+    pub slowest: Option<ReportBook>,
+    /// 按完成日期排序
+    pub books: Vec<ReportBook>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub cover_url: Option<String>,
+    pub rating: Option<i64>,
+    pub started_at: Option<String>,
+    pub finished_at: String,
+    pub days_to_finish: Option<i64>,
+    pub page_count: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BookBadgeQuery {
+    /// 只统计这一年读完的书，不传则统计全部
+    pub year: Option<i32>,
+}
+
 #[derive(Debug)]
 pub struct CreateBookFile {
     pub book_id: i64,
@@ -94,6 +342,10 @@ fn default_reading_status() -> String {
     "want_to_read".to_string()
 }
 
+fn default_highlight_color() -> String {
+    "yellow".to_string()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const COMMENT_PENDING: &str = "pending";
+pub const COMMENT_APPROVED: &str = "approved";
+pub const COMMENT_REJECTED: &str = "rejected";
+pub const COMMENT_SPAM: &str = "spam";
+
+/// 评论完整记录（管理端用，含邮箱和 IP）。`post_id` 为空表示留言板。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Comment {
+    pub id: i64,
+    pub post_id: Option<i64>,
+    pub post_title: Option<String>,
+    pub parent_id: Option<i64>,
+    pub author_name: String,
+    pub author_email: Option<String>,
+    pub author_url: Option<String>,
+    pub email_hash: Option<String>,
+    pub avatar_url: Option<String>,
+    pub content: String,
+    pub status: String,
+    #[sqlx(json)]
+    pub spam_reasons: Vec<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub source: String,
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 公开展示的评论：不含邮箱和 IP，回复嵌套在 `replies` 里。
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicComment {
+    pub id: i64,
+    pub parent_id: Option<i64>,
+    pub author_name: String,
+    pub author_url: Option<String>,
+    pub avatar_url: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub replies: Vec<PublicComment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCommentRequest {
+    pub author_name: String,
+    pub author_email: Option<String>,
+    pub author_url: Option<String>,
+    pub content: String,
+    pub parent_id: Option<i64>,
+    /// 蜜罐字段：页面上隐藏，正常用户不会填写。
+    #[serde(default)]
+    pub website: String,
+}
+
+/// 提交结果；`status` 为 `pending` 时前端提示等待审核。
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentSubmitted {
+    pub id: i64,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommentListQuery {
+    /// pending（默认）、approved、rejected、spam 或 all
+    pub status: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BanCommenterRequest {
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// GitHub Discussions 导出（giscus 的数据源）。既接受讨论数组，也接受
+/// `{ "discussions": ... }`；列表字段可以是数组或 GraphQL 的 `{ "nodes": [...] }`。
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum GiscusExport {
+    Discussions(Vec<GiscusDiscussion>),
+    Wrapped {
+        discussions: GiscusNodes<GiscusDiscussion>,
+    },
+}
+
+impl GiscusExport {
+    pub fn into_discussions(self) -> Vec<GiscusDiscussion> {
+        match self {
+            Self::Discussions(discussions) => discussions,
+            Self::Wrapped { discussions } => discussions.into_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum GiscusNodes<T> {
+    List(Vec<T>),
+    Connection { nodes: Vec<T> },
+}
+
+impl<T> Default for GiscusNodes<T> {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+impl<T> GiscusNodes<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::List(items) | Self::Connection { nodes: items } => items,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GiscusDiscussion {
+    /// giscus 用 `mapping="specific"`：文章讨论的标题是文章标题，留言板是 `guestbook`。
+    pub title: String,
+    #[serde(default)]
+    pub comments: GiscusNodes<GiscusComment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiscusComment {
+    pub id: String,
+    /// 已注销的 GitHub 用户为 null
+    pub author: Option<GiscusAuthor>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub is_minimized: bool,
+    #[serde(default)]
+    pub replies: GiscusNodes<GiscusComment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiscusAuthor {
+    pub login: String,
+    pub avatar_url: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct GiscusImportSummary {
+    pub imported: usize,
+    /// 已导入过或在 GitHub 上被折叠的评论
+    pub skipped: usize,
+    /// 找不到对应文章的讨论标题
+    pub unmatched_threads: Vec<String>,
+}
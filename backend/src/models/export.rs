@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+/// 管理端批量导出 EPUB：`post_ids`、`category_id`、`tag_ids`、`series_id` 四选一。
+#[derive(Debug, Default, Deserialize)]
+pub struct EpubExportRequest {
+    /// 按给定顺序导出这些文章
+    #[serde(default)]
+    pub post_ids: Vec<i64>,
+    pub category_id: Option<i64>,
+    /// 带任一标签的文章
+    #[serde(default)]
+    pub tag_ids: Vec<i64>,
+    pub series_id: Option<i64>,
+    /// 书名，默认按所选内容生成
+    pub title: Option<String>,
+    /// 同时导出草稿、私密和定时文章
+    #[serde(default)]
+    pub include_unpublished: bool,
+}
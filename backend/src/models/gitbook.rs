@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 提交转换的请求体，也原样存为任务的 payload。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Gitbook2EpubRequest {
+    pub url: String,
+    /// 是否下载并内嵌图片。默认 false：图片是这台小内存机器的主要内存压力来源，
+    /// 默认跳过可保证转换又快又稳，需要图文版时再显式开启。
+    #[serde(default)]
+    pub include_images: bool,
+}
+
+/// 转换成功后存进 `jobs.result`。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Gitbook2EpubResult {
+    pub file_name: String,
+    pub size: u64,
+    /// 过期后结果文件会被清理，需要重新提交
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 提交和轮询返回的转换状态。`job_id` 即任务令牌。
+#[derive(Debug, Clone, Serialize)]
+pub struct Gitbook2EpubJob {
+    pub job_id: String,
+    pub status: String,
+    /// 前面还有几个转换在排队或执行；已开始或已结束时为 0
+    pub queue_position: i64,
+    pub progress: f64,
+    pub progress_message: Option<String>,
+    pub error: Option<String>,
+    pub result: Option<Gitbook2EpubResult>,
+    pub download_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const JOB_QUEUED: &str = "queued";
+pub const JOB_RUNNING: &str = "running";
+pub const JOB_SUCCEEDED: &str = "succeeded";
+pub const JOB_FAILED: &str = "failed";
+pub const JOB_CANCELLED: &str = "cancelled";
+
+/// 后台任务的种类；`jobs.kind` 存 snake_case 名称。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// 把上传目录里的 JPG/PNG/GIF 批量转成 WebP
+    OptimizeImages,
+    /// 抓取在线书并打包成 EPUB（gitbook2epub 工具）
+    #[serde(rename = "gitbook2epub")]
+    Gitbook2Epub,
+    /// 用 ffprobe 读视频的时长、分辨率和编码，并截一帧做封面
+    ProbeVideo,
+    /// 从刚上传的 EPUB/PDF 里读出作者、简介、页数和封面
+    ExtractBookMetadata,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 4] = [
+        JobKind::OptimizeImages,
+        JobKind::Gitbook2Epub,
+        JobKind::ProbeVideo,
+        JobKind::ExtractBookMetadata,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OptimizeImages => "optimize_images",
+            Self::Gitbook2Epub => "gitbook2epub",
+            Self::ProbeVideo => "probe_video",
+            Self::ExtractBookMetadata => "extract_book_metadata",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+
+    /// 同时最多执行几个，`None` 表示只受 worker 数限制。由 `JobService::claim` 保证：
+    /// 达到上限时跳过这种任务去领别的，不让 worker 干等。
+    pub fn max_running(self) -> Option<i64> {
+        match self {
+            // 保护小内存机器，防止 OOM 拖垮博客；提取元数据要把整本书读进内存
+            Self::Gitbook2Epub | Self::ExtractBookMetadata => Some(1),
+            Self::OptimizeImages | Self::ProbeVideo => None,
+        }
+    }
+
+    /// 含首次执行在内最多跑几次。
+    pub fn max_attempts(self) -> i64 {
+        match self {
+            Self::OptimizeImages => 2,
+            // 失败多半是链接本身的问题，重跑也一样，还白占唯一的转换槽位
+            Self::Gitbook2Epub => 1,
+            // R2 刚完成分片合并时偶尔读不到，重试一次
+            Self::ProbeVideo | Self::ExtractBookMetadata => 2,
+        }
+    }
+}
+
+/// 任务完整记录（管理端用）。`payload`/`result` 是任务种类自己定义的 JSON。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Job {
+    pub id: i64,
+    pub token: String,
+    pub kind: String,
+    #[sqlx(json)]
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub progress: f64,
+    pub progress_message: Option<String>,
+    #[sqlx(json)]
+    pub result: serde_json::Value,
+    pub error: Option<String>,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub run_at: DateTime<Utc>,
+    pub cancel_requested: bool,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub dedup_key: Option<String>,
+}
+
+impl Job {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            JOB_SUCCEEDED | JOB_FAILED | JOB_CANCELLED
+        )
+    }
+}
+
+/// 按令牌公开轮询时返回的任务状态：不含自增 id 和请求参数。
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicJob {
+    pub token: String,
+    pub kind: String,
+    pub status: String,
+    pub progress: f64,
+    pub progress_message: Option<String>,
+    pub result: serde_json::Value,
+    pub error: Option<String>,
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<Job> for PublicJob {
+    fn from(job: Job) -> Self {
+        Self {
+            token: job.token,
+            kind: job.kind,
+            status: job.status,
+            progress: job.progress,
+            progress_message: job.progress_message,
+            result: job.result,
+            error: job.error,
+            attempts: job.attempts,
+            created_at: job.created_at,
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnqueueJobRequest {
+    pub kind: JobKind,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobListQuery {
+    pub status: Option<String>,
+    pub kind: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
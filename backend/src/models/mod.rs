@@ -1,21 +1,41 @@
 pub mod about;
+pub mod ai;
+pub mod analytics;
 pub mod book;
 pub mod category;
 pub mod changelog;
+pub mod comment;
 pub mod download;
+pub mod export;
+pub mod gitbook;
+pub mod job;
 pub mod music;
 pub mod pdf;
 pub mod post;
 pub mod response;
+pub mod search;
+pub mod series;
 pub mod tag;
+pub mod translation;
+pub mod video;
 
 pub use about::*;
+pub use ai::*;
+pub use analytics::*;
 pub use book::*;
 pub use category::*;
 pub use changelog::*;
+pub use comment::*;
 pub use download::*;
+pub use export::*;
+pub use gitbook::*;
+pub use job::*;
 pub use music::*;
 pub use pdf::*;
 pub use post::*;
 pub use response::*;
+pub use search::*;
+pub use series::*;
 pub use tag::*;
+pub use translation::*;
+pub use video::*;
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::utils::markdown::RenderedMarkdown;
+
 #[derive(Debug, Clone, Default)]
 pub enum NullablePatch<T> {
     #[default]
@@ -35,14 +37,16 @@ impl<T> NullablePatch<T> {
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Post {
     pub id: i64,
+    pub slug: Option<String>,
     pub title: String,
     pub cover_url: Option<String>,
     pub content: String,
     pub category_name: Option<String>,
     pub category_id: Option<i64>,
     pub status: i32,
-    pub post_images: Option<String>, // JSON array of image URLs
-    pub pdf_url: Option<String>,     // PDF file URL
+    pub post_images: Option<String>,       // JSON array of image URLs
+    pub pdf_url: Option<String>,           // PDF file URL
+    pub publish_at: Option<DateTime<Utc>>, // 定时发布时间，仅 Scheduled 状态下有值
     #[serde(default)]
     #[sqlx(skip)]
     pub tags: Vec<super::tag::Tag>, // 文章标签列表
@@ -57,6 +61,7 @@ pub enum PostStatus {
     Published = 1,
     Deleted = 2,
     Private = 3,
+    Scheduled = 4,
 }
 
 impl<'de> serde::Deserialize<'de> for PostStatus {
@@ -84,10 +89,12 @@ impl<'de> serde::Deserialize<'de> for PostStatus {
                     "Published" => Ok(PostStatus::Published),
                     "Deleted" => Ok(PostStatus::Deleted),
                     "Private" => Ok(PostStatus::Private),
+                    "Scheduled" => Ok(PostStatus::Scheduled),
                     "0" => Ok(PostStatus::Draft),
                     "1" => Ok(PostStatus::Published),
                     "2" => Ok(PostStatus::Deleted),
                     "3" => Ok(PostStatus::Private),
+                    "4" => Ok(PostStatus::Scheduled),
                     _ => Err(Error::unknown_variant(
                         value,
                         &[
@@ -95,10 +102,12 @@ impl<'de> serde::Deserialize<'de> for PostStatus {
                             "Published",
                             "Deleted",
                             "Private",
+                            "Scheduled",
                             "0",
                             "1",
                             "2",
                             "3",
+                            "4",
                         ],
                     )),
                 }
@@ -113,9 +122,10 @@ impl<'de> serde::Deserialize<'de> for PostStatus {
                     1 => Ok(PostStatus::Published),
                     2 => Ok(PostStatus::Deleted),
                     3 => Ok(PostStatus::Private),
+                    4 => Ok(PostStatus::Scheduled),
                     _ => Err(Error::invalid_value(
                         serde::de::Unexpected::Signed(value),
-                        &"0, 1, 2, or 3",
+                        &"0, 1, 2, 3, or 4",
                     )),
                 }
             }
@@ -139,6 +149,7 @@ impl From<i32> for PostStatus {
             1 => PostStatus::Published,
             2 => PostStatus::Deleted,
             3 => PostStatus::Private,
+            4 => PostStatus::Scheduled,
             _ => PostStatus::Draft,
         }
     }
@@ -159,6 +170,7 @@ pub struct CreatePostRequest {
     pub status: Option<PostStatus>,
     pub post_images: Option<Vec<String>>,
     pub pdf_url: Option<String>,
+    pub publish_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub tag_ids: Option<Vec<i64>>,
 }
@@ -177,9 +189,27 @@ pub struct UpdatePostRequest {
     #[serde(default)]
     pub pdf_url: NullablePatch<String>,
     #[serde(default)]
+    pub publish_at: NullablePatch<DateTime<Utc>>,
+    /// 新的 URL slug；改名后旧 slug 会 301 到新 slug。
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
     pub tag_ids: Option<Vec<i64>>,
 }
 
+/// 文章详情：原始 Markdown（`content`）之外附带服务端渲染的 HTML、目录和字数。
+#[derive(Debug, Clone, Serialize)]
+pub struct PostDetail {
+    #[serde(flatten)]
+    pub post: Post,
+    #[serde(flatten)]
+    pub rendered: RenderedMarkdown,
+    /// 实际返回的语言；请求的译文不存在时是原文语言
+    pub locale: super::translation::Locale,
+    /// 这篇文章有哪些语言版本（含原文）
+    pub available_locales: Vec<super::translation::Locale>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct PostWithTags {
     #[serde(flatten)]
@@ -197,13 +227,18 @@ pub struct PostWithDetails {
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct AdjacentPost {
     pub id: i64,
+    pub slug: Option<String>,
     pub title: String,
 }
 
+/// 相邻文章。文章属于某个系列时，`older`/`newer` 是系列里的上一篇/下一篇，
+/// 并带上 `series`；否则按发布时间取相邻的文章。
 #[derive(Debug, Clone, Serialize)]
 pub struct AdjacentPosts {
     pub newer: Option<AdjacentPost>,
     pub older: Option<AdjacentPost>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<super::series::PostSeriesInfo>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -216,6 +251,27 @@ pub struct PostListQuery {
     pub tag_id: Option<i64>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrashListQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+/// 回收站条目；`purge_at` 之后会被后台任务彻底删除（连同资源文件）。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TrashedPost {
+    pub id: i64,
+    pub slug: Option<String>,
+    pub title: String,
+    pub cover_url: Option<String>,
+    pub category_id: Option<i64>,
+    pub previous_status: Option<i32>,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 impl Default for PostListQuery {
     fn default() -> Self {
         Self {
@@ -228,3 +284,75 @@ impl Default for PostListQuery {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PostRevision {
+    pub id: i64,
+    pub post_id: i64,
+    pub title: String,
+    pub content: String,
+    pub cover_url: Option<String>,
+    pub category_id: Option<i64>,
+    #[sqlx(json)]
+    pub tag_ids: Vec<i64>,
+    pub status: i32,
+    pub post_images: Option<String>,
+    pub pdf_url: Option<String>,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 修订列表项：不带正文，避免长文章的历史列表过大。
+#[derive(Debug, Clone, Serialize)]
+pub struct PostRevisionSummary {
+    pub id: i64,
+    pub post_id: i64,
+    pub title: String,
+    pub status: i32,
+    pub content_chars: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&PostRevision> for PostRevisionSummary {
+    fn from(revision: &PostRevision) -> Self {
+        Self {
+            id: revision.id,
+            post_id: revision.post_id,
+            title: revision.title.clone(),
+            status: revision.status,
+            content_chars: revision.content.chars().count(),
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i64,
+    pub to: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PostRevisionDiff {
+    pub from: PostRevisionSummary,
+    pub to: PostRevisionSummary,
+    /// 除正文外发生变化的字段名（title、cover_url、category_id、tag_ids、status、pdf_url）。
+    pub changed_fields: Vec<&'static str>,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    /// `context`、`insert` 或 `delete`
+    pub kind: &'static str,
+    pub text: String,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+/// 一条搜索命中。`title_highlight` 与 `snippet` 已做 HTML 转义，命中词包在 `<mark>` 中。
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: i64,
+    pub slug: Option<String>,
+    pub title: String,
+    pub title_highlight: String,
+    pub snippet: String,
+    pub cover_url: Option<String>,
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>,
+    pub tags: Vec<String>,
+    pub rank: f64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
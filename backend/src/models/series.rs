@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 系列（多篇连载的教程等）。`part_count` 在公开接口里只数已发布的文章。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Series {
+    pub id: i64,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub part_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 系列里的一篇；`part` 从 1 开始，按可见的文章重新编号。
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SeriesPart {
+    #[sqlx(default)]
+    pub part: i64,
+    pub id: i64,
+    pub slug: Option<String>,
+    pub title: String,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesDetail {
+    #[serde(flatten)]
+    pub series: Series,
+    pub parts: Vec<SeriesPart>,
+}
+
+/// 一篇文章在所属系列中的位置（已发布的文章里第几篇、共几篇）。
+#[derive(Debug, Clone, Serialize)]
+pub struct PostSeriesInfo {
+    pub id: i64,
+    pub slug: String,
+    pub title: String,
+    pub part: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSeriesRequest {
+    pub title: String,
+    /// 不填时按标题生成
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateSeriesRequest {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+}
+
+/// 整体替换系列的文章及顺序；已在其它系列里的文章会被移过来。
+#[derive(Debug, Deserialize)]
+pub struct SetSeriesPostsRequest {
+    pub post_ids: Vec<i64>,
+}
+
+/// 系列内的上一篇/下一篇，供 `/api/post/adjacent/:id` 替换按时间排序的结果。
+#[derive(Debug, Clone)]
+pub struct SeriesNavigation {
+    pub series: PostSeriesInfo,
+    pub previous: Option<super::post::AdjacentPost>,
+    pub next: Option<super::post::AdjacentPost>,
+}
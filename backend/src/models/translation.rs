@@ -0,0 +1,83 @@
+use crate::utils::text::{truncate_safely, SUMMARY_CHARS};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 站点支持的语言。文章正文（posts 表）默认是中文，其它语言存成译文。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Zh,
+    En,
+}
+
+pub const DEFAULT_LOCALE: Locale = Locale::Zh;
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::Zh, Locale::En];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Zh => "zh",
+            Self::En => "en",
+        }
+    }
+
+    /// `<link hreflang>`、JSON-LD `inLanguage` 用的语言标签。
+    pub fn hreflang(self) -> &'static str {
+        match self {
+            Self::Zh => "zh-CN",
+            Self::En => "en",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PostTranslation {
+    pub post_id: i64,
+    pub locale: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertTranslationRequest {
+    pub title: String,
+    pub content: String,
+}
+
+/// 公开接口的 `?lang=`；不认识的语言和没有对应译文时都回退到原文。
+#[derive(Debug, Default, Deserialize)]
+pub struct LangQuery {
+    pub lang: Option<String>,
+}
+
+impl LangQuery {
+    pub fn locale(&self) -> Locale {
+        self.lang
+            .as_deref()
+            .and_then(Locale::parse)
+            .unwrap_or(DEFAULT_LOCALE)
+    }
+}
+
+impl PostTranslation {
+    /// 用译文覆盖文章的标题和正文，其余字段（slug、封面、标签等）沿用原文。
+    pub fn apply_to(&self, post: &mut super::Post) {
+        post.title = self.title.clone();
+        post.content = self.content.clone();
+    }
+
+    /// 列表用：正文和原文一样只给摘要。
+    pub fn apply_summary_to(&self, post: &mut super::Post) {
+        post.title = self.title.clone();
+        post.content = truncate_safely(&self.content, SUMMARY_CHARS);
+    }
+}
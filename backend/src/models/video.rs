@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const PROBE_PENDING: &str = "pending";
+pub const PROBE_READY: &str = "ready";
+pub const PROBE_FAILED: &str = "failed";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Video {
+    pub id: i64,
+    pub title: String,
+    pub file_name: String,
+    pub r2_key: String,
+    pub public_url: String,
+    pub content_type: String,
+    pub file_size: i64,
+    /// 秒，探测完成前为空
+    pub duration: Option<f64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub codec: Option<String>,
+    pub poster_url: Option<String>,
+    /// `pending`、`ready` 或 `failed`
+    pub probe_status: String,
+    pub probe_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateVideo {
+    pub title: String,
+    pub file_name: String,
+    pub r2_key: String,
+    pub public_url: String,
+    pub content_type: String,
+    pub file_size: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateVideoRequest {
+    pub title: Option<String>,
+    pub poster_url: Option<Option<String>>,
+}
+
+/// `probe_video` 任务的参数。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeVideoPayload {
+    pub video_id: i64,
+}
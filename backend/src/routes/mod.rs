@@ -1,12 +1,15 @@
 use crate::config::Config;
 use crate::database::Database;
 use crate::handlers::{
-    about_handler, auth_handler, book_handler, category_handler, changelog_handler,
-    download_handler, health_handler, mail_handler, music_handler, pdf_handler, post_handler,
-    quant_handler, resource_handler, seo_handler, tag_handler, tools_handler, video_handler,
+    about_handler, ai_handler, analytics_handler, auth_handler, book_handler, category_handler,
+    changelog_handler, comment_handler, download_handler, epub_handler, export_handler,
+    health_handler, job_handler, mail_handler, music_handler, opds_handler, pdf_handler,
+    post_handler, quant_handler, resource_handler, search_handler, seo_handler, series_handler,
+    tag_handler, tools_handler, translation_handler, video_handler,
 };
 use crate::middleware::auth::admin_middleware;
-use crate::services::Services;
+use crate::middleware::metrics::{prometheus_handle, track_metrics};
+use crate::services::{scheduler, Services};
 use crate::utils::{FileHandler, R2Storage};
 use axum::{
     extract::FromRef,
@@ -57,6 +60,7 @@ impl FromRef<AppState> for Services {
 
 pub async fn create_app(database: Database, config: &Config) -> Router {
     let config = Arc::new(config.clone());
+    prometheus_handle();
     let file_handler = Arc::new(FileHandler::new(
         config.storage.upload_dir.clone(),
         config.storage.max_file_size,
@@ -67,7 +71,24 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
         database.clone(),
         file_handler.clone(),
         config.storage.upload_dir.clone(),
+        config.ai.clone(),
+        r2_storage.clone(),
     );
+    match services.post.backfill_slugs().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Generated slugs for {} posts", count),
+        Err(error) => tracing::warn!("Failed to generate missing post slugs: {}", error),
+    }
+    scheduler::spawn_post_publisher(services.post.clone());
+    scheduler::spawn_trash_sweeper(services.post.clone());
+    scheduler::spawn_analytics_flusher(services.analytics.clone());
+    match services.jobs.recover().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Requeued {} interrupted background jobs", count),
+        Err(error) => tracing::warn!("Failed to recover background jobs: {}", error),
+    }
+    scheduler::spawn_job_workers(services.clone(), scheduler::JOB_WORKERS);
+    scheduler::spawn_gitbook2epub_sweeper(services.gitbook2epub.clone());
     let app_state = AppState {
         database,
         config,
@@ -100,10 +121,30 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
             get(post_handler::list_posts_with_details),
         )
         .route("/api/post/get/:id", get(post_handler::get_post))
+        .route(
+            "/api/post/by-slug/:slug",
+            get(post_handler::get_post_by_slug),
+        )
         .route(
             "/api/post/adjacent/:id",
             get(post_handler::get_adjacent_posts),
         )
+        .route("/api/post/:id/export.epub", get(export_handler::post_epub))
+        // Full-text search over published posts
+        .route("/api/search", get(search_handler::search_posts))
+        // Series (ordered multi-part collections)
+        .route("/api/series", get(series_handler::list_public))
+        .route("/api/series/:key", get(series_handler::get_public))
+        .route(
+            "/api/series/:key/export.epub",
+            get(export_handler::series_epub),
+        )
+        // Background job status, polled with the token returned on enqueue
+        .route(
+            "/api/jobs/:token",
+            get(job_handler::status).delete(job_handler::cancel_by_token),
+        )
+        .route("/api/jobs/:token/result", get(job_handler::result))
         // Music public routes
         .route("/api/music/list", get(music_handler::list_music))
         .route("/api/music/get/:id", get(music_handler::get_music))
@@ -133,13 +174,60 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
         .route("/api/about/get", get(about_handler::get_about))
         // Books and site changelog
         .route("/api/books", get(book_handler::list_public))
+        .route("/api/books/stats", get(book_handler::stats_public))
+        .route("/api/books/stats/badge.svg", get(book_handler::stats_badge))
+        .route("/api/books/reports/:year", get(book_handler::report_public))
+        .route(
+            "/api/books/:book_id/files/:file_id/link",
+            post(book_handler::create_file_link),
+        )
         .route(
             "/api/books/:book_id/files/:file_id/content",
             get(book_handler::read_file),
         )
+        // Chapter-level EPUB reader
+        .route(
+            "/api/books/:book_id/files/:file_id/epub",
+            get(epub_handler::contents),
+        )
+        .route(
+            "/api/books/:book_id/files/:file_id/epub/chapters/*path",
+            get(epub_handler::chapter),
+        )
+        .route(
+            "/api/books/:book_id/files/:file_id/epub/resources/*path",
+            get(epub_handler::resource),
+        )
+        // OPDS catalog for ebook reader apps
+        .route("/api/opds", get(opds_handler::root))
+        .route("/api/opds/books", get(opds_handler::books))
+        .route("/api/opds/opensearch.xml", get(opds_handler::opensearch))
+        .route(
+            "/api/opds/books/:book_id/files/:file_id",
+            get(opds_handler::download),
+        )
         .route("/api/changelog", get(changelog_handler::list_public))
+        // Native comments (post threads and the guestbook)
+        .route(
+            "/api/comments/post/:post_id",
+            get(comment_handler::list_post_comments).post(comment_handler::create_post_comment),
+        )
+        .route(
+            "/api/comments/guestbook",
+            get(comment_handler::list_guestbook).post(comment_handler::create_guestbook_comment),
+        )
+        // Cookie-free page view beacon
+        .route("/api/analytics/collect", post(analytics_handler::collect))
         // Online tools
         .route("/api/tools/gitbook2epub", post(tools_handler::gitbook2epub))
+        .route(
+            "/api/tools/gitbook2epub/:job_id",
+            get(tools_handler::gitbook2epub_status),
+        )
+        .route(
+            "/api/tools/gitbook2epub/:job_id/download",
+            get(tools_handler::gitbook2epub_download),
+        )
         // 邮箱阅读（IMAP）：凭据由请求当场传入，服务端零存储、地址白名单。
         .route("/api/mail/list", post(mail_handler::list))
         .route("/api/mail/body", post(mail_handler::body))
@@ -148,16 +236,106 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
 
     // Admin routes (authentication required)
     let admin_routes = Router::new()
+        // Prometheus scrape endpoint (use the admin bearer token in the scrape config)
+        .route("/metrics", get(health_handler::prometheus_metrics))
         // Dashboard stats
         .route(
             "/api/admin/dashboard/stats",
             get(health_handler::get_dashboard_stats),
         )
+        // AI writing assistant (streams SSE to the editor)
+        .route("/api/admin/ai/:task", post(ai_handler::assist))
+        // Page view analytics
+        .route(
+            "/api/admin/analytics/top-posts",
+            get(analytics_handler::top_posts),
+        )
+        .route(
+            "/api/admin/analytics/referrers",
+            get(analytics_handler::top_referrers),
+        )
+        .route(
+            "/api/admin/analytics/sections",
+            get(analytics_handler::sections),
+        )
+        .route(
+            "/api/admin/analytics/timeseries",
+            get(analytics_handler::timeseries),
+        )
         .route(
             "/api/admin/posts",
             get(post_handler::admin_list_posts_with_details),
         )
         .route("/api/admin/posts/:id", get(post_handler::admin_get_post))
+        // Post trash bin
+        .route("/api/admin/posts/trash", get(post_handler::list_trash))
+        .route(
+            "/api/admin/posts/trash/:id",
+            delete(post_handler::purge_post),
+        )
+        .route(
+            "/api/admin/posts/trash/:id/restore",
+            post(post_handler::restore_post_from_trash),
+        )
+        // Post revision history
+        .route(
+            "/api/admin/posts/:id/revisions",
+            get(post_handler::list_post_revisions),
+        )
+        .route(
+            "/api/admin/posts/:id/revisions/diff",
+            get(post_handler::diff_post_revisions),
+        )
+        .route(
+            "/api/admin/posts/:id/revisions/:revision_id",
+            get(post_handler::get_post_revision),
+        )
+        .route(
+            "/api/admin/posts/:id/revisions/:revision_id/restore",
+            post(post_handler::restore_post_revision),
+        )
+        // Series management
+        .route(
+            "/api/admin/series",
+            get(series_handler::list_admin).post(series_handler::create),
+        )
+        .route(
+            "/api/admin/series/:id",
+            get(series_handler::get_admin)
+                .put(series_handler::update)
+                .delete(series_handler::delete_series),
+        )
+        .route(
+            "/api/admin/series/:id/posts",
+            put(series_handler::set_posts),
+        )
+        // EPUB export
+        .route("/api/admin/export/epub", post(export_handler::bulk_epub))
+        // Background jobs
+        .route(
+            "/api/admin/jobs",
+            get(job_handler::list).post(job_handler::enqueue),
+        )
+        .route("/api/admin/jobs/:id", get(job_handler::get))
+        .route("/api/admin/jobs/:id/cancel", post(job_handler::cancel))
+        // Post translations (the post itself is the default locale)
+        .route(
+            "/api/admin/posts/:id/translations",
+            get(translation_handler::list_translations),
+        )
+        .route(
+            "/api/admin/posts/:id/translations/:locale",
+            get(translation_handler::get_translation)
+                .put(translation_handler::upsert_translation)
+                .delete(translation_handler::delete_translation),
+        )
+        // Video library (uploaded straight to R2)
+        .route("/api/admin/videos", get(video_handler::list))
+        .route(
+            "/api/admin/videos/:id",
+            put(video_handler::update).delete(video_handler::delete_video),
+        )
+        .route("/api/admin/videos/:id/probe", post(video_handler::reprobe))
         .route(
             "/api/admin/videos/multipart",
             post(video_handler::begin_video_upload),
@@ -179,6 +357,15 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
             "/api/admin/books/:id",
             put(book_handler::update).delete(book_handler::delete_book),
         )
+        .route(
+            "/api/admin/books/import",
+            post(book_handler::import_history),
+        )
+        .route("/api/admin/books/stats", get(book_handler::stats_admin))
+        .route(
+            "/api/admin/books/reports/:year",
+            get(book_handler::report_admin),
+        )
         .route(
             "/api/admin/books/:id/files/multipart",
             post(book_handler::begin_file_upload),
@@ -195,6 +382,27 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
             "/api/admin/books/files/:id",
             delete(book_handler::delete_file),
         )
+        // Reading progress and highlights
+        .route(
+            "/api/admin/books/:id/progress",
+            get(book_handler::list_progress),
+        )
+        .route(
+            "/api/admin/books/:id/files/:file_id/progress",
+            put(book_handler::save_progress),
+        )
+        .route(
+            "/api/admin/books/:id/highlights",
+            get(book_handler::list_highlights).post(book_handler::create_highlight),
+        )
+        .route(
+            "/api/admin/books/highlights/:id",
+            put(book_handler::update_highlight).delete(book_handler::delete_highlight),
+        )
+        .route(
+            "/api/admin/books/:id/notes.md",
+            get(book_handler::export_notes),
+        )
         // Changelog management
         .route(
             "/api/admin/changelog",
@@ -204,6 +412,25 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
             "/api/admin/changelog/:id",
             put(changelog_handler::update).delete(changelog_handler::delete_entry),
         )
+        // Comment moderation
+        .route("/api/admin/comments", get(comment_handler::list_admin))
+        .route(
+            "/api/admin/comments/import/giscus",
+            post(comment_handler::import_giscus),
+        )
+        .route(
+            "/api/admin/comments/:id",
+            delete(comment_handler::delete_comment),
+        )
+        .route(
+            "/api/admin/comments/:id/approve",
+            post(comment_handler::approve),
+        )
+        .route(
+            "/api/admin/comments/:id/reject",
+            post(comment_handler::reject),
+        )
+        .route("/api/admin/comments/:id/ban", post(comment_handler::ban))
         // Post admin routes
         .route("/api/post/create", post(post_handler::create_post))
         .route("/api/post/update/:id", put(post_handler::update_post))
@@ -292,5 +519,6 @@ pub async fn create_app(database: Database, config: &Config) -> Router {
         .merge(public_routes)
         .merge(admin_routes)
         .fallback(seo_handler::spa_fallback)
+        .layer(middleware::from_fn(track_metrics))
         .with_state(app_state)
 }
@@ -0,0 +1,361 @@
+//! 编辑器的 AI 写作助手：摘要、SEO 描述、标签建议、标题候选和中英互译。
+//! 调用 OpenAI 兼容的 DeepSeek Chat Completions 接口（`stream: true`），
+//! 把上游的增量文本原样转成事件推给编辑器，结束时再附上整理好的结果。
+
+use crate::config::AiConfig;
+use crate::database::{repositories::TagRepository, Database};
+use crate::models::{AiAssistRequest, AiLanguage, AiStreamEvent, AiTask, Tag, TagSuggestion};
+use crate::utils::error::{AppError, Result};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 摘要、标签这类任务只看开头就够了，也省 token。
+const MAX_CONTEXT_CHARS: usize = 12_000;
+/// 翻译要整篇送出，太长的文章请分段翻译。
+const MAX_TRANSLATE_CHARS: usize = 30_000;
+const MAX_TAG_SUGGESTIONS: usize = 8;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 上游两段输出之间最多等这么久，超时按失败处理。
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct AiService {
+    database: Database,
+    client: reqwest::Client,
+    config: AiConfig,
+}
+
+impl AiService {
+    pub fn new(database: Database, config: AiConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            database,
+            client,
+            config,
+        }
+    }
+
+    /// 发起请求并返回事件流。配置缺失、参数不对或上游拒绝时直接返回错误，
+    /// 开始输出之后的失败以 `Error` 事件结束流。
+    pub async fn assist(
+        &self,
+        task: AiTask,
+        request: AiAssistRequest,
+    ) -> Result<mpsc::Receiver<AiStreamEvent>> {
+        if self.config.deepseek_api_key.trim().is_empty() {
+            return Err(AppError::BadRequest(
+                "AI assistant is not configured (DEEPSEEK_API_KEY)".to_string(),
+            ));
+        }
+        let content = request.content.trim();
+        if content.is_empty() {
+            return Err(AppError::Validation("内容不能为空".to_string()));
+        }
+        let existing_tags = match task {
+            AiTask::Tags => TagRepository::list(self.database.pool()).await?,
+            _ => Vec::new(),
+        };
+        let (system, temperature) = match task {
+            AiTask::Summary => (
+                "你是博客编辑。用 2 到 3 句话概括文章要点，使用文章本身的语言，只输出摘要正文。"
+                    .to_string(),
+                0.7,
+            ),
+            AiTask::SeoDescription => (
+                "你是 SEO 编辑。为文章写一段不超过 150 个字符的 meta description，使用文章本身的语言，\
+                 准确概括内容、自然包含关键词，只输出描述本身，不要引号。"
+                    .to_string(),
+                0.7,
+            ),
+            AiTask::Tags => (
+                format!(
+                    "你是博客编辑。为文章挑选 3 到 5 个标签，优先从已有标签中选择，确实没有合适的才提出新标签。\
+                     只输出用逗号分隔的标签名。\n已有标签：{}",
+                    existing_tags
+                        .iter()
+                        .map(|tag| tag.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                0.3,
+            ),
+            AiTask::Title => (
+                "你是博客编辑。为文章给出 5 个候选标题，使用文章本身的语言，简洁具体，\
+                 每行一个，不要编号和引号。"
+                    .to_string(),
+                1.0,
+            ),
+            AiTask::Translate => {
+                let target = match request.target_language {
+                    Some(AiLanguage::En) => "English",
+                    Some(AiLanguage::Zh) => "简体中文",
+                    None => {
+                        return Err(AppError::Validation(
+                            "翻译需要指定 target_language（zh 或 en）".to_string(),
+                        ))
+                    }
+                };
+                if content.chars().count() > MAX_TRANSLATE_CHARS {
+                    return Err(AppError::Validation(format!(
+                        "文章超过 {MAX_TRANSLATE_CHARS} 字，请分段翻译"
+                    )));
+                }
+                (
+                    format!(
+                        "你是技术博客译者。把用户给出的 Markdown 文章翻译成{target}。保留 Markdown 结构、\
+                         代码块、行内代码、链接地址和图片地址不变，术语准确、行文自然，只输出译文。"
+                    ),
+                    0.3,
+                )
+            }
+        };
+        let user = match task {
+            AiTask::Translate => content.to_string(),
+            _ => {
+                let excerpt: String = content.chars().take(MAX_CONTEXT_CHARS).collect();
+                format!("标题：{}\n\n{}", request.title.trim(), excerpt)
+            }
+        };
+
+        let response = self
+            .client
+            .post(chat_completions_url(&self.config.deepseek_api_url))
+            .bearer_auth(self.config.deepseek_api_key.trim())
+            .json(&json!({
+                "model": self.config.deepseek_model,
+                "stream": true,
+                "temperature": temperature,
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": user },
+                ],
+            }))
+            .send()
+            .await
+            .map_err(|error| AppError::Upstream(format!("AI 服务连接失败: {error}")))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            tracing::warn!("AI upstream returned {}: {}", status, body);
+            return Err(AppError::Upstream(format!(
+                "AI 服务返回错误 ({})",
+                status.as_u16()
+            )));
+        }
+
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(relay(response, task, existing_tags, tx));
+        Ok(rx)
+    }
+}
+
+/// 读上游 SSE，逐段转发；编辑器断开（发送失败）时停止读取，连带断开上游。
+async fn relay(
+    mut response: reqwest::Response,
+    task: AiTask,
+    existing_tags: Vec<Tag>,
+    tx: mpsc::Sender<AiStreamEvent>,
+) {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut full_text = String::new();
+    loop {
+        let chunk = match tokio::time::timeout(IDLE_TIMEOUT, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => chunk,
+            Ok(Ok(None)) => break,
+            Ok(Err(error)) => {
+                let _ = tx
+                    .send(AiStreamEvent::Error {
+                        message: format!("AI 服务连接中断: {error}"),
+                    })
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx
+                    .send(AiStreamEvent::Error {
+                        message: "AI 服务响应超时".to_string(),
+                    })
+                    .await;
+                return;
+            }
+        };
+        buffer.extend_from_slice(&chunk);
+        // 只处理完整的行，半行（可能截断了 UTF-8 字符）留到下一块
+        while let Some(newline) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            match parse_sse_line(line.trim()) {
+                SseLine::Delta(text) => {
+                    full_text.push_str(&text);
+                    if tx.send(AiStreamEvent::Delta { text }).await.is_err() {
+                        return;
+                    }
+                }
+                SseLine::Done => {
+                    let _ = tx.send(finish(task, full_text, &existing_tags)).await;
+                    return;
+                }
+                SseLine::Ignore => {}
+            }
+        }
+    }
+    // 部分兼容实现不发 [DONE]，直接断开
+    let _ = tx.send(finish(task, full_text, &existing_tags)).await;
+}
+
+fn finish(task: AiTask, text: String, existing_tags: &[Tag]) -> AiStreamEvent {
+    let text = text.trim().to_string();
+    AiStreamEvent::Done {
+        tags: (task == AiTask::Tags).then(|| match_tags(&text, existing_tags)),
+        titles: (task == AiTask::Title).then(|| split_titles(&text)),
+        text,
+    }
+}
+
+enum SseLine {
+    Delta(String),
+    Done,
+    Ignore,
+}
+
+fn parse_sse_line(line: &str) -> SseLine {
+    let Some(data) = line.strip_prefix("data:") else {
+        return SseLine::Ignore;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return SseLine::Done;
+    }
+    serde_json::from_str::<serde_json::Value>(data)
+        .ok()
+        .and_then(|value| {
+            value["choices"][0]["delta"]["content"]
+                .as_str()
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        })
+        .map_or(SseLine::Ignore, SseLine::Delta)
+}
+
+/// `DEEPSEEK_API_URL` 既可以是根地址，也可以是完整的 `/chat/completions` 地址。
+fn chat_completions_url(base: &str) -> String {
+    let base = base.trim().trim_end_matches('/');
+    if base.ends_with("/chat/completions") {
+        base.to_string()
+    } else {
+        format!("{base}/chat/completions")
+    }
+}
+
+/// 把模型输出的标签列表拆开，和已有标签按名称（不区分大小写）对上 id。
+fn match_tags(text: &str, existing: &[Tag]) -> Vec<TagSuggestion> {
+    let mut suggestions: Vec<TagSuggestion> = Vec::new();
+    for name in text.split([',', '，', '、', '\n']) {
+        let name = name
+            .trim()
+            .trim_matches(['#', '"', '\'', '“', '”', '`', '-', '*', ' ']);
+        if name.is_empty() || name.chars().count() > 30 {
+            continue;
+        }
+        let suggestion = match existing
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(name))
+        {
+            Some(tag) => TagSuggestion {
+                id: Some(tag.id),
+                name: tag.name.clone(),
+            },
+            None => TagSuggestion {
+                id: None,
+                name: name.to_string(),
+            },
+        };
+        if !suggestions
+            .iter()
+            .any(|seen| seen.name.eq_ignore_ascii_case(&suggestion.name))
+        {
+            suggestions.push(suggestion);
+        }
+        if suggestions.len() == MAX_TAG_SUGGESTIONS {
+            break;
+        }
+    }
+    suggestions
+}
+
+fn split_titles(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| {
+                    c.is_ascii_digit() || matches!(c, '.' | '、' | ')' | '-' | '*')
+                })
+                .trim()
+                .trim_matches(['"', '“', '”', '《', '》'])
+                .to_string()
+        })
+        .filter(|title| !title.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn tag(id: i64, name: &str) -> Tag {
+        Tag {
+            id,
+            name: name.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn upstream_lines_and_urls_are_parsed() {
+        assert!(matches!(
+            parse_sse_line(r#"data: {"choices":[{"delta":{"content":"你好"}}]}"#),
+            SseLine::Delta(text) if text == "你好"
+        ));
+        assert!(matches!(parse_sse_line("data: [DONE]"), SseLine::Done));
+        assert!(matches!(parse_sse_line(": keep-alive"), SseLine::Ignore));
+        assert_eq!(
+            chat_completions_url("https://api.deepseek.com/"),
+            "https://api.deepseek.com/chat/completions"
+        );
+        assert_eq!(
+            chat_completions_url("https://api.deepseek.com/v1/chat/completions"),
+            "https://api.deepseek.com/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn suggestions_are_matched_to_existing_tags() {
+        let existing = [tag(1, "Rust"), tag(2, "数据库")];
+        assert_eq!(
+            match_tags("#rust， SQLite、数据库, rust", &existing),
+            [
+                TagSuggestion {
+                    id: Some(1),
+                    name: "Rust".to_string()
+                },
+                TagSuggestion {
+                    id: None,
+                    name: "SQLite".to_string()
+                },
+                TagSuggestion {
+                    id: Some(2),
+                    name: "数据库".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            split_titles("1. 《第一个》\n\n2) 第二个"),
+            ["第一个", "第二个"]
+        );
+    }
+}
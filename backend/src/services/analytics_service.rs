@@ -0,0 +1,437 @@
+//! 无 Cookie 的访问统计。浏览先记在内存里，由后台任务定期批量写入 SQLite，
+//! 库里只有按天汇总的计数。独立访客用「当日随机盐 + IP + UA」的哈希去重：
+//! 盐只存在内存里、每天（UTC）换一次，过后既无法反推 IP，也无法跨天关联同一访客。
+
+use crate::database::{repositories::PostRepository, Database};
+use crate::models::{DailyViews, ReferrerStat, SectionStat, TopPost};
+use crate::utils::error::{AppError, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+/// 两次写库之间最多攒这么多条，超出的直接丢弃（多半是刷量）。
+const MAX_PENDING: usize = 50_000;
+const MAX_PATH_CHARS: usize = 200;
+const DEFAULT_DAYS: u32 = 30;
+const MAX_DAYS: u32 = 365;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+/// 前端路由的一级路径；其他路径归到 `other`，避免随便拼的地址撑出大量统计行。
+const SECTIONS: &[&str] = &[
+    "home",
+    "articles",
+    "article",
+    "projects",
+    "tools",
+    "about",
+    "guestbook",
+    "books",
+    "changelog",
+];
+const OTHER_SECTION: &str = "other";
+
+const BOT_MARKERS: &[&str] = &[
+    "bot",
+    "spider",
+    "crawl",
+    "slurp",
+    "headless",
+    "lighthouse",
+    "preview",
+    "curl",
+    "wget",
+    "python-requests",
+];
+
+struct PendingView {
+    day: String,
+    path: String,
+    visitor: String,
+    referrer: Option<String>,
+}
+
+struct VisitorSalt {
+    day: NaiveDate,
+    key: [u8; 32],
+}
+
+pub struct AnalyticsService {
+    database: Database,
+    pending: Mutex<Vec<PendingView>>,
+    salt: Mutex<VisitorSalt>,
+}
+
+impl AnalyticsService {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            pending: Mutex::new(Vec::new()),
+            salt: Mutex::new(VisitorSalt {
+                day: NaiveDate::MIN,
+                key: [0; 32],
+            }),
+        }
+    }
+
+    /// 记一次浏览（只进内存）。路径不合法、是后台页面或缓冲已满时返回 false。
+    pub fn record(
+        &self,
+        path: &str,
+        referrer_host: Option<String>,
+        ip: &str,
+        user_agent: &str,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Some(path) = normalize_path(path) else {
+            return false;
+        };
+        let day = now.date_naive();
+        let view = PendingView {
+            day: day.to_string(),
+            path,
+            visitor: self.visitor_hash(day, ip, user_agent),
+            referrer: referrer_host,
+        };
+        let mut pending = lock(&self.pending);
+        if pending.len() >= MAX_PENDING {
+            return false;
+        }
+        pending.push(view);
+        true
+    }
+
+    fn visitor_hash(&self, day: NaiveDate, ip: &str, user_agent: &str) -> String {
+        let mut salt = lock(&self.salt);
+        if salt.day != day {
+            salt.day = day;
+            rand::thread_rng().fill_bytes(&mut salt.key);
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(salt.key);
+        hasher.update(ip.as_bytes());
+        hasher.update([0]);
+        hasher.update(user_agent.as_bytes());
+        hex::encode(&hasher.finalize()[..16])
+    }
+
+    /// 把内存里的浏览汇总写入数据库，返回写入的浏览数。
+    /// 写入失败时这批浏览放回队列，等下次再写。
+    pub async fn flush(&self, now: DateTime<Utc>) -> Result<usize> {
+        let mut batch = std::mem::take(&mut *lock(&self.pending));
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        match self.write_batch(&batch, now).await {
+            Ok(()) => Ok(batch.len()),
+            Err(e) => {
+                let mut pending = lock(&self.pending);
+                batch.append(&mut pending);
+                batch.truncate(MAX_PENDING);
+                *pending = batch;
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_batch(&self, batch: &[PendingView], now: DateTime<Utc>) -> Result<()> {
+        // 先在事务外把文章路径解析成 id，事务里只做写入
+        let mut post_ids: HashMap<&str, Option<i64>> = HashMap::new();
+        for view in batch {
+            if let Some(reference) = article_ref(&view.path) {
+                if !post_ids.contains_key(reference) {
+                    post_ids.insert(reference, self.resolve_post(reference).await?);
+                }
+            }
+        }
+
+        let mut views: HashMap<(&str, String), (Option<i64>, i64)> = HashMap::new();
+        let mut visitors: HashSet<(&str, String, &str)> = HashSet::new();
+        let mut referrers: HashMap<(&str, &str), i64> = HashMap::new();
+        for view in batch {
+            let mut scopes = vec![
+                ("site".to_string(), None),
+                (format!("section:{}", section_of(&view.path)), None),
+            ];
+            if let Some(&Some(post_id)) = article_ref(&view.path).and_then(|r| post_ids.get(r)) {
+                scopes.push((format!("post:{post_id}"), Some(post_id)));
+            }
+            for (scope, post_id) in scopes {
+                views
+                    .entry((view.day.as_str(), scope.clone()))
+                    .or_insert((post_id, 0))
+                    .1 += 1;
+                visitors.insert((view.day.as_str(), scope, view.visitor.as_str()));
+            }
+            if let Some(host) = &view.referrer {
+                *referrers.entry((view.day.as_str(), host)).or_default() += 1;
+            }
+        }
+
+        let mut tx = self.database.pool().begin().await?;
+        let mut new_visitors: HashMap<(&str, String), i64> = HashMap::new();
+        for (day, scope, visitor) in visitors {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO analytics_visitors (day, scope, visitor) VALUES (?, ?, ?)",
+            )
+            .bind(day)
+            .bind(&scope)
+            .bind(visitor)
+            .execute(&mut *tx)
+            .await?;
+            if inserted.rows_affected() > 0 {
+                *new_visitors.entry((day, scope)).or_default() += 1;
+            }
+        }
+        for (key, (post_id, count)) in views {
+            let unique = new_visitors.get(&key).copied().unwrap_or(0);
+            sqlx::query(
+                "INSERT INTO analytics_daily (day, scope, post_id, views, visitors) VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(day, scope) DO UPDATE SET
+                     views = views + excluded.views,
+                     visitors = visitors + excluded.visitors",
+            )
+            .bind(key.0)
+            .bind(&key.1)
+            .bind(post_id)
+            .bind(count)
+            .bind(unique)
+            .execute(&mut *tx)
+            .await?;
+        }
+        for ((day, host), count) in referrers {
+            sqlx::query(
+                "INSERT INTO analytics_referrers (day, host, views) VALUES (?, ?, ?)
+                 ON CONFLICT(day, host) DO UPDATE SET views = views + excluded.views",
+            )
+            .bind(day)
+            .bind(host)
+            .bind(count)
+            .execute(&mut *tx)
+            .await?;
+        }
+        // 盐已经换过，之前日期的访客哈希再也匹配不上，留着没用
+        sqlx::query("DELETE FROM analytics_visitors WHERE day < ?")
+            .bind(now.date_naive().to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn resolve_post(&self, reference: &str) -> Result<Option<i64>> {
+        match reference.parse::<i64>() {
+            Ok(id) => sqlx::query_scalar("SELECT id FROM posts WHERE id = ?")
+                .bind(id)
+                .fetch_optional(self.database.pool())
+                .await
+                .map_err(Into::into),
+            Err(_) => PostRepository::find_id_by_slug(self.database.pool(), reference).await,
+        }
+    }
+
+    /// 全站累计浏览量（仪表盘用）。
+    pub async fn total_views(&self) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(views), 0) FROM analytics_daily WHERE scope = 'site'",
+        )
+        .fetch_one(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn top_posts(&self, days: Option<u32>, limit: Option<u32>) -> Result<Vec<TopPost>> {
+        sqlx::query_as::<_, TopPost>(
+            "SELECT d.post_id AS post_id, p.title, p.slug,
+                    SUM(d.views) AS views, SUM(d.visitors) AS visitors
+             FROM analytics_daily d JOIN posts p ON p.id = d.post_id
+             WHERE d.day >= ? AND d.scope LIKE 'post:%'
+             GROUP BY d.post_id ORDER BY views DESC, d.post_id ASC LIMIT ?",
+        )
+        .bind(since(days))
+        .bind(clamp_limit(limit))
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn top_referrers(
+        &self,
+        days: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<ReferrerStat>> {
+        sqlx::query_as::<_, ReferrerStat>(
+            "SELECT host, SUM(views) AS views FROM analytics_referrers
+             WHERE day >= ? GROUP BY host ORDER BY views DESC, host ASC LIMIT ?",
+        )
+        .bind(since(days))
+        .bind(clamp_limit(limit))
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn sections(&self, days: Option<u32>) -> Result<Vec<SectionStat>> {
+        sqlx::query_as::<_, SectionStat>(
+            "SELECT substr(scope, 9) AS section, SUM(views) AS views, SUM(visitors) AS visitors
+             FROM analytics_daily WHERE day >= ? AND scope LIKE 'section:%'
+             GROUP BY scope ORDER BY views DESC, section ASC",
+        )
+        .bind(since(days))
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 按天的浏览曲线，没有数据的日子补 0。`post_id` 优先于 `section`，都没有时是全站。
+    pub async fn timeseries(
+        &self,
+        post_id: Option<i64>,
+        section: Option<&str>,
+        days: Option<u32>,
+    ) -> Result<Vec<DailyViews>> {
+        let scope = match (post_id, section) {
+            (Some(post_id), _) => format!("post:{post_id}"),
+            (None, Some(section)) if section == OTHER_SECTION || SECTIONS.contains(&section) => {
+                format!("section:{section}")
+            }
+            (None, Some(section)) => {
+                return Err(AppError::Validation(format!("未知的栏目: {section}")));
+            }
+            (None, None) => "site".to_string(),
+        };
+        let start = since(days);
+        let rows = sqlx::query_as::<_, DailyViews>(
+            "SELECT day, views, visitors FROM analytics_daily WHERE scope = ? AND day >= ?",
+        )
+        .bind(&scope)
+        .bind(&start)
+        .fetch_all(self.database.pool())
+        .await?;
+        let mut by_day: HashMap<String, DailyViews> =
+            rows.into_iter().map(|row| (row.day.clone(), row)).collect();
+
+        let today = Utc::now().date_naive();
+        let mut day: NaiveDate = start.parse().unwrap_or(today);
+        let mut series = Vec::new();
+        while day <= today {
+            let key = day.to_string();
+            series.push(by_day.remove(&key).unwrap_or(DailyViews {
+                day: key,
+                views: 0,
+                visitors: 0,
+            }));
+            day += Duration::days(1);
+        }
+        Ok(series)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // 统计不是关键路径，锁中毒也继续用
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn since(days: Option<u32>) -> String {
+    let days = days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    (Utc::now().date_naive() - Duration::days(i64::from(days) - 1)).to_string()
+}
+
+fn clamp_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+/// 去掉查询串、锚点和末尾的 `/`；后台和接口路径不统计。
+fn normalize_path(path: &str) -> Option<String> {
+    let path = path.split(['?', '#']).next().unwrap_or_default().trim();
+    if !path.starts_with('/') || path.chars().count() > MAX_PATH_CHARS {
+        return None;
+    }
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    let first = path[1..].split('/').next().unwrap_or_default();
+    if first == "admin" || first == "api" {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+fn section_of(path: &str) -> &str {
+    match path[1..].split('/').next().unwrap_or_default() {
+        "" => "home",
+        first => SECTIONS
+            .iter()
+            .find(|section| **section == first)
+            .copied()
+            .unwrap_or(OTHER_SECTION),
+    }
+}
+
+/// `/article/<id 或 slug>` 里的文章标识。
+fn article_ref(path: &str) -> Option<&str> {
+    path.strip_prefix("/article/")
+        .filter(|rest| !rest.is_empty() && !rest.contains('/'))
+}
+
+/// 爬虫、预览抓取和命令行工具不算访客。
+pub fn is_bot(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_ascii_lowercase();
+    user_agent.is_empty() || BOT_MARKERS.iter().any(|marker| user_agent.contains(marker))
+}
+
+/// 来源只保留域名（去掉 `www.`）；站内跳转（与 `own_host` 相同）不算来源。
+pub fn referrer_host(referrer: &str, own_host: Option<&str>) -> Option<String> {
+    let url = url::Url::parse(referrer.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    let own = own_host
+        .map(|own| own.split(':').next().unwrap_or(own).to_ascii_lowercase())
+        .map(|own| own.strip_prefix("www.").unwrap_or(&own).to_string());
+    (own.as_deref() != Some(host.as_str())).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_normalized_and_grouped_into_sections() {
+        assert_eq!(
+            normalize_path("/article/hello/?utm=x#top").as_deref(),
+            Some("/article/hello")
+        );
+        assert_eq!(normalize_path("/").as_deref(), Some("/"));
+        assert_eq!(normalize_path("/admin/posts"), None);
+        assert_eq!(normalize_path("https://evil.example/"), None);
+
+        assert_eq!(section_of("/"), "home");
+        assert_eq!(section_of("/tools/quant"), "tools");
+        assert_eq!(section_of("/wp-login.php"), "other");
+        assert_eq!(article_ref("/article/hello"), Some("hello"));
+        assert_eq!(article_ref("/articles"), None);
+    }
+
+    #[test]
+    fn referrers_keep_only_external_hosts() {
+        assert_eq!(
+            referrer_host("https://www.Google.com/search?q=x", Some("blog.chuyi.uk")).as_deref(),
+            Some("google.com")
+        );
+        assert_eq!(
+            referrer_host("https://blog.chuyi.uk/articles", Some("blog.chuyi.uk")),
+            None
+        );
+        assert_eq!(referrer_host("android-app://com.slack", None), None);
+        assert!(is_bot("Mozilla/5.0 (compatible; Googlebot/2.1)"));
+        assert!(!is_bot("Mozilla/5.0 (Macintosh) Safari/605.1.15"));
+    }
+}
@@ -0,0 +1,76 @@
+//! 上传完成后提取书籍元数据：`extract_book_metadata` 后台任务从 R2 取回文件，读出作者、
+//! 简介、页数和封面，写回书籍（见 `BookService::apply_metadata`）。上传请求本身不再等它。
+
+use crate::models::{BookFile, ExtractBookMetadataPayload, Job, JobKind};
+use crate::services::job_service::{JobContext, JobService};
+use crate::services::BookService;
+use crate::utils::book_metadata;
+use crate::utils::error::{AppError, Result};
+use crate::utils::R2Storage;
+use std::sync::Arc;
+
+/// 超过这个大小的文件不解析，避免把整本大书读进内存。
+const MAX_METADATA_SOURCE_SIZE: i64 = 200 * 1024 * 1024;
+
+pub struct BookMetadataService {
+    book: Arc<BookService>,
+    jobs: Arc<JobService>,
+    storage: Arc<R2Storage>,
+}
+
+impl BookMetadataService {
+    pub fn new(book: Arc<BookService>, jobs: Arc<JobService>, storage: Arc<R2Storage>) -> Self {
+        Self {
+            book,
+            jobs,
+            storage,
+        }
+    }
+
+    /// 只处理不太大的 EPUB 和 PDF，其它文件返回 `None`。
+    pub async fn enqueue(&self, file: &BookFile) -> Result<Option<Job>> {
+        if !matches!(file.format.as_str(), "epub" | "pdf")
+            || file.file_size > MAX_METADATA_SOURCE_SIZE
+        {
+            return Ok(None);
+        }
+        let payload = ExtractBookMetadataPayload {
+            book_id: file.book_id,
+            file_id: file.id,
+        };
+        self.jobs
+            .enqueue(JobKind::ExtractBookMetadata, serde_json::to_value(payload)?)
+            .await
+            .map(Some)
+    }
+
+    /// 任务结果是 `ExtractedBookMetadata`，文件里读不出东西时为 null。
+    pub async fn extract(&self, job: &Job, context: &JobContext) -> Result<serde_json::Value> {
+        let payload: ExtractBookMetadataPayload = serde_json::from_value(job.payload.clone())?;
+        let file = match self.book.get_file(payload.book_id, payload.file_id).await {
+            Ok((_, file)) => file,
+            // 排队期间文件或书被删掉了
+            Err(AppError::NotFound(_)) => return Ok(serde_json::Value::Null),
+            Err(error) => return Err(error),
+        };
+
+        context.progress(0.1, "downloading book file").await;
+        let data = self
+            .storage
+            .get_object(&file.r2_key, MAX_METADATA_SOURCE_SIZE as u64)
+            .await?;
+        context.progress(0.6, "reading metadata").await;
+        let format = file.format.clone();
+        let metadata = tokio::task::spawn_blocking(move || book_metadata::extract(&format, &data))
+            .await
+            .map_err(|error| AppError::Internal(format!("Metadata task failed: {error}")))??;
+        let Some(metadata) = metadata else {
+            return Ok(serde_json::Value::Null);
+        };
+        let (_, extracted) = self
+            .book
+            .apply_metadata(payload.book_id, payload.file_id, metadata)
+            .await?;
+        Ok(serde_json::to_value(extracted)?)
+    }
+}
@@ -1,13 +1,31 @@
 use crate::database::Database;
 use crate::models::{
-    Book, BookFile, BookRecord, CreateBookFile, CreateBookRequest, UpdateBookRequest,
+    Book, BookFile, BookHighlight, BookImportItem, BookImportReport, BookRecord, BookStats,
+    CreateBookFile, CreateBookRequest, CreateHighlightRequest, ExtractedBookMetadata, PublicBook,
+    ReadingProgress, ReadingReport, ReportBook, SaveReadingProgressRequest, UpdateBookRequest,
+    UpdateHighlightRequest, YearlyReadingCount,
 };
+use crate::services::search_service::escape_like;
+use crate::utils::book_metadata::BookMetadata;
 use crate::utils::error::{AppError, Result};
+use crate::utils::reading_history::{HistorySource, ImportedBook};
 use crate::utils::FileHandler;
+use chrono::{Datelike, NaiveDate};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
 const BOOK_COLUMNS: &str = "id, title, author, description, cover_url, reading_status, progress, rating, notes, started_at, finished_at, is_public, download_enabled, created_at, updated_at";
+const BOOK_FILE_COLUMNS: &str =
+    "id, book_id, format, file_url, r2_key, file_name, file_size, mime_type, page_count, created_at";
 const VALID_READING_STATUSES: &[&str] = &["want_to_read", "reading", "finished", "paused"];
+const HIGHLIGHT_COLUMNS: &str =
+    "id, book_id, book_file_id, locator, chapter, text, note, color, created_at, updated_at";
+const VALID_HIGHLIGHT_COLORS: &[&str] = &["yellow", "green", "blue", "pink", "purple"];
+const MAX_LOCATOR_LENGTH: usize = 2048;
+const MAX_HIGHLIGHT_TEXT_CHARS: usize = 5_000;
+const MAX_HIGHLIGHT_NOTE_CHARS: usize = 10_000;
+/// 读到这个百分比就算读完，EPUB 的最后一页通常到不了整 100。
+const FINISHED_PERCENTAGE: f64 = 99.0;
 
 pub struct BookService {
     database: Database,
@@ -22,8 +40,13 @@ impl BookService {
         }
     }
 
-    pub async fn list_public(&self) -> Result<Vec<Book>> {
-        self.list(true).await
+    pub async fn list_public(&self) -> Result<Vec<PublicBook>> {
+        Ok(self
+            .list(true)
+            .await?
+            .into_iter()
+            .map(PublicBook::from)
+            .collect())
     }
 
     pub async fn list_admin(&self) -> Result<Vec<Book>> {
@@ -46,6 +69,53 @@ impl BookService {
         Ok(books)
     }
 
+    /// OPDS 目录用：可按阅读状态筛选，按书名或作者模糊搜索。
+    pub async fn catalog(
+        &self,
+        public_only: bool,
+        reading_status: Option<&str>,
+        query: Option<&str>,
+    ) -> Result<Vec<Book>> {
+        let mut conditions = Vec::new();
+        if public_only {
+            conditions.push("is_public = 1");
+        }
+        if let Some(status) = reading_status {
+            if !VALID_READING_STATUSES.contains(&status) {
+                return Err(AppError::BadRequest("Invalid reading status".to_string()));
+            }
+            conditions.push("reading_status = ?");
+        }
+        let pattern = query
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+            .map(|query| format!("%{}%", escape_like(query)));
+        if pattern.is_some() {
+            conditions.push("(title LIKE ? ESCAPE '\\' OR author LIKE ? ESCAPE '\\')");
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT {BOOK_COLUMNS} FROM books {where_clause} ORDER BY updated_at DESC, id DESC"
+        );
+        let mut records = sqlx::query_as::<_, BookRecord>(&sql);
+        if let Some(status) = reading_status {
+            records = records.bind(status);
+        }
+        if let Some(pattern) = &pattern {
+            records = records.bind(pattern).bind(pattern);
+        }
+        let records = records.fetch_all(self.database.pool()).await?;
+        let mut books = Vec::with_capacity(records.len());
+        for record in records {
+            books.push(self.with_files(record).await?);
+        }
+        Ok(books)
+    }
+
     pub async fn get(&self, id: i64, public_only: bool) -> Result<Book> {
         let sql = if public_only {
             format!("SELECT {BOOK_COLUMNS} FROM books WHERE id = ? AND is_public = 1")
@@ -61,22 +131,103 @@ impl BookService {
     }
 
     async fn with_files(&self, record: BookRecord) -> Result<Book> {
-        let files = sqlx::query_as::<_, BookFile>(
-            "SELECT id, book_id, format, file_url, r2_key, file_name, file_size, mime_type, created_at FROM book_files WHERE book_id = ? ORDER BY id",
-        )
+        let files = sqlx::query_as::<_, BookFile>(&format!(
+            "SELECT {BOOK_FILE_COLUMNS} FROM book_files WHERE book_id = ? ORDER BY id"
+        ))
         .bind(record.id)
         .fetch_all(self.database.pool())
         .await?;
         Ok(Book { record, files })
     }
 
-    pub async fn get_public_file(&self, book_id: i64, file_id: i64) -> Result<BookFile> {
-        self.get(book_id, true)
+    pub async fn stats(&self, public_only: bool) -> Result<BookStats> {
+        let books = self.list(public_only).await?;
+        let count = |status: &str| {
+            books
+                .iter()
+                .filter(|book| book.record.reading_status == status)
+                .count() as i64
+        };
+        let mut years: BTreeMap<i32, Vec<i64>> = BTreeMap::new();
+        for date in books.iter().filter_map(|book| finished_on(&book.record)) {
+            years.entry(date.year()).or_insert_with(|| vec![0; 12])[date.month0() as usize] += 1;
+        }
+        Ok(BookStats {
+            total: books.len() as i64,
+            want_to_read: count("want_to_read"),
+            reading: count("reading"),
+            finished: count("finished"),
+            paused: count("paused"),
+            average_rating: average(books.iter().filter_map(|book| book.record.rating)),
+            average_days_to_finish: average(
+                books.iter().filter_map(|book| days_to_finish(&book.record)),
+            ),
+            years: years
+                .into_iter()
+                .rev()
+                .map(|(year, months)| YearlyReadingCount {
+                    year,
+                    finished: months.iter().sum(),
+                    months,
+                })
+                .collect(),
+        })
+    }
+
+    /// 某一年读完的书（按 `finished_at` 算），没有记录时返回空报告。
+    pub async fn report(&self, public_only: bool, year: i32) -> Result<ReadingReport> {
+        let mut finished: Vec<(NaiveDate, ReportBook)> = self
+            .list(public_only)
             .await?
+            .into_iter()
+            .filter_map(|book| {
+                let date = finished_on(&book.record).filter(|date| date.year() == year)?;
+                Some((
+                    date,
+                    ReportBook {
+                        id: book.record.id,
+                        days_to_finish: days_to_finish(&book.record),
+                        page_count: book.files.iter().filter_map(|file| file.page_count).max(),
+                        title: book.record.title,
+                        author: book.record.author,
+                        cover_url: book.record.cover_url,
+                        rating: book.record.rating,
+                        started_at: book.record.started_at,
+                        finished_at: date.format("%Y-%m-%d").to_string(),
+                    },
+                ))
+            })
+            .collect();
+        finished.sort_by(|(a, a_book), (b, b_book)| a.cmp(b).then(a_book.id.cmp(&b_book.id)));
+
+        let mut months = vec![0; 12];
+        for (date, _) in &finished {
+            months[date.month0() as usize] += 1;
+        }
+        let books: Vec<ReportBook> = finished.into_iter().map(|(_, book)| book).collect();
+        let timed = || books.iter().filter(|book| book.days_to_finish.is_some());
+        Ok(ReadingReport {
+            year,
+            finished: books.len() as i64,
+            months,
+            average_rating: average(books.iter().filter_map(|book| book.rating)),
+            average_days_to_finish: average(books.iter().filter_map(|book| book.days_to_finish)),
+            pages_read: books.iter().filter_map(|book| book.page_count).sum(),
+            fastest: timed().min_by_key(|book| book.days_to_finish).cloned(),
+            slowest: timed().max_by_key(|book| book.days_to_finish).cloned(),
+            books,
+        })
+    }
+
+    /// 不区分是否公开，由调用方按书籍的开关决定能否访问。
+    pub async fn get_file(&self, book_id: i64, file_id: i64) -> Result<(BookRecord, BookFile)> {
+        let book = self.get(book_id, false).await?;
+        let file = book
             .files
             .into_iter()
             .find(|file| file.id == file_id)
-            .ok_or_else(|| AppError::NotFound("Book file not found".to_string()))
+            .ok_or_else(|| AppError::NotFound("Book file not found".to_string()))?;
+        Ok((book.record, file))
     }
 
     pub async fn create(&self, request: CreateBookRequest) -> Result<Book> {
@@ -160,19 +311,70 @@ impl BookService {
         .bind(file.mime_type)
         .execute(self.database.pool())
         .await?;
-        sqlx::query_as::<_, BookFile>(
-            "SELECT id, book_id, format, file_url, r2_key, file_name, file_size, mime_type, created_at FROM book_files WHERE id = ?",
-        )
+        sqlx::query_as::<_, BookFile>(&format!(
+            "SELECT {BOOK_FILE_COLUMNS} FROM book_files WHERE id = ?"
+        ))
         .bind(result.last_insert_rowid())
         .fetch_one(self.database.pool())
         .await
         .map_err(Into::into)
     }
 
-    pub async fn delete_file(&self, file_id: i64) -> Result<()> {
-        let file = sqlx::query_as::<_, BookFile>(
-            "SELECT id, book_id, format, file_url, r2_key, file_name, file_size, mime_type, created_at FROM book_files WHERE id = ?",
+    /// 把上传完成后提取的元数据写回：文件记下页数，书籍只填空着的作者、简介和封面。
+    /// 标题总是由管理员建书时填写，不覆盖，原样返回给前端决定是否采用。
+    pub async fn apply_metadata(
+        &self,
+        book_id: i64,
+        file_id: i64,
+        metadata: BookMetadata,
+    ) -> Result<(Book, ExtractedBookMetadata)> {
+        let (book, _) = self.get_file(book_id, file_id).await?;
+        let cover_url = match metadata.cover {
+            Some(cover) if book.cover_url.is_none() => match self
+                .file_handler
+                .save_optimized_image_data(cover.data, &cover.file_name, "covers", None)
+                .await
+            {
+                Ok((url, _, _)) => Some(url),
+                Err(error) => {
+                    tracing::warn!("Failed to save cover extracted from book {book_id}: {error}");
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let mut tx = self.database.pool().begin().await?;
+        sqlx::query("UPDATE book_files SET page_count = ? WHERE id = ?")
+            .bind(metadata.page_count)
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE books SET author = CASE WHEN author = '' THEN COALESCE(?, '') ELSE author END, description = CASE WHEN description = '' THEN COALESCE(?, '') ELSE description END, cover_url = COALESCE(cover_url, ?), updated_at = CURRENT_TIMESTAMP WHERE id = ?",
         )
+        .bind(&metadata.author)
+        .bind(&metadata.description)
+        .bind(&cover_url)
+        .bind(book_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let extracted = ExtractedBookMetadata {
+            title: metadata.title,
+            author: metadata.author,
+            description: metadata.description,
+            page_count: metadata.page_count,
+            cover_url,
+        };
+        Ok((self.get(book_id, false).await?, extracted))
+    }
+
+    pub async fn delete_file(&self, file_id: i64) -> Result<()> {
+        let file = sqlx::query_as::<_, BookFile>(&format!(
+            "SELECT {BOOK_FILE_COLUMNS} FROM book_files WHERE id = ?"
+        ))
         .bind(file_id)
         .fetch_optional(self.database.pool())
         .await?
@@ -184,6 +386,302 @@ impl BookService {
             .await?;
         Ok(())
     }
+
+    pub async fn list_progress(&self, book_id: i64) -> Result<Vec<ReadingProgress>> {
+        self.get(book_id, false).await?;
+        sqlx::query_as::<_, ReadingProgress>(
+            "SELECT book_file_id, book_id, locator, percentage, updated_at FROM book_reading_progress WHERE book_id = ? ORDER BY book_file_id",
+        )
+        .bind(book_id)
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 保存读到的位置，并据此更新书的 `progress`、`reading_status`、`started_at` 和 `finished_at`。
+    /// 已读完的书重读时不会被改回“在读”。
+    pub async fn save_progress(
+        &self,
+        book_id: i64,
+        file_id: i64,
+        request: SaveReadingProgressRequest,
+    ) -> Result<ReadingProgress> {
+        let locator = validate_locator(&request.locator)?;
+        if !request.percentage.is_finite() || !(0.0..=100.0).contains(&request.percentage) {
+            return Err(AppError::BadRequest(
+                "Percentage must be between 0 and 100".to_string(),
+            ));
+        }
+        self.get_file(book_id, file_id).await?;
+
+        let mut tx = self.database.pool().begin().await?;
+        sqlx::query(
+            "INSERT INTO book_reading_progress (book_file_id, book_id, locator, percentage) VALUES (?, ?, ?, ?)
+             ON CONFLICT(book_file_id) DO UPDATE SET locator = excluded.locator, percentage = excluded.percentage, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(file_id)
+        .bind(book_id)
+        .bind(locator)
+        .bind(request.percentage)
+        .execute(&mut *tx)
+        .await?;
+
+        let today = chrono::Utc::now().date_naive().to_string();
+        if request.percentage >= FINISHED_PERCENTAGE {
+            sqlx::query(
+                "UPDATE books SET reading_status = 'finished', progress = 100, started_at = COALESCE(started_at, ?), finished_at = COALESCE(finished_at, ?), updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(&today)
+            .bind(&today)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(
+                "UPDATE books SET reading_status = 'reading', progress = ?, started_at = COALESCE(started_at, ?), updated_at = CURRENT_TIMESTAMP WHERE id = ? AND reading_status != 'finished'",
+            )
+            .bind(request.percentage.floor() as i64)
+            .bind(&today)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        let progress = sqlx::query_as::<_, ReadingProgress>(
+            "SELECT book_file_id, book_id, locator, percentage, updated_at FROM book_reading_progress WHERE book_file_id = ?",
+        )
+        .bind(file_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(progress)
+    }
+
+    pub async fn list_highlights(
+        &self,
+        book_id: i64,
+        file_id: Option<i64>,
+    ) -> Result<Vec<BookHighlight>> {
+        self.get(book_id, false).await?;
+        sqlx::query_as::<_, BookHighlight>(&format!(
+            "SELECT {HIGHLIGHT_COLUMNS} FROM book_highlights WHERE book_id = ? AND (? IS NULL OR book_file_id = ?) ORDER BY book_file_id, id"
+        ))
+        .bind(book_id)
+        .bind(file_id)
+        .bind(file_id)
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn create_highlight(
+        &self,
+        book_id: i64,
+        request: CreateHighlightRequest,
+    ) -> Result<BookHighlight> {
+        let locator = validate_locator(&request.locator)?;
+        let text = request.text.trim();
+        if text.is_empty() || text.chars().count() > MAX_HIGHLIGHT_TEXT_CHARS {
+            return Err(AppError::BadRequest(format!(
+                "Highlight text must be between 1 and {MAX_HIGHLIGHT_TEXT_CHARS} characters"
+            )));
+        }
+        validate_note(&request.note)?;
+        validate_color(&request.color)?;
+        self.get_file(book_id, request.file_id).await?;
+
+        let result = sqlx::query(
+            "INSERT INTO book_highlights (book_id, book_file_id, locator, chapter, text, note, color) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(book_id)
+        .bind(request.file_id)
+        .bind(locator)
+        .bind(request.chapter.trim())
+        .bind(text)
+        .bind(request.note.trim())
+        .bind(&request.color)
+        .execute(self.database.pool())
+        .await?;
+        self.get_highlight(result.last_insert_rowid()).await
+    }
+
+    pub async fn update_highlight(
+        &self,
+        id: i64,
+        request: UpdateHighlightRequest,
+    ) -> Result<BookHighlight> {
+        let current = self.get_highlight(id).await?;
+        let note = request.note.unwrap_or(current.note);
+        let color = request.color.unwrap_or(current.color);
+        validate_note(&note)?;
+        validate_color(&color)?;
+        sqlx::query(
+            "UPDATE book_highlights SET note = ?, color = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(note.trim())
+        .bind(color)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        self.get_highlight(id).await
+    }
+
+    pub async fn delete_highlight(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM book_highlights WHERE id = ?")
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Highlight not found".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn get_highlight(&self, id: i64) -> Result<BookHighlight> {
+        sqlx::query_as::<_, BookHighlight>(&format!(
+            "SELECT {HIGHLIGHT_COLUMNS} FROM book_highlights WHERE id = ?"
+        ))
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Highlight not found".to_string()))
+    }
+
+    /// 导入 Goodreads / 豆瓣的读书记录。按书名加作者去重：已有的书更新阅读状态和评分，
+    /// 日期和笔记只在空着时补上；新书默认不公开。整个导入在一个事务里，dry run 时回滚。
+    pub async fn import_history(
+        &self,
+        source: HistorySource,
+        records: Vec<ImportedBook>,
+        dry_run: bool,
+        is_public: bool,
+    ) -> Result<BookImportReport> {
+        let mut tx = self.database.pool().begin().await?;
+        let existing: HashMap<String, BookRecord> = sqlx::query_as::<_, BookRecord>(&format!(
+            "SELECT {BOOK_COLUMNS} FROM books ORDER BY id"
+        ))
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .rev()
+        .map(|record| (import_key(&record.title, &record.author), record))
+        .collect();
+        let mut seen = HashSet::new();
+        let mut report = BookImportReport {
+            dry_run,
+            source: source.as_str().to_string(),
+            created: 0,
+            updated: 0,
+            skipped: 0,
+            items: Vec::with_capacity(records.len()),
+        };
+
+        for record in records {
+            let mut item = BookImportItem {
+                line: record.line,
+                action: "skip".to_string(),
+                title: record.title.clone(),
+                author: record.author.clone(),
+                book_id: None,
+                changes: Vec::new(),
+                reason: None,
+            };
+            let key = import_key(&record.title, &record.author);
+            if record.title.trim().is_empty() {
+                item.reason = Some("Missing title".to_string());
+            } else if !seen.insert(key.clone()) {
+                item.reason = Some("Duplicate of an earlier row".to_string());
+            } else if let Some(current) = existing.get(&key) {
+                item.book_id = Some(current.id);
+                let mut merged = current.clone();
+                if record.reading_status != current.reading_status {
+                    merged.reading_status = record.reading_status.clone();
+                    if record.reading_status == "finished" {
+                        merged.progress = 100;
+                    }
+                    item.changes.push("reading_status".to_string());
+                }
+                if record.rating.is_some() && record.rating != current.rating {
+                    merged.rating = record.rating;
+                    item.changes.push("rating".to_string());
+                }
+                if current.started_at.is_none() && record.started_at.is_some() {
+                    merged.started_at = record.started_at;
+                    item.changes.push("started_at".to_string());
+                }
+                if current.finished_at.is_none() && record.finished_at.is_some() {
+                    merged.finished_at = record.finished_at;
+                    item.changes.push("finished_at".to_string());
+                }
+                if current.notes.trim().is_empty() && !record.notes.is_empty() {
+                    merged.notes = record.notes;
+                    item.changes.push("notes".to_string());
+                }
+                if item.changes.is_empty() {
+                    item.reason = Some("Already up to date".to_string());
+                } else {
+                    sqlx::query(
+                        "UPDATE books SET reading_status = ?, progress = ?, rating = ?, notes = ?, started_at = ?, finished_at = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    )
+                    .bind(&merged.reading_status)
+                    .bind(merged.progress)
+                    .bind(merged.rating)
+                    .bind(&merged.notes)
+                    .bind(&merged.started_at)
+                    .bind(&merged.finished_at)
+                    .bind(current.id)
+                    .execute(&mut *tx)
+                    .await?;
+                    item.action = "update".to_string();
+                }
+            } else {
+                validate_book(&record.title, &record.reading_status, 0, record.rating)?;
+                let progress = if record.reading_status == "finished" {
+                    100
+                } else {
+                    0
+                };
+                let result = sqlx::query(
+                    "INSERT INTO books (title, author, reading_status, progress, rating, notes, started_at, finished_at, is_public) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(record.title.trim())
+                .bind(record.author.trim())
+                .bind(&record.reading_status)
+                .bind(progress)
+                .bind(record.rating)
+                .bind(&record.notes)
+                .bind(&record.started_at)
+                .bind(&record.finished_at)
+                .bind(is_public)
+                .execute(&mut *tx)
+                .await?;
+                if !dry_run {
+                    item.book_id = Some(result.last_insert_rowid());
+                }
+                item.action = "create".to_string();
+            }
+            match item.action.as_str() {
+                "create" => report.created += 1,
+                "update" => report.updated += 1,
+                _ => report.skipped += 1,
+            }
+            report.items.push(item);
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(report)
+    }
+
+    /// 把一本书的划线和批注导出成 Markdown 读书笔记，按章节分组。
+    pub async fn export_notes(&self, book_id: i64) -> Result<(Book, String)> {
+        let book = self.get(book_id, false).await?;
+        let highlights = self.list_highlights(book_id, None).await?;
+        let markdown = notes_markdown(&book, &highlights);
+        Ok((book, markdown))
+    }
 }
 
 fn validate_book(title: &str, status: &str, progress: i64, rating: Option<i64>) -> Result<()> {
@@ -205,3 +703,104 @@ fn validate_book(title: &str, status: &str, progress: i64, rating: Option<i64>)
     }
     Ok(())
 }
+
+/// 只有读完且完成日期有效的书才计入年度统计。
+fn finished_on(record: &BookRecord) -> Option<NaiveDate> {
+    if record.reading_status != "finished" {
+        return None;
+    }
+    NaiveDate::parse_from_str(record.finished_at.as_deref()?, "%Y-%m-%d").ok()
+}
+
+/// 当天读完算 1 天；日期缺失或完成早于开始时不计。
+fn days_to_finish(record: &BookRecord) -> Option<i64> {
+    let started = NaiveDate::parse_from_str(record.started_at.as_deref()?, "%Y-%m-%d").ok()?;
+    let days = (finished_on(record)? - started).num_days();
+    (days >= 0).then_some(days + 1)
+}
+
+fn average(values: impl Iterator<Item = i64>) -> Option<f64> {
+    let (sum, count) = values.fold((0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| (sum as f64 / count as f64 * 10.0).round() / 10.0)
+}
+
+/// 去重用的键：书名和作者忽略大小写和多余空白。
+fn import_key(title: &str, author: &str) -> String {
+    let normalize = |value: &str| {
+        value
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    format!("{}\u{1f}{}", normalize(title), normalize(author))
+}
+
+fn validate_locator(locator: &str) -> Result<&str> {
+    let locator = locator.trim();
+    if locator.is_empty() || locator.len() > MAX_LOCATOR_LENGTH {
+        return Err(AppError::BadRequest("Invalid reading locator".to_string()));
+    }
+    Ok(locator)
+}
+
+fn validate_note(note: &str) -> Result<()> {
+    if note.chars().count() > MAX_HIGHLIGHT_NOTE_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "Notes are limited to {MAX_HIGHLIGHT_NOTE_CHARS} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_color(color: &str) -> Result<()> {
+    if !VALID_HIGHLIGHT_COLORS.contains(&color) {
+        return Err(AppError::BadRequest("Invalid highlight color".to_string()));
+    }
+    Ok(())
+}
+
+fn notes_markdown(book: &Book, highlights: &[BookHighlight]) -> String {
+    let record = &book.record;
+    let mut markdown = format!("# {}\n\n", record.title.trim());
+    if !record.author.trim().is_empty() {
+        markdown.push_str(&format!("*{}*\n\n", record.author.trim()));
+    }
+    if highlights.is_empty() {
+        markdown.push_str("还没有划线。\n");
+        return markdown;
+    }
+    let multiple_files = highlights
+        .iter()
+        .any(|highlight| highlight.book_file_id != highlights[0].book_file_id);
+    let mut section: Option<(i64, &str)> = None;
+    for highlight in highlights {
+        let current = (highlight.book_file_id, highlight.chapter.as_str());
+        if section != Some(current) {
+            section = Some(current);
+            let mut heading = if highlight.chapter.is_empty() {
+                "未分章节".to_string()
+            } else {
+                highlight.chapter.clone()
+            };
+            if multiple_files {
+                if let Some(file) = book.files.iter().find(|f| f.id == highlight.book_file_id) {
+                    heading = format!("{heading}（{}）", file.format.to_uppercase());
+                }
+            }
+            markdown.push_str(&format!("## {heading}\n\n"));
+        }
+        for line in highlight.text.lines() {
+            markdown.push_str(&format!("> {line}\n"));
+        }
+        if let Some(page) = highlight.locator.strip_prefix("page:") {
+            markdown.push_str(&format!(">\n> — 第 {page} 页\n"));
+        }
+        markdown.push('\n');
+        if !highlight.note.is_empty() {
+            markdown.push_str(&highlight.note);
+            markdown.push_str("\n\n");
+        }
+    }
+    markdown
+}
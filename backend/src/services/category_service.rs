@@ -19,6 +19,10 @@ impl CategoryService {
         CategoryRepository::list(self.database.pool()).await
     }
 
+    pub async fn get_category(&self, id: i64) -> Result<Option<Category>> {
+        CategoryRepository::get_by_id(self.database.pool(), id).await
+    }
+
     pub async fn update_category(
         &self,
         id: i64,
@@ -17,7 +17,8 @@ impl ChangelogService {
 
     pub async fn list(&self, published_only: bool) -> Result<Vec<ChangelogEntry>> {
         let sql = if published_only {
-            format!("SELECT {CHANGELOG_COLUMNS} FROM changelog_entries WHERE status = 1 ORDER BY published_at DESC, id DESC")
+            // published_at 在未来的条目视为定时发布，到点前不公开。
+            format!("SELECT {CHANGELOG_COLUMNS} FROM changelog_entries WHERE status = 1 AND julianday(published_at) <= julianday('now') ORDER BY published_at DESC, id DESC")
         } else {
             format!("SELECT {CHANGELOG_COLUMNS} FROM changelog_entries ORDER BY published_at DESC, id DESC")
         };
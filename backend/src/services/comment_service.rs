@@ -0,0 +1,542 @@
+use crate::database::{repositories::PostRepository, Database};
+use crate::models::{
+    BanCommenterRequest, Comment, CommentSubmitted, CreateCommentRequest, GiscusComment,
+    GiscusExport, GiscusImportSummary, PostStatus, PublicComment, COMMENT_APPROVED,
+    COMMENT_PENDING, COMMENT_REJECTED, COMMENT_SPAM,
+};
+use crate::utils::error::{AppError, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const COMMENT_COLUMNS: &str = "c.id, c.post_id, p.title AS post_title, c.parent_id, c.author_name, c.author_email, c.author_url, c.email_hash, c.avatar_url, c.content, c.status, c.spam_reasons, c.ip, c.user_agent, c.source, c.external_id, c.created_at, c.updated_at";
+
+const MAX_NAME_CHARS: usize = 50;
+const MAX_CONTENT_CHARS: usize = 5000;
+const MAX_URL_CHARS: usize = 200;
+/// 超过这个数量的链接基本都是广告。
+const MAX_LINKS: usize = 2;
+const SPAM_KEYWORDS: &[&str] = &[
+    "viagra",
+    "cialis",
+    "casino",
+    "porn",
+    "crypto giveaway",
+    "seo service",
+    "博彩",
+    "赌场",
+    "代开发票",
+    "刷单",
+    "加微信",
+];
+
+pub struct CommentService {
+    database: Database,
+}
+
+impl CommentService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// 已通过审核的评论树；`post_id` 为 None 是留言板。
+    pub async fn list_thread(&self, post_id: Option<i64>) -> Result<Vec<PublicComment>> {
+        if let Some(post_id) = post_id {
+            self.ensure_published(post_id).await?;
+        }
+        let comments = sqlx::query_as::<_, Comment>(&format!(
+            "SELECT {COMMENT_COLUMNS} FROM comments c LEFT JOIN posts p ON p.id = c.post_id
+             WHERE c.post_id IS ? AND c.status = ? ORDER BY c.created_at ASC, c.id ASC"
+        ))
+        .bind(post_id)
+        .bind(COMMENT_APPROVED)
+        .fetch_all(self.database.pool())
+        .await?;
+        Ok(build_tree(comments))
+    }
+
+    pub async fn submit(
+        &self,
+        post_id: Option<i64>,
+        request: CreateCommentRequest,
+        ip: &str,
+        user_agent: Option<String>,
+    ) -> Result<CommentSubmitted> {
+        let author_name = request.author_name.trim().to_string();
+        let content = request.content.trim().to_string();
+        let author_email = non_empty(request.author_email);
+        let author_url = non_empty(request.author_url);
+        validate_comment(
+            &author_name,
+            &content,
+            author_email.as_deref(),
+            author_url.as_deref(),
+        )?;
+        if let Some(post_id) = post_id {
+            self.ensure_published(post_id).await?;
+        }
+        if let Some(parent_id) = request.parent_id {
+            let parent_ok: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM comments WHERE id = ? AND post_id IS ? AND status = ?)",
+            )
+            .bind(parent_id)
+            .bind(post_id)
+            .bind(COMMENT_APPROVED)
+            .fetch_one(self.database.pool())
+            .await?;
+            if !parent_ok {
+                return Err(AppError::BadRequest(
+                    "Reply target does not exist".to_string(),
+                ));
+            }
+        }
+
+        let email_hash = author_email.as_deref().map(email_hash);
+        let banned: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM comment_bans WHERE (kind = 'ip' AND value = ?) OR (kind = 'email' AND value = ?))",
+        )
+        .bind(ip)
+        .bind(&email_hash)
+        .fetch_one(self.database.pool())
+        .await?;
+        if banned {
+            return Err(AppError::Forbidden(
+                "You are not allowed to comment".to_string(),
+            ));
+        }
+
+        let mut reasons = spam_reasons(&content, &author_name, &request.website);
+        let duplicate: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM comments WHERE ip = ? AND content = ? AND julianday(created_at) > julianday('now', '-1 day'))",
+        )
+        .bind(ip)
+        .bind(&content)
+        .fetch_one(self.database.pool())
+        .await?;
+        if duplicate {
+            reasons.push("duplicate".to_string());
+        }
+
+        // 同一邮箱此前有评论被通过，就直接放行；其余进入审核队列。
+        let status = if !reasons.is_empty() {
+            COMMENT_SPAM
+        } else if self.is_trusted(email_hash.as_deref()).await? {
+            COMMENT_APPROVED
+        } else {
+            COMMENT_PENDING
+        };
+
+        let result = sqlx::query(
+            "INSERT INTO comments (post_id, parent_id, author_name, author_email, author_url, email_hash, content, status, spam_reasons, ip, user_agent) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(post_id)
+        .bind(request.parent_id)
+        .bind(&author_name)
+        .bind(&author_email)
+        .bind(&author_url)
+        .bind(&email_hash)
+        .bind(&content)
+        .bind(status)
+        .bind(serde_json::to_string(&reasons)?)
+        .bind(ip)
+        .bind(user_agent)
+        .execute(self.database.pool())
+        .await?;
+        Ok(CommentSubmitted {
+            id: result.last_insert_rowid(),
+            status: status.to_string(),
+        })
+    }
+
+    /// 管理端列表；`status` 为 None 时默认看待审核队列，`all` 看全部。
+    pub async fn list_admin(
+        &self,
+        status: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<Comment>, i64)> {
+        let status = match status.unwrap_or(COMMENT_PENDING) {
+            "all" => None,
+            status => Some(parse_status(status)?),
+        };
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM comments WHERE ? IS NULL OR status = ?")
+                .bind(status)
+                .bind(status)
+                .fetch_one(self.database.pool())
+                .await?;
+        let comments = sqlx::query_as::<_, Comment>(&format!(
+            "SELECT {COMMENT_COLUMNS} FROM comments c LEFT JOIN posts p ON p.id = c.post_id
+             WHERE ? IS NULL OR c.status = ? ORDER BY c.created_at DESC, c.id DESC LIMIT ? OFFSET ?"
+        ))
+        .bind(status)
+        .bind(status)
+        .bind(page_size as i64)
+        .bind((page.saturating_sub(1) * page_size) as i64)
+        .fetch_all(self.database.pool())
+        .await?;
+        Ok((comments, total))
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Comment> {
+        sqlx::query_as::<_, Comment>(&format!(
+            "SELECT {COMMENT_COLUMNS} FROM comments c LEFT JOIN posts p ON p.id = c.post_id WHERE c.id = ?"
+        ))
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Comment not found".to_string()))
+    }
+
+    pub async fn approve(&self, id: i64) -> Result<Comment> {
+        self.set_status(id, COMMENT_APPROVED).await
+    }
+
+    pub async fn reject(&self, id: i64) -> Result<Comment> {
+        self.set_status(id, COMMENT_REJECTED).await
+    }
+
+    /// 封禁评论者（IP 和邮箱），这条评论以及同一来源仍在队列里的评论都标记为垃圾。
+    pub async fn ban(&self, id: i64, request: BanCommenterRequest) -> Result<Comment> {
+        let comment = self.get(id).await?;
+        let ip = comment.ip.clone().filter(|ip| ip != "unknown");
+        let mut tx = self.database.pool().begin().await?;
+        for (kind, value) in [("ip", &ip), ("email", &comment.email_hash)] {
+            if let Some(value) = value {
+                sqlx::query(
+                    "INSERT INTO comment_bans (kind, value, reason) VALUES (?, ?, ?) ON CONFLICT(kind, value) DO UPDATE SET reason = excluded.reason",
+                )
+                .bind(kind)
+                .bind(value)
+                .bind(request.reason.trim())
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        sqlx::query(
+            "UPDATE comments SET status = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ? OR (status = ? AND ((ip IS NOT NULL AND ip = ?) OR (email_hash IS NOT NULL AND email_hash = ?)))",
+        )
+        .bind(COMMENT_SPAM)
+        .bind(id)
+        .bind(COMMENT_PENDING)
+        .bind(&ip)
+        .bind(&comment.email_hash)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        self.get(id).await
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM comments WHERE id = ?")
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Comment not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 导入 giscus（GitHub Discussions）的历史评论。按讨论标题对应文章，
+    /// `guestbook` 对应留言板；已导入过的评论按外部 id 跳过，可重复执行。
+    pub async fn import_giscus(&self, export: GiscusExport) -> Result<GiscusImportSummary> {
+        let mut summary = GiscusImportSummary::default();
+        let mut threads = Vec::new();
+        for discussion in export.into_discussions() {
+            if discussion.title.trim().eq_ignore_ascii_case("guestbook") {
+                threads.push((None, discussion));
+                continue;
+            }
+            match self.find_thread_post(discussion.title.trim()).await? {
+                Some(post_id) => threads.push((Some(post_id), discussion)),
+                None => summary.unmatched_threads.push(discussion.title),
+            }
+        }
+
+        let mut tx = self.database.pool().begin().await?;
+        for (post_id, discussion) in threads {
+            // 用栈按原顺序展开成 (评论, 父评论在本库的 id)。
+            let mut pending: Vec<(GiscusComment, Option<i64>)> = discussion
+                .comments
+                .into_vec()
+                .into_iter()
+                .map(|comment| (comment, None))
+                .collect();
+            pending.reverse();
+            while let Some((comment, parent_id)) = pending.pop() {
+                if comment.is_minimized {
+                    summary.skipped += 1;
+                    continue;
+                }
+                let (author_name, avatar_url, author_url) = match &comment.author {
+                    Some(author) => (
+                        author.login.clone(),
+                        author.avatar_url.clone(),
+                        author.url.clone(),
+                    ),
+                    None => ("ghost".to_string(), None, None),
+                };
+                let inserted: Option<i64> = sqlx::query_scalar(
+                    "INSERT INTO comments (post_id, parent_id, author_name, author_url, avatar_url, content, status, source, external_id, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, 'giscus', ?, ?, ?)
+                     ON CONFLICT(external_id) DO NOTHING RETURNING id",
+                )
+                .bind(post_id)
+                .bind(parent_id)
+                .bind(&author_name)
+                .bind(&author_url)
+                .bind(&avatar_url)
+                .bind(comment.body.trim())
+                .bind(COMMENT_APPROVED)
+                .bind(&comment.id)
+                .bind(comment.created_at)
+                .bind(comment.created_at)
+                .fetch_optional(&mut *tx)
+                .await?;
+                let id = match inserted {
+                    Some(id) => {
+                        summary.imported += 1;
+                        id
+                    }
+                    None => {
+                        summary.skipped += 1;
+                        sqlx::query_scalar("SELECT id FROM comments WHERE external_id = ?")
+                            .bind(&comment.id)
+                            .fetch_one(&mut *tx)
+                            .await?
+                    }
+                };
+                let mut replies = comment.replies.into_vec();
+                replies.reverse();
+                pending.extend(replies.into_iter().map(|reply| (reply, Some(id))));
+            }
+        }
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    async fn set_status(&self, id: i64, status: &str) -> Result<Comment> {
+        let result = sqlx::query(
+            "UPDATE comments SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(status)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Comment not found".to_string()));
+        }
+        self.get(id).await
+    }
+
+    async fn ensure_published(&self, post_id: i64) -> Result<()> {
+        let published: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM posts WHERE id = ? AND status = ?)")
+                .bind(post_id)
+                .bind(PostStatus::Published as i32)
+                .fetch_one(self.database.pool())
+                .await?;
+        if published {
+            Ok(())
+        } else {
+            Err(AppError::NotFound("Post not found".to_string()))
+        }
+    }
+
+    async fn is_trusted(&self, email_hash: Option<&str>) -> Result<bool> {
+        let Some(email_hash) = email_hash else {
+            return Ok(false);
+        };
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM comments WHERE email_hash = ? AND status = ?)",
+        )
+        .bind(email_hash)
+        .bind(COMMENT_APPROVED)
+        .fetch_one(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 讨论标题 → 文章：先按标题精确匹配（giscus 的 term 就是文章标题），
+    /// 再兼容 pathname 映射的 `article/<id 或 slug>`。
+    async fn find_thread_post(&self, title: &str) -> Result<Option<i64>> {
+        let by_title: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM posts WHERE title = ? AND status != ? ORDER BY id LIMIT 1",
+        )
+        .bind(title)
+        .bind(PostStatus::Deleted as i32)
+        .fetch_optional(self.database.pool())
+        .await?;
+        if by_title.is_some() {
+            return Ok(by_title);
+        }
+        let Some(key) = title
+            .trim_matches('/')
+            .strip_prefix("article/")
+            .map(|key| key.trim_matches('/'))
+        else {
+            return Ok(None);
+        };
+        match key.parse::<i64>() {
+            Ok(id) => Ok(PostRepository::get_by_id(self.database.pool(), id)
+                .await?
+                .map(|post| post.id)),
+            Err(_) => PostRepository::find_id_by_slug(self.database.pool(), key).await,
+        }
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_status(status: &str) -> Result<&'static str> {
+    [
+        COMMENT_PENDING,
+        COMMENT_APPROVED,
+        COMMENT_REJECTED,
+        COMMENT_SPAM,
+    ]
+    .into_iter()
+    .find(|known| *known == status)
+    .ok_or_else(|| AppError::BadRequest(format!("Unknown comment status: {status}")))
+}
+
+fn validate_comment(
+    author_name: &str,
+    content: &str,
+    author_email: Option<&str>,
+    author_url: Option<&str>,
+) -> Result<()> {
+    if author_name.is_empty() || author_name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "Name is required and must be at most {MAX_NAME_CHARS} characters"
+        )));
+    }
+    if content.is_empty() || content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "Comment is required and must be at most {MAX_CONTENT_CHARS} characters"
+        )));
+    }
+    if let Some(email) = author_email {
+        let valid = email.len() <= 254
+            && email
+                .split_once('@')
+                .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+        if !valid {
+            return Err(AppError::BadRequest("Invalid email address".to_string()));
+        }
+    }
+    if let Some(url) = author_url {
+        if url.len() > MAX_URL_CHARS || !(url.starts_with("https://") || url.starts_with("http://"))
+        {
+            return Err(AppError::BadRequest(
+                "Website must be an http(s) URL".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 垃圾评论启发式规则，返回命中的规则名；命中任何一条都进垃圾箱，由管理员复核。
+fn spam_reasons(content: &str, author_name: &str, honeypot: &str) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if !honeypot.trim().is_empty() {
+        reasons.push("honeypot".to_string());
+    }
+    if content.matches("http://").count() + content.matches("https://").count() > MAX_LINKS {
+        reasons.push("too_many_links".to_string());
+    }
+    let haystack = format!("{author_name} {content}").to_lowercase();
+    if SPAM_KEYWORDS
+        .iter()
+        .any(|keyword| haystack.contains(keyword))
+    {
+        reasons.push("keyword".to_string());
+    }
+    let mut run = 0;
+    let mut previous = None;
+    for c in content.chars().filter(|c| !c.is_whitespace()) {
+        run = if previous == Some(c) { run + 1 } else { 1 };
+        previous = Some(c);
+        if run >= 20 {
+            reasons.push("repeated_characters".to_string());
+            break;
+        }
+    }
+    reasons
+}
+
+fn email_hash(email: &str) -> String {
+    hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()))
+}
+
+/// 导入的 GitHub 用户用自己的头像；其余用 Gravatar（SHA-256 邮箱哈希），
+/// 没留邮箱的按昵称生成 identicon，保证同名同头像。
+fn avatar_for(comment: &Comment) -> String {
+    if let Some(url) = &comment.avatar_url {
+        return url.clone();
+    }
+    let hash = comment
+        .email_hash
+        .clone()
+        .unwrap_or_else(|| email_hash(&comment.author_name));
+    format!("https://www.gravatar.com/avatar/{hash}?d=identicon&s=80")
+}
+
+/// 按 parent_id 组装评论树；父评论不可见（未审核、被拒）时其回复也不展示。
+fn build_tree(comments: Vec<Comment>) -> Vec<PublicComment> {
+    let mut children: HashMap<Option<i64>, Vec<PublicComment>> = HashMap::new();
+    for comment in comments {
+        children
+            .entry(comment.parent_id)
+            .or_default()
+            .push(PublicComment {
+                id: comment.id,
+                parent_id: comment.parent_id,
+                avatar_url: avatar_for(&comment),
+                author_name: comment.author_name,
+                author_url: comment.author_url,
+                content: comment.content,
+                created_at: comment.created_at,
+                replies: Vec::new(),
+            });
+    }
+    fn attach(
+        comment: &mut PublicComment,
+        children: &mut HashMap<Option<i64>, Vec<PublicComment>>,
+    ) {
+        comment.replies = children.remove(&Some(comment.id)).unwrap_or_default();
+        for reply in &mut comment.replies {
+            attach(reply, children);
+        }
+    }
+    let mut roots = children.remove(&None).unwrap_or_default();
+    for root in &mut roots {
+        attach(root, &mut children);
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spam_heuristics_flag_links_keywords_and_honeypot() {
+        assert!(spam_reasons("Nice post, thanks!", "reader", "").is_empty());
+        assert_eq!(
+            spam_reasons("a https://x.io b https://y.io c http://z.io", "bot", ""),
+            ["too_many_links"]
+        );
+        assert_eq!(spam_reasons("hello", "Best CASINO", ""), ["keyword"]);
+        assert_eq!(spam_reasons("hello", "bot", "http://bot.io"), ["honeypot"]);
+        assert_eq!(
+            spam_reasons(&"!".repeat(25), "bot", ""),
+            ["repeated_characters"]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! 把文章导出成 EPUB：单篇、分类、标签或系列各成一本书，每篇文章一章。
+//! 正文按站内同样的规则渲染，`/uploads/` 下的图片和封面打包进书里。
+
+use crate::database::Database;
+use crate::models::{EpubExportRequest, PostStatus};
+use crate::utils::epub::{self, EpubBook, EpubChapter, EpubResource};
+use crate::utils::error::{AppError, Result};
+use crate::utils::{markdown, FileHandler};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const BOOK_AUTHOR: &str = "chuyi";
+const BOOK_LANGUAGE: &str = "zh-CN";
+/// 一本书最多收多少篇文章。
+const MAX_EXPORT_POSTS: usize = 200;
+const MAX_TITLE_CHARS: usize = 100;
+/// 前台导出的书缓存多少本。
+const PUBLIC_CACHE_ENTRIES: usize = 32;
+
+#[derive(Clone)]
+pub struct EpubFile {
+    /// ASCII 文件名，用作 `Content-Disposition` 的 `filename`
+    pub file_name: String,
+    pub title: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExportPost {
+    id: i64,
+    slug: Option<String>,
+    title: String,
+    cover_url: Option<String>,
+    content: String,
+    updated_at: DateTime<Utc>,
+}
+
+/// 选中的文章和默认书名、标识。
+struct Selection {
+    posts: Vec<ExportPost>,
+    title: String,
+    identifier: String,
+    file_stem: String,
+}
+
+impl Selection {
+    /// 篇目、顺序、书名或任一篇文章有改动时都会变。
+    fn version(&self) -> String {
+        let ids: Vec<String> = self.posts.iter().map(|post| post.id.to_string()).collect();
+        let updated_at = self.posts.iter().map(|post| post.updated_at).max();
+        format!("{}|{}|{updated_at:?}", self.title, ids.join("-"))
+    }
+}
+
+struct CachedEpub {
+    version: String,
+    file: EpubFile,
+    used: Instant,
+}
+
+pub struct ExportService {
+    database: Database,
+    file_handler: Arc<FileHandler>,
+    /// 前台的导出链接任何人都能反复请求，生成好的书按选择缓存，文章没变就不重新打包。
+    public_cache: Mutex<HashMap<String, CachedEpub>>,
+}
+
+impl ExportService {
+    pub fn new(database: Database, file_handler: Arc<FileHandler>) -> Self {
+        Self {
+            database,
+            file_handler,
+            public_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `published_only` 为 false 时（管理端）还可以导出草稿、私密和定时文章。
+    pub async fn export_epub(
+        &self,
+        request: EpubExportRequest,
+        published_only: bool,
+    ) -> Result<EpubFile> {
+        let selection = self.select(&request, published_only).await?;
+        if selection.posts.is_empty() {
+            return Err(AppError::NotFound("没有可导出的文章".to_string()));
+        }
+        if selection.posts.len() > MAX_EXPORT_POSTS {
+            return Err(AppError::Validation(format!(
+                "一次最多导出 {MAX_EXPORT_POSTS} 篇文章"
+            )));
+        }
+        let title = match request.title.as_deref().map(str::trim) {
+            Some(title) if !title.is_empty() => {
+                if title.chars().count() > MAX_TITLE_CHARS {
+                    return Err(AppError::Validation(format!(
+                        "书名不超过 {MAX_TITLE_CHARS} 字"
+                    )));
+                }
+                title.to_string()
+            }
+            _ => selection.title.clone(),
+        };
+        // 管理端可能带草稿或自定书名，不缓存
+        let cache_key = (published_only && request.title.is_none())
+            .then(|| (selection.identifier.clone(), selection.version()));
+        if let Some((key, version)) = &cache_key {
+            if let Some(file) = self.cached(key, version) {
+                return Ok(file);
+            }
+        }
+
+        let mut images: HashMap<String, String> = HashMap::new();
+        let mut resources = Vec::new();
+        let mut chapters = Vec::with_capacity(selection.posts.len());
+        for post in &selection.posts {
+            let rendered = markdown::render(&post.content, None);
+            for src in epub::image_sources(&rendered.html) {
+                if images.contains_key(&src) {
+                    continue;
+                }
+                let stem = format!("images/{:04}", resources.len() + 1);
+                if let Some(resource) = self.load_image(&src, &stem).await {
+                    images.insert(src, resource.href.clone());
+                    resources.push(resource);
+                }
+            }
+            chapters.push(EpubChapter {
+                title: post.title.clone(),
+                body: epub::to_xhtml(&rendered.html, &images),
+                toc: rendered.toc,
+            });
+        }
+        let mut cover = None;
+        for cover_url in selection
+            .posts
+            .iter()
+            .filter_map(|p| p.cover_url.as_deref())
+        {
+            cover = self.load_image(cover_url, "images/cover").await;
+            if cover.is_some() {
+                break;
+            }
+        }
+
+        let book = EpubBook {
+            identifier: selection.identifier,
+            title: title.clone(),
+            author: BOOK_AUTHOR.to_string(),
+            language: BOOK_LANGUAGE.to_string(),
+            modified: selection
+                .posts
+                .iter()
+                .map(|post| post.updated_at)
+                .max()
+                .unwrap_or_else(Utc::now),
+            cover,
+            chapters,
+            resources,
+        };
+        let data = tokio::task::spawn_blocking(move || epub::build(&book))
+            .await
+            .map_err(|e| AppError::Internal(format!("EPUB build task failed: {e}")))??;
+        let file = EpubFile {
+            file_name: format!("{}.epub", selection.file_stem),
+            title,
+            data,
+        };
+        if let Some((key, version)) = cache_key {
+            self.cache(key, version, file.clone());
+        }
+        Ok(file)
+    }
+
+    fn cached(&self, key: &str, version: &str) -> Option<EpubFile> {
+        let mut cache = self.lock_cache();
+        let entry = cache
+            .get_mut(key)
+            .filter(|entry| entry.version == version)?;
+        entry.used = Instant::now();
+        Some(entry.file.clone())
+    }
+
+    fn cache(&self, key: String, version: String, file: EpubFile) {
+        let mut cache = self.lock_cache();
+        if cache.len() >= PUBLIC_CACHE_ENTRIES && !cache.contains_key(&key) {
+            if let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone())
+            {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            key,
+            CachedEpub {
+                version,
+                file,
+                used: Instant::now(),
+            },
+        );
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedEpub>> {
+        match self.public_cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    async fn select(&self, request: &EpubExportRequest, published_only: bool) -> Result<Selection> {
+        let selectors = [
+            !request.post_ids.is_empty(),
+            request.category_id.is_some(),
+            !request.tag_ids.is_empty(),
+            request.series_id.is_some(),
+        ];
+        if selectors.iter().filter(|selected| **selected).count() != 1 {
+            return Err(AppError::Validation(
+                "post_ids、category_id、tag_ids、series_id 需要且只能指定一个".to_string(),
+            ));
+        }
+        let visible = if published_only {
+            format!("p.status = {}", PostStatus::Published as i32)
+        } else {
+            format!("p.status != {}", PostStatus::Deleted as i32)
+        };
+        let select =
+            "SELECT p.id, p.slug, p.title, p.cover_url, p.content, p.updated_at FROM posts p";
+
+        if !request.post_ids.is_empty() {
+            let mut posts = sqlx::query_as::<_, ExportPost>(&format!(
+                "{select} WHERE p.id IN (SELECT value FROM json_each(?)) AND {visible}"
+            ))
+            .bind(serde_json::to_string(&request.post_ids)?)
+            .fetch_all(self.database.pool())
+            .await?;
+            posts.sort_by_key(|post| request.post_ids.iter().position(|id| *id == post.id));
+            let (title, file_stem) = match posts.as_slice() {
+                [post] => (
+                    post.title.clone(),
+                    post.slug
+                        .clone()
+                        .filter(|slug| slug.is_ascii())
+                        .unwrap_or_else(|| format!("post-{}", post.id)),
+                ),
+                _ => ("文章合集".to_string(), "posts".to_string()),
+            };
+            let ids: Vec<String> = request.post_ids.iter().map(i64::to_string).collect();
+            return Ok(Selection {
+                posts,
+                title,
+                identifier: format!("urn:chuyi-blog:posts:{}", ids.join("-")),
+                file_stem,
+            });
+        }
+
+        if let Some(category_id) = request.category_id {
+            let name: String = sqlx::query_scalar("SELECT name FROM categories WHERE id = ?")
+                .bind(category_id)
+                .fetch_optional(self.database.pool())
+                .await?
+                .ok_or_else(|| AppError::NotFound("Category not found".to_string()))?;
+            let posts = sqlx::query_as::<_, ExportPost>(&format!(
+                "{select} WHERE p.category_id = ? AND {visible} ORDER BY p.created_at ASC, p.id ASC"
+            ))
+            .bind(category_id)
+            .fetch_all(self.database.pool())
+            .await?;
+            return Ok(Selection {
+                posts,
+                title: name,
+                identifier: format!("urn:chuyi-blog:category:{category_id}"),
+                file_stem: format!("category-{category_id}"),
+            });
+        }
+
+        if let Some(series_id) = request.series_id {
+            let title: String = sqlx::query_scalar("SELECT title FROM series WHERE id = ?")
+                .bind(series_id)
+                .fetch_optional(self.database.pool())
+                .await?
+                .ok_or_else(|| AppError::NotFound("Series not found".to_string()))?;
+            let posts = sqlx::query_as::<_, ExportPost>(&format!(
+                "{select} INNER JOIN series_posts sp ON sp.post_id = p.id
+                 WHERE sp.series_id = ? AND {visible} ORDER BY sp.position ASC, p.id ASC"
+            ))
+            .bind(series_id)
+            .fetch_all(self.database.pool())
+            .await?;
+            return Ok(Selection {
+                posts,
+                title,
+                identifier: format!("urn:chuyi-blog:series:{series_id}"),
+                file_stem: format!("series-{series_id}"),
+            });
+        }
+
+        let mut tag_ids = request.tag_ids.clone();
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        let tag_ids_json = serde_json::to_string(&tag_ids)?;
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM tags WHERE id IN (SELECT value FROM json_each(?)) ORDER BY name",
+        )
+        .bind(&tag_ids_json)
+        .fetch_all(self.database.pool())
+        .await?;
+        if names.len() != tag_ids.len() {
+            return Err(AppError::NotFound("Tag not found".to_string()));
+        }
+        let posts = sqlx::query_as::<_, ExportPost>(&format!(
+            "{select} WHERE p.id IN (
+                 SELECT post_id FROM post_tags WHERE tag_id IN (SELECT value FROM json_each(?))
+             ) AND {visible} ORDER BY p.created_at ASC, p.id ASC"
+        ))
+        .bind(&tag_ids_json)
+        .fetch_all(self.database.pool())
+        .await?;
+        let ids: Vec<String> = tag_ids.iter().map(i64::to_string).collect();
+        Ok(Selection {
+            posts,
+            title: names.join("、"),
+            identifier: format!("urn:chuyi-blog:tags:{}", ids.join("-")),
+            file_stem: format!("tags-{}", ids.join("-")),
+        })
+    }
+
+    /// 读取 `/uploads/` 下的图片；站外地址、读不到或不是图片时返回 None。
+    async fn load_image(&self, url: &str, href_stem: &str) -> Option<EpubResource> {
+        let path: PathBuf = self.file_handler.get_file_path(url).ok()?;
+        let media_type = mime_guess::from_path(&path).first()?;
+        if media_type.type_() != mime_guess::mime::IMAGE {
+            return None;
+        }
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match tokio::fs::read(&path).await {
+            Ok(data) => Some(EpubResource {
+                href: format!("{href_stem}.{extension}"),
+                media_type: media_type.essence_str().to_string(),
+                data,
+            }),
+            Err(error) => {
+                tracing::warn!("Skipping image {} in EPUB export: {}", url, error);
+                None
+            }
+        }
+    }
+}
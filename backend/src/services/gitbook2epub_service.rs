@@ -0,0 +1,352 @@
+//! gitbook2epub 在线工具：调用 Python 脚本抓取在线书并用 pandoc 打包成 EPUB。
+//!
+//! 转换跑在后台任务里（见 `JobService`），进度来自脚本 `--progress` 写到 stderr 的
+//! `@@PROGRESS <0-1> <说明>` 行。结果文件在磁盘上保留 `RESULT_TTL`；同样的链接和选项在
+//! `DEDUP_WINDOW` 内直接复用已有任务，不重复转换。
+
+use crate::models::{
+    Gitbook2EpubJob, Gitbook2EpubRequest, Gitbook2EpubResult, Job, JobKind, JOB_SUCCEEDED,
+};
+use crate::services::job_service::{JobContext, JobService};
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+// GNU `timeout` 杀掉整个进程组（含 pandoc 孙进程），是真正的时限。
+// 转换不再占着 HTTP 请求，不受 Cloudflare 100s 的限制。
+const SCRIPT_TIMEOUT_SECS: u64 = 600;
+// tokio 兜底（万一 `timeout` 本身卡住）。
+const HARD_TIMEOUT_SECS: u64 = 630;
+
+/// 结果文件保留多久。
+pub const RESULT_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+/// 相同请求在这段时间内复用已成功的任务；比 TTL 短，复用到的结果还能下载一阵。
+const DEDUP_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// 排队加执行中的转换上限，满了直接拒绝。
+const MAX_PENDING: i64 = 10;
+const PROGRESS_PREFIX: &str = "@@PROGRESS ";
+/// 失败时只需要 stderr 的最后几行来判断原因。
+const STDERR_TAIL_LINES: usize = 100;
+const RESULT_FILE_NAME: &str = "book.epub";
+
+pub struct Gitbook2EpubService {
+    jobs: Arc<JobService>,
+    /// 结果文件和转换缓存都放这里，过期后由 `sweep_expired` 清理
+    work_dir: PathBuf,
+    script: String,
+}
+
+impl Gitbook2EpubService {
+    pub fn new(jobs: Arc<JobService>) -> Self {
+        Self {
+            jobs,
+            work_dir: std::env::temp_dir().join("gb2epub"),
+            script: std::env::var("GITBOOK2EPUB_SCRIPT").unwrap_or_else(|_| {
+                "/var/www/blog/backend/tools/gitbook2epub/gitbook2epub.py".to_string()
+            }),
+        }
+    }
+
+    /// 正在运行的转换任务数（指标用）。
+    pub async fn jobs_in_use(&self) -> Result<i64> {
+        self.jobs.running_count(JobKind::Gitbook2Epub).await
+    }
+
+    /// 提交转换。`request.url` 须已通过 SSRF 检查。
+    pub async fn submit(&self, request: Gitbook2EpubRequest) -> Result<Job> {
+        let key = dedup_key(&request);
+        if let Some(job) = self
+            .jobs
+            .find_reusable(JobKind::Gitbook2Epub, &key, DEDUP_WINDOW)
+            .await?
+        {
+            // 结果文件被提前清掉（比如临时目录被清空）时重新转换
+            if job.status != JOB_SUCCEEDED || self.result_path(&job.token).exists() {
+                return Ok(job);
+            }
+        }
+        if self.jobs.pending_count(JobKind::Gitbook2Epub).await? >= MAX_PENDING {
+            return Err(AppError::TooManyRequests(
+                "排队的转换太多了，请稍后再试".to_string(),
+            ));
+        }
+        self.jobs
+            .enqueue_keyed(
+                JobKind::Gitbook2Epub,
+                serde_json::to_value(&request)?,
+                Some(&key),
+            )
+            .await
+    }
+
+    /// 按令牌查转换任务；令牌属于别的任务种类时当作不存在。
+    pub async fn get(&self, token: &str) -> Result<Job> {
+        let job = self.jobs.get_by_token(token).await?;
+        if job.kind != JobKind::Gitbook2Epub.as_str() {
+            return Err(AppError::NotFound("Job not found".to_string()));
+        }
+        Ok(job)
+    }
+
+    pub async fn describe(&self, job: Job) -> Result<Gitbook2EpubJob> {
+        let queue_position = self.jobs.queue_position(&job).await?;
+        let result = finished_result(&job);
+        let download_url = result
+            .as_ref()
+            .filter(|result| result.expires_at > Utc::now())
+            .map(|_| format!("/api/tools/gitbook2epub/{}/download", job.token));
+        Ok(Gitbook2EpubJob {
+            job_id: job.token,
+            status: job.status,
+            queue_position,
+            progress: job.progress,
+            progress_message: job.progress_message,
+            error: job.error,
+            result,
+            download_url,
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+        })
+    }
+
+    /// 成功且未过期的结果文件路径和文件名。
+    pub async fn result_file(&self, job: &Job) -> Result<(PathBuf, String)> {
+        if job.status != JOB_SUCCEEDED {
+            return Err(AppError::BadRequest(format!("Job is {}", job.status)));
+        }
+        let expired = || AppError::NotFound("转换结果已过期，请重新提交".to_string());
+        let result = finished_result(job).ok_or_else(expired)?;
+        let path = self.result_path(&job.token);
+        if result.expires_at <= Utc::now() || !tokio::fs::try_exists(&path).await? {
+            return Err(expired());
+        }
+        Ok((path, result.file_name))
+    }
+
+    /// 后台任务执行入口。
+    pub async fn convert(&self, job: &Job, context: &JobContext) -> Result<serde_json::Value> {
+        // 同时只跑一个转换，由 `JobService::claim` 按 `JobKind::max_running` 保证
+        let request: Gitbook2EpubRequest = serde_json::from_value(job.payload.clone())?;
+        tokio::fs::create_dir_all(&self.work_dir).await?;
+        let out = self.result_path(&job.token);
+        // 默认缓存目录在脚本旁边（生产环境只读），必须显式指向可写的目录。
+        let cache = self.work_dir.join(format!("cache-{}", job.token));
+        let outcome = self.run_script(&request, &out, &cache, context).await;
+        let _ = tokio::fs::remove_dir_all(&cache).await;
+        if let Err(error) = outcome {
+            let _ = tokio::fs::remove_file(&out).await;
+            return Err(error);
+        }
+
+        let size = tokio::fs::metadata(&out)
+            .await
+            .map_err(|_| AppError::Internal("转换完成但读取文件失败".to_string()))?
+            .len();
+        let ttl = chrono::Duration::from_std(RESULT_TTL).expect("TTL fits in chrono::Duration");
+        Ok(serde_json::to_value(Gitbook2EpubResult {
+            file_name: RESULT_FILE_NAME.to_string(),
+            size,
+            expires_at: Utc::now() + ttl,
+        })?)
+    }
+
+    /// 删除超过 `RESULT_TTL` 的结果文件和残留的缓存目录（取消的任务来不及清理）。
+    pub async fn sweep_expired(&self) -> Result<usize> {
+        let mut entries = match tokio::fs::read_dir(&self.work_dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+        let cutoff = SystemTime::now() - RESULT_TTL;
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.modified()? > cutoff {
+                continue;
+            }
+            let path = entry.path();
+            if metadata.is_dir() {
+                tokio::fs::remove_dir_all(&path).await?;
+            } else {
+                tokio::fs::remove_file(&path).await?;
+            }
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    fn result_path(&self, token: &str) -> PathBuf {
+        self.work_dir.join(format!("{token}.epub"))
+    }
+
+    async fn run_script(
+        &self,
+        request: &Gitbook2EpubRequest,
+        out: &Path,
+        cache: &Path,
+        context: &JobContext,
+    ) -> Result<()> {
+        // 通过 GNU `timeout` 启动：到点用 SIGKILL 杀掉整个进程组，连带 pandoc 孙进程，
+        // 避免超时后残留进程在小内存机器上继续吃内存。
+        let mut cmd = Command::new("timeout");
+        cmd.arg("-s")
+            .arg("KILL")
+            .arg("-k")
+            .arg("5")
+            .arg(SCRIPT_TIMEOUT_SECS.to_string())
+            .arg("python3")
+            .arg(&self.script)
+            .arg(&request.url)
+            .arg("-o")
+            .arg(out)
+            .arg("--no-auto-install") // 服务器上绝不自动 pip 安装/下载浏览器
+            .arg("--cache")
+            .arg(cache)
+            .arg("--progress")
+            .current_dir(&self.work_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        if !request.include_images {
+            cmd.arg("--no-images"); // 默认纯文字，省内存、避免拖垮小机器
+        }
+
+        let mut child = cmd.spawn().map_err(|e| {
+            tracing::error!("gitbook2epub 启动失败: {}", e);
+            AppError::Internal("服务器无法启动转换".to_string())
+        })?;
+        let mut terminate = TerminateOnDrop(child.id());
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let read_stderr = async {
+            let mut lines = BufReader::new(stderr).lines();
+            let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some((fraction, message)) = parse_progress(&line) {
+                    context.progress(fraction, message).await;
+                    continue;
+                }
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+            Vec::from(tail).join("\n")
+        };
+        let run = async { tokio::join!(read_stderr, child.wait()) };
+        let (stderr, status) =
+            match tokio::time::timeout(Duration::from_secs(HARD_TIMEOUT_SECS), run).await {
+                Ok(finished) => finished,
+                Err(_) => return Err(timeout_error()),
+            };
+        terminate.disarm();
+        let status = status?;
+
+        // GNU timeout 在超时杀掉命令时自身以 124 退出。
+        if status.code() == Some(124) {
+            return Err(timeout_error());
+        }
+        if !status.success() {
+            tracing::warn!("gitbook2epub 失败: {}", stderr.trim());
+            let hint = if stderr.contains("pandoc") {
+                "服务器缺少 pandoc"
+            } else if stderr.contains("SSRFError") || stderr.contains("blocked") {
+                "该链接指向了不被允许的地址"
+            } else {
+                "转换失败，请确认链接是可访问的 gitbook / bookdown 在线书"
+            };
+            return Err(AppError::Upstream(hint.to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 任务被取消或超时（future 被丢弃）时给 `timeout` 发 SIGTERM，由它转发给整个进程组；
+/// 直接 SIGKILL `timeout` 的话脚本会变成孤儿继续跑。
+struct TerminateOnDrop(Option<u32>);
+
+impl TerminateOnDrop {
+    fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for TerminateOnDrop {
+    fn drop(&mut self) {
+        if let Some(pid) = self.0 {
+            let _ = std::process::Command::new("kill")
+                .arg("-TERM")
+                .arg(pid.to_string())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+    }
+}
+
+fn timeout_error() -> AppError {
+    AppError::Upstream("转换超时（书太大或站点太慢），请换更小的书或稍后再试".to_string())
+}
+
+/// `@@PROGRESS 0.42 下载页面 21/50` → (0.42, "下载页面 21/50")
+fn parse_progress(line: &str) -> Option<(f64, &str)> {
+    let rest = line.strip_prefix(PROGRESS_PREFIX)?;
+    let (fraction, message) = rest.split_once(' ').unwrap_or((rest, ""));
+    let fraction: f64 = fraction.parse().ok()?;
+    fraction
+        .is_finite()
+        .then(|| (fraction.clamp(0.0, 1.0), message.trim()))
+}
+
+fn finished_result(job: &Job) -> Option<Gitbook2EpubResult> {
+    if job.status != JOB_SUCCEEDED {
+        return None;
+    }
+    serde_json::from_value(job.result.clone()).ok()
+}
+
+fn dedup_key(request: &Gitbook2EpubRequest) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.url.as_bytes());
+    hasher.update([0, request.include_images as u8]);
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_progress_lines() {
+        assert_eq!(
+            parse_progress("@@PROGRESS 0.42 下载页面 21/50"),
+            Some((0.42, "下载页面 21/50"))
+        );
+        assert_eq!(parse_progress("@@PROGRESS 1"), Some((1.0, "")));
+        assert_eq!(parse_progress("@@PROGRESS 7 done"), Some((1.0, "done")));
+        assert_eq!(parse_progress("@@PROGRESS NaN x"), None);
+        assert_eq!(parse_progress("  ! 抓取失败 https://example.com"), None);
+    }
+
+    #[test]
+    fn dedup_key_depends_on_options() {
+        let text = Gitbook2EpubRequest {
+            url: "https://example.com/book/".to_string(),
+            include_images: false,
+        };
+        let images = Gitbook2EpubRequest {
+            include_images: true,
+            ..text.clone()
+        };
+        assert_eq!(dedup_key(&text), dedup_key(&text.clone()));
+        assert_ne!(dedup_key(&text), dedup_key(&images));
+    }
+}
@@ -0,0 +1,503 @@
+//! 持久化在 SQLite 里的后台任务队列。
+//!
+//! 入队只写一行 `jobs`；进程内的 worker（见 `scheduler::spawn_job_workers`）按 `run_at`
+//! 领取任务执行，失败按指数退避重试，直到用完 `max_attempts`。任务状态、进度和结果都在
+//! 表里，进程重启后 `recover` 把中断的任务放回队列。取消是协作式的：排队中的直接取消，
+//! 执行中的由 worker 中止任务 future。
+
+use crate::database::Database;
+use crate::models::{
+    Job, JobKind, JobListQuery, JOB_CANCELLED, JOB_FAILED, JOB_QUEUED, JOB_RUNNING, JOB_SUCCEEDED,
+};
+use crate::services::Services;
+use crate::utils::error::{AppError, Result};
+use crate::utils::OptimizeResult;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+const JOB_COLUMNS: &str = "id, token, kind, payload, status, progress, progress_message, COALESCE(result, 'null') AS result, error, attempts, max_attempts, run_at, cancel_requested, created_at, started_at, finished_at, updated_at, dedup_key";
+
+/// 第 n 次失败后等 `RETRY_BASE_SECS * 2^(n-1)` 秒再试，最多等 `RETRY_MAX_SECS`。
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 60 * 60;
+/// 没有新任务通知时也定期看一眼，退避到期的任务靠这个被领取。
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_ERROR_CHARS: usize = 2000;
+
+pub struct JobService {
+    database: Database,
+    /// 有新任务入队时唤醒空闲的 worker
+    wakeup: Notify,
+    /// 执行中任务的取消信号
+    running: Mutex<HashMap<i64, Arc<Notify>>>,
+}
+
+impl JobService {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            wakeup: Notify::new(),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn enqueue(&self, kind: JobKind, payload: serde_json::Value) -> Result<Job> {
+        self.enqueue_keyed(kind, payload, None).await
+    }
+
+    /// 带去重键入队，配合 `find_reusable` 让相同请求复用已有任务。
+    pub async fn enqueue_keyed(
+        &self,
+        kind: JobKind,
+        payload: serde_json::Value,
+        dedup_key: Option<&str>,
+    ) -> Result<Job> {
+        let payload = if payload.is_null() {
+            json!({})
+        } else {
+            payload
+        };
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let job = sqlx::query_as::<_, Job>(&format!(
+            "INSERT INTO jobs (token, kind, payload, max_attempts, dedup_key) VALUES (?, ?, ?, ?, ?)
+             RETURNING {JOB_COLUMNS}"
+        ))
+        .bind(&token)
+        .bind(kind.as_str())
+        .bind(payload.to_string())
+        .bind(kind.max_attempts())
+        .bind(dedup_key)
+        .fetch_one(self.database.pool())
+        .await?;
+        self.wakeup.notify_one();
+        Ok(job)
+    }
+
+    /// 同一去重键下还在排队/执行的任务，或 `window` 内成功结束的任务。
+    pub async fn find_reusable(
+        &self,
+        kind: JobKind,
+        dedup_key: &str,
+        window: Duration,
+    ) -> Result<Option<Job>> {
+        sqlx::query_as::<_, Job>(&format!(
+            "SELECT {JOB_COLUMNS} FROM jobs
+             WHERE kind = ? AND dedup_key = ?
+               AND (status IN (?, ?)
+                    OR (status = ? AND julianday(finished_at) > julianday('now', ?)))
+             ORDER BY id DESC LIMIT 1"
+        ))
+        .bind(kind.as_str())
+        .bind(dedup_key)
+        .bind(JOB_QUEUED)
+        .bind(JOB_RUNNING)
+        .bind(JOB_SUCCEEDED)
+        .bind(format!("-{} seconds", window.as_secs()))
+        .fetch_optional(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    /// 某种任务里还在排队或执行的数量。
+    pub async fn pending_count(&self, kind: JobKind) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE kind = ? AND status IN (?, ?)")
+            .bind(kind.as_str())
+            .bind(JOB_QUEUED)
+            .bind(JOB_RUNNING)
+            .fetch_one(self.database.pool())
+            .await
+            .map_err(Into::into)
+    }
+
+    /// 某种任务正在执行的数量。
+    pub async fn running_count(&self, kind: JobKind) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE kind = ? AND status = ?")
+            .bind(kind.as_str())
+            .bind(JOB_RUNNING)
+            .fetch_one(self.database.pool())
+            .await
+            .map_err(Into::into)
+    }
+
+    /// 排队中的任务前面还有几个同类任务在排队或执行；其它状态为 0。
+    pub async fn queue_position(&self, job: &Job) -> Result<i64> {
+        if job.status != JOB_QUEUED {
+            return Ok(0);
+        }
+        // 与 `claim` 的领取顺序一致：run_at 在前，同时刻按 id
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM jobs
+             WHERE kind = ?
+               AND (status = ?
+                    OR (status = ? AND (julianday(run_at), id) <
+                        (SELECT julianday(run_at), id FROM jobs WHERE id = ?)))",
+        )
+        .bind(&job.kind)
+        .bind(JOB_RUNNING)
+        .bind(JOB_QUEUED)
+        .bind(job.id)
+        .fetch_one(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Job> {
+        sqlx::query_as::<_, Job>(&format!("SELECT {JOB_COLUMNS} FROM jobs WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(self.database.pool())
+            .await?
+            .ok_or_else(job_not_found)
+    }
+
+    pub async fn get_by_token(&self, token: &str) -> Result<Job> {
+        sqlx::query_as::<_, Job>(&format!("SELECT {JOB_COLUMNS} FROM jobs WHERE token = ?"))
+            .bind(token)
+            .fetch_optional(self.database.pool())
+            .await?
+            .ok_or_else(job_not_found)
+    }
+
+    pub async fn list(
+        &self,
+        query: &JobListQuery,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<Job>, i64)> {
+        let filter = "(? IS NULL OR status = ?) AND (? IS NULL OR kind = ?)";
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM jobs WHERE {filter}"))
+            .bind(&query.status)
+            .bind(&query.status)
+            .bind(&query.kind)
+            .bind(&query.kind)
+            .fetch_one(self.database.pool())
+            .await?;
+        let jobs = sqlx::query_as::<_, Job>(&format!(
+            "SELECT {JOB_COLUMNS} FROM jobs WHERE {filter} ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"
+        ))
+        .bind(&query.status)
+        .bind(&query.status)
+        .bind(&query.kind)
+        .bind(&query.kind)
+        .bind(page_size as i64)
+        .bind(((page - 1) * page_size) as i64)
+        .fetch_all(self.database.pool())
+        .await?;
+        Ok((jobs, total))
+    }
+
+    /// 排队中的任务直接取消；执行中的标记后通知 worker 中止。
+    pub async fn cancel(&self, id: i64) -> Result<Job> {
+        let job = self.get(id).await?;
+        if job.is_finished() {
+            return Err(AppError::BadRequest(format!(
+                "Job is already {}",
+                job.status
+            )));
+        }
+        sqlx::query(
+            "UPDATE jobs SET
+                 status = CASE WHEN status = ? THEN ? ELSE status END,
+                 finished_at = CASE WHEN status = ? THEN CURRENT_TIMESTAMP ELSE finished_at END,
+                 cancel_requested = 1,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?",
+        )
+        .bind(JOB_QUEUED)
+        .bind(JOB_CANCELLED)
+        .bind(JOB_QUEUED)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        if let Some(signal) = self
+            .running
+            .lock()
+            .expect("job registry lock poisoned")
+            .get(&id)
+        {
+            signal.notify_one();
+        }
+        self.get(id).await
+    }
+
+    /// 启动时调用：上次进程退出时还在执行的任务放回队列（已请求取消的直接取消）。
+    pub async fn recover(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE jobs SET
+                 status = CASE WHEN cancel_requested = 1 THEN ? ELSE ? END,
+                 finished_at = CASE WHEN cancel_requested = 1 THEN CURRENT_TIMESTAMP ELSE NULL END,
+                 run_at = CURRENT_TIMESTAMP,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE status = ?",
+        )
+        .bind(JOB_CANCELLED)
+        .bind(JOB_QUEUED)
+        .bind(JOB_RUNNING)
+        .execute(self.database.pool())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 领取一个到期的排队任务并标记为执行中；同一任务只会被一个 worker 领到。
+    /// 执行数已达 `JobKind::max_running` 的种类先跳过，领后面别的任务。
+    pub async fn claim(&self) -> Result<Option<Job>> {
+        let saturated = JobKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                kind.max_running().map(|limit| {
+                    format!(
+                        " AND NOT (kind = '{kind}' AND (SELECT COUNT(*) FROM jobs AS running WHERE running.kind = '{kind}' AND running.status = '{JOB_RUNNING}') >= {limit})",
+                        kind = kind.as_str()
+                    )
+                })
+            })
+            .collect::<String>();
+        sqlx::query_as::<_, Job>(&format!(
+            "UPDATE jobs SET
+                 status = ?,
+                 attempts = attempts + 1,
+                 progress = 0,
+                 progress_message = NULL,
+                 started_at = CURRENT_TIMESTAMP,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = (
+                 SELECT id FROM jobs
+                 WHERE status = ? AND julianday(run_at) <= julianday('now'){saturated}
+                 ORDER BY run_at ASC, id ASC LIMIT 1
+             ) AND status = ?
+             RETURNING {JOB_COLUMNS}"
+        ))
+        .bind(JOB_RUNNING)
+        .bind(JOB_QUEUED)
+        .bind(JOB_QUEUED)
+        .fetch_optional(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    /// `fraction` 取 0 到 1。
+    pub async fn report_progress(&self, id: i64, fraction: f64, message: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE jobs SET progress = ?, progress_message = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ? AND status = ?",
+        )
+        .bind(fraction.clamp(0.0, 1.0))
+        .bind(message)
+        .bind(id)
+        .bind(JOB_RUNNING)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn complete(&self, id: i64, result: &serde_json::Value) -> Result<()> {
+        sqlx::query(
+            "UPDATE jobs SET status = ?, progress = 1, result = ?, error = NULL,
+                 finished_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?",
+        )
+        .bind(JOB_SUCCEEDED)
+        .bind(result.to_string())
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// 还有重试次数就按退避放回队列，返回 true；否则标记为失败。
+    pub async fn fail(&self, id: i64, error: &str) -> Result<bool> {
+        let job = self.get(id).await?;
+        let error: String = error.chars().take(MAX_ERROR_CHARS).collect();
+        if job.attempts < job.max_attempts {
+            let delay = retry_delay_secs(job.attempts);
+            sqlx::query(
+                "UPDATE jobs SET status = ?, error = ?, run_at = datetime('now', ?),
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?",
+            )
+            .bind(JOB_QUEUED)
+            .bind(&error)
+            .bind(format!("+{delay} seconds"))
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+            Ok(true)
+        } else {
+            sqlx::query(
+                "UPDATE jobs SET status = ?, error = ?, finished_at = CURRENT_TIMESTAMP,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?",
+            )
+            .bind(JOB_FAILED)
+            .bind(&error)
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+            Ok(false)
+        }
+    }
+
+    async fn mark_cancelled(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            "UPDATE jobs SET status = ?, finished_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?",
+        )
+        .bind(JOB_CANCELLED)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// 等新任务入队，最多等 `IDLE_POLL_INTERVAL`。
+    pub async fn wait_for_work(&self) {
+        let _ = tokio::time::timeout(IDLE_POLL_INTERVAL, self.wakeup.notified()).await;
+    }
+
+    fn register_running(&self, id: i64) -> Arc<Notify> {
+        let signal = Arc::new(Notify::new());
+        self.running
+            .lock()
+            .expect("job registry lock poisoned")
+            .insert(id, signal.clone());
+        signal
+    }
+
+    fn unregister_running(&self, id: i64) {
+        self.running
+            .lock()
+            .expect("job registry lock poisoned")
+            .remove(&id);
+    }
+}
+
+/// 执行中的任务用来汇报进度。
+#[derive(Clone)]
+pub struct JobContext {
+    jobs: Arc<JobService>,
+    job_id: i64,
+}
+
+impl JobContext {
+    pub async fn progress(&self, fraction: f64, message: impl AsRef<str>) {
+        if let Err(error) = self
+            .jobs
+            .report_progress(self.job_id, fraction, message.as_ref())
+            .await
+        {
+            tracing::warn!(
+                "Failed to report progress of job {}: {}",
+                self.job_id,
+                error
+            );
+        }
+    }
+}
+
+/// 执行一个已领取的任务，并把结果、失败或取消写回表里。
+pub async fn run_claimed(services: &Services, job: Job) {
+    let jobs = services.jobs.clone();
+    let cancel = jobs.register_running(job.id);
+    // 领取之后、登记之前收到的取消请求
+    if job.cancel_requested || jobs.get(job.id).await.is_ok_and(|job| job.cancel_requested) {
+        cancel.notify_one();
+    }
+    let context = JobContext {
+        jobs: jobs.clone(),
+        job_id: job.id,
+    };
+    // 放进独立的 task：任务 panic 不会带走 worker，取消时可以直接 abort
+    let mut handle = tokio::spawn(execute(services.clone(), job.clone(), context));
+    let outcome = tokio::select! {
+        joined = &mut handle => Some(joined),
+        _ = cancel.notified() => {
+            handle.abort();
+            None
+        }
+    };
+    jobs.unregister_running(job.id);
+
+    let recorded = match outcome {
+        None => {
+            tracing::info!("Job {} ({}) cancelled", job.id, job.kind);
+            jobs.mark_cancelled(job.id).await
+        }
+        Some(Ok(Ok(result))) => jobs.complete(job.id, &result).await,
+        Some(Ok(Err(error))) => record_failure(&jobs, &job, &error.to_string()).await,
+        Some(Err(join_error)) => {
+            record_failure(&jobs, &job, &format!("job panicked: {join_error}")).await
+        }
+    };
+    if let Err(error) = recorded {
+        tracing::error!("Failed to record outcome of job {}: {}", job.id, error);
+    }
+}
+
+async fn record_failure(jobs: &JobService, job: &Job, error: &str) -> Result<()> {
+    let retried = jobs.fail(job.id, error).await?;
+    tracing::warn!(
+        "Job {} ({}) failed on attempt {}/{}{}: {}",
+        job.id,
+        job.kind,
+        job.attempts,
+        job.max_attempts,
+        if retried { ", will retry" } else { "" },
+        error
+    );
+    Ok(())
+}
+
+/// 按任务种类分发。返回值存进 `jobs.result`。
+async fn execute(services: Services, job: Job, context: JobContext) -> Result<serde_json::Value> {
+    let kind = JobKind::parse(&job.kind)
+        .ok_or_else(|| AppError::Validation(format!("Unknown job kind: {}", job.kind)))?;
+    match kind {
+        JobKind::OptimizeImages => {
+            let directories = crate::services::ResourceService::IMAGE_DIRECTORIES;
+            let mut total = OptimizeResult::default();
+            for (index, directory) in directories.iter().enumerate() {
+                context
+                    .progress(
+                        index as f64 / directories.len() as f64,
+                        format!("optimizing {directory}"),
+                    )
+                    .await;
+                total.merge(services.resource.optimize_images(directory).await?);
+            }
+            tracing::info!(
+                "Image optimization complete: {} converted, {} skipped, {} failed",
+                total.converted,
+                total.skipped,
+                total.failed
+            );
+            Ok(serde_json::to_value(total)?)
+        }
+        JobKind::Gitbook2Epub => services.gitbook2epub.convert(&job, &context).await,
+        JobKind::ProbeVideo => services.video.probe(&job, &context).await,
+        JobKind::ExtractBookMetadata => services.book_metadata.extract(&job, &context).await,
+    }
+}
+
+fn retry_delay_secs(attempts: i64) -> i64 {
+    let exponent = attempts.clamp(1, 20) - 1;
+    (RETRY_BASE_SECS << exponent).min(RETRY_MAX_SECS)
+}
+
+fn job_not_found() -> AppError {
+    AppError::NotFound("Job not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay_secs(1), 30);
+        assert_eq!(retry_delay_secs(2), 60);
+        assert_eq!(retry_delay_secs(4), 240);
+        assert_eq!(retry_delay_secs(50), RETRY_MAX_SECS);
+    }
+}
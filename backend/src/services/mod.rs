@@ -1,57 +1,123 @@
 pub mod about_service;
+pub mod ai_service;
+pub mod analytics_service;
+pub mod book_metadata_service;
 pub mod book_service;
 pub mod category_service;
 pub mod changelog_service;
+pub mod comment_service;
 pub mod download_service;
+pub mod export_service;
+pub mod gitbook2epub_service;
+pub mod job_service;
 pub mod music_service;
 pub mod pdf_service;
 pub mod post_service;
+pub mod render_service;
 pub mod resource_service;
+pub mod scheduler;
+pub mod search_service;
+pub mod series_service;
 pub mod tag_service;
+pub mod translation_service;
+pub mod video_service;
 
 pub use about_service::AboutService;
+pub use ai_service::AiService;
+pub use analytics_service::AnalyticsService;
+pub use book_metadata_service::BookMetadataService;
 pub use book_service::BookService;
 pub use category_service::CategoryService;
 pub use changelog_service::ChangelogService;
+pub use comment_service::CommentService;
 pub use download_service::DownloadService;
+pub use export_service::ExportService;
+pub use gitbook2epub_service::Gitbook2EpubService;
+pub use job_service::JobService;
 pub use music_service::MusicService;
 pub use pdf_service::PdfService;
 pub use post_service::PostService;
+pub use render_service::RenderService;
 pub use resource_service::ResourceService;
+pub use search_service::SearchService;
+pub use series_service::SeriesService;
 pub use tag_service::TagService;
+pub use translation_service::TranslationService;
+pub use video_service::VideoService;
 
+use crate::config::AiConfig;
 use crate::database::Database;
-use crate::utils::FileHandler;
+use crate::utils::{FileHandler, R2Storage};
 use std::sync::Arc;
 
 /// 所有Service的集中容器，用于AppState注入
 #[derive(Clone)]
 pub struct Services {
     pub post: Arc<PostService>,
+    pub render: Arc<RenderService>,
     pub music: Arc<MusicService>,
     pub download: Arc<DownloadService>,
     pub category: Arc<CategoryService>,
     pub tag: Arc<TagService>,
     pub about: Arc<AboutService>,
+    pub ai: Arc<AiService>,
+    pub analytics: Arc<AnalyticsService>,
     pub book: Arc<BookService>,
+    pub book_metadata: Arc<BookMetadataService>,
     pub changelog: Arc<ChangelogService>,
+    pub comment: Arc<CommentService>,
+    pub export: Arc<ExportService>,
+    pub gitbook2epub: Arc<Gitbook2EpubService>,
+    pub jobs: Arc<JobService>,
     pub pdf: Arc<PdfService>,
     pub resource: Arc<ResourceService>,
+    pub search: Arc<SearchService>,
+    pub series: Arc<SeriesService>,
+    pub translation: Arc<TranslationService>,
+    pub video: Arc<VideoService>,
 }
 
 impl Services {
-    pub fn new(database: Database, file_handler: Arc<FileHandler>, upload_dir: String) -> Self {
+    pub fn new(
+        database: Database,
+        file_handler: Arc<FileHandler>,
+        upload_dir: String,
+        ai_config: AiConfig,
+        r2_storage: Arc<R2Storage>,
+    ) -> Self {
+        let jobs = Arc::new(JobService::new(database.clone()));
+        let book = Arc::new(BookService::new(database.clone(), file_handler.clone()));
         Self {
             post: Arc::new(PostService::new(database.clone(), file_handler.clone())),
+            render: Arc::new(RenderService::new()),
             music: Arc::new(MusicService::new(database.clone(), file_handler.clone())),
             download: Arc::new(DownloadService::new(database.clone(), file_handler.clone())),
             category: Arc::new(CategoryService::new(database.clone())),
             tag: Arc::new(TagService::new(database.clone())),
             about: Arc::new(AboutService::new(database.clone())),
-            book: Arc::new(BookService::new(database.clone(), file_handler.clone())),
+            ai: Arc::new(AiService::new(database.clone(), ai_config)),
+            analytics: Arc::new(AnalyticsService::new(database.clone())),
+            book_metadata: Arc::new(BookMetadataService::new(
+                book.clone(),
+                jobs.clone(),
+                r2_storage,
+            )),
+            book,
             changelog: Arc::new(ChangelogService::new(database.clone())),
+            comment: Arc::new(CommentService::new(database.clone())),
+            export: Arc::new(ExportService::new(database.clone(), file_handler.clone())),
+            gitbook2epub: Arc::new(Gitbook2EpubService::new(jobs.clone())),
             pdf: Arc::new(PdfService::new(database.clone(), file_handler.clone())),
+            search: Arc::new(SearchService::new(database.clone())),
+            series: Arc::new(SeriesService::new(database.clone())),
+            translation: Arc::new(TranslationService::new(database.clone())),
+            video: Arc::new(VideoService::new(
+                database.clone(),
+                file_handler.clone(),
+                jobs.clone(),
+            )),
             resource: Arc::new(ResourceService::new(database, file_handler, upload_dir)),
+            jobs,
         }
     }
 }
@@ -1,14 +1,24 @@
-use crate::database::repositories::TagRepository;
+use crate::database::repositories::{CategoryRepository, PostRevisionRepository, TagRepository};
 use crate::database::{repositories::PostRepository, Database};
 use crate::models::{
-    AdjacentPosts, CreatePostRequest, Post, PostListQuery, PostWithDetails, UpdatePostRequest,
+    AdjacentPosts, CreatePostRequest, DiffHunk, DiffLine, NullablePatch, Post, PostListQuery,
+    PostRevision, PostRevisionDiff, PostRevisionSummary, PostStatus, PostWithDetails, TrashedPost,
+    UpdatePostRequest,
 };
 use crate::utils::error::{AppError, Result};
-use crate::utils::text::markdown_image_urls;
+use crate::utils::text::{markdown_image_urls, slugify_title};
 use crate::utils::FileHandler;
+use chrono::{DateTime, Utc};
+use similar::{ChangeTag, TextDiff};
 use std::collections::HashSet;
 use std::sync::Arc;
 
+/// 每篇文章保留的修订数；更早的修订被裁剪时，只被它们引用的资源才会真正删除。
+const MAX_REVISIONS_PER_POST: i64 = 50;
+const DIFF_CONTEXT_LINES: usize = 3;
+/// 回收站保留天数，超过后文章和资源会被后台任务彻底删除。
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
 pub struct PostService {
     database: Database,
     file_handler: Arc<FileHandler>,
@@ -24,12 +34,22 @@ impl PostService {
 
     pub async fn create_post(&self, mut request: CreatePostRequest) -> Result<Post> {
         request.post_images = Some(markdown_image_urls(&request.content));
+        let (status, publish_at) = resolve_schedule(
+            request.status.unwrap_or(PostStatus::Draft),
+            request.publish_at,
+        )?;
+        request.status = Some(status);
+        request.publish_at = publish_at;
         let tag_ids = request.tag_ids.clone();
         let mut tx = self.database.pool().begin().await?;
-        let post = PostRepository::create_in_tx(&mut tx, request).await?;
+        let mut post = PostRepository::create_in_tx(&mut tx, request).await?;
+        let slug = unique_slug_in_tx(&mut tx, &post.title, post.id).await?;
+        PostRepository::set_slug_in_tx(&mut tx, post.id, &slug).await?;
+        post.slug = Some(slug);
         if let Some(tag_ids) = tag_ids {
             TagRepository::update_post_tags_in_tx(&mut tx, post.id, &tag_ids).await?;
         }
+        PostRevisionRepository::snapshot_in_tx(&mut tx, post.id).await?;
         tx.commit().await?;
         Ok(post)
     }
@@ -38,6 +58,29 @@ impl PostService {
         PostRepository::get_by_id_with_complete_info(self.database.pool(), id).await
     }
 
+    /// 按当前或改名前的 slug 查找文章，返回的文章带当前 slug，调用方据此判断是否需要跳转。
+    pub async fn get_post_by_slug(&self, slug: &str) -> Result<Option<Post>> {
+        match PostRepository::find_id_by_slug(self.database.pool(), slug).await? {
+            Some(id) => self.get_post_detail(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// 为还没有 slug 的文章（迁移前的旧数据）按标题生成 slug。
+    pub async fn backfill_slugs(&self) -> Result<usize> {
+        let missing = PostRepository::list_missing_slugs(self.database.pool()).await?;
+        if missing.is_empty() {
+            return Ok(0);
+        }
+        let mut tx = self.database.pool().begin().await?;
+        for (id, title) in &missing {
+            let slug = unique_slug_in_tx(&mut tx, title, *id).await?;
+            PostRepository::set_slug_in_tx(&mut tx, *id, &slug).await?;
+        }
+        tx.commit().await?;
+        Ok(missing.len())
+    }
+
     pub async fn get_adjacent_posts(&self, id: i64) -> Result<Option<AdjacentPosts>> {
         PostRepository::get_adjacent_published(self.database.pool(), id).await
     }
@@ -46,6 +89,16 @@ impl PostService {
         PostRepository::list_with_complete_info(self.database.pool(), query).await
     }
 
+    pub async fn list_feed_posts(
+        &self,
+        category_id: Option<i64>,
+        tag_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Post>> {
+        PostRepository::list_published_for_feed(self.database.pool(), category_id, tag_id, limit)
+            .await
+    }
+
     pub async fn list_posts_with_details(
         &self,
         query: PostListQuery,
@@ -64,38 +117,96 @@ impl PostService {
         if let Some(content) = &request.content {
             request.post_images = crate::models::NullablePatch::Value(markdown_image_urls(content));
         }
+        let (status, publish_at) = resolve_schedule(
+            request
+                .status
+                .unwrap_or_else(|| PostStatus::from(existing_post.status)),
+            std::mem::take(&mut request.publish_at).resolve(existing_post.publish_at),
+        )?;
+        request.status = Some(status);
+        request.publish_at = nullable(publish_at);
 
         let tag_ids = request.tag_ids.clone();
-        let updated_post = if let Some(tag_ids) = tag_ids {
-            let mut tx = self.database.pool().begin().await?;
-            let Some(post) = PostRepository::update_in_tx(&mut tx, id, request).await? else {
-                tx.rollback().await?;
-                return Ok(None);
-            };
-            TagRepository::update_post_tags_in_tx(&mut tx, id, &tag_ids).await?;
-            tx.commit().await?;
-            Some(post)
-        } else {
-            PostRepository::update(self.database.pool(), id, request).await?
+        let new_slug = request.slug.take();
+        let mut tx = self.database.pool().begin().await?;
+        let Some(mut updated_post) = PostRepository::update_in_tx(&mut tx, id, request).await?
+        else {
+            tx.rollback().await?;
+            return Ok(None);
         };
-
-        if let Some(updated_post) = &updated_post {
-            self.delete_assets_removed_from_post(&existing_post, updated_post)
-                .await;
+        if let Some(new_slug) = new_slug {
+            let slug = slugify_title(&new_slug).ok_or_else(|| {
+                AppError::BadRequest("Slug must contain letters or digits".to_string())
+            })?;
+            match PostRepository::slug_owner_in_tx(&mut tx, &slug).await? {
+                Some(owner) if owner != id => {
+                    return Err(AppError::BadRequest(
+                        "Slug is already used by another post".to_string(),
+                    ));
+                }
+                Some(_) => {}
+                None => PostRepository::set_slug_in_tx(&mut tx, id, &slug).await?,
+            }
+            updated_post.slug = Some(slug);
+        }
+        if let Some(tag_ids) = tag_ids {
+            TagRepository::update_post_tags_in_tx(&mut tx, id, &tag_ids).await?;
         }
-        Ok(updated_post)
+        PostRevisionRepository::snapshot_in_tx(&mut tx, id).await?;
+        tx.commit().await?;
+
+        self.delete_assets_removed_from_post(&existing_post, &updated_post)
+            .await;
+        self.prune_revisions(id).await;
+        Ok(Some(updated_post))
     }
 
+    /// 移入回收站。资源文件保留到彻底删除（手动或超过保留期）时才清理。
     pub async fn delete_post(&self, id: i64) -> Result<bool> {
-        if let Some(post) = PostRepository::get_by_id(self.database.pool(), id).await? {
-            let deleted = PostRepository::delete(self.database.pool(), id).await?;
-            if deleted {
-                self.delete_asset_urls(post_asset_urls(&post)).await;
+        PostRepository::delete(self.database.pool(), id).await
+    }
+
+    pub async fn list_trash(&self, page: u32, page_size: u32) -> Result<(Vec<TrashedPost>, i64)> {
+        PostRepository::list_trashed(self.database.pool(), page, page_size, TRASH_RETENTION_DAYS)
+            .await
+    }
+
+    pub async fn restore_from_trash(&self, id: i64) -> Result<Post> {
+        if !PostRepository::restore_from_trash(self.database.pool(), id).await? {
+            return Err(AppError::NotFound("Post not found in trash".to_string()));
+        }
+        self.get_post_detail(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))
+    }
+
+    /// 彻底删除回收站中的文章，并删除它和它的历史修订引用的资源文件。
+    pub async fn purge_post(&self, id: i64) -> Result<()> {
+        let post = PostRepository::get_trashed(self.database.pool(), id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Post not found in trash".to_string()))?;
+        let mut urls = self.revision_asset_urls(id).await?;
+        urls.extend(post_asset_urls(&post));
+        if PostRepository::purge(self.database.pool(), id).await? {
+            self.delete_asset_urls(urls).await;
+        }
+        Ok(())
+    }
+
+    /// 彻底删除在回收站里超过保留期的文章，返回删除的数量；由后台定时任务调用。
+    pub async fn purge_expired_trash(&self, now: DateTime<Utc>) -> Result<usize> {
+        let cutoff = now - chrono::Duration::days(TRASH_RETENTION_DAYS);
+        let expired = PostRepository::list_trashed_before(self.database.pool(), cutoff).await?;
+        let mut purged = 0;
+        for id in expired {
+            match self.purge_post(id).await {
+                Ok(()) => purged += 1,
+                // 期间被恢复或已被手动删除
+                Err(AppError::NotFound(_)) => {}
+                Err(error) => return Err(error),
             }
-            Ok(deleted)
-        } else {
-            Ok(false)
         }
+        Ok(purged)
     }
 
     pub async fn update_post_cover(&self, id: i64, new_cover_url: String) -> Result<Option<Post>> {
@@ -109,14 +220,24 @@ impl PostService {
                 status: None,
                 post_images: crate::models::NullablePatch::Missing,
                 pdf_url: crate::models::NullablePatch::Missing,
+                publish_at: crate::models::NullablePatch::Missing,
+                slug: None,
                 tag_ids: None,
             };
 
-            match PostRepository::update_in_tx(&mut tx, id, update_request).await {
+            let updated = match PostRepository::update_in_tx(&mut tx, id, update_request).await {
+                Ok(post) => PostRevisionRepository::snapshot_in_tx(&mut tx, id)
+                    .await
+                    .map(|()| post),
+                Err(error) => Err(error),
+            };
+            match updated {
                 Ok(post) => match tx.commit().await {
                     Ok(()) => {
-                        if let Some(old_cover_url) = &existing_post.cover_url {
-                            let _ = self.file_handler.delete_file(old_cover_url).await;
+                        if let Some(updated_post) = &post {
+                            self.delete_assets_removed_from_post(&existing_post, updated_post)
+                                .await;
+                            self.prune_revisions(id).await;
                         }
                         Ok(post)
                     }
@@ -147,14 +268,170 @@ impl PostService {
             return Err(AppError::NotFound("Post not found".to_string()));
         }
 
-        TagRepository::update_post_tags(self.database.pool(), post_id, tag_ids).await
+        let mut tx = self.database.pool().begin().await?;
+        TagRepository::update_post_tags_in_tx(&mut tx, post_id, &tag_ids).await?;
+        PostRevisionRepository::snapshot_in_tx(&mut tx, post_id).await?;
+        tx.commit().await?;
+        self.prune_revisions(post_id).await;
+        Ok(())
+    }
+
+    /// 发布所有到期的定时文章，并为每篇记录一条修订；由后台定时任务调用。
+    pub async fn publish_due_posts(&self, now: DateTime<Utc>) -> Result<Vec<i64>> {
+        let mut tx = self.database.pool().begin().await?;
+        let published = PostRepository::publish_due_in_tx(&mut tx, now).await?;
+        for post_id in &published {
+            PostRevisionRepository::snapshot_in_tx(&mut tx, *post_id).await?;
+        }
+        tx.commit().await?;
+        for post_id in &published {
+            self.prune_revisions(*post_id).await;
+        }
+        Ok(published)
+    }
+
+    pub async fn list_revisions(&self, post_id: i64) -> Result<Vec<PostRevisionSummary>> {
+        self.require_post(post_id).await?;
+        let revisions =
+            PostRevisionRepository::list_for_post(self.database.pool(), post_id).await?;
+        Ok(revisions.iter().map(PostRevisionSummary::from).collect())
+    }
+
+    pub async fn get_revision(&self, post_id: i64, revision_id: i64) -> Result<PostRevision> {
+        PostRevisionRepository::get(self.database.pool(), post_id, revision_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Revision not found".to_string()))
+    }
+
+    pub async fn diff_revisions(
+        &self,
+        post_id: i64,
+        from_id: i64,
+        to_id: i64,
+    ) -> Result<PostRevisionDiff> {
+        let from = self.get_revision(post_id, from_id).await?;
+        let to = self.get_revision(post_id, to_id).await?;
+        Ok(PostRevisionDiff {
+            changed_fields: changed_fields(&from, &to),
+            hunks: line_diff(&from.content, &to.content),
+            from: PostRevisionSummary::from(&from),
+            to: PostRevisionSummary::from(&to),
+        })
+    }
+
+    /// 把文章恢复为某条修订的内容；恢复本身也会生成一条新修订。
+    ///
+    /// 正文图片随内容一起重新登记到 `post_images`，修订引用的资源在裁剪前都不会被删除，
+    /// 所以恢复后的图片仍然可用。已删除的分类和标签会被忽略。
+    pub async fn restore_revision(&self, post_id: i64, revision_id: i64) -> Result<Post> {
+        let revision = self.get_revision(post_id, revision_id).await?;
+        let category_id = match revision.category_id {
+            Some(category_id) => CategoryRepository::get_by_id(self.database.pool(), category_id)
+                .await?
+                .map(|category| category.id),
+            None => None,
+        };
+        let existing_tags: HashSet<i64> = TagRepository::list(self.database.pool())
+            .await?
+            .into_iter()
+            .map(|tag| tag.id)
+            .collect();
+        let tag_ids = revision
+            .tag_ids
+            .iter()
+            .copied()
+            .filter(|tag_id| existing_tags.contains(tag_id))
+            .collect();
+
+        let request = UpdatePostRequest {
+            title: Some(revision.title),
+            cover_url: nullable(revision.cover_url),
+            content: Some(revision.content),
+            category_id: nullable(category_id),
+            status: Some(PostStatus::from(revision.status)),
+            post_images: NullablePatch::Missing,
+            pdf_url: nullable(revision.pdf_url),
+            publish_at: nullable(revision.publish_at),
+            slug: None,
+            tag_ids: Some(tag_ids),
+        };
+        self.update_post(post_id, request)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))
+    }
+
+    async fn require_post(&self, post_id: i64) -> Result<Post> {
+        PostRepository::get_by_id(self.database.pool(), post_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))
     }
 
+    /// 删除本次保存中不再引用的资源，但仍被历史修订引用的资源会保留，以便恢复。
     async fn delete_assets_removed_from_post(&self, before: &Post, after: &Post) {
         let before_urls = post_asset_urls(before);
         let after_urls = post_asset_urls(after);
-        self.delete_asset_urls(before_urls.difference(&after_urls).cloned())
-            .await;
+        let retained = match self.revision_asset_urls(after.id).await {
+            Ok(urls) => urls,
+            Err(error) => {
+                tracing::warn!(
+                    "Skipping asset cleanup for post {}: could not load revisions: {}",
+                    after.id,
+                    error
+                );
+                return;
+            }
+        };
+        let removed: Vec<String> = before_urls
+            .difference(&after_urls)
+            .filter(|url| !retained.contains(*url))
+            .cloned()
+            .collect();
+        self.delete_asset_urls(removed).await;
+    }
+
+    async fn revision_asset_urls(&self, post_id: i64) -> Result<HashSet<String>> {
+        let revisions =
+            PostRevisionRepository::list_for_post(self.database.pool(), post_id).await?;
+        Ok(revisions.iter().flat_map(revision_asset_urls).collect())
+    }
+
+    /// 裁剪超出上限的旧修订，并删除只被这些修订引用的资源。
+    async fn prune_revisions(&self, post_id: i64) {
+        let pruned = match PostRevisionRepository::prune(
+            self.database.pool(),
+            post_id,
+            MAX_REVISIONS_PER_POST,
+        )
+        .await
+        {
+            Ok(pruned) if !pruned.is_empty() => pruned,
+            Ok(_) => return,
+            Err(error) => {
+                tracing::warn!("Failed to prune revisions of post {}: {}", post_id, error);
+                return;
+            }
+        };
+        let mut still_referenced = match self.revision_asset_urls(post_id).await {
+            Ok(urls) => urls,
+            Err(error) => {
+                tracing::warn!("Failed to load revisions of post {}: {}", post_id, error);
+                return;
+            }
+        };
+        match PostRepository::get_by_id(self.database.pool(), post_id).await {
+            Ok(Some(post)) => still_referenced.extend(post_asset_urls(&post)),
+            Ok(None) => {}
+            Err(error) => {
+                tracing::warn!("Failed to load post {}: {}", post_id, error);
+                return;
+            }
+        }
+        let orphaned: HashSet<String> = pruned
+            .iter()
+            .flat_map(revision_asset_urls)
+            .filter(|url| !still_referenced.contains(url))
+            .collect();
+        self.delete_asset_urls(orphaned).await;
     }
 
     async fn delete_asset_urls(&self, urls: impl IntoIterator<Item = String>) {
@@ -171,14 +448,170 @@ impl PostService {
 }
 
 fn post_asset_urls(post: &Post) -> HashSet<String> {
-    let mut urls: HashSet<String> = markdown_image_urls(&post.content).into_iter().collect();
-    if let Some(cover_url) = &post.cover_url {
-        urls.insert(cover_url.clone());
+    asset_urls(
+        &post.content,
+        post.cover_url.as_deref(),
+        post.post_images.as_deref(),
+    )
+}
+
+fn revision_asset_urls(revision: &PostRevision) -> HashSet<String> {
+    asset_urls(
+        &revision.content,
+        revision.cover_url.as_deref(),
+        revision.post_images.as_deref(),
+    )
+}
+
+fn asset_urls(
+    content: &str,
+    cover_url: Option<&str>,
+    post_images: Option<&str>,
+) -> HashSet<String> {
+    let mut urls: HashSet<String> = markdown_image_urls(content).into_iter().collect();
+    if let Some(cover_url) = cover_url {
+        urls.insert(cover_url.to_string());
     }
-    if let Some(stored_images) = &post.post_images {
+    if let Some(stored_images) = post_images {
         if let Ok(stored_images) = serde_json::from_str::<Vec<String>>(stored_images) {
             urls.extend(stored_images);
         }
     }
     urls
 }
+
+/// 由标题生成未被其它文章占用的 slug，重名时依次追加 `-2`、`-3`…
+async fn unique_slug_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    title: &str,
+    post_id: i64,
+) -> Result<String> {
+    let base = slugify_title(title).unwrap_or_else(|| format!("post-{post_id}"));
+    let mut candidate = base.clone();
+    let mut suffix = 1;
+    loop {
+        match PostRepository::slug_owner_in_tx(tx, &candidate).await? {
+            Some(owner) if owner != post_id => {
+                suffix += 1;
+                candidate = format!("{base}-{suffix}");
+            }
+            _ => return Ok(candidate),
+        }
+    }
+}
+
+/// 定时发布必须带发布时间；时间已过则直接发布。其它状态不保留 `publish_at`。
+fn resolve_schedule(
+    status: PostStatus,
+    publish_at: Option<DateTime<Utc>>,
+) -> Result<(PostStatus, Option<DateTime<Utc>>)> {
+    match (status, publish_at) {
+        (PostStatus::Scheduled, None) => Err(AppError::BadRequest(
+            "publish_at is required for scheduled posts".to_string(),
+        )),
+        (PostStatus::Scheduled, Some(publish_at)) if publish_at <= Utc::now() => {
+            Ok((PostStatus::Published, None))
+        }
+        (PostStatus::Scheduled, Some(publish_at)) => Ok((PostStatus::Scheduled, Some(publish_at))),
+        (status, _) => Ok((status, None)),
+    }
+}
+
+fn nullable<T>(value: Option<T>) -> NullablePatch<T> {
+    value
+        .map(NullablePatch::Value)
+        .unwrap_or(NullablePatch::Null)
+}
+
+fn changed_fields(from: &PostRevision, to: &PostRevision) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if from.title != to.title {
+        fields.push("title");
+    }
+    if from.cover_url != to.cover_url {
+        fields.push("cover_url");
+    }
+    if from.category_id != to.category_id {
+        fields.push("category_id");
+    }
+    if from.tag_ids != to.tag_ids {
+        fields.push("tag_ids");
+    }
+    if from.status != to.status {
+        fields.push("status");
+    }
+    if from.pdf_url != to.pdf_url {
+        fields.push("pdf_url");
+    }
+    if from.publish_at != to.publish_at {
+        fields.push("publish_at");
+    }
+    fields
+}
+
+/// 按行比较正文，输出带上下文的 unified 风格分块（行号从 1 开始）。
+fn line_diff(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(DIFF_CONTEXT_LINES)
+        .into_iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Equal => "context",
+                        ChangeTag::Insert => "insert",
+                        ChangeTag::Delete => "delete",
+                    },
+                    text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                })
+                .collect();
+            Some(DiffHunk {
+                old_start: old_range.start + 1,
+                old_lines: old_range.len(),
+                new_start: new_range.start + 1,
+                new_lines: new_range.len(),
+                lines,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_diff_reports_changed_lines_with_context() {
+        let old = "intro\nkeep\nold line\noutro\n";
+        let new = "intro\nkeep\nnew line\noutro\nextra\n";
+
+        let hunks = line_diff(old, new);
+
+        assert_eq!(hunks.len(), 1);
+        let kinds: Vec<(&str, &str)> = hunks[0]
+            .lines
+            .iter()
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("context", "intro"),
+                ("context", "keep"),
+                ("delete", "old line"),
+                ("insert", "new line"),
+                ("context", "outro"),
+                ("insert", "extra"),
+            ]
+        );
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines), (1, 4));
+        assert_eq!((hunks[0].new_start, hunks[0].new_lines), (1, 5));
+        assert!(line_diff(old, old).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::models::{Locale, Post, PostTranslation, DEFAULT_LOCALE};
+use crate::utils::markdown::{self, RenderedMarkdown};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// 缓存上限；满了直接清空——文章总量不大，重新渲染的代价很低。
+const RENDER_CACHE_CAPACITY: usize = 512;
+
+/// (文章 id, 语言, 站点根地址)；订阅源要绝对链接，和站内页面分开缓存。
+type RenderKey = (i64, Locale, Option<&'static str>);
+
+struct CachedRender {
+    updated_at: DateTime<Utc>,
+    rendered: Arc<RenderedMarkdown>,
+}
+
+/// 文章正文渲染，按 (文章 id, 语言) 缓存并比对 updated_at：文章或译文一旦保存，updated_at 变化，旧结果自然失效。
+pub struct RenderService {
+    cache: RwLock<HashMap<RenderKey, CachedRender>>,
+}
+
+impl Default for RenderService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderService {
+    pub fn new() -> Self {
+        Self {
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn render_post(&self, post: &Post) -> Arc<RenderedMarkdown> {
+        self.render_cached(
+            (post.id, DEFAULT_LOCALE, None),
+            post.updated_at,
+            &post.content,
+        )
+    }
+
+    /// 站内链接和图片补成 `base_url` 开头的绝对地址（订阅源用）。
+    pub fn render_post_absolute(
+        &self,
+        post: &Post,
+        base_url: &'static str,
+    ) -> Arc<RenderedMarkdown> {
+        self.render_cached(
+            (post.id, DEFAULT_LOCALE, Some(base_url)),
+            post.updated_at,
+            &post.content,
+        )
+    }
+
+    /// 译文单独缓存；locale 存的是合法值，解析失败只会退化成按原文语言的键。
+    pub fn render_translation(&self, translation: &PostTranslation) -> Arc<RenderedMarkdown> {
+        let locale = Locale::parse(&translation.locale).unwrap_or(DEFAULT_LOCALE);
+        self.render_cached(
+            (translation.post_id, locale, None),
+            translation.updated_at,
+            &translation.content,
+        )
+    }
+
+    fn render_cached(
+        &self,
+        key: RenderKey,
+        updated_at: DateTime<Utc>,
+        content: &str,
+    ) -> Arc<RenderedMarkdown> {
+        if let Some(cached) = self
+            .cache
+            .read()
+            .expect("render cache lock poisoned")
+            .get(&key)
+        {
+            if cached.updated_at == updated_at {
+                return cached.rendered.clone();
+            }
+        }
+
+        let rendered = Arc::new(markdown::render(content, key.2));
+        let mut cache = self.cache.write().expect("render cache lock poisoned");
+        if cache.len() >= RENDER_CACHE_CAPACITY && !cache.contains_key(&key) {
+            cache.clear();
+        }
+        cache.insert(
+            key,
+            CachedRender {
+                updated_at,
+                rendered: rendered.clone(),
+            },
+        );
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(content: &str, updated_at: DateTime<Utc>) -> Post {
+        Post {
+            id: 1,
+            slug: None,
+            title: "t".to_string(),
+            cover_url: None,
+            content: content.to_string(),
+            category_name: None,
+            category_id: None,
+            status: 1,
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tags: Vec::new(),
+            created_at: updated_at,
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn cached_render_is_reused_until_updated_at_changes() {
+        let service = RenderService::new();
+        let saved_at = Utc::now();
+        let first = service.render_post(&post("# One", saved_at));
+        let again = service.render_post(&post("# One", saved_at));
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let edited = service.render_post(&post("# Two", saved_at + chrono::Duration::seconds(1)));
+        assert_eq!(edited.toc[0].id, "two");
+    }
+
+    #[test]
+    fn absolute_renders_are_cached_apart_from_site_renders() {
+        let service = RenderService::new();
+        let post = post("![a](/uploads/a.png)", Utc::now());
+        let site = service.render_post(&post);
+        let feed = service.render_post_absolute(&post, "https://example.com");
+        assert!(site.html.contains(r#"src="/uploads/a.png""#));
+        assert!(feed
+            .html
+            .contains(r#"src="https://example.com/uploads/a.png""#));
+        assert!(Arc::ptr_eq(
+            &feed,
+            &service.render_post_absolute(&post, "https://example.com")
+        ));
+    }
+}
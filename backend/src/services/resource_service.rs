@@ -73,6 +73,7 @@ fn normalize_file_type(subdir: &str) -> String {
         "music_covers" => "music_cover",
         "pdfs" => "pdf",
         "downloads" => "download",
+        "video_posters" => "video_poster",
         "music" => "music",
         _ => subdir,
     }
@@ -80,6 +81,9 @@ fn normalize_file_type(subdir: &str) -> String {
 }
 
 impl ResourceService {
+    /// 存放图片、需要批量转 WebP 的上传子目录
+    pub const IMAGE_DIRECTORIES: [&'static str; 3] = ["images", "covers", "music_covers"];
+
     pub fn new(database: Database, file_handler: Arc<FileHandler>, upload_dir: String) -> Self {
         Self {
             database,
@@ -100,6 +104,7 @@ impl ResourceService {
             "music_covers",
             "pdfs",
             "downloads",
+            "video_posters",
         ];
 
         for subdir in subdirs {
@@ -165,12 +170,11 @@ impl ResourceService {
     async fn get_resource_usage(&self) -> Result<HashMap<String, ResourceUsage>> {
         let mut usage_map: HashMap<String, ResourceUsage> = HashMap::new();
 
-        // Get post cover URLs
-        let covers: Vec<(i64, String, Option<String>)> = sqlx::query_as(
-            "SELECT id, title, cover_url FROM posts WHERE cover_url IS NOT NULL AND status != 2",
-        )
-        .fetch_all(self.database.pool.as_ref())
-        .await?;
+        // Get post cover URLs（回收站里的文章在彻底删除前仍占用资源）
+        let covers: Vec<(i64, String, Option<String>)> =
+            sqlx::query_as("SELECT id, title, cover_url FROM posts WHERE cover_url IS NOT NULL")
+                .fetch_all(self.database.pool.as_ref())
+                .await?;
 
         for (id, title, cover_url) in covers {
             if let Some(url) = cover_url {
@@ -186,10 +190,10 @@ impl ResourceService {
 
         // Get post content images (stored as JSON array in post_images)
         let post_images: Vec<(i64, String, Option<String>)> = sqlx::query_as(
-            "SELECT id, title, post_images FROM posts WHERE post_images IS NOT NULL AND status != 2"
+            "SELECT id, title, post_images FROM posts WHERE post_images IS NOT NULL",
         )
-            .fetch_all(self.database.pool.as_ref())
-            .await?;
+        .fetch_all(self.database.pool.as_ref())
+        .await?;
 
         for (id, title, images_json) in post_images {
             if let Some(json) = images_json {
@@ -296,9 +300,58 @@ impl ResourceService {
             });
         }
 
+        // Get video poster URLs
+        let posters: Vec<(i64, String, String)> =
+            sqlx::query_as("SELECT id, title, poster_url FROM videos WHERE poster_url IS NOT NULL")
+                .fetch_all(self.database.pool.as_ref())
+                .await?;
+
+        for (id, title, url) in posters {
+            let entry = usage_map.entry(url).or_default();
+            entry.is_used = true;
+            entry.used_by.push(UsageRef {
+                ref_type: "video_poster".to_string(),
+                ref_id: id,
+                ref_title: title,
+            });
+        }
+
         Ok(usage_map)
     }
 
+    /// 每个视频被哪些文章嵌入，按视频 id 分组。
+    ///
+    /// 文章里的 `<video src>` 可能用公开链接，也可能换过域名，所以按 R2 对象 key 匹配正文
+    /// （key 里带 UUID，不会误判）。
+    pub async fn video_usage(&self) -> Result<HashMap<i64, Vec<UsageRef>>> {
+        let videos: Vec<(i64, String)> = sqlx::query_as("SELECT id, r2_key FROM videos")
+            .fetch_all(self.database.pool.as_ref())
+            .await?;
+        let mut usage: HashMap<i64, Vec<UsageRef>> = HashMap::new();
+        if videos.is_empty() {
+            return Ok(usage);
+        }
+
+        // 回收站里的文章在彻底删除前仍算引用
+        let posts: Vec<(i64, String, String)> =
+            sqlx::query_as("SELECT id, title, content FROM posts ORDER BY id")
+                .fetch_all(self.database.pool.as_ref())
+                .await?;
+
+        for (post_id, title, content) in &posts {
+            for (video_id, key) in &videos {
+                if content.contains(key.as_str()) {
+                    usage.entry(*video_id).or_default().push(UsageRef {
+                        ref_type: "post_content".to_string(),
+                        ref_id: *post_id,
+                        ref_title: title.clone(),
+                    });
+                }
+            }
+        }
+        Ok(usage)
+    }
+
     /// Get resource statistics
     pub async fn get_stats(&self) -> Result<ResourceStats> {
         let resources = self.list_resources().await?;
@@ -346,20 +399,4 @@ impl ResourceService {
     pub async fn optimize_images(&self, subdir: &str) -> Result<OptimizeResult> {
         self.file_handler.optimize_existing_images(subdir).await
     }
-
-    /// Optimize all image directories
-    pub async fn optimize_all_images(&self) -> Result<OptimizeResult> {
-        let mut total = OptimizeResult::default();
-
-        for subdir in ["images", "covers", "music_covers"] {
-            let result = self.file_handler.optimize_existing_images(subdir).await?;
-            total.converted += result.converted;
-            total.skipped += result.skipped;
-            total.failed += result.failed;
-            total.original_size += result.original_size;
-            total.optimized_size += result.optimized_size;
-        }
-
-        Ok(total)
-    }
 }
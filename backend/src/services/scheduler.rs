@@ -0,0 +1,104 @@
+//! 服务内的后台定时任务。
+
+use crate::services::job_service;
+use crate::services::{AnalyticsService, Gitbook2EpubService, PostService, Services};
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// 定时文章的检查间隔，文章最迟在计划时间之后这么久上线。
+const PUBLISH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const TRASH_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// 访问统计的写库间隔；进程重启会丢掉最后这段时间的浏览。
+const ANALYTICS_FLUSH_INTERVAL: Duration = Duration::from_secs(15);
+/// 同时执行的后台任务数。
+pub const JOB_WORKERS: usize = 2;
+const JOB_CLAIM_RETRY_DELAY: Duration = Duration::from_secs(5);
+const TOOL_RESULT_SWEEP_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// 周期性地把到期的 Scheduled 文章改为 Published。
+pub fn spawn_post_publisher(posts: Arc<PostService>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PUBLISH_CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match posts.publish_due_posts(Utc::now()).await {
+                Ok(published) if !published.is_empty() => {
+                    tracing::info!("Published scheduled posts: {:?}", published);
+                }
+                Ok(_) => {}
+                Err(error) => tracing::warn!("Failed to publish scheduled posts: {}", error),
+            }
+        }
+    })
+}
+
+/// 周期性地彻底删除回收站里超过保留期的文章。
+pub fn spawn_trash_sweeper(posts: Arc<PostService>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(TRASH_SWEEP_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match posts.purge_expired_trash(Utc::now()).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired posts from trash", purged),
+                Err(error) => tracing::warn!("Failed to purge expired trash: {}", error),
+            }
+        }
+    })
+}
+
+/// 周期性地把内存里攒的浏览批量写入数据库。
+pub fn spawn_analytics_flusher(analytics: Arc<AnalyticsService>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ANALYTICS_FLUSH_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Err(error) = analytics.flush(Utc::now()).await {
+                tracing::warn!("Failed to flush page views: {}", error);
+            }
+        }
+    })
+}
+
+/// 启动 `workers` 个后台任务 worker，各自循环领取 `jobs` 表里到期的任务。
+pub fn spawn_job_workers(services: Services, workers: usize) -> Vec<JoinHandle<()>> {
+    (0..workers)
+        .map(|_| {
+            let services = services.clone();
+            tokio::spawn(async move {
+                loop {
+                    match services.jobs.claim().await {
+                        Ok(Some(job)) => job_service::run_claimed(&services, job).await,
+                        Ok(None) => services.jobs.wait_for_work().await,
+                        Err(error) => {
+                            tracing::warn!("Failed to claim background job: {}", error);
+                            tokio::time::sleep(JOB_CLAIM_RETRY_DELAY).await;
+                        }
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// 周期性地删除过期的 gitbook2epub 结果文件。
+pub fn spawn_gitbook2epub_sweeper(gitbook2epub: Arc<Gitbook2EpubService>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(TOOL_RESULT_SWEEP_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match gitbook2epub.sweep_expired().await {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("Removed {} expired gitbook2epub files", removed),
+                Err(error) => tracing::warn!("Failed to sweep gitbook2epub results: {}", error),
+            }
+        }
+    })
+}
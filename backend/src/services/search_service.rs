@@ -0,0 +1,328 @@
+//! 文章全文检索：基于 `posts_fts`(FTS5 trigram)的 bm25 排序与高亮摘要。
+//!
+//! trigram 分词无法匹配少于 3 个字符的词（中文双字词很常见），这类词退化为对
+//! 索引列的 LIKE 过滤；整条查询都是短词时不走 MATCH，按发布时间排序并在 Rust
+//! 侧生成高亮。
+
+use crate::database::Database;
+use crate::models::{PostStatus, SearchHit};
+use crate::utils::error::{AppError, Result};
+use sqlx::Row;
+use std::collections::HashMap;
+
+const MIN_TRIGRAM_CHARS: usize = 3;
+const MAX_QUERY_CHARS: usize = 100;
+const MAX_TERMS: usize = 8;
+const SNIPPET_TOKENS: i64 = 32;
+const FALLBACK_SNIPPET_CHARS: usize = 120;
+
+// 高亮先用控制字符占位，转义 HTML 后再换成 <mark>，避免文章内容里的标签被原样输出。
+const MARK_OPEN: char = '\u{2}';
+const MARK_CLOSE: char = '\u{3}';
+
+// 列顺序与 migrations/006_add_post_search.sql 一致：title, content, tags, category。
+const RANK_EXPR: &str = "bm25(posts_fts, 10.0, 1.0, 5.0, 3.0)";
+
+pub struct SearchService {
+    database: Database,
+}
+
+impl SearchService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// 检索已发布文章；草稿、私密、已删除的文章永远不会出现在结果里。
+    pub async fn search_published(
+        &self,
+        query: &str,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<SearchHit>, i64)> {
+        let terms = parse_terms(query);
+        if terms.is_empty() {
+            return Err(AppError::BadRequest("Search query is required".to_string()));
+        }
+        let (long_terms, short_terms): (Vec<&String>, Vec<&String>) = terms
+            .iter()
+            .partition(|term| term.chars().count() >= MIN_TRIGRAM_CHARS);
+        let match_expr = (!long_terms.is_empty()).then(|| fts_match_expression(&long_terms));
+        let like_patterns: Vec<String> = short_terms
+            .iter()
+            .map(|term| format!("%{}%", escape_like(term)))
+            .collect();
+
+        let mut where_conditions = vec!["p.status = ?".to_string()];
+        if match_expr.is_some() {
+            where_conditions.push("posts_fts MATCH ?".to_string());
+        }
+        for _ in &like_patterns {
+            where_conditions.push(
+                "(posts_fts.title || ' ' || posts_fts.content || ' ' || posts_fts.tags || ' ' || posts_fts.category) LIKE ? ESCAPE '\\'"
+                    .to_string(),
+            );
+        }
+        let where_clause = where_conditions.join(" AND ");
+        let from_clause = "FROM posts_fts
+             INNER JOIN posts p ON p.id = posts_fts.rowid
+             LEFT JOIN categories c ON c.id = p.category_id";
+
+        let count_sql = format!("SELECT COUNT(*) AS count {from_clause} WHERE {where_clause}");
+        let mut count_query = sqlx::query(&count_sql).bind(PostStatus::Published as i32);
+        if let Some(expr) = &match_expr {
+            count_query = count_query.bind(expr.clone());
+        }
+        for pattern in &like_patterns {
+            count_query = count_query.bind(pattern.clone());
+        }
+        let total: i64 = count_query
+            .fetch_one(self.database.pool())
+            .await?
+            .get("count");
+
+        let (ranked_columns, order_by) = if match_expr.is_some() {
+            (
+                format!(
+                    "{RANK_EXPR} AS rank,
+                     highlight(posts_fts, 0, char(2), char(3)) AS title_highlight,
+                     snippet(posts_fts, -1, char(2), char(3), '…', {SNIPPET_TOKENS}) AS snippet"
+                ),
+                "rank ASC, p.created_at DESC, p.id DESC",
+            )
+        } else {
+            (
+                "0.0 AS rank, p.title AS title_highlight, p.content AS snippet".to_string(),
+                "p.created_at DESC, p.id DESC",
+            )
+        };
+        let hits_sql = format!(
+            "SELECT p.id, p.slug, p.title, p.cover_url, p.category_id, c.name AS category_name,
+                    p.created_at, p.updated_at, {ranked_columns}
+             {from_clause}
+             WHERE {where_clause}
+             ORDER BY {order_by}
+             LIMIT ? OFFSET ?"
+        );
+        let mut hits_query = sqlx::query(&hits_sql).bind(PostStatus::Published as i32);
+        if let Some(expr) = &match_expr {
+            hits_query = hits_query.bind(expr.clone());
+        }
+        for pattern in &like_patterns {
+            hits_query = hits_query.bind(pattern.clone());
+        }
+        let offset = (page.max(1) - 1) as i64 * page_size as i64;
+        let rows = hits_query
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(self.database.pool())
+            .await?;
+
+        let post_ids: Vec<i64> = rows.iter().map(|row| row.get("id")).collect();
+        let mut tags_by_post = self.load_tag_names(&post_ids).await?;
+        let hits = rows
+            .into_iter()
+            .map(|row| {
+                let id: i64 = row.get("id");
+                let (title_highlight, snippet) = if match_expr.is_some() {
+                    (
+                        row.get::<String, _>("title_highlight"),
+                        row.get::<String, _>("snippet"),
+                    )
+                } else {
+                    let title: String = row.get("title_highlight");
+                    let content: String = row.get("snippet");
+                    (
+                        mark_terms(&title, &terms),
+                        fallback_snippet(&content, &terms),
+                    )
+                };
+                SearchHit {
+                    id,
+                    slug: row.get("slug"),
+                    title: row.get("title"),
+                    title_highlight: render_highlight(&title_highlight),
+                    snippet: render_highlight(&snippet),
+                    cover_url: row.get("cover_url"),
+                    category_id: row.get("category_id"),
+                    category_name: row.get("category_name"),
+                    tags: tags_by_post.remove(&id).unwrap_or_default(),
+                    rank: row.get("rank"),
+                    created_at: row
+                        .get::<Option<chrono::NaiveDateTime>, _>("created_at")
+                        .unwrap_or_default()
+                        .and_utc(),
+                    updated_at: row
+                        .get::<Option<chrono::NaiveDateTime>, _>("updated_at")
+                        .unwrap_or_default()
+                        .and_utc(),
+                }
+            })
+            .collect();
+
+        Ok((hits, total))
+    }
+
+    async fn load_tag_names(&self, post_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = sqlx::query(
+            "SELECT pt.post_id, t.name
+             FROM post_tags pt
+             INNER JOIN tags t ON t.id = pt.tag_id
+             WHERE pt.post_id IN (SELECT value FROM json_each(?))
+             ORDER BY t.name ASC",
+        )
+        .bind(serde_json::to_string(post_ids)?)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        let mut tags_by_post = HashMap::<i64, Vec<String>>::new();
+        for row in rows {
+            tags_by_post
+                .entry(row.get("post_id"))
+                .or_default()
+                .push(row.get("name"));
+        }
+        Ok(tags_by_post)
+    }
+}
+
+/// 按空白切词（控制字符视为空白），去重并限制长度和词数。
+fn parse_terms(query: &str) -> Vec<String> {
+    let query: String = query
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        let term = term.to_string();
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+        if terms.len() == MAX_TERMS {
+            break;
+        }
+    }
+    terms
+}
+
+/// 每个词都作为 FTS5 短语加引号，用户输入里的 `AND`/`*`/`"` 等语法不会生效。
+fn fts_match_expression(terms: &[&String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// 在文本中为每个命中词（不区分 ASCII 大小写）加上高亮占位符。
+fn mark_terms(text: &str, terms: &[String]) -> String {
+    let lower = text.to_lowercase();
+    if lower.len() != text.len() {
+        return text.to_string();
+    }
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let needle = term.to_lowercase();
+        if needle.is_empty() {
+            continue;
+        }
+        let mut from = 0;
+        while let Some(position) = lower[from..].find(&needle) {
+            let start = from + position;
+            ranges.push((start, start + needle.len()));
+            from = start + needle.len();
+        }
+    }
+    ranges.sort_unstable();
+
+    let mut marked = String::with_capacity(text.len() + ranges.len() * 2);
+    let mut cursor = 0;
+    for (start, end) in ranges {
+        if start < cursor {
+            continue;
+        }
+        marked.push_str(&text[cursor..start]);
+        marked.push(MARK_OPEN);
+        marked.push_str(&text[start..end]);
+        marked.push(MARK_CLOSE);
+        cursor = end;
+    }
+    marked.push_str(&text[cursor..]);
+    marked
+}
+
+/// 没有 MATCH 时无法使用 FTS5 的 snippet()，取首个命中词附近的一段正文。
+fn fallback_snippet(content: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = content.to_lowercase().chars().collect();
+    let first_hit = if lower.len() == chars.len() {
+        terms
+            .iter()
+            .filter_map(|term| {
+                let needle: Vec<char> = term.to_lowercase().chars().collect();
+                lower
+                    .windows(needle.len().max(1))
+                    .position(|window| window == needle.as_slice())
+            })
+            .min()
+    } else {
+        None
+    };
+    let start = first_hit
+        .map(|position| position.saturating_sub(FALLBACK_SNIPPET_CHARS / 4))
+        .unwrap_or(0);
+    let end = (start + FALLBACK_SNIPPET_CHARS).min(chars.len());
+    let mut excerpt: String = chars[start..end].iter().collect();
+    if start > 0 {
+        excerpt.insert(0, '…');
+    }
+    if end < chars.len() {
+        excerpt.push('…');
+    }
+    mark_terms(&excerpt, terms)
+}
+
+fn render_highlight(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MARK_OPEN, "<mark>")
+        .replace(MARK_CLOSE, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_input_cannot_inject_fts_syntax() {
+        let terms = parse_terms(r#"rust" OR title:*   OR"#);
+        let refs: Vec<&String> = terms.iter().collect();
+
+        assert_eq!(terms.len(), 3);
+        assert_eq!(fts_match_expression(&refs), r#""rust""" "OR" "title:*""#);
+    }
+
+    #[test]
+    fn highlights_are_escaped_before_marks_are_inserted() {
+        let marked = mark_terms(
+            "<b>Rust</b> 异步",
+            &["rust".to_string(), "异步".to_string()],
+        );
+
+        assert_eq!(
+            render_highlight(&marked),
+            "&lt;b&gt;<mark>Rust</mark>&lt;/b&gt; <mark>异步</mark>"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! 系列文章：把若干篇文章按顺序组织成「第 N 篇」。公开接口只展示已发布的文章，
+//! 编号也按已发布的文章重新计算，草稿不会在系列里留下空位。
+
+use crate::database::Database;
+use crate::models::{
+    AdjacentPost, CreateSeriesRequest, PostSeriesInfo, PostStatus, Series, SeriesDetail,
+    SeriesNavigation, SeriesPart, SetSeriesPostsRequest, UpdateSeriesRequest,
+};
+use crate::utils::error::{AppError, Result};
+use crate::utils::text::slugify_title;
+
+const MAX_TITLE_CHARS: usize = 100;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+pub struct SeriesService {
+    database: Database,
+}
+
+impl SeriesService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// `published_only` 时不列出还没有已发布文章的系列。
+    pub async fn list(&self, published_only: bool) -> Result<Vec<Series>> {
+        let series = sqlx::query_as::<_, Series>(&format!(
+            "{} ORDER BY s.updated_at DESC, s.id DESC",
+            series_select(published_only)
+        ))
+        .fetch_all(self.database.pool())
+        .await?;
+        Ok(series
+            .into_iter()
+            .filter(|series| !published_only || series.part_count > 0)
+            .collect())
+    }
+
+    /// `key` 可以是数字 id 或 slug。
+    pub async fn get(&self, key: &str, published_only: bool) -> Result<SeriesDetail> {
+        let id = match key.parse::<i64>() {
+            Ok(id) => Some(id),
+            Err(_) => {
+                sqlx::query_scalar("SELECT id FROM series WHERE slug = ?")
+                    .bind(key)
+                    .fetch_optional(self.database.pool())
+                    .await?
+            }
+        };
+        let Some(id) = id else {
+            return Err(series_not_found());
+        };
+        let series = sqlx::query_as::<_, Series>(&format!(
+            "{} WHERE s.id = ?",
+            series_select(published_only)
+        ))
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(series_not_found)?;
+        let parts = self.parts(id, published_only).await?;
+        if published_only && parts.is_empty() {
+            return Err(series_not_found());
+        }
+        Ok(SeriesDetail { series, parts })
+    }
+
+    pub async fn create(&self, request: CreateSeriesRequest) -> Result<SeriesDetail> {
+        let title = validate_title(&request.title)?;
+        validate_description(&request.description)?;
+        let slug = self
+            .resolve_slug(request.slug.as_deref(), &title, None)
+            .await?;
+        let result = sqlx::query("INSERT INTO series (slug, title, description) VALUES (?, ?, ?)")
+            .bind(&slug)
+            .bind(&title)
+            .bind(request.description.trim())
+            .execute(self.database.pool())
+            .await?;
+        self.get(&result.last_insert_rowid().to_string(), false)
+            .await
+    }
+
+    pub async fn update(&self, id: i64, request: UpdateSeriesRequest) -> Result<SeriesDetail> {
+        let current = self.get(&id.to_string(), false).await?.series;
+        let title = match request.title {
+            Some(title) => validate_title(&title)?,
+            None => current.title,
+        };
+        let description = request.description.unwrap_or(current.description);
+        validate_description(&description)?;
+        let slug = match request.slug {
+            Some(slug) => self.resolve_slug(Some(&slug), &title, Some(id)).await?,
+            None => current.slug,
+        };
+        sqlx::query(
+            "UPDATE series SET slug = ?, title = ?, description = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&slug)
+        .bind(&title)
+        .bind(description.trim())
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        self.get(&id.to_string(), false).await
+    }
+
+    /// 删除系列只解除文章的归属，文章本身不动。
+    pub async fn delete(&self, id: i64) -> Result<()> {
+        let result = sqlx::query("DELETE FROM series WHERE id = ?")
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(series_not_found());
+        }
+        Ok(())
+    }
+
+    pub async fn set_posts(&self, id: i64, request: SetSeriesPostsRequest) -> Result<SeriesDetail> {
+        let post_ids = request.post_ids;
+        let mut seen = std::collections::HashSet::new();
+        if let Some(duplicate) = post_ids.iter().find(|id| !seen.insert(**id)) {
+            return Err(AppError::Validation(format!("文章 {duplicate} 重复出现")));
+        }
+        self.get(&id.to_string(), false).await?;
+        let ids_json = serde_json::to_string(&post_ids)?;
+        let existing: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM posts WHERE status != ? AND id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(PostStatus::Deleted as i32)
+        .bind(&ids_json)
+        .fetch_one(self.database.pool())
+        .await?;
+        if existing != post_ids.len() as i64 {
+            return Err(AppError::Validation("部分文章不存在或已删除".to_string()));
+        }
+
+        let mut tx = self.database.pool().begin().await?;
+        sqlx::query(
+            "DELETE FROM series_posts WHERE series_id = ? OR post_id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(id)
+        .bind(&ids_json)
+        .execute(&mut *tx)
+        .await?;
+        for (position, post_id) in post_ids.iter().enumerate() {
+            sqlx::query("INSERT INTO series_posts (series_id, post_id, position) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(post_id)
+                .bind(position as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("UPDATE series SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        self.get(&id.to_string(), false).await
+    }
+
+    /// 已发布文章在系列中的位置及前后篇；文章不在任何系列里时返回 None。
+    pub async fn navigation(&self, post_id: i64) -> Result<Option<SeriesNavigation>> {
+        let series: Option<(i64, String, String)> = sqlx::query_as(
+            "SELECT s.id, s.slug, s.title FROM series_posts sp INNER JOIN series s ON s.id = sp.series_id
+             WHERE sp.post_id = ?",
+        )
+        .bind(post_id)
+        .fetch_optional(self.database.pool())
+        .await?;
+        let Some((series_id, slug, title)) = series else {
+            return Ok(None);
+        };
+        let parts = self.parts(series_id, true).await?;
+        let Some(index) = parts.iter().position(|part| part.id == post_id) else {
+            return Ok(None);
+        };
+        let adjacent = |part: &SeriesPart| AdjacentPost {
+            id: part.id,
+            slug: part.slug.clone(),
+            title: part.title.clone(),
+        };
+        Ok(Some(SeriesNavigation {
+            series: PostSeriesInfo {
+                id: series_id,
+                slug,
+                title,
+                part: parts[index].part,
+                total: parts.len() as i64,
+            },
+            previous: index
+                .checked_sub(1)
+                .map(|previous| adjacent(&parts[previous])),
+            next: parts.get(index + 1).map(adjacent),
+        }))
+    }
+
+    async fn parts(&self, series_id: i64, published_only: bool) -> Result<Vec<SeriesPart>> {
+        let mut parts = sqlx::query_as::<_, SeriesPart>(&format!(
+            "SELECT p.id, p.slug, p.title, p.status, p.created_at
+             FROM series_posts sp INNER JOIN posts p ON p.id = sp.post_id
+             WHERE sp.series_id = ? AND {}
+             ORDER BY sp.position ASC, p.id ASC",
+            visible_condition(published_only)
+        ))
+        .bind(series_id)
+        .fetch_all(self.database.pool())
+        .await?;
+        for (part, number) in parts.iter_mut().zip(1..) {
+            part.part = number;
+        }
+        Ok(parts)
+    }
+
+    /// 显式给的 slug 冲突时报错；按标题生成的自动加数字后缀。
+    async fn resolve_slug(
+        &self,
+        requested: Option<&str>,
+        title: &str,
+        current_id: Option<i64>,
+    ) -> Result<String> {
+        let explicit = requested.filter(|slug| !slug.trim().is_empty());
+        let base = slugify_title(explicit.unwrap_or(title))
+            .ok_or_else(|| AppError::Validation("无法从标题生成 slug，请手动填写".to_string()))?;
+        let mut candidate = base.clone();
+        for suffix in 2.. {
+            let owner: Option<i64> = sqlx::query_scalar("SELECT id FROM series WHERE slug = ?")
+                .bind(&candidate)
+                .fetch_optional(self.database.pool())
+                .await?;
+            match owner {
+                None => break,
+                Some(owner) if Some(owner) == current_id => break,
+                Some(_) if explicit.is_some() => {
+                    return Err(AppError::BadRequest(format!(
+                        "Slug '{candidate}' is already used by another series"
+                    )))
+                }
+                Some(_) => candidate = format!("{base}-{suffix}"),
+            }
+        }
+        Ok(candidate)
+    }
+}
+
+/// `part_count` 按可见的文章计数。
+fn series_select(published_only: bool) -> String {
+    format!(
+        "SELECT s.id, s.slug, s.title, s.description,
+                (SELECT COUNT(*) FROM series_posts sp INNER JOIN posts p ON p.id = sp.post_id
+                 WHERE sp.series_id = s.id AND {}) AS part_count,
+                s.created_at, s.updated_at
+         FROM series s",
+        visible_condition(published_only)
+    )
+}
+
+fn visible_condition(published_only: bool) -> String {
+    if published_only {
+        format!("p.status = {}", PostStatus::Published as i32)
+    } else {
+        format!("p.status != {}", PostStatus::Deleted as i32)
+    }
+}
+
+fn series_not_found() -> AppError {
+    AppError::NotFound("Series not found".to_string())
+}
+
+fn validate_title(title: &str) -> Result<String> {
+    let title = title.trim();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+        return Err(AppError::Validation(format!(
+            "系列标题不能为空且不超过 {MAX_TITLE_CHARS} 字"
+        )));
+    }
+    Ok(title.to_string())
+}
+
+fn validate_description(description: &str) -> Result<()> {
+    if description.chars().count() > MAX_DESCRIPTION_CHARS {
+        return Err(AppError::Validation(format!(
+            "系列简介不超过 {MAX_DESCRIPTION_CHARS} 字"
+        )));
+    }
+    Ok(())
+}
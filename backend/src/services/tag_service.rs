@@ -19,6 +19,10 @@ impl TagService {
         TagRepository::list(self.database.pool()).await
     }
 
+    pub async fn get_tag(&self, id: i64) -> Result<Option<Tag>> {
+        TagRepository::get_by_id(self.database.pool(), id).await
+    }
+
     pub async fn update_tag(&self, id: i64, request: UpdateTagRequest) -> Result<Option<Tag>> {
         TagRepository::update(self.database.pool(), id, request).await
     }
@@ -0,0 +1,178 @@
+//! 文章译文。posts 里的标题和正文是默认语言（中文），译文按 (文章, 语言) 一条，
+//! 公开接口带 `?lang=` 时用译文覆盖标题和正文，没有译文就回退到原文。
+
+use crate::database::Database;
+use crate::models::{
+    Locale, Post, PostStatus, PostTranslation, UpsertTranslationRequest, DEFAULT_LOCALE,
+};
+use crate::utils::error::{AppError, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+const TRANSLATION_COLUMNS: &str = "post_id, locale, title, content, created_at, updated_at";
+const MAX_TITLE_CHARS: usize = 200;
+
+pub struct TranslationService {
+    database: Database,
+}
+
+impl TranslationService {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    pub async fn list(&self, post_id: i64) -> Result<Vec<PostTranslation>> {
+        self.ensure_post(post_id).await?;
+        sqlx::query_as::<_, PostTranslation>(&format!(
+            "SELECT {TRANSLATION_COLUMNS} FROM post_translations WHERE post_id = ? ORDER BY locale"
+        ))
+        .bind(post_id)
+        .fetch_all(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn get(&self, post_id: i64, locale: Locale) -> Result<PostTranslation> {
+        self.find(post_id, locale)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Translation not found".to_string()))
+    }
+
+    pub async fn find(&self, post_id: i64, locale: Locale) -> Result<Option<PostTranslation>> {
+        sqlx::query_as::<_, PostTranslation>(&format!(
+            "SELECT {TRANSLATION_COLUMNS} FROM post_translations WHERE post_id = ? AND locale = ?"
+        ))
+        .bind(post_id)
+        .bind(locale.as_str())
+        .fetch_optional(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn upsert(
+        &self,
+        post_id: i64,
+        locale: Locale,
+        request: UpsertTranslationRequest,
+    ) -> Result<PostTranslation> {
+        if locale == DEFAULT_LOCALE {
+            return Err(AppError::Validation(format!(
+                "{} 是原文语言，直接编辑文章即可",
+                locale.as_str()
+            )));
+        }
+        let title = request.title.trim();
+        if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+            return Err(AppError::Validation(format!(
+                "标题不能为空且不超过 {MAX_TITLE_CHARS} 字"
+            )));
+        }
+        if request.content.trim().is_empty() {
+            return Err(AppError::Validation("正文不能为空".to_string()));
+        }
+        self.ensure_post(post_id).await?;
+
+        sqlx::query_as::<_, PostTranslation>(&format!(
+            "INSERT INTO post_translations (post_id, locale, title, content) VALUES (?, ?, ?, ?)
+             ON CONFLICT (post_id, locale) DO UPDATE SET
+                 title = excluded.title,
+                 content = excluded.content,
+                 updated_at = CURRENT_TIMESTAMP
+             RETURNING {TRANSLATION_COLUMNS}"
+        ))
+        .bind(post_id)
+        .bind(locale.as_str())
+        .bind(title)
+        .bind(&request.content)
+        .fetch_one(self.database.pool())
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn delete(&self, post_id: i64, locale: Locale) -> Result<()> {
+        let result = sqlx::query("DELETE FROM post_translations WHERE post_id = ? AND locale = ?")
+            .bind(post_id)
+            .bind(locale.as_str())
+            .execute(self.database.pool())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Translation not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 文章可用的语言，原文语言排第一。
+    pub async fn locales(&self, post_id: i64) -> Result<Vec<Locale>> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT locale FROM post_translations WHERE post_id = ? ORDER BY locale",
+        )
+        .bind(post_id)
+        .fetch_all(self.database.pool())
+        .await?;
+        Ok(std::iter::once(DEFAULT_LOCALE)
+            .chain(rows.iter().filter_map(|locale| Locale::parse(locale)))
+            .collect())
+    }
+
+    /// 列表页用：一次查出这批文章在 `locale` 下的译文，用标题和正文摘要覆盖到文章上。
+    pub async fn localize(&self, mut posts: Vec<&mut Post>, locale: Locale) -> Result<()> {
+        if locale == DEFAULT_LOCALE || posts.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<i64> = posts.iter().map(|post| post.id).collect();
+        let translations = sqlx::query_as::<_, PostTranslation>(&format!(
+            "SELECT {TRANSLATION_COLUMNS} FROM post_translations
+             WHERE locale = ? AND post_id IN (SELECT value FROM json_each(?))"
+        ))
+        .bind(locale.as_str())
+        .bind(serde_json::to_string(&ids)?)
+        .fetch_all(self.database.pool())
+        .await?;
+        let by_post: HashMap<i64, PostTranslation> = translations
+            .into_iter()
+            .map(|translation| (translation.post_id, translation))
+            .collect();
+        for post in posts.iter_mut() {
+            if let Some(translation) = by_post.get(&post.id) {
+                translation.apply_summary_to(post);
+            }
+        }
+        Ok(())
+    }
+
+    /// 所有已发布文章的译文及更新时间，给 sitemap 用。
+    pub async fn published_locales(&self) -> Result<HashMap<i64, Vec<(Locale, DateTime<Utc>)>>> {
+        let rows: Vec<(i64, String, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT t.post_id, t.locale, t.updated_at FROM post_translations t
+             INNER JOIN posts p ON p.id = t.post_id
+             WHERE p.status = ? ORDER BY t.post_id, t.locale",
+        )
+        .bind(PostStatus::Published as i32)
+        .fetch_all(self.database.pool())
+        .await?;
+        let mut locales: HashMap<i64, Vec<(Locale, DateTime<Utc>)>> = HashMap::new();
+        for (post_id, locale, updated_at) in rows {
+            if let Some(locale) = Locale::parse(&locale) {
+                locales
+                    .entry(post_id)
+                    .or_default()
+                    .push((locale, updated_at));
+            }
+        }
+        Ok(locales)
+    }
+
+    async fn ensure_post(&self, post_id: i64) -> Result<()> {
+        let exists: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM posts WHERE id = ? AND status != ?)")
+                .bind(post_id)
+                .bind(PostStatus::Deleted as i32)
+                .fetch_one(self.database.pool())
+                .await?;
+        if exists {
+            Ok(())
+        } else {
+            Err(AppError::NotFound("Post not found".to_string()))
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! 视频库：分片上传到 R2 的视频在完成时登记一条记录，随后由 `probe_video` 后台任务
+//! 补上时长、分辨率、编码和封面帧（见 `utils::video_probe`）。
+
+use crate::database::Database;
+use crate::models::{
+    CreateVideo, Job, JobKind, ProbeVideoPayload, UpdateVideoRequest, Video, PROBE_FAILED,
+    PROBE_PENDING, PROBE_READY,
+};
+use crate::services::job_service::{JobContext, JobService};
+use crate::utils::error::{AppError, Result};
+use crate::utils::video_probe::{self, VideoProbe};
+use crate::utils::FileHandler;
+use std::path::Path;
+use std::sync::Arc;
+
+const VIDEO_COLUMNS: &str = "id, title, file_name, r2_key, public_url, content_type, file_size, duration, width, height, codec, poster_url, probe_status, probe_error, created_at, updated_at";
+/// 自动截取的封面帧放在这个上传子目录，删除视频或换封面时一并清理。
+pub const POSTER_DIRECTORY: &str = "video_posters";
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_PROBE_ERROR_CHARS: usize = 1000;
+
+pub struct VideoService {
+    database: Database,
+    file_handler: Arc<FileHandler>,
+    jobs: Arc<JobService>,
+}
+
+impl VideoService {
+    pub fn new(database: Database, file_handler: Arc<FileHandler>, jobs: Arc<JobService>) -> Self {
+        Self {
+            database,
+            file_handler,
+            jobs,
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<Video>> {
+        Ok(sqlx::query_as::<_, Video>(&format!(
+            "SELECT {VIDEO_COLUMNS} FROM videos ORDER BY created_at DESC, id DESC"
+        ))
+        .fetch_all(self.database.pool())
+        .await?)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Video> {
+        sqlx::query_as::<_, Video>(&format!("SELECT {VIDEO_COLUMNS} FROM videos WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(self.database.pool())
+            .await?
+            .ok_or_else(|| AppError::NotFound("Video not found".to_string()))
+    }
+
+    /// 登记上传完成的视频，并排队探测元数据。
+    pub async fn create(&self, video: CreateVideo) -> Result<Video> {
+        let title = match video.title.trim() {
+            "" => Path::new(&video.file_name)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(&video.file_name)
+                .to_string(),
+            title => title.to_string(),
+        };
+        validate_title(&title)?;
+        let id = sqlx::query(
+            "INSERT INTO videos (title, file_name, r2_key, public_url, content_type, file_size) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(title)
+        .bind(video.file_name)
+        .bind(video.r2_key)
+        .bind(video.public_url)
+        .bind(video.content_type)
+        .bind(video.file_size)
+        .execute(self.database.pool())
+        .await?
+        .last_insert_rowid();
+        self.enqueue_probe(id).await?;
+        self.get(id).await
+    }
+
+    pub async fn update(&self, id: i64, request: UpdateVideoRequest) -> Result<Video> {
+        let current = self.get(id).await?;
+        let title = request
+            .title
+            .map(|title| title.trim().to_string())
+            .unwrap_or(current.title);
+        validate_title(&title)?;
+        let poster_url = request
+            .poster_url
+            .map(|url| url.filter(|url| !url.trim().is_empty()))
+            .unwrap_or_else(|| current.poster_url.clone());
+        sqlx::query(
+            "UPDATE videos SET title = ?, poster_url = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(title)
+        .bind(&poster_url)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        if current.poster_url != poster_url {
+            self.delete_generated_poster(current.poster_url.as_deref())
+                .await;
+        }
+        self.get(id).await
+    }
+
+    /// 删除记录、R2 上的视频和自动截取的封面。调用方负责确认没有文章还在引用。
+    pub async fn delete(&self, id: i64) -> Result<()> {
+        let video = self.get(id).await?;
+        self.file_handler.delete_file(&video.public_url).await?;
+        self.delete_generated_poster(video.poster_url.as_deref())
+            .await;
+        sqlx::query("DELETE FROM videos WHERE id = ?")
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// 重新探测（比如服务器后来才装上 ffmpeg）。
+    pub async fn reprobe(&self, id: i64) -> Result<Job> {
+        self.get(id).await?;
+        sqlx::query(
+            "UPDATE videos SET probe_status = ?, probe_error = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(PROBE_PENDING)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        self.enqueue_probe(id).await
+    }
+
+    /// 写入探测结果。只在还没有封面时用自动截取的帧。
+    pub async fn apply_probe(
+        &self,
+        id: i64,
+        probe: &VideoProbe,
+        poster_url: Option<String>,
+    ) -> Result<Video> {
+        sqlx::query(
+            "UPDATE videos SET duration = ?, width = ?, height = ?, codec = ?, poster_url = COALESCE(poster_url, ?), probe_status = ?, probe_error = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(probe.duration)
+        .bind(probe.width)
+        .bind(probe.height)
+        .bind(&probe.codec)
+        .bind(poster_url)
+        .bind(PROBE_READY)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        self.get(id).await
+    }
+
+    /// `probe_video` 任务：ffprobe 读元数据，没有封面时再用 ffmpeg 截一帧。
+    pub async fn probe(&self, job: &Job, context: &JobContext) -> Result<serde_json::Value> {
+        let payload: ProbeVideoPayload = serde_json::from_value(job.payload.clone())?;
+        let video = match self.get(payload.video_id).await {
+            Ok(video) => video,
+            // 排队期间视频被删掉了
+            Err(AppError::NotFound(_)) => return Ok(serde_json::Value::Null),
+            Err(error) => return Err(error),
+        };
+
+        context.progress(0.1, "reading video metadata").await;
+        let probe = match video_probe::probe(&video.public_url).await {
+            Ok(probe) => probe,
+            Err(error) => {
+                self.record_probe_failure(video.id, &error.to_string())
+                    .await?;
+                return Err(error);
+            }
+        };
+
+        let mut poster_url = None;
+        if video.poster_url.is_none() && probe.has_video_stream() {
+            context.progress(0.5, "extracting poster frame").await;
+            // 截不到封面不影响元数据
+            match self.extract_poster(&video, &probe).await {
+                Ok(url) => poster_url = Some(url),
+                Err(error) => {
+                    tracing::warn!("Failed to extract poster for video {}: {error}", video.id)
+                }
+            }
+        }
+        let video = self.apply_probe(video.id, &probe, poster_url).await?;
+        Ok(serde_json::to_value(video)?)
+    }
+
+    async fn extract_poster(&self, video: &Video, probe: &VideoProbe) -> Result<String> {
+        let frame = video_probe::poster_frame(&video.public_url, probe.poster_offset()).await?;
+        let (url, _, _) = self
+            .file_handler
+            .save_optimized_image_data(frame, "poster.jpg", POSTER_DIRECTORY, None)
+            .await?;
+        Ok(url)
+    }
+
+    async fn enqueue_probe(&self, id: i64) -> Result<Job> {
+        self.jobs
+            .enqueue(
+                JobKind::ProbeVideo,
+                serde_json::to_value(ProbeVideoPayload { video_id: id })?,
+            )
+            .await
+    }
+
+    async fn record_probe_failure(&self, id: i64, error: &str) -> Result<()> {
+        let error: String = error.chars().take(MAX_PROBE_ERROR_CHARS).collect();
+        sqlx::query(
+            "UPDATE videos SET probe_status = ?, probe_error = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(PROBE_FAILED)
+        .bind(error)
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// 只删自动截取的封面，手动指定的图片可能还用在别处。
+    async fn delete_generated_poster(&self, poster_url: Option<&str>) {
+        let Some(url) = poster_url.filter(|url| url.contains(&format!("/{POSTER_DIRECTORY}/")))
+        else {
+            return;
+        };
+        if let Err(error) = self.file_handler.delete_file(url).await {
+            tracing::warn!("Failed to delete video poster {url}: {error}");
+        }
+    }
+}
+
+fn validate_title(title: &str) -> Result<()> {
+    if title.is_empty() {
+        return Err(AppError::BadRequest("Video title is required".to_string()));
+    }
+    if title.chars().count() > MAX_TITLE_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "Video title must be at most {MAX_TITLE_CHARS} characters"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,642 @@
+//! 从电子书文件里读元数据：EPUB 的 OPF 包文件、PDF 的 Info 字典和页面树。
+//!
+//! 只做尽力而为的解析，不引入完整的 zip / PDF 库：EPUB 的 zip 和 OPF 解析在 `epub_reader` 里，
+//! PDF 在原始字节和压缩的对象流（`/ObjStm`）里按对象号查找。
+//! Zip64 和加密的 PDF 字符串不支持，遇到时对应字段留空。
+
+use crate::utils::epub_reader::{inflate, parse_package, resolve_href, rootfile_path, ZipArchive};
+use crate::utils::error::{AppError, Result};
+use flate2::read::ZlibDecoder;
+use regex::bytes::Regex;
+use std::sync::LazyLock;
+
+const MAX_TEXT_CHARS: usize = 5_000;
+
+#[derive(Debug, Default)]
+pub struct BookMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub page_count: Option<i64>,
+    pub cover: Option<CoverImage>,
+}
+
+#[derive(Debug)]
+pub struct CoverImage {
+    /// 书内的文件名，只用于日志
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// `format` 是文件扩展名（`epub` / `pdf`），其它格式返回 `Ok(None)`。
+pub fn extract(format: &str, data: &[u8]) -> Result<Option<BookMetadata>> {
+    match format {
+        "epub" => extract_epub(data).map(Some),
+        "pdf" => extract_pdf(data).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn invalid(message: &str) -> AppError {
+    AppError::BadRequest(message.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// EPUB
+// ---------------------------------------------------------------------------
+
+fn extract_epub(data: &[u8]) -> Result<BookMetadata> {
+    let archive = ZipArchive::open(data)?;
+    let container = archive.read_text("META-INF/container.xml")?;
+    let opf_path =
+        rootfile_path(&container).ok_or_else(|| invalid("EPUB container.xml has no rootfile"))?;
+    let package = parse_package(&archive.read_text(&opf_path)?)?;
+
+    let cover = match package.cover_href() {
+        Some(href) => {
+            let path = resolve_href(&opf_path, href);
+            archive.read(&path).ok().map(|data| CoverImage {
+                file_name: path.rsplit('/').next().unwrap_or(&path).to_string(),
+                data,
+            })
+        }
+        None => None,
+    };
+    Ok(BookMetadata {
+        title: package.title,
+        author: (!package.creators.is_empty()).then(|| package.creators.join(", ")),
+        description: package.description.map(|text| strip_tags(&text)),
+        page_count: None,
+        cover,
+    }
+    .normalized())
+}
+
+/// 简介常常是转义过的 HTML，去掉标签，块级元素换成换行。
+fn strip_tags(html: &str) -> String {
+    static BLOCK_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r"(?i)<br\s*/?>|</(p|div|li|h[1-6])>").expect("block tag regex is valid")
+    });
+    static TAG_RE: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"<[^>]*>").expect("tag regex is valid"));
+    static BLANK_LINES_RE: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\n\s*\n+").expect("blank line regex is valid"));
+
+    let text = BLOCK_RE.replace_all(html, "\n");
+    let text = TAG_RE.replace_all(&text, "");
+    let text = quick_xml::escape::unescape(&text)
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| text.into_owned());
+    BLANK_LINES_RE.replace_all(text.trim(), "\n\n").into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// PDF
+// ---------------------------------------------------------------------------
+
+static INFO_REF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/Info\s*(\d+)\s+(\d+)\s+R").expect("info regex is valid"));
+static ROOT_REF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/Root\s*(\d+)\s+(\d+)\s+R").expect("root regex is valid"));
+static OBJECT_STREAM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/Type\s*/ObjStm\b").expect("object stream regex is valid"));
+static PAGES_COUNT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"/Type\s*/Pages\b[^>]*?/Count\s+(\d+)|/Count\s+(\d+)[^>]*?/Type\s*/Pages\b")
+        .expect("pages regex is valid")
+});
+
+fn extract_pdf(data: &[u8]) -> Result<BookMetadata> {
+    if !data.starts_with(b"%PDF-") {
+        return Err(invalid("File is not a PDF"));
+    }
+    let document = PdfDocument::new(data);
+    let mut metadata = BookMetadata {
+        page_count: document.page_count(),
+        ..BookMetadata::default()
+    };
+    // 加密文档的字符串是密文，不去读
+    if !document.is_encrypted() {
+        if let Some(info) = last_reference(&INFO_REF_RE, data).and_then(|id| document.object(id)) {
+            let info = dictionary(&info).unwrap_or_default();
+            metadata.title = document.string_entry(info, b"Title");
+            metadata.author = document.string_entry(info, b"Author");
+            metadata.description = document.string_entry(info, b"Subject");
+        }
+    }
+    Ok(metadata.normalized())
+}
+
+struct PdfDocument<'a> {
+    data: &'a [u8],
+    /// 解压后的对象流：(对象号, 对象内容)
+    compressed_objects: Vec<(u32, Vec<u8>)>,
+}
+
+impl<'a> PdfDocument<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let mut compressed_objects = Vec::new();
+        for found in OBJECT_STREAM_RE.find_iter(data) {
+            let Some(dict_start) = find_backwards(&data[..found.start()], b"<<") else {
+                continue;
+            };
+            let Some(dict) = dictionary(&data[dict_start..]) else {
+                continue;
+            };
+            if let Some(stream) = stream_data(data, dict_start + dict.len() + 4, dict) {
+                compressed_objects.extend(object_stream_entries(dict, &stream));
+            }
+        }
+        Self {
+            data,
+            compressed_objects,
+        }
+    }
+
+    fn is_encrypted(&self) -> bool {
+        static ENCRYPT_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"/Encrypt\s*\d+\s+\d+\s+R").expect("regex is valid"));
+        ENCRYPT_RE.is_match(self.data)
+    }
+
+    /// 对象 `id` 的内容（`obj` 之后的部分），增量更新时取最后一个版本。
+    fn object(&self, id: u32) -> Option<Vec<u8>> {
+        let pattern = Regex::new(&format!(r"(?:^|[^0-9]){id}\s+\d+\s+obj\b")).ok()?;
+        if let Some(found) = pattern.find_iter(self.data).last() {
+            return Some(self.data[found.end()..].to_vec());
+        }
+        self.compressed_objects
+            .iter()
+            .rev()
+            .find(|(object_id, _)| *object_id == id)
+            .map(|(_, body)| body.clone())
+    }
+
+    /// 从根目录的 `/Pages` 节点读 `/Count`；读不到时取所有 Pages 节点里最大的计数。
+    fn page_count(&self) -> Option<i64> {
+        let from_tree = || {
+            let catalog = self.object(last_reference(&ROOT_REF_RE, self.data)?)?;
+            let pages_id = reference_entry(dictionary(&catalog)?, b"Pages")?;
+            let pages = self.object(pages_id)?;
+            integer_entry(dictionary(&pages)?, b"Count")
+        };
+        from_tree().or_else(|| {
+            let raw = PAGES_COUNT_RE.captures_iter(self.data);
+            let compressed = self
+                .compressed_objects
+                .iter()
+                .flat_map(|(_, body)| PAGES_COUNT_RE.captures_iter(body));
+            raw.chain(compressed)
+                .filter_map(|captures| {
+                    let count = captures.get(1).or_else(|| captures.get(2))?;
+                    std::str::from_utf8(count.as_bytes()).ok()?.parse().ok()
+                })
+                .max()
+        })
+    }
+
+    /// 字典里的字符串，值也可能是指向字符串对象的间接引用。
+    fn string_entry(&self, dict: &[u8], key: &[u8]) -> Option<String> {
+        let value = entry_value(dict, key)?;
+        if let Some(id) = parse_reference(value) {
+            return parse_string(&self.object(id)?).map(|bytes| decode_text_string(&bytes));
+        }
+        parse_string(value).map(|bytes| decode_text_string(&bytes))
+    }
+}
+
+fn last_reference(pattern: &Regex, data: &[u8]) -> Option<u32> {
+    let captures = pattern.captures_iter(data).last()?;
+    std::str::from_utf8(&captures[1]).ok()?.parse().ok()
+}
+
+fn find_backwards(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|&byte| !is_whitespace(byte))
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+/// 从 `<<` 开始的字典内容（不含两端的尖括号），正确跳过嵌套字典和字符串。
+fn dictionary(data: &[u8]) -> Option<&[u8]> {
+    let data = skip_whitespace(data);
+    if !data.starts_with(b"<<") {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < data.len() {
+        match data[index] {
+            b'(' => {
+                index += literal_string_length(&data[index..])?;
+                continue;
+            }
+            b'<' if data.get(index + 1) == Some(&b'<') => {
+                depth += 1;
+                index += 2;
+                continue;
+            }
+            b'<' => {
+                index += hex_string_length(&data[index..])?;
+                continue;
+            }
+            b'>' if data.get(index + 1) == Some(&b'>') => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    return Some(&data[2..index - 2]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+/// 字典里某个键对应的值的起始位置（只看顶层的键）。
+fn entry_value<'d>(dict: &'d [u8], key: &[u8]) -> Option<&'d [u8]> {
+    let mut index = 0;
+    let mut depth = 0usize;
+    while index < dict.len() {
+        match dict[index] {
+            b'(' => {
+                index += literal_string_length(&dict[index..])?;
+                continue;
+            }
+            b'<' if dict.get(index + 1) == Some(&b'<') => {
+                depth += 1;
+                index += 2;
+                continue;
+            }
+            b'<' => {
+                index += hex_string_length(&dict[index..])?;
+                continue;
+            }
+            b'>' if dict.get(index + 1) == Some(&b'>') => {
+                depth = depth.saturating_sub(1);
+                index += 2;
+                continue;
+            }
+            b'/' if depth == 0 => {
+                let name_end = dict[index + 1..]
+                    .iter()
+                    .position(|&byte| is_whitespace(byte) || b"/<>[]()".contains(&byte))
+                    .map_or(dict.len(), |end| index + 1 + end);
+                if &dict[index + 1..name_end] == key {
+                    return Some(skip_whitespace(&dict[name_end..]));
+                }
+                index = name_end;
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+fn integer_entry(dict: &[u8], key: &[u8]) -> Option<i64> {
+    let value = entry_value(dict, key)?;
+    let end = value
+        .iter()
+        .position(|byte| !byte.is_ascii_digit())
+        .unwrap_or(value.len());
+    std::str::from_utf8(&value[..end]).ok()?.parse().ok()
+}
+
+fn reference_entry(dict: &[u8], key: &[u8]) -> Option<u32> {
+    parse_reference(entry_value(dict, key)?)
+}
+
+/// `12 0 R`
+fn parse_reference(value: &[u8]) -> Option<u32> {
+    static REFERENCE_RE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(\d+)\s+\d+\s+R\b").expect("reference regex is valid"));
+    let captures = REFERENCE_RE.captures(value)?;
+    std::str::from_utf8(&captures[1]).ok()?.parse().ok()
+}
+
+/// `stream` 关键字之后的数据，按 `/Filter` 解压（只支持 FlateDecode）。
+fn stream_data(data: &[u8], after_dict: usize, dict: &[u8]) -> Option<Vec<u8>> {
+    let rest = skip_whitespace(data.get(after_dict..)?);
+    let rest = rest.strip_prefix(b"stream")?;
+    let rest = rest
+        .strip_prefix(b"\r\n")
+        .or_else(|| rest.strip_prefix(b"\n"))?;
+    let end = match integer_entry(dict, b"Length") {
+        Some(length) if (length as usize) <= rest.len() => length as usize,
+        _ => rest.windows(9).position(|window| window == b"endstream")?,
+    };
+    let raw = &rest[..end];
+    match entry_value(dict, b"Filter") {
+        None => Some(raw.to_vec()),
+        Some(filter)
+            if filter.starts_with(b"/FlateDecode") || filter.starts_with(b"[/FlateDecode]") =>
+        {
+            inflate(ZlibDecoder::new(raw))
+        }
+        Some(_) => None,
+    }
+}
+
+/// 对象流开头是 `/N` 对「对象号 偏移」，偏移相对 `/First`。
+fn object_stream_entries(dict: &[u8], stream: &[u8]) -> Vec<(u32, Vec<u8>)> {
+    let (Some(count), Some(first)) = (integer_entry(dict, b"N"), integer_entry(dict, b"First"))
+    else {
+        return Vec::new();
+    };
+    let first = first as usize;
+    let Some(header) = stream.get(..first) else {
+        return Vec::new();
+    };
+    let numbers: Vec<usize> = String::from_utf8_lossy(header)
+        .split_ascii_whitespace()
+        .filter_map(|token| token.parse().ok())
+        .take(count as usize * 2)
+        .collect();
+    let offsets: Vec<(u32, usize)> = numbers
+        .chunks_exact(2)
+        .map(|pair| (pair[0] as u32, first + pair[1]))
+        .collect();
+    offsets
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(id, start))| {
+            let end = offsets.get(index + 1).map_or(stream.len(), |next| next.1);
+            Some((id, stream.get(start..end.max(start))?.to_vec()))
+        })
+        .collect()
+}
+
+/// 十六进制字符串 `<...>` 的总长度。
+fn hex_string_length(data: &[u8]) -> Option<usize> {
+    data.iter()
+        .position(|&byte| byte == b'>')
+        .map(|end| end + 1)
+}
+
+/// 字面量字符串 `( ... )` 的总长度，括号可以嵌套，`\` 转义下一个字符。
+fn literal_string_length(data: &[u8]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < data.len() {
+        match data[index] {
+            b'\\' => index += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+/// 解析字面量字符串或十六进制字符串，返回原始字节。
+fn parse_string(value: &[u8]) -> Option<Vec<u8>> {
+    let value = skip_whitespace(value);
+    match value.first()? {
+        b'(' => {
+            let inner = &value[1..literal_string_length(value)? - 1];
+            Some(unescape_literal(inner))
+        }
+        b'<' if value.get(1) != Some(&b'<') => {
+            let end = value.iter().position(|&byte| byte == b'>')?;
+            let mut digits: Vec<u8> = value[1..end]
+                .iter()
+                .copied()
+                .filter(u8::is_ascii_hexdigit)
+                .collect();
+            if digits.len() % 2 == 1 {
+                digits.push(b'0');
+            }
+            hex::decode(digits).ok()
+        }
+        _ => None,
+    }
+}
+
+fn unescape_literal(inner: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(inner.len());
+    let mut index = 0;
+    while index < inner.len() {
+        let byte = inner[index];
+        index += 1;
+        if byte == b'\r' {
+            // 行尾统一成 \n
+            if inner.get(index) == Some(&b'\n') {
+                index += 1;
+            }
+            output.push(b'\n');
+            continue;
+        }
+        if byte != b'\\' {
+            output.push(byte);
+            continue;
+        }
+        let Some(&escaped) = inner.get(index) else {
+            break;
+        };
+        index += 1;
+        match escaped {
+            b'n' => output.push(b'\n'),
+            b'r' => output.push(b'\r'),
+            b't' => output.push(b'\t'),
+            b'b' => output.push(0x08),
+            b'f' => output.push(0x0c),
+            b'0'..=b'7' => {
+                let mut code = u32::from(escaped - b'0');
+                for _ in 0..2 {
+                    match inner.get(index) {
+                        Some(&digit @ b'0'..=b'7') => {
+                            code = code * 8 + u32::from(digit - b'0');
+                            index += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                output.push(code as u8);
+            }
+            // 反斜杠加换行是续行
+            b'\r' => {
+                if inner.get(index) == Some(&b'\n') {
+                    index += 1;
+                }
+            }
+            b'\n' => {}
+            other => output.push(other),
+        }
+    }
+    output
+}
+
+/// PDF 文本字符串：带 BOM 的 UTF-16BE / UTF-8，否则按 PDFDocEncoding（近似 Latin-1）。
+fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(b"\xfe\xff") {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    if let Some(utf8) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        return String::from_utf8_lossy(utf8).into_owned();
+    }
+    bytes.iter().map(|&byte| byte as char).collect()
+}
+
+impl BookMetadata {
+    /// 去掉空白字段，过长的文本截断。
+    fn normalized(self) -> Self {
+        let clean = |value: Option<String>| {
+            value
+                .map(|text| text.trim().chars().take(MAX_TEXT_CHARS).collect::<String>())
+                .filter(|text| !text.is_empty())
+        };
+        Self {
+            title: clean(self.title),
+            author: clean(self.author),
+            description: clean(self.description),
+            page_count: self.page_count.filter(|&count| count > 0),
+            cover: self.cover.filter(|cover| !cover.data.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::epub::{self, EpubBook, EpubChapter, EpubResource};
+    use chrono::Utc;
+
+    #[test]
+    fn reads_epub_package_metadata_and_cover() {
+        let book = EpubBook {
+            identifier: "urn:uuid:test".to_string(),
+            title: "三体 & 其他".to_string(),
+            author: "刘慈欣".to_string(),
+            language: "zh-CN".to_string(),
+            modified: Utc::now(),
+            cover: Some(EpubResource {
+                href: "images/cover.png".to_string(),
+                media_type: "image/png".to_string(),
+                data: b"not really a png".to_vec(),
+            }),
+            chapters: vec![EpubChapter {
+                title: "第一章".to_string(),
+                body: "<p>正文</p>".to_string(),
+                toc: Vec::new(),
+            }],
+            resources: Vec::new(),
+        };
+        let data = epub::build(&book).expect("build epub");
+
+        let metadata = extract("epub", &data).expect("extract").expect("metadata");
+        assert_eq!(metadata.title.as_deref(), Some("三体 & 其他"));
+        assert_eq!(metadata.author.as_deref(), Some("刘慈欣"));
+        assert_eq!(metadata.page_count, None);
+        let cover = metadata.cover.expect("cover");
+        assert_eq!(cover.file_name, "cover.png");
+        assert_eq!(cover.data, b"not really a png");
+
+        assert!(extract("epub", b"PK not a zip").is_err());
+        assert!(extract("mobi", &data).expect("mobi").is_none());
+    }
+
+    #[test]
+    fn parses_epub2_package_details() {
+        let package = parse_package(
+            r#"<?xml version="1.0"?>
+            <package xmlns="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <metadata>
+                <dc:title>Meditations</dc:title>
+                <dc:creator>Marcus Aurelius</dc:creator>
+                <dc:creator>George Long</dc:creator>
+                <dc:description>&lt;p&gt;Book one.&lt;/p&gt;&lt;p&gt;Book &amp;amp; two.&lt;/p&gt;</dc:description>
+                <meta name="cover" content="img-1"/>
+              </metadata>
+              <manifest>
+                <item id="img-1" href="../Images/front%20page.jpg" media-type="image/jpeg"/>
+              </manifest>
+            </package>"#,
+        )
+        .expect("parse package");
+        assert_eq!(package.title.as_deref(), Some("Meditations"));
+        assert_eq!(package.creators, ["Marcus Aurelius", "George Long"]);
+        assert_eq!(
+            strip_tags(package.description.as_deref().unwrap()),
+            "Book one.\nBook & two."
+        );
+        assert_eq!(
+            resolve_href("OEBPS/Text/content.opf", package.cover_href().unwrap()),
+            "OEBPS/Images/front page.jpg"
+        );
+    }
+
+    #[test]
+    fn reads_pdf_info_dictionary_and_page_tree() {
+        let pdf = b"%PDF-1.4\n\
+            1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+            2 0 obj\n<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>\nendobj\n\
+            6 0 obj\n<< /Title (Notes \\(draft\\) \\351t\\351) /Author <FEFF 5218 6148 6B23> \
+            /Subject 7 0 R /Producer (x) >>\nendobj\n\
+            7 0 obj\n(An essay)\nendobj\n\
+            trailer\n<< /Size 8 /Root 1 0 R /Info 6 0 R >>\n%%EOF\n";
+        let metadata = extract("pdf", pdf).expect("extract").expect("metadata");
+        assert_eq!(metadata.title.as_deref(), Some("Notes (draft) été"));
+        assert_eq!(metadata.author.as_deref(), Some("刘慈欣"));
+        assert_eq!(metadata.description.as_deref(), Some("An essay"));
+        assert_eq!(metadata.page_count, Some(3));
+        assert!(metadata.cover.is_none());
+
+        assert!(extract("pdf", b"<html>").is_err());
+    }
+
+    #[test]
+    fn reads_pdf_objects_from_compressed_object_streams() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let objects = b"<< /Type /Catalog /Pages 2 0 R >> << /Type /Pages /Count 412 >> << /Title (Compressed) >>";
+        let header = b"1 0 2 34 6 64 ";
+        let mut body = header.to_vec();
+        body.extend_from_slice(objects);
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&body).unwrap();
+        let stream = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.5\n9 0 obj\n".to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "<< /Type /ObjStm /N 3 /First {} /Length {} /Filter /FlateDecode >>\nstream\n",
+                header.len(),
+                stream.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&stream);
+        pdf.extend_from_slice(
+            b"\nendstream\nendobj\n10 0 obj\n<< /Type /XRef /Root 1 0 R /Info 6 0 R >>\nendobj\n",
+        );
+
+        let metadata = extract("pdf", &pdf).expect("extract").expect("metadata");
+        assert_eq!(metadata.title.as_deref(), Some("Compressed"));
+        assert_eq!(metadata.page_count, Some(412));
+    }
+}
@@ -0,0 +1,563 @@
+//! EPUB 3 打包
+//!
+//! 不依赖外部工具：正文沿用 `markdown::render` 的输出（ammonia 清洗过的 HTML），
+//! 在这里转成 XHTML，和图片、封面、导航文档（nav.xhtml）一起写进 zip 容器。
+//! `mimetype` 按规范放在第一个条目且不压缩。
+
+use crate::handlers::seo_handler::abs_url;
+use crate::utils::markdown::TocEntry;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use quick_xml::escape::escape;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{self, Cursor, Write};
+use std::sync::LazyLock;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+pub const EPUB_MEDIA_TYPE: &str = "application/epub+zip";
+
+const STYLESHEET: &str = "body { line-height: 1.7; margin: 0 5%; }
+h1 { font-size: 1.6em; margin: 1.2em 0 0.8em; }
+h2 { font-size: 1.3em; }
+h3 { font-size: 1.1em; }
+img, video { max-width: 100%; height: auto; }
+pre { white-space: pre-wrap; font-size: 0.85em; background: #f5f5f5; padding: 0.6em; }
+code { font-family: monospace; }
+blockquote { margin-left: 0; padding-left: 1em; border-left: 3px solid #ccc; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; }
+.cover { text-align: center; margin: 0; padding: 0; }
+.cover img { max-height: 100%; }
+";
+
+/// HTML 的空元素，XHTML 里必须自闭合。
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+static ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([^\s=/>"]+)(?:="([^"]*)")?"#).expect("valid regex"));
+
+pub struct EpubBook {
+    /// `dc:identifier`，同一本书多次导出应保持不变
+    pub identifier: String,
+    pub title: String,
+    pub author: String,
+    /// BCP 47 语言标签，如 `zh-CN`
+    pub language: String,
+    pub modified: DateTime<Utc>,
+    pub cover: Option<EpubResource>,
+    pub chapters: Vec<EpubChapter>,
+    /// 正文引用的图片，`href` 与 `to_xhtml` 改写后的地址一致
+    pub resources: Vec<EpubResource>,
+}
+
+pub struct EpubChapter {
+    pub title: String,
+    /// XHTML 片段（`to_xhtml` 的输出）
+    pub body: String,
+    /// 章内标题，写进导航文档的下一级
+    pub toc: Vec<TocEntry>,
+}
+
+pub struct EpubResource {
+    /// 相对 OEBPS 目录的路径，如 `images/1a2b3c.webp`
+    pub href: String,
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// 正文里 `<img>` 的 src，按出现顺序去重。
+pub fn image_sources(html: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    for tag in tags(html) {
+        if tag_name(tag) == "img" {
+            if let Some(src) = attributes(tag)
+                .into_iter()
+                .find(|(name, _)| name == "src")
+                .and_then(|(_, value)| value)
+            {
+                let src = unescape_attribute(&src);
+                if !sources.contains(&src) {
+                    sources.push(src);
+                }
+            }
+        }
+    }
+    sources
+}
+
+/// 把 ammonia 输出的 HTML 转成 XHTML 片段。
+///
+/// html5ever 的序列化结果很规整：属性值都带双引号，文本里的 `<` 都已转义，所以逐个改写
+/// 标签即可：空元素自闭合，`&nbsp;` 换成数字引用，属性值里的 `<` 转义。`<img>` 的 src 在
+/// `images` 里时换成书内路径；不在的（站外或读不到的图片）换成指向原地址的链接。
+/// 书里没有站点根目录，`/` 开头的站内链接都换成站点上的绝对地址。
+pub fn to_xhtml(html: &str, images: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(html.len() + html.len() / 8);
+    let mut rest = html;
+    let mut open_anchors = 0usize;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&xml_text(&rest[..start]));
+        let after = &rest[start..];
+        let end = tag_end(after);
+        let tag = &after[..end];
+        rest = &after[end..];
+
+        if let Some(closing) = tag.strip_prefix("</") {
+            if tag_name(closing) == "a" {
+                open_anchors = open_anchors.saturating_sub(1);
+            }
+            out.push_str(tag);
+            continue;
+        }
+        let name = tag_name(tag);
+        let mut attrs: Vec<(String, String)> = attributes(tag)
+            .into_iter()
+            .skip(1)
+            .map(|(name, value)| {
+                let value = value.unwrap_or_else(|| name.clone());
+                (name, value)
+            })
+            .collect();
+        if name == "a" {
+            open_anchors += 1;
+            for (attr, value) in attrs.iter_mut() {
+                if let Some(url) = (attr == "href")
+                    .then(|| site_url(&unescape_attribute(value)))
+                    .flatten()
+                {
+                    *value = escape(url.as_str()).into_owned();
+                }
+            }
+        }
+        if name == "img" {
+            let src = attrs
+                .iter()
+                .find(|(name, _)| name == "src")
+                .map(|(_, value)| unescape_attribute(value))
+                .unwrap_or_default();
+            match images.get(&src) {
+                Some(href) => {
+                    for (name, value) in attrs.iter_mut() {
+                        if name == "src" {
+                            *value = escape(href.as_str()).into_owned();
+                        }
+                    }
+                    if !attrs.iter().any(|(name, _)| name == "alt") {
+                        attrs.push(("alt".to_string(), String::new()));
+                    }
+                }
+                None => {
+                    out.push_str(&missing_image(&src, &attrs, open_anchors > 0));
+                    continue;
+                }
+            }
+        }
+        out.push('<');
+        out.push_str(&name);
+        for (attr, value) in &attrs {
+            out.push_str(&format!(" {attr}=\"{}\"", xml_attribute(value)));
+        }
+        out.push_str(if VOID_ELEMENTS.contains(&name.as_str()) {
+            " />"
+        } else {
+            ">"
+        });
+    }
+    out.push_str(&xml_text(rest));
+    out
+}
+
+/// 组装完整的 EPUB 文件。
+pub fn build(book: &EpubBook) -> io::Result<Vec<u8>> {
+    let mut zip = EpubZip::new(book.modified);
+    zip.add("mimetype", EPUB_MEDIA_TYPE.as_bytes(), false)?;
+    zip.add("META-INF/container.xml", CONTAINER_XML.as_bytes(), true)?;
+    zip.add("OEBPS/content.opf", package_document(book).as_bytes(), true)?;
+    zip.add(
+        "OEBPS/nav.xhtml",
+        navigation_document(book).as_bytes(),
+        true,
+    )?;
+    zip.add("OEBPS/style.css", STYLESHEET.as_bytes(), true)?;
+    if let Some(cover) = &book.cover {
+        zip.add(&format!("OEBPS/{}", cover.href), &cover.data, false)?;
+        let body = format!(
+            "<div class=\"cover\"><img src=\"{}\" alt=\"{}\" /></div>",
+            escape(cover.href.as_str()),
+            escape(book.title.as_str())
+        );
+        zip.add(
+            "OEBPS/cover.xhtml",
+            xhtml_document(&book.language, &book.title, &body).as_bytes(),
+            true,
+        )?;
+    }
+    for (index, chapter) in book.chapters.iter().enumerate() {
+        let body = format!(
+            "<section epub:type=\"chapter\">\n<h1>{}</h1>\n{}\n</section>",
+            escape(chapter.title.as_str()),
+            chapter.body
+        );
+        zip.add(
+            &format!("OEBPS/{}", chapter_href(index)),
+            xhtml_document(&book.language, &chapter.title, &body).as_bytes(),
+            true,
+        )?;
+    }
+    for resource in &book.resources {
+        zip.add(&format!("OEBPS/{}", resource.href), &resource.data, false)?;
+    }
+    zip.finish()
+}
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn chapter_href(index: usize) -> String {
+    format!("chapter-{:03}.xhtml", index + 1)
+}
+
+fn package_document(book: &EpubBook) -> String {
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+         \x20   <item id=\"css\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::new();
+    let mut cover_meta = String::new();
+    if let Some(cover) = &book.cover {
+        manifest.push_str(&format!(
+            "    <item id=\"cover-image\" href=\"{}\" media-type=\"{}\" properties=\"cover-image\"/>\n\
+             \x20   <item id=\"cover\" href=\"cover.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+            escape(cover.href.as_str()),
+            escape(cover.media_type.as_str())
+        ));
+        spine.push_str("    <itemref idref=\"cover\"/>\n");
+        // EPUB 2 阅读器认这个
+        cover_meta.push_str("    <meta name=\"cover\" content=\"cover-image\"/>\n");
+    }
+    spine.push_str("    <itemref idref=\"nav\"/>\n");
+    for index in 0..book.chapters.len() {
+        manifest.push_str(&format!(
+            "    <item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            index + 1,
+            chapter_href(index)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", index + 1));
+    }
+    for (index, resource) in book.resources.iter().enumerate() {
+        manifest.push_str(&format!(
+            "    <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            index + 1,
+            escape(resource.href.as_str()),
+            escape(resource.media_type.as_str())
+        ));
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:creator>{author}</dc:creator>
+    <dc:language>{lang}</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+{cover_meta}  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        lang = escape(book.language.as_str()),
+        identifier = escape(book.identifier.as_str()),
+        title = escape(book.title.as_str()),
+        author = escape(book.author.as_str()),
+        modified = book.modified.format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
+
+fn navigation_document(book: &EpubBook) -> String {
+    fn entries(out: &mut String, href: &str, toc: &[TocEntry]) {
+        out.push_str("<ol>\n");
+        for entry in toc {
+            out.push_str(&format!(
+                "<li><a href=\"{href}#{}\">{}</a>",
+                escape(entry.id.as_str()),
+                escape(entry.text.as_str())
+            ));
+            if !entry.children.is_empty() {
+                entries(out, href, &entry.children);
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ol>\n");
+    }
+
+    let mut toc = String::from("<nav epub:type=\"toc\" id=\"toc\">\n<h1>目录</h1>\n<ol>\n");
+    for (index, chapter) in book.chapters.iter().enumerate() {
+        let href = chapter_href(index);
+        toc.push_str(&format!(
+            "<li><a href=\"{href}\">{}</a>",
+            escape(chapter.title.as_str())
+        ));
+        if !chapter.toc.is_empty() {
+            entries(&mut toc, &href, &chapter.toc);
+        }
+        toc.push_str("</li>\n");
+    }
+    toc.push_str("</ol>\n</nav>");
+    xhtml_document(&book.language, &book.title, &toc)
+}
+
+fn xhtml_document(language: &str, title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+<meta charset="UTF-8" />
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="style.css" />
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        lang = escape(language),
+        title = escape(title),
+    )
+}
+
+fn missing_image(src: &str, attrs: &[(String, String)], inside_link: bool) -> String {
+    let alt = attrs
+        .iter()
+        .find(|(name, _)| name == "alt")
+        .map(|(_, value)| value.as_str())
+        .filter(|alt| !alt.is_empty())
+        .unwrap_or("图片");
+    let label = format!("[{}]", xml_text(&alt.replace('<', "&lt;")));
+    if inside_link || src.is_empty() {
+        format!("<span class=\"missing-image\">{label}</span>")
+    } else {
+        let href = site_url(src).unwrap_or_else(|| src.to_string());
+        format!("<a href=\"{}\">{label}</a>", escape(href.as_str()))
+    }
+}
+
+/// 站内的根相对地址（`/post/1`、`/uploads/...`）换成绝对地址，其它的原样保留。
+fn site_url(url: &str) -> Option<String> {
+    (url.starts_with('/') && !url.starts_with("//")).then(|| abs_url(url))
+}
+
+fn tags(html: &str) -> Vec<&str> {
+    let mut tags = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start..];
+        let end = tag_end(after);
+        tags.push(&after[..end]);
+        rest = &after[end..];
+    }
+    tags
+}
+
+/// 从 `<` 开始，返回标签结束（含 `>`）的位置；引号里的 `>` 不算。
+fn tag_end(from_lt: &str) -> usize {
+    let mut in_quote = false;
+    for (index, c) in from_lt.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '>' if !in_quote => return index + 1,
+            _ => {}
+        }
+    }
+    from_lt.len()
+}
+
+/// 开始/结束标签的小写元素名（`tag` 可带或不带开头的 `<`、`</`）。
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/'])
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 第一个是元素名本身（值为 None）。
+fn attributes(tag: &str) -> Vec<(String, Option<String>)> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    ATTRIBUTE
+        .captures_iter(inner)
+        .map(|captures| {
+            (
+                captures[1].to_ascii_lowercase(),
+                captures.get(2).map(|value| value.as_str().to_string()),
+            )
+        })
+        .collect()
+}
+
+fn unescape_attribute(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn xml_text(text: &str) -> String {
+    text.replace("&nbsp;", "&#160;")
+}
+
+fn xml_attribute(value: &str) -> String {
+    xml_text(value).replace('<', "&lt;")
+}
+
+/// zip 容器的薄封装：所有条目用同一个修改时间，图片等已压缩过的数据原样存储。
+struct EpubZip {
+    writer: zip::ZipWriter<Cursor<Vec<u8>>>,
+    options: SimpleFileOptions,
+}
+
+impl EpubZip {
+    fn new(modified: DateTime<Utc>) -> Self {
+        let modified = zip::DateTime::from_date_and_time(
+            modified.year().clamp(1980, 2107) as u16,
+            modified.month() as u8,
+            modified.day() as u8,
+            modified.hour() as u8,
+            modified.minute() as u8,
+            modified.second() as u8,
+        )
+        .unwrap_or_default();
+        Self {
+            writer: zip::ZipWriter::new(Cursor::new(Vec::new())),
+            options: SimpleFileOptions::default().last_modified_time(modified),
+        }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8], compress: bool) -> io::Result<()> {
+        let method = if compress {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+        self.writer
+            .start_file(name, self.options.compression_method(method))
+            .map_err(io::Error::other)?;
+        self.writer.write_all(data)
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        Ok(self.writer.finish().map_err(io::Error::other)?.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::seo_handler::SITE;
+    use std::io::Read;
+
+    fn unzip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).expect("valid zip");
+        (0..archive.len())
+            .map(|index| {
+                let mut file = archive.by_index(index).expect("entry");
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).expect("read entry");
+                (file.name().to_string(), data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn html_becomes_well_formed_xhtml() {
+        let images = HashMap::from([(
+            "/uploads/images/a.webp".to_string(),
+            "images/1.webp".to_string(),
+        )]);
+        let xhtml = to_xhtml(
+            "<p>a&nbsp;b<br><img src=\"/uploads/images/a.webp\" title=\"x>y\"></p>\
+             <hr><a href=\"/post/1?a=1&amp;b=2\"><img src=\"https://cdn.example/b.png\" alt=\"B\"></a>\
+             <img src=\"https://cdn.example/c.png\"><img src=\"/uploads/images/gone.webp\">\
+             <a href=\"#top\">^</a><a href=\"//cdn.example/d\">d</a>\
+             <input type=\"checkbox\" checked=\"\" disabled=\"\">",
+            &images,
+        );
+        assert_eq!(
+            xhtml,
+            format!(
+                "<p>a&#160;b<br /><img src=\"images/1.webp\" title=\"x>y\" alt=\"\" /></p>\
+                 <hr /><a href=\"{SITE}/post/1?a=1&amp;b=2\"><span class=\"missing-image\">[B]</span></a>\
+                 <a href=\"https://cdn.example/c.png\">[图片]</a>\
+                 <a href=\"{SITE}/uploads/images/gone.webp\">[图片]</a>\
+                 <a href=\"#top\">^</a><a href=\"//cdn.example/d\">d</a>\
+                 <input type=\"checkbox\" checked=\"\" disabled=\"\" />"
+            )
+        );
+        assert_eq!(
+            image_sources(
+                "<img src=\"/a.png\"><p><img src=\"/a.png\"><img src=\"/b&amp;c.png\"></p>"
+            ),
+            vec!["/a.png".to_string(), "/b&c.png".to_string()]
+        );
+    }
+
+    #[test]
+    fn builds_a_zip_with_uncompressed_mimetype_first() {
+        let book = EpubBook {
+            identifier: "urn:test:1".to_string(),
+            title: "Rust & 书".to_string(),
+            author: "chuyi".to_string(),
+            language: "zh-CN".to_string(),
+            modified: DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            cover: Some(EpubResource {
+                href: "images/cover.png".to_string(),
+                media_type: "image/png".to_string(),
+                data: vec![1, 2, 3],
+            }),
+            chapters: vec![EpubChapter {
+                title: "第一章".to_string(),
+                body: "<h2 id=\"intro\">Intro</h2><p>hi</p>".to_string(),
+                toc: vec![TocEntry {
+                    id: "intro".to_string(),
+                    text: "Intro".to_string(),
+                    level: 2,
+                    children: Vec::new(),
+                }],
+            }],
+            resources: Vec::new(),
+        };
+        let bytes = build(&book).unwrap();
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], EPUB_MEDIA_TYPE.as_bytes());
+
+        let files: HashMap<String, Vec<u8>> = unzip(&bytes).into_iter().collect();
+        let text = |name: &str| String::from_utf8(files[name].clone()).unwrap();
+        let opf = text("OEBPS/content.opf");
+        assert!(opf.contains("<dc:title>Rust &amp; 书</dc:title>"));
+        assert!(opf.contains("<meta property=\"dcterms:modified\">2026-03-04T05:06:07Z</meta>"));
+        assert!(opf.contains("properties=\"cover-image\""));
+        assert!(opf.contains("<itemref idref=\"chapter-1\"/>"));
+        let nav = text("OEBPS/nav.xhtml");
+        assert!(nav.contains("<a href=\"chapter-001.xhtml\">第一章</a>"));
+        assert!(nav.contains("<a href=\"chapter-001.xhtml#intro\">Intro</a>"));
+        assert!(text("OEBPS/chapter-001.xhtml").contains("<p>hi</p>"));
+        assert_eq!(files["OEBPS/images/cover.png"], vec![1, 2, 3]);
+        assert!(files.contains_key("META-INF/container.xml"));
+    }
+}
@@ -0,0 +1,1064 @@
+//! 在服务端解开 EPUB，给按章节加载的阅读器用。
+//!
+//! 打开时校验 zip 目录、条目路径和 OPF，之后只在内存里保留压缩数据，
+//! 章节和资源按需解压。章节正文用 ammonia 清洗，站内链接改写成章节 / 资源接口的地址。
+//! 元数据提取（`book_metadata`）也复用这里的 zip 读取和 OPF 解析。
+
+use crate::utils::error::{AppError, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use zip::result::ZipError;
+
+/// 单个解压条目的上限，防止压缩炸弹。
+pub(crate) const MAX_INFLATED_SIZE: u64 = 32 * 1024 * 1024;
+/// 整本书解压后的上限和条目数上限，超过的不当作正常的电子书。
+const MAX_TOTAL_INFLATED_SIZE: u64 = 1024 * 1024 * 1024;
+const MAX_ENTRIES: usize = 20_000;
+
+/// 资源接口只返回这些类型；章节（XHTML）必须走清洗过的章节接口。
+const RESOURCE_MEDIA_PREFIXES: [&str; 4] = ["image/", "font/", "audio/", "video/"];
+const RESOURCE_MEDIA_TYPES: [&str; 5] = [
+    "text/css",
+    "application/font-woff",
+    "application/vnd.ms-opentype",
+    "application/x-font-ttf",
+    "application/x-font-otf",
+];
+
+static SANITIZER_TAGS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    let mut tags: HashSet<&str> = ammonia::Builder::default().clone_tags();
+    tags.extend([
+        "section",
+        "article",
+        "aside",
+        "header",
+        "footer",
+        "nav",
+        "figure",
+        "figcaption",
+        "ruby",
+        "rt",
+        "rp",
+    ]);
+    tags
+});
+static TITLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").expect("title regex is valid"));
+static BODY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body[^>]*>(.*)</body>").expect("body regex is valid"));
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<link\b[^>]*>").expect("link regex is valid"));
+static ATTRIBUTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("attribute regex is valid")
+});
+/// 封面页常用 `<svg><image xlink:href="..."/></svg>`，ammonia 不认 SVG，先换成 `<img>`。
+static SVG_IMAGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<image\b[^>]*?\bhref\s*=\s*"([^"]+)"[^>]*>"#)
+        .expect("svg image regex is valid")
+});
+
+fn invalid(message: &str) -> AppError {
+    AppError::BadRequest(message.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// 解析好的 EPUB
+// ---------------------------------------------------------------------------
+
+/// 校验过的 EPUB，数据保持压缩状态。
+pub struct EpubArchive {
+    zip: ZipArchive<Arc<[u8]>>,
+    title: Option<String>,
+    /// 路径（相对 zip 根目录）到 media-type
+    manifest: HashMap<String, String>,
+    spine: Vec<SpineEntry>,
+    toc: Vec<EpubTocItem>,
+}
+
+struct SpineEntry {
+    path: String,
+    linear: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubContents {
+    pub title: Option<String>,
+    pub spine: Vec<EpubSpineItem>,
+    pub toc: Vec<EpubTocItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubSpineItem {
+    pub index: usize,
+    pub path: String,
+    /// `linear="no"` 的条目（注释、附录弹窗等）不在顺序阅读里出现
+    pub linear: bool,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EpubTocItem {
+    pub title: String,
+    pub path: String,
+    pub fragment: Option<String>,
+    pub children: Vec<EpubTocItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubChapterContent {
+    pub index: usize,
+    pub path: String,
+    pub title: Option<String>,
+    /// 清洗过的 `<body>` 内容；图片指向资源接口，跳到其它章节的链接指向章节接口
+    pub html: String,
+    pub stylesheets: Vec<String>,
+    pub previous: Option<String>,
+    pub next: Option<String>,
+}
+
+impl EpubArchive {
+    pub fn open(data: Vec<u8>) -> Result<Self> {
+        let zip = ZipArchive::open(Arc::from(data))?;
+        if zip.len() > MAX_ENTRIES {
+            return Err(invalid("EPUB has too many entries"));
+        }
+        if zip.uncompressed_size()? > MAX_TOTAL_INFLATED_SIZE {
+            return Err(invalid("EPUB is too large once unpacked"));
+        }
+        if let Some(name) = zip.names().find(|name| !is_safe_path(name)) {
+            return Err(AppError::BadRequest(format!(
+                "EPUB entry {name} has an unsafe path"
+            )));
+        }
+        if zip.contains("mimetype") && zip.read("mimetype")?.trim_ascii() != b"application/epub+zip"
+        {
+            return Err(invalid("EPUB mimetype is wrong"));
+        }
+
+        let container = zip.read_text("META-INF/container.xml")?;
+        let opf_path = rootfile_path(&container)
+            .ok_or_else(|| invalid("EPUB container.xml has no rootfile"))?;
+        let package = parse_package(&zip.read_text(&opf_path)?)?;
+        let by_id: HashMap<&str, &ManifestItem> = package
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item))
+            .collect();
+        let manifest: HashMap<String, String> = package
+            .items
+            .iter()
+            .map(|item| (resolve_href(&opf_path, &item.href), item.media_type.clone()))
+            .filter(|(path, _)| zip.contains(path))
+            .collect();
+        let spine: Vec<SpineEntry> = package
+            .spine
+            .iter()
+            .filter_map(|itemref| {
+                let path = resolve_href(&opf_path, &by_id.get(itemref.idref.as_str())?.href);
+                manifest.contains_key(&path).then_some(SpineEntry {
+                    path,
+                    linear: itemref.linear,
+                })
+            })
+            .collect();
+        if spine.is_empty() {
+            return Err(invalid("EPUB spine has no readable chapters"));
+        }
+
+        // EPUB 3 的导航文档优先，没有时用 EPUB 2 的 NCX
+        let nav = package
+            .items
+            .iter()
+            .find(|item| item.properties.split_whitespace().any(|p| p == "nav"))
+            .map(|item| (resolve_href(&opf_path, &item.href), true))
+            .or_else(|| {
+                let item = package
+                    .toc_id
+                    .as_deref()
+                    .and_then(|id| by_id.get(id))
+                    .copied()
+                    .or_else(|| {
+                        package
+                            .items
+                            .iter()
+                            .find(|item| item.media_type == "application/x-dtbncx+xml")
+                    })?;
+                Some((resolve_href(&opf_path, &item.href), false))
+            });
+        let toc = match nav {
+            Some((path, is_nav)) => match zip.read_text(&path) {
+                Ok(text) if is_nav => parse_nav(&text, &path),
+                Ok(text) => parse_ncx(&text, &path),
+                Err(_) => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            zip,
+            title: package.title,
+            manifest,
+            spine,
+            toc,
+        })
+    }
+
+    /// 压缩数据的大小，缓存按它计算占用。
+    pub fn size(&self) -> usize {
+        self.zip.size
+    }
+
+    pub fn contents(&self, links: EpubLinks) -> EpubContents {
+        EpubContents {
+            title: self.title.clone(),
+            spine: self
+                .spine
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| EpubSpineItem {
+                    index,
+                    path: entry.path.clone(),
+                    linear: entry.linear,
+                    url: links.chapter(&entry.path),
+                })
+                .collect(),
+            toc: self.toc.clone(),
+        }
+    }
+
+    pub fn chapter(&self, path: &str, links: EpubLinks) -> Result<EpubChapterContent> {
+        let index = self
+            .spine
+            .iter()
+            .position(|entry| entry.path == path)
+            .ok_or_else(|| AppError::NotFound("Chapter not found".to_string()))?;
+        let document = self.zip.read_text(path)?;
+        let title = TITLE_RE
+            .captures(&document)
+            .map(|captures| unescape(captures[1].trim()))
+            .filter(|title| !title.is_empty());
+        let stylesheets = LINK_RE
+            .find_iter(&document)
+            .filter_map(|link| {
+                let attributes = tag_attributes(link.as_str());
+                let is_stylesheet = attributes.get("rel").is_some_and(|rel| {
+                    rel.split_whitespace()
+                        .any(|value| value.eq_ignore_ascii_case("stylesheet"))
+                });
+                let href = attributes.get("href")?;
+                let target = resolve_href(path, href.split('#').next().unwrap_or_default());
+                (is_stylesheet && self.manifest.contains_key(&target))
+                    .then(|| links.resource(&target))
+            })
+            .collect();
+        let body = BODY_RE
+            .captures(&document)
+            .map_or(document.as_str(), |captures| {
+                captures.get(1).map_or("", |body| body.as_str())
+            });
+        let body = SVG_IMAGE_RE.replace_all(body, r#"<img src="$1" alt="" />"#);
+        Ok(EpubChapterContent {
+            index,
+            path: path.to_string(),
+            title,
+            html: self.sanitize(&body, path, links),
+            stylesheets,
+            previous: index
+                .checked_sub(1)
+                .map(|previous| links.chapter(&self.spine[previous].path)),
+            next: self
+                .spine
+                .get(index + 1)
+                .map(|next| links.chapter(&next.path)),
+        })
+    }
+
+    /// 返回资源内容和 media-type。章节等文档不从这里返回。
+    pub fn resource(&self, path: &str) -> Result<(Vec<u8>, String)> {
+        let media_type = self
+            .manifest
+            .get(path)
+            .filter(|media_type| {
+                RESOURCE_MEDIA_PREFIXES
+                    .iter()
+                    .any(|prefix| media_type.starts_with(prefix))
+                    || RESOURCE_MEDIA_TYPES.contains(&media_type.as_str())
+            })
+            .ok_or_else(|| AppError::NotFound("Resource not found".to_string()))?;
+        Ok((self.zip.read(path)?, media_type.clone()))
+    }
+
+    fn sanitize(&self, html: &str, chapter_path: &str, links: EpubLinks) -> String {
+        let spine: HashSet<&str> = self.spine.iter().map(|entry| entry.path.as_str()).collect();
+        let rewrite = url_rewriter(move |url| {
+            if url.starts_with('#') {
+                return Some(Cow::Owned(url.to_string()));
+            }
+            let (href, fragment) = match url.split_once('#') {
+                Some((href, fragment)) => (href, Some(fragment)),
+                None => (url, None),
+            };
+            let path = resolve_href(chapter_path, href.split('?').next().unwrap_or_default());
+            let target = if spine.contains(path.as_str()) {
+                links.chapter(&path)
+            } else if self.manifest.contains_key(&path) {
+                links.resource(&path)
+            } else {
+                return None;
+            };
+            Some(Cow::Owned(match fragment {
+                Some(fragment) => format!("{target}#{fragment}"),
+                None => target,
+            }))
+        });
+        ammonia::Builder::default()
+            .tags(SANITIZER_TAGS.clone())
+            .add_generic_attributes(["id", "class", "title", "lang", "dir"])
+            .add_tag_attributes("img", ["width", "height"])
+            .add_tag_attributes("td", ["colspan", "rowspan"])
+            .add_tag_attributes("th", ["colspan", "rowspan"])
+            .link_rel(Some("noopener noreferrer"))
+            .url_relative(ammonia::UrlRelative::Custom(Box::new(rewrite)))
+            .clean(html)
+            .to_string()
+    }
+}
+
+/// 给闭包标注返回值借用参数的签名，ammonia 的 `UrlRelative::Custom` 需要。
+fn url_rewriter<F>(rewrite: F) -> F
+where
+    F: for<'url> Fn(&'url str) -> Option<Cow<'url, str>>,
+{
+    rewrite
+}
+
+/// 章节、资源链接的前缀和查询串。`base` 如 `/api/books/1/files/2/epub`；
+/// `query` 是签名参数，每个链接都要带上，管理员读私密书籍时为空。
+#[derive(Debug, Clone, Copy)]
+pub struct EpubLinks<'a> {
+    pub base: &'a str,
+    pub query: &'a str,
+}
+
+impl EpubLinks<'_> {
+    fn chapter(&self, path: &str) -> String {
+        self.with_query(format!("{}/chapters/{}", self.base, encode_path(path)))
+    }
+
+    fn resource(&self, path: &str) -> String {
+        self.with_query(format!("{}/resources/{}", self.base, encode_path(path)))
+    }
+
+    fn with_query(&self, url: String) -> String {
+        if self.query.is_empty() {
+            url
+        } else {
+            format!("{url}?{}", self.query)
+        }
+    }
+}
+
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 条目路径不能是绝对路径、不能含 `..` 或反斜杠。
+fn is_safe_path(name: &str) -> bool {
+    !name.starts_with('/')
+        && !name.contains('\\')
+        && !name.contains('\0')
+        && name.split('/').all(|segment| segment != "..")
+}
+
+fn tag_attributes(tag: &str) -> HashMap<String, String> {
+    ATTRIBUTE_RE
+        .captures_iter(tag)
+        .map(|captures| {
+            let value = captures
+                .get(2)
+                .or(captures.get(3))
+                .map_or("", |v| v.as_str());
+            (captures[1].to_ascii_lowercase(), unescape(value))
+        })
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    quick_xml::escape::unescape(text)
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// 缓存
+// ---------------------------------------------------------------------------
+
+type LoadingCell = Arc<tokio::sync::OnceCell<Arc<EpubArchive>>>;
+
+/// 解开的 EPUB 按文件缓存在内存里，总大小超出时淘汰最久没用的，过期的也会丢掉。
+/// 文件上传后内容不变（重新上传是新的文件记录），所以不需要按内容失效。
+pub struct EpubCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Arc<EpubArchive>, Instant)>>,
+    /// 正在加载的书；阅读器打开时会同时请求目录和多个资源，只让其中一个去下载和解析。
+    loading: Mutex<HashMap<String, LoadingCell>>,
+}
+
+impl EpubCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            loading: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 缓存里没有时调用 `load`。同一个 key 同时只加载一次，其余请求等它的结果；
+    /// 加载失败不记住，下一个请求重新加载。
+    pub async fn get_or_load<F, Fut>(&self, key: &str, load: F) -> Result<Arc<EpubArchive>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<EpubArchive>>,
+    {
+        if let Some(archive) = self.get(key) {
+            return Ok(archive);
+        }
+        let cell = Arc::clone(lock(&self.loading).entry(key.to_string()).or_default());
+        let result = cell
+            .get_or_try_init(|| async {
+                let archive = Arc::new(load().await?);
+                self.insert(key, Arc::clone(&archive));
+                Ok(archive)
+            })
+            .await
+            .cloned();
+        let mut loading = lock(&self.loading);
+        if loading
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            loading.remove(key);
+        }
+        result
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<EpubArchive>> {
+        let mut entries = lock(&self.entries);
+        let now = Instant::now();
+        match entries.get_mut(key) {
+            Some((archive, used)) if now.duration_since(*used) < self.ttl => {
+                *used = now;
+                Some(Arc::clone(archive))
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// 单本超过容量的书不缓存。
+    pub fn insert(&self, key: &str, archive: Arc<EpubArchive>) {
+        if archive.size() > self.capacity {
+            return;
+        }
+        let mut entries = lock(&self.entries);
+        let now = Instant::now();
+        entries.retain(|_, (_, used)| now.duration_since(*used) < self.ttl);
+        entries.insert(key.to_string(), (archive, now));
+        while entries
+            .values()
+            .map(|(archive, _)| archive.size())
+            .sum::<usize>()
+            > self.capacity
+        {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OPF 包文件和目录
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+pub(crate) struct PackageDocument {
+    pub(crate) title: Option<String>,
+    pub(crate) creators: Vec<String>,
+    pub(crate) description: Option<String>,
+    /// EPUB 2 的 `<meta name="cover" content="item-id"/>`
+    cover_id: Option<String>,
+    items: Vec<ManifestItem>,
+    spine: Vec<SpineItemRef>,
+    /// EPUB 2 的 `<spine toc="ncx">`
+    toc_id: Option<String>,
+}
+
+struct ManifestItem {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+struct SpineItemRef {
+    idref: String,
+    linear: bool,
+}
+
+impl PackageDocument {
+    /// EPUB 3 的 `cover-image` 属性优先，其次 EPUB 2 的 meta，最后按 id / 文件名猜。
+    pub(crate) fn cover_href(&self) -> Option<&str> {
+        let images = || {
+            self.items
+                .iter()
+                .filter(|item| item.media_type.starts_with("image/"))
+        };
+        images()
+            .find(|item| {
+                item.properties
+                    .split_whitespace()
+                    .any(|p| p == "cover-image")
+            })
+            .or_else(|| {
+                let id = self.cover_id.as_deref()?;
+                images().find(|item| item.id == id)
+            })
+            .or_else(|| {
+                images().find(|item| {
+                    item.id.to_ascii_lowercase().contains("cover")
+                        || item.href.to_ascii_lowercase().contains("cover")
+                })
+            })
+            .map(|item| item.href.as_str())
+    }
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+pub(crate) fn rootfile_path(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element) | Event::Empty(element))
+                if element.local_name().as_ref() == b"rootfile" =>
+            {
+                return attribute(&element, b"full-path");
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn parse_package(xml: &str) -> Result<PackageDocument> {
+    let mut reader = Reader::from_str(xml);
+    let mut package = PackageDocument::default();
+    // 正在读取文本的 dc 元素
+    let mut current: Option<Vec<u8>> = None;
+    let mut text = String::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|error| AppError::BadRequest(format!("Invalid EPUB package: {error}")))?;
+        let is_start = matches!(event, Event::Start(_));
+        match event {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                name @ (b"title" | b"creator" | b"description") if is_start => {
+                    current = Some(name.to_vec());
+                    text.clear();
+                }
+                b"meta" if attribute(&element, b"name").as_deref() == Some("cover") => {
+                    package.cover_id = attribute(&element, b"content");
+                }
+                b"item" => {
+                    if let (Some(id), Some(href)) =
+                        (attribute(&element, b"id"), attribute(&element, b"href"))
+                    {
+                        package.items.push(ManifestItem {
+                            id,
+                            href,
+                            media_type: attribute(&element, b"media-type").unwrap_or_default(),
+                            properties: attribute(&element, b"properties").unwrap_or_default(),
+                        });
+                    }
+                }
+                b"spine" => package.toc_id = attribute(&element, b"toc"),
+                b"itemref" => {
+                    if let Some(idref) = attribute(&element, b"idref") {
+                        package.spine.push(SpineItemRef {
+                            idref,
+                            linear: attribute(&element, b"linear").as_deref() != Some("no"),
+                        });
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(value) if current.is_some() => {
+                text.push_str(&value.unescape().unwrap_or_default());
+            }
+            Event::CData(value) if current.is_some() => {
+                text.push_str(&String::from_utf8_lossy(&value));
+            }
+            Event::End(element) if current.as_deref() == Some(element.local_name().as_ref()) => {
+                let value = text.trim().to_string();
+                match current.take().as_deref() {
+                    Some(b"title") if package.title.is_none() => package.title = Some(value),
+                    Some(b"creator") if !value.is_empty() => package.creators.push(value),
+                    Some(b"description") if package.description.is_none() => {
+                        package.description = Some(value)
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(package)
+}
+
+/// manifest 里的 href 相对 `base`（OPF、章节或导航文档）所在目录，且可能经过百分号编码。
+pub(crate) fn resolve_href(base: &str, href: &str) -> String {
+    let href = urlencoding::decode(href)
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| href.to_string());
+    let mut segments: Vec<&str> = match base.rsplit_once('/') {
+        Some((dir, _)) => dir.split('/').collect(),
+        None => Vec::new(),
+    };
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn toc_item(title: String, href: &str, base: &str) -> EpubTocItem {
+    let (path, fragment) = match href.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment.to_string())),
+        None => (href, None),
+    };
+    EpubTocItem {
+        title: title.split_whitespace().collect::<Vec<_>>().join(" "),
+        path: if path.is_empty() {
+            String::new()
+        } else {
+            resolve_href(base, path)
+        },
+        fragment,
+        children: Vec::new(),
+    }
+}
+
+/// EPUB 3 导航文档里 `epub:type="toc"` 的 `<nav>`：嵌套的 `ol > li > a`。
+fn parse_nav(xml: &str, nav_path: &str) -> Vec<EpubTocItem> {
+    let mut reader = Reader::from_str(xml);
+    let mut in_toc = false;
+    // 每层 `<ol>` 收集到的条目，和还没结束的 `<li>`（标题、链接）
+    let mut levels: Vec<Vec<EpubTocItem>> = Vec::new();
+    let mut open: Vec<(String, String, Vec<EpubTocItem>)> = Vec::new();
+    let mut root = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => match element.local_name().as_ref() {
+                b"nav" if attribute(&element, b"type").is_some_and(|t| t.contains("toc")) => {
+                    in_toc = true;
+                }
+                b"ol" if in_toc => levels.push(Vec::new()),
+                b"li" if in_toc => open.push((String::new(), String::new(), Vec::new())),
+                b"a" if in_toc => {
+                    if let Some((_, href, _)) = open.last_mut() {
+                        *href = attribute(&element, b"href").unwrap_or_default();
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(text)) if in_toc && !open.is_empty() && levels.len() == open.len() => {
+                if let Some((title, _, _)) = open.last_mut() {
+                    title.push_str(&text.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::End(element)) if in_toc => match element.local_name().as_ref() {
+                b"nav" => in_toc = false,
+                b"ol" => {
+                    let items = levels.pop().unwrap_or_default();
+                    match open.last_mut() {
+                        Some((_, _, children)) => *children = items,
+                        None => root.extend(items),
+                    }
+                }
+                b"li" => {
+                    if let Some((title, href, children)) = open.pop() {
+                        let mut item = toc_item(title, &href, nav_path);
+                        item.children = children;
+                        if let Some(level) = levels.last_mut() {
+                            level.push(item);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    root
+}
+
+/// EPUB 2 的 NCX：嵌套的 `navPoint`，标题在 `navLabel/text`，链接在 `content@src`。
+fn parse_ncx(xml: &str, ncx_path: &str) -> Vec<EpubTocItem> {
+    let mut reader = Reader::from_str(xml);
+    let mut open: Vec<(String, String, Vec<EpubTocItem>)> = Vec::new();
+    let mut root = Vec::new();
+    let mut in_text = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => match element.local_name().as_ref() {
+                b"navPoint" => open.push((String::new(), String::new(), Vec::new())),
+                b"text" => in_text = true,
+                _ => {}
+            },
+            Ok(Event::Empty(element)) if element.local_name().as_ref() == b"content" => {
+                if let Some((_, href, _)) = open.last_mut() {
+                    if href.is_empty() {
+                        *href = attribute(&element, b"src").unwrap_or_default();
+                    }
+                }
+            }
+            Ok(Event::Text(text)) if in_text => {
+                if let Some((title, _, _)) = open.last_mut() {
+                    title.push_str(&text.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::End(element)) => match element.local_name().as_ref() {
+                b"text" => in_text = false,
+                b"navPoint" => {
+                    if let Some((title, href, children)) = open.pop() {
+                        let mut item = toc_item(title, &href, ncx_path);
+                        item.children = children;
+                        match open.last_mut() {
+                            Some((_, _, siblings)) => siblings.push(item),
+                            None => root.push(item),
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    root
+}
+
+// ---------------------------------------------------------------------------
+// zip
+// ---------------------------------------------------------------------------
+
+/// 只读的 zip 容器，读取时按条目限制解压大小。`D` 是便宜可克隆的数据
+/// （`Arc<[u8]>` 或 `&[u8]`），每次读取克隆一个读取器，多个请求可以同时读同一本书。
+pub(crate) struct ZipArchive<D: AsRef<[u8]> + Clone> {
+    archive: zip::ZipArchive<Cursor<D>>,
+    /// 压缩数据的大小
+    size: usize,
+}
+
+impl<D: AsRef<[u8]> + Clone> ZipArchive<D> {
+    pub(crate) fn open(data: D) -> Result<Self> {
+        let size = data.as_ref().len();
+        let archive = zip::ZipArchive::new(Cursor::new(data))
+            .map_err(|_| invalid("EPUB is not a valid zip archive"))?;
+        Ok(Self { archive, size })
+    }
+
+    fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.archive.file_names()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.archive.index_for_name(name).is_some()
+    }
+
+    /// 中央目录里记录的解压后总大小。
+    fn uncompressed_size(&self) -> Result<u64> {
+        let mut archive = self.archive.clone();
+        (0..archive.len()).try_fold(0u64, |total, index| {
+            let file = archive
+                .by_index_raw(index)
+                .map_err(|_| invalid("EPUB is not a valid zip archive"))?;
+            Ok(total.saturating_add(file.size()))
+        })
+    }
+
+    pub(crate) fn read(&self, name: &str) -> Result<Vec<u8>> {
+        let mut archive = self.archive.clone();
+        let file = archive.by_name(name).map_err(|error| match error {
+            ZipError::FileNotFound => AppError::BadRequest(format!("EPUB has no {name}")),
+            error => AppError::BadRequest(format!("EPUB entry {name} is unreadable: {error}")),
+        })?;
+        if file.size() > MAX_INFLATED_SIZE {
+            return Err(AppError::BadRequest(format!(
+                "EPUB entry {name} is too large"
+            )));
+        }
+        inflate(file).ok_or_else(|| AppError::BadRequest(format!("EPUB entry {name} is corrupt")))
+    }
+
+    pub(crate) fn read_text(&self, name: &str) -> Result<String> {
+        let data = self.read(name)?;
+        Ok(
+            String::from_utf8_lossy(data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&data))
+                .into_owned(),
+        )
+    }
+}
+
+/// 解压到 `MAX_INFLATED_SIZE` 为止，超过返回 `None`。
+pub(crate) fn inflate(decoder: impl Read) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    decoder
+        .take(MAX_INFLATED_SIZE + 1)
+        .read_to_end(&mut output)
+        .ok()?;
+    (output.len() as u64 <= MAX_INFLATED_SIZE).then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::epub::{self, EpubBook, EpubChapter, EpubResource};
+    use crate::utils::markdown::TocEntry;
+    use chrono::Utc;
+
+    const BASE: &str = "/api/books/1/files/2/epub";
+    const QUERY: &str = "expires=1&ip=false&sig=abc";
+    const LINKS: EpubLinks = EpubLinks {
+        base: BASE,
+        query: "",
+    };
+    const SIGNED_LINKS: EpubLinks = EpubLinks {
+        base: BASE,
+        query: QUERY,
+    };
+
+    fn sample() -> Vec<u8> {
+        epub::build(&EpubBook {
+            identifier: "urn:uuid:reader".to_string(),
+            title: "读者".to_string(),
+            author: "某人".to_string(),
+            language: "zh-CN".to_string(),
+            modified: Utc::now(),
+            cover: Some(EpubResource {
+                href: "images/cover.png".to_string(),
+                media_type: "image/png".to_string(),
+                data: b"png".to_vec(),
+            }),
+            chapters: vec![
+                EpubChapter {
+                    title: "第一章".to_string(),
+                    body: "<p id=\"p1\" onclick=\"alert(1)\">正文<script>alert(2)</script></p>\
+                           <p><img src=\"images/cover.png\" alt=\"图\" /> \
+                           <a href=\"chapter-002.xhtml#s1\">下一章</a> \
+                           <a href=\"#p1\">本章</a> \
+                           <a href=\"javascript:alert(3)\">坏链接</a></p>"
+                        .to_string(),
+                    toc: vec![TocEntry {
+                        level: 2,
+                        id: "p1".to_string(),
+                        text: "第一节".to_string(),
+                        children: Vec::new(),
+                    }],
+                },
+                EpubChapter {
+                    title: "第二章".to_string(),
+                    body: "<p id=\"s1\">完</p>".to_string(),
+                    toc: Vec::new(),
+                },
+            ],
+            resources: Vec::new(),
+        })
+        .expect("build epub")
+    }
+
+    #[test]
+    fn reads_spine_and_navigation() {
+        let archive = EpubArchive::open(sample()).expect("open epub");
+        let contents = archive.contents(LINKS);
+        assert_eq!(contents.title.as_deref(), Some("读者"));
+        let paths: Vec<&str> = contents
+            .spine
+            .iter()
+            .map(|item| item.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "OEBPS/cover.xhtml",
+                "OEBPS/nav.xhtml",
+                "OEBPS/chapter-001.xhtml",
+                "OEBPS/chapter-002.xhtml"
+            ]
+        );
+        assert_eq!(
+            contents.spine[2].url,
+            format!("{BASE}/chapters/OEBPS/chapter-001.xhtml")
+        );
+        assert_eq!(contents.toc.len(), 2);
+        assert_eq!(contents.toc[0].title, "第一章");
+        assert_eq!(contents.toc[0].path, "OEBPS/chapter-001.xhtml");
+        assert_eq!(contents.toc[0].children[0].fragment.as_deref(), Some("p1"));
+        assert_eq!(contents.toc[1].children, []);
+    }
+
+    #[test]
+    fn sanitizes_chapters_and_rewrites_links() {
+        let archive = EpubArchive::open(sample()).expect("open epub");
+        let chapter = archive
+            .chapter("OEBPS/chapter-001.xhtml", SIGNED_LINKS)
+            .expect("chapter");
+        assert_eq!(chapter.title.as_deref(), Some("第一章"));
+        assert_eq!(
+            chapter.stylesheets,
+            [format!("{BASE}/resources/OEBPS/style.css?{QUERY}")]
+        );
+        let html = &chapter.html;
+        assert!(!html.contains("script") && !html.contains("onclick"));
+        assert!(!html.contains("javascript:"));
+        // ammonia 会把属性里的 & 转义
+        let query = QUERY.replace('&', "&amp;");
+        assert!(html.contains(&format!(
+            r#"src="{BASE}/resources/OEBPS/images/cover.png?{query}""#
+        )));
+        assert!(html.contains(&format!(
+            r#"href="{BASE}/chapters/OEBPS/chapter-002.xhtml?{query}#s1""#
+        )));
+        assert!(html.contains(r##"href="#p1""##));
+        assert_eq!(
+            chapter.next.as_deref(),
+            Some(format!("{BASE}/chapters/OEBPS/chapter-002.xhtml?{QUERY}").as_str())
+        );
+
+        assert!(archive.chapter("OEBPS/style.css", LINKS).is_err());
+        assert_eq!(
+            archive.resource("OEBPS/images/cover.png").expect("image").0,
+            b"png"
+        );
+        // 章节只能走章节接口
+        assert!(archive.resource("OEBPS/chapter-001.xhtml").is_err());
+        assert!(archive.resource("OEBPS/missing.png").is_err());
+    }
+
+    #[test]
+    fn parses_ncx_table_of_contents() {
+        let toc = parse_ncx(
+            r#"<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/"><navMap>
+              <navPoint id="n1"><navLabel><text>Part One</text></navLabel><content src="Text/part1.xhtml"/>
+                <navPoint id="n2"><navLabel><text>Chapter
+                  1</text></navLabel><content src="Text/ch1.xhtml#start"/></navPoint>
+              </navPoint>
+              <navPoint id="n3"><navLabel><text>Notes</text></navLabel><content src="../notes.xhtml"/></navPoint>
+            </navMap></ncx>"#,
+            "OEBPS/toc.ncx",
+        );
+        assert_eq!(toc.len(), 2);
+        assert_eq!(toc[0].path, "OEBPS/Text/part1.xhtml");
+        assert_eq!(toc[0].children[0].title, "Chapter 1");
+        assert_eq!(toc[0].children[0].fragment.as_deref(), Some("start"));
+        assert_eq!(toc[1].path, "notes.xhtml");
+    }
+
+    #[test]
+    fn rejects_unsafe_archives_and_evicts_cache() {
+        assert!(EpubArchive::open(b"PK not a zip".to_vec()).is_err());
+        assert!(!is_safe_path("../etc/passwd"));
+        assert!(!is_safe_path("/abs"));
+        assert!(is_safe_path("OEBPS/Text/ch1.xhtml"));
+
+        let first = Arc::new(EpubArchive::open(sample()).expect("open epub"));
+        let size = first.size();
+        let cache = EpubCache::new(size * 2, Duration::from_secs(60));
+        cache.insert("a", Arc::clone(&first));
+        cache.insert("b", Arc::new(EpubArchive::open(sample()).expect("open")));
+        assert!(cache.get("a").is_some());
+        // 放第三本时淘汰最久没用的 b
+        cache.insert("c", Arc::new(EpubArchive::open(sample()).expect("open")));
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+        assert!(cache.get("b").is_none());
+
+        let expired = EpubCache::new(size * 2, Duration::ZERO);
+        expired.insert("a", first);
+        assert!(expired.get("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_load_once() {
+        let cache = EpubCache::new(usize::MAX, Duration::from_secs(60));
+        let loads = std::sync::atomic::AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            EpubArchive::open(sample())
+        };
+        let (first, second) =
+            tokio::join!(cache.get_or_load("a", load), cache.get_or_load("a", load));
+        assert!(Arc::ptr_eq(
+            &first.expect("first"),
+            &second.expect("second")
+        ));
+        assert_eq!(loads.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(cache.get("a").is_some());
+
+        // 失败不缓存，下次重新加载
+        let failed = cache
+            .get_or_load("b", || async { EpubArchive::open(b"PK".to_vec()) })
+            .await;
+        assert!(failed.is_err());
+        assert!(cache.get_or_load("b", load).await.is_ok());
+        assert_eq!(loads.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+}
@@ -29,6 +29,15 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    #[error("Upstream error: {0}")]
+    Upstream(String),
+
     #[error("Internal server error: {0}")]
     Internal(String),
 
@@ -67,6 +76,18 @@ impl IntoResponse for AppError {
                 tracing::warn!("Bad request: {}", message);
                 (StatusCode::BAD_REQUEST, message.as_str())
             }
+            AppError::Forbidden(ref message) => {
+                tracing::warn!("Forbidden: {}", message);
+                (StatusCode::FORBIDDEN, message.as_str())
+            }
+            AppError::TooManyRequests(ref message) => {
+                tracing::warn!("Too many requests: {}", message);
+                (StatusCode::TOO_MANY_REQUESTS, message.as_str())
+            }
+            AppError::Upstream(ref message) => {
+                tracing::warn!("Upstream error: {}", message);
+                (StatusCode::BAD_GATEWAY, message.as_str())
+            }
             AppError::Internal(ref message) => {
                 tracing::error!("Internal error: {}", message);
                 (StatusCode::INTERNAL_SERVER_ERROR, message.as_str())
@@ -451,6 +451,25 @@ impl FileHandler {
             data.extend_from_slice(&chunk);
         }
 
+        self.save_optimized_image_data(data, &file_name, subfolder, Some(options))
+            .await
+    }
+
+    /// 同 `save_optimized_image`，图片已经在内存里（比如从电子书里提取的封面）。
+    pub async fn save_optimized_image_data(
+        &self,
+        data: Vec<u8>,
+        file_name: &str,
+        subfolder: &str,
+        options: Option<ImageOptimizeOptions>,
+    ) -> Result<(String, String, u64)> {
+        let options = options.unwrap_or_default();
+        if data.len() > self.max_file_size as usize {
+            return Err(AppError::BadRequest(
+                "File size exceeds maximum allowed size".to_string(),
+            ));
+        }
+
         // Process image
         let source_size = data.len();
         let optimized_data =
@@ -627,6 +646,16 @@ pub struct OptimizeResult {
     pub optimized_size: u64,
 }
 
+impl OptimizeResult {
+    pub fn merge(&mut self, other: OptimizeResult) {
+        self.converted += other.converted;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+        self.original_size += other.original_size;
+        self.optimized_size += other.optimized_size;
+    }
+}
+
 // File type constants
 pub const IMAGE_TYPES: &[&str] = &["jpg", "jpeg", "png", "gif", "webp"];
 pub const MUSIC_TYPES: &[&str] = &["mp3", "wav", "flac", "aac", "ogg"];
@@ -0,0 +1,274 @@
+//! HTTP 条件请求和 Range 请求（RFC 9110 §13、§14）。
+//!
+//! 只支持单段 `bytes=` 范围；多段范围按规范允许的方式退化为返回整个文件。
+
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, TimeZone, Utc};
+use std::fs::Metadata;
+use std::time::UNIX_EPOCH;
+
+/// 本地文件的校验器：强 ETag（大小 + 修改时间，和 nginx 的做法一样）和 Last-Modified。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl Validators {
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+        let etag = match modified {
+            Some(modified) => format!("\"{:x}-{:x}\"", modified.as_secs(), metadata.len()),
+            None => format!("\"{:x}\"", metadata.len()),
+        };
+        Self {
+            etag,
+            last_modified: modified
+                .and_then(|modified| Utc.timestamp_opt(modified.as_secs() as i64, 0).single()),
+        }
+    }
+
+    pub fn last_modified_header(&self) -> Option<String> {
+        self.last_modified.map(http_date)
+    }
+}
+
+/// 对一个请求该怎么响应。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeOutcome {
+    /// 304
+    NotModified,
+    /// 200，整个文件
+    Full,
+    /// 206，闭区间 `start..=end`
+    Partial { start: u64, end: u64 },
+    /// 416
+    Unsatisfiable,
+}
+
+/// 按 RFC 9110 §13.2.2 的顺序依次处理 If-None-Match、If-Modified-Since、If-Range 和 Range。
+pub fn evaluate(headers: &HeaderMap, validators: &Validators, size: u64) -> RangeOutcome {
+    if let Some(if_none_match) = header_str(headers, header::IF_NONE_MATCH) {
+        if etag_list_matches(if_none_match, &validators.etag) {
+            return RangeOutcome::NotModified;
+        }
+    } else if let (Some(since), Some(last_modified)) = (
+        header_str(headers, header::IF_MODIFIED_SINCE).and_then(parse_http_date),
+        validators.last_modified,
+    ) {
+        if last_modified <= since {
+            return RangeOutcome::NotModified;
+        }
+    }
+
+    let Some(range) = header_str(headers, header::RANGE) else {
+        return RangeOutcome::Full;
+    };
+    if let Some(if_range) = header_str(headers, header::IF_RANGE) {
+        if !if_range_matches(if_range, validators) {
+            return RangeOutcome::Full;
+        }
+    }
+    parse_range(range, size)
+}
+
+/// 解析 `Range` 头。语法不对的头按规范忽略，返回 `Full`。
+pub fn parse_range(value: &str, size: u64) -> RangeOutcome {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return RangeOutcome::Full;
+    };
+    if spec.contains(',') {
+        return RangeOutcome::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return RangeOutcome::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+
+    if first.is_empty() {
+        // bytes=-N：最后 N 个字节
+        return match last.parse::<u64>() {
+            Ok(0) => RangeOutcome::Unsatisfiable,
+            Ok(_) if size == 0 => RangeOutcome::Unsatisfiable,
+            Ok(suffix) => RangeOutcome::Partial {
+                start: size.saturating_sub(suffix),
+                end: size - 1,
+            },
+            Err(_) => RangeOutcome::Full,
+        };
+    }
+    let Ok(start) = first.parse::<u64>() else {
+        return RangeOutcome::Full;
+    };
+    let end = if last.is_empty() {
+        u64::MAX
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return RangeOutcome::Full,
+        }
+    };
+    if start >= size {
+        return RangeOutcome::Unsatisfiable;
+    }
+    RangeOutcome::Partial {
+        start,
+        end: end.min(size - 1),
+    }
+}
+
+/// `Content-Range` 的值；`Unsatisfiable` 时是 `bytes */size`。
+pub fn content_range(outcome: RangeOutcome, size: u64) -> Option<String> {
+    match outcome {
+        RangeOutcome::Partial { start, end } => Some(format!("bytes {start}-{end}/{size}")),
+        RangeOutcome::Unsatisfiable => Some(format!("bytes */{size}")),
+        _ => None,
+    }
+}
+
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// If-None-Match 用弱比较：忽略 `W/` 前缀。
+fn etag_list_matches(list: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    list.split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// If-Range 用强比较：弱 ETag 永远不匹配；日期必须和 Last-Modified 完全相同。
+fn if_range_matches(value: &str, validators: &Validators) -> bool {
+    let value = value.trim();
+    if value.starts_with('"') {
+        return !validators.etag.starts_with("W/") && value == validators.etag;
+    }
+    if value.starts_with("W/") {
+        return false;
+    }
+    match (parse_http_date(value), validators.last_modified) {
+        (Some(date), Some(last_modified)) => date == last_modified,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn validators() -> Validators {
+        Validators {
+            etag: "\"abc-10\"".to_string(),
+            last_modified: Utc.timestamp_opt(1_700_000_000, 0).single(),
+        }
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn parses_single_byte_ranges() {
+        use RangeOutcome::*;
+        assert_eq!(
+            parse_range("bytes=0-99", 1000),
+            Partial { start: 0, end: 99 }
+        );
+        assert_eq!(
+            parse_range("bytes=900-", 1000),
+            Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-5000", 1000),
+            Partial { start: 0, end: 999 }
+        );
+        assert_eq!(parse_range("bytes=1000-", 1000), Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 1000), Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), Unsatisfiable);
+        // 无效或不支持的写法忽略
+        assert_eq!(parse_range("bytes=5-1", 1000), Full);
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), Full);
+        assert_eq!(parse_range("items=0-1", 1000), Full);
+        assert_eq!(parse_range("bytes=a-b", 1000), Full);
+        assert_eq!(
+            content_range(Partial { start: 0, end: 99 }, 1000).as_deref(),
+            Some("bytes 0-99/1000")
+        );
+        assert_eq!(
+            content_range(Unsatisfiable, 1000).as_deref(),
+            Some("bytes */1000")
+        );
+    }
+
+    #[test]
+    fn conditional_headers_follow_rfc_precedence() {
+        let v = validators();
+        let date = http_date(v.last_modified.unwrap());
+        assert_eq!(date, "Tue, 14 Nov 2023 22:13:20 GMT");
+
+        let h = headers(&[(header::IF_NONE_MATCH, "\"other\", W/\"abc-10\"")]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::NotModified);
+        // If-None-Match 存在时忽略 If-Modified-Since
+        let h = headers(&[
+            (header::IF_NONE_MATCH, "\"other\""),
+            (header::IF_MODIFIED_SINCE, &date),
+        ]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::Full);
+        let h = headers(&[(header::IF_MODIFIED_SINCE, &date)]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::NotModified);
+        let h = headers(&[(header::IF_MODIFIED_SINCE, "Mon, 13 Nov 2023 00:00:00 GMT")]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::Full);
+
+        let range = (header::RANGE, "bytes=2-");
+        let partial = RangeOutcome::Partial { start: 2, end: 9 };
+        assert_eq!(
+            evaluate(&headers(std::slice::from_ref(&range)), &v, 10),
+            partial
+        );
+        let h = headers(&[range.clone(), (header::IF_RANGE, "\"abc-10\"")]);
+        assert_eq!(evaluate(&h, &v, 10), partial);
+        let h = headers(&[range.clone(), (header::IF_RANGE, &date)]);
+        assert_eq!(evaluate(&h, &v, 10), partial);
+        // 文件变了：If-Range 不匹配，返回整个文件
+        let h = headers(&[range.clone(), (header::IF_RANGE, "\"abc-11\"")]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::Full);
+        let h = headers(&[range, (header::IF_RANGE, "W/\"abc-10\"")]);
+        assert_eq!(evaluate(&h, &v, 10), RangeOutcome::Full);
+    }
+}
@@ -0,0 +1,332 @@
+//! Markdown → HTML 渲染
+//!
+//! 文章详情和订阅源共用：GFM 表格、脚注、删除线、任务列表；标题带 id（与前端
+//! rehype-slug 的规则一致，锚点链接两边通用）并生成嵌套目录；代码块保留
+//! `language-*` class 供前端高亮。输出经 ammonia 白名单清洗，正文里的原始 HTML
+//! 只保留安全的标签和属性（含文章里嵌的 `<video>`）。
+
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// 中文按字计、英文按词计的阅读速度（每分钟）。
+const WORDS_PER_MINUTE: usize = 300;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TocEntry {
+    pub id: String,
+    pub text: String,
+    pub level: u8,
+    pub children: Vec<TocEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+    pub word_count: usize,
+    pub reading_time_minutes: usize,
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+}
+
+static SANITIZER: LazyLock<ammonia::Builder<'static>> = LazyLock::new(|| {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(["input", "video", "source"])
+        .add_tag_attributes("h1", ["id"])
+        .add_tag_attributes("h2", ["id"])
+        .add_tag_attributes("h3", ["id"])
+        .add_tag_attributes("h4", ["id"])
+        .add_tag_attributes("h5", ["id"])
+        .add_tag_attributes("h6", ["id"])
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("sup", ["class"])
+        .add_tag_attributes("div", ["class", "id"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .add_tag_attributes(
+            "video",
+            [
+                "src",
+                "title",
+                "poster",
+                "controls",
+                "preload",
+                "playsinline",
+            ],
+        )
+        .add_tag_attributes("source", ["src", "type"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("code", "class") => value
+                .split_whitespace()
+                .find(|class| class.starts_with("language-"))
+                .map(|class| class.to_string().into()),
+            ("th" | "td", "style") => matches!(
+                value,
+                "text-align: left" | "text-align: center" | "text-align: right"
+            )
+            .then(|| value.into()),
+            ("input", "type") => (value == "checkbox").then(|| value.into()),
+            _ => Some(value.into()),
+        });
+    builder
+});
+
+/// 与 github-slugger（rehype-slug）一致：小写，去掉标点，空白换成 `-`，重复时追加 `-1`、`-2`。
+fn heading_slug(text: &str, seen: &mut HashMap<String, usize>) -> String {
+    let base: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_whitespace() {
+                Some('-')
+            } else if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut slug = base.clone();
+    while let Some(count) = seen.get_mut(&slug) {
+        *count += 1;
+        slug = format!("{base}-{count}");
+    }
+    seen.insert(slug.clone(), 0);
+    slug
+}
+
+fn absolutize<'a>(url: CowStr<'a>, base: Option<&str>) -> CowStr<'a> {
+    match base {
+        Some(base) if url.starts_with('/') && !url.starts_with("//") => {
+            CowStr::from(format!("{base}{url}"))
+        }
+        _ => url,
+    }
+}
+
+/// 按标题层级把平铺的标题折成树；跳级（h2 直接到 h4）时挂在最近的上级下面。
+fn nest_toc(flat: Vec<TocEntry>) -> Vec<TocEntry> {
+    fn attach(siblings: &mut Vec<TocEntry>, entry: TocEntry) {
+        match siblings.last_mut() {
+            Some(last) if last.level < entry.level => attach(&mut last.children, entry),
+            _ => siblings.push(entry),
+        }
+    }
+    let mut roots = Vec::new();
+    for entry in flat {
+        attach(&mut roots, entry);
+    }
+    roots
+}
+
+fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                count += 1;
+            }
+            in_word = true;
+        } else {
+            in_word = false;
+        }
+    }
+    count
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+/// 渲染 Markdown。`base_url`（如 `https://example.com`）用于把 `/` 开头的链接和图片
+/// 补成绝对地址，给站外阅读的订阅源用；站内展示传 None。
+pub fn render(markdown: &str, base_url: Option<&str>) -> RenderedMarkdown {
+    let mut events: Vec<Event> = Vec::new();
+    let mut flat_toc = Vec::new();
+    let mut seen_ids = HashMap::new();
+    let mut heading: Option<(usize, String)> = None;
+    let mut in_code_block = false;
+    let mut prose = String::new();
+
+    for event in Parser::new_ext(markdown, options()) {
+        match &event {
+            Event::Start(Tag::Heading { .. }) => heading = Some((events.len(), String::new())),
+            Event::End(TagEnd::Heading(level)) => {
+                if let Some((start, text)) = heading.take() {
+                    let id = heading_slug(&text, &mut seen_ids);
+                    if let Event::Start(Tag::Heading { id: slot, .. }) = &mut events[start] {
+                        *slot = Some(CowStr::from(id.clone()));
+                    }
+                    flat_toc.push(TocEntry {
+                        id,
+                        text: text.trim().to_string(),
+                        level: *level as u8,
+                        children: Vec::new(),
+                    });
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text)) = &mut heading {
+                    heading_text.push_str(text);
+                }
+                if !in_code_block {
+                    prose.push_str(text);
+                    prose.push(' ');
+                }
+            }
+            _ => {}
+        }
+        events.push(match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: absolutize(dest_url, base_url),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: absolutize(dest_url, base_url),
+                title,
+                id,
+            }),
+            // `~~~ rust title=x` 这类信息串只取第一个词作语言
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let lang = info
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang.into())))
+            }
+            other => other,
+        });
+    }
+
+    let mut raw = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut raw, events.into_iter());
+    let word_count = count_words(&prose);
+    RenderedMarkdown {
+        html: SANITIZER.clean(&raw).to_string(),
+        toc: nest_toc(flat_toc),
+        word_count,
+        reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE).max(1),
+    }
+}
+
+/// 正文纯文本（不含代码块、图片地址和 HTML 标签），用于摘要。
+pub fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+    let mut image_depth = 0usize;
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            Event::Text(t) | Event::Code(t) if !in_code_block && image_depth == 0 => {
+                text.push_str(&t);
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings_get_unique_ids_and_a_nested_toc() {
+        let rendered = render(
+            "# Intro\n\n## Setup & Install\n\n### 配置 文件\n\n## Setup & Install\n\n# Next",
+            None,
+        );
+
+        assert!(rendered.html.contains(r#"<h2 id="setup--install">"#));
+        assert!(rendered.html.contains(r#"<h2 id="setup--install-1">"#));
+        assert!(rendered.html.contains(r#"<h3 id="配置-文件">"#));
+        assert_eq!(rendered.toc.len(), 2);
+        assert_eq!(rendered.toc[0].children.len(), 2);
+        assert_eq!(rendered.toc[0].children[0].children[0].text, "配置 文件");
+        assert_eq!(rendered.toc[1].id, "next");
+    }
+
+    #[test]
+    fn raw_html_is_sanitized_but_markdown_features_survive() {
+        let rendered = render(
+            "<script>alert(1)</script>\n\n[x](javascript:alert(1)) <img src=x onerror=alert(1)>\n\n\
+             | a |\n|:-:|\n| 1 |\n\n- [x] done\n\n```rust title=main.rs\nfn main() {}\n```\n\n\
+             Note[^1]\n\n[^1]: footnote\n\n<video src=\"/uploads/v.mp4\" controls onplay=\"x()\"></video>",
+            None,
+        );
+
+        assert!(!rendered.html.contains("<script"));
+        assert!(!rendered.html.contains("javascript:"));
+        assert!(!rendered.html.contains("onerror"));
+        assert!(!rendered.html.contains("onplay"));
+        assert!(rendered.html.contains(r#"style="text-align: center""#));
+        assert!(rendered.html.contains(r#"type="checkbox""#));
+        assert!(rendered.html.contains(r#"<code class="language-rust">"#));
+        assert!(rendered.html.contains(r#"class="footnote-definition""#));
+        assert!(rendered
+            .html
+            .contains(r#"<video src="/uploads/v.mp4" controls"#));
+    }
+
+    #[test]
+    fn relative_urls_become_absolute_with_a_base() {
+        let rendered = render(
+            "![cover](/uploads/a.webp) [ext](https://x.dev/p)",
+            Some("https://blog.example"),
+        );
+
+        assert!(rendered
+            .html
+            .contains(r#"src="https://blog.example/uploads/a.webp""#));
+        assert!(rendered.html.contains(r#"href="https://x.dev/p""#));
+    }
+
+    #[test]
+    fn words_count_cjk_characters_and_latin_words_outside_code() {
+        let rendered = render("你好世界 hello world\n\n```\nignored code\n```", None);
+
+        assert_eq!(rendered.word_count, 6);
+        assert_eq!(rendered.reading_time_minutes, 1);
+        assert_eq!(
+            plain_text("# T\n\n![alt](/a.png) see `x`")
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            ["T", "see", "x"]
+        );
+    }
+}
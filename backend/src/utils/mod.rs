@@ -1,7 +1,16 @@
+pub mod book_metadata;
+pub mod epub;
+pub mod epub_reader;
 pub mod error;
 pub mod file_handler;
+pub mod http_range;
+pub mod markdown;
 pub mod r2_video;
+pub mod rate_limit;
+pub mod reading_history;
+pub mod signed_url;
 pub mod text;
+pub mod video_probe;
 
 // 重新导出常用类型和常量，便于外部使用
 pub use file_handler::{
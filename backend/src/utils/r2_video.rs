@@ -13,6 +13,10 @@ const TARGET_PART_SIZE: u64 = 64 * 1024 * 1024;
 const MAX_PARTS: u64 = 1_000;
 const MAX_VIDEO_SIZE: u64 = 20 * 1024 * 1024 * 1024;
 const UPLOAD_URL_TTL_SECONDS: u32 = 24 * 60 * 60;
+/// 服务端自己读取对象用的链接，只要够发出请求。
+const DOWNLOAD_URL_TTL_SECONDS: u32 = 5 * 60;
+/// SigV4 预签名链接最长 7 天。
+const MAX_PRESIGN_TTL_SECONDS: u32 = 7 * 24 * 60 * 60;
 
 #[derive(Clone)]
 struct R2Client {
@@ -164,6 +168,77 @@ impl R2Storage {
         abort_multipart(self.client()?, key, upload_id).await
     }
 
+    /// 预签名的 GET 链接，浏览器直接从 R2 下载，不经过后端。
+    /// `content_disposition` 通过 `response-content-disposition` 让 R2 带回对应的响应头。
+    pub fn presign_download(
+        &self,
+        key: &str,
+        ttl_seconds: u32,
+        content_disposition: &str,
+    ) -> Result<String> {
+        if key.is_empty() || key.starts_with('/') || key.split('/').any(|part| part == "..") {
+            return Err(AppError::BadRequest("Invalid object key".to_string()));
+        }
+        if ttl_seconds == 0 || ttl_seconds > MAX_PRESIGN_TTL_SECONDS {
+            return Err(AppError::BadRequest("Invalid link lifetime".to_string()));
+        }
+        Ok(presign(
+            self.client()?,
+            "GET",
+            key,
+            ttl_seconds,
+            vec![(
+                "response-content-disposition".to_string(),
+                content_disposition.to_string(),
+            )],
+            Utc::now(),
+        ))
+    }
+
+    /// 把整个对象读进内存，超过 `max_size` 时报错，用于上传完成后在服务端处理文件。
+    pub async fn get_object(&self, key: &str, max_size: u64) -> Result<Vec<u8>> {
+        let url = presign(
+            self.client()?,
+            "GET",
+            key,
+            DOWNLOAD_URL_TTL_SECONDS,
+            Vec::new(),
+            Utc::now(),
+        );
+        let too_large = || AppError::BadRequest(format!("Object {key} is too large to process"));
+        let mut response = self
+            .client()?
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|error| AppError::Upstream(format!("R2 download failed: {error}")))?;
+        if !response.status().is_success() {
+            return Err(AppError::Upstream(format!(
+                "R2 download failed with {}",
+                response.status()
+            )));
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_size)
+        {
+            return Err(too_large());
+        }
+        let mut data = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|error| AppError::Upstream(format!("R2 download failed: {error}")))?
+        {
+            if (data.len() + chunk.len()) as u64 > max_size {
+                return Err(too_large());
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
     fn client(&self) -> Result<&R2Client> {
         self.client
             .as_ref()
@@ -457,12 +532,34 @@ fn presign_upload_part(
     upload_id: &str,
     part_number: u32,
     now: DateTime<Utc>,
+) -> String {
+    presign(
+        client,
+        "PUT",
+        key,
+        UPLOAD_URL_TTL_SECONDS,
+        vec![
+            ("partNumber".to_string(), part_number.to_string()),
+            ("uploadId".to_string(), upload_id.to_string()),
+        ],
+        now,
+    )
+}
+
+/// SigV4 查询串签名（只签 host 头），`parameters` 是除 X-Amz-* 之外的查询参数。
+fn presign(
+    client: &R2Client,
+    method: &str,
+    key: &str,
+    ttl_seconds: u32,
+    mut parameters: Vec<(String, String)>,
+    now: DateTime<Utc>,
 ) -> String {
     let date = now.format("%Y%m%d").to_string();
     let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
     let scope = credential_scope(client, &date);
     let path = object_path(client, key);
-    let query = canonical_query(vec![
+    parameters.extend([
         (
             "X-Amz-Algorithm".to_string(),
             "AWS4-HMAC-SHA256".to_string(),
@@ -472,16 +569,12 @@ fn presign_upload_part(
             format!("{}/{}", client.access_key, scope),
         ),
         ("X-Amz-Date".to_string(), amz_date.clone()),
-        (
-            "X-Amz-Expires".to_string(),
-            UPLOAD_URL_TTL_SECONDS.to_string(),
-        ),
+        ("X-Amz-Expires".to_string(), ttl_seconds.to_string()),
         ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
-        ("partNumber".to_string(), part_number.to_string()),
-        ("uploadId".to_string(), upload_id.to_string()),
     ]);
+    let query = canonical_query(parameters);
     let canonical_request = format!(
-        "PUT\n{path}\n{query}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+        "{method}\n{path}\n{query}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
         client.host
     );
     let string_to_sign = string_to_sign(&amz_date, &scope, &canonical_request);
@@ -661,6 +754,35 @@ mod tests {
         assert!(url.contains("uploadId=upload%2Fid%2B"));
     }
 
+    #[test]
+    fn presigned_download_carries_content_disposition() {
+        let storage = R2Storage::new(&test_config(
+            "https://account.r2.cloudflarestorage.com".to_string(),
+        ));
+        let url = storage
+            .presign_download(
+                "books/3/notes.pdf",
+                900,
+                "attachment; filename=\"notes.pdf\"",
+            )
+            .expect("presign download");
+
+        assert!(url.starts_with(
+            "https://account.r2.cloudflarestorage.com/blog-assets/books/3/notes.pdf?"
+        ));
+        assert!(url.contains("X-Amz-Expires=900"));
+        assert!(
+            url.contains("response-content-disposition=attachment%3B%20filename%3D%22notes.pdf%22")
+        );
+        assert!(url.contains("X-Amz-Signature="));
+        assert!(storage
+            .presign_download("../secret", 900, "inline")
+            .is_err());
+        assert!(storage
+            .presign_download("books/3/notes.pdf", 0, "inline")
+            .is_err());
+    }
+
     #[test]
     fn rejects_unsupported_files_and_unsafe_completion_data() {
         assert!(validate_video("archive.zip", "application/zip", 10).is_err());
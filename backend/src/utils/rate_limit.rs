@@ -0,0 +1,104 @@
+//! 公开写接口共用的访客 IP 提取与每 IP 固定窗口限流（邮箱阅读、评论）。
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 取真实访客 IP：站点在 Cloudflare + nginx 后面，套接字对端是本机，
+/// 所以优先信 Cloudflare 注入的 `CF-Connecting-IP`，退而取 `X-Forwarded-For` 首段。
+pub fn client_ip(headers: &HeaderMap) -> String {
+    if let Some(ip) = headers
+        .get("cf-connecting-ip")
+        .and_then(|v| v.to_str().ok())
+    {
+        let t = ip.trim();
+        if !t.is_empty() {
+            return t.to_string();
+        }
+    }
+    if let Some(xff) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+        if let Some(first) = xff.split(',').next() {
+            let t = first.trim();
+            if !t.is_empty() {
+                return t.to_string();
+            }
+        }
+    }
+    "unknown".to_string()
+}
+
+/// 固定窗口限流：每个 key 在 `window` 内最多放行 `limit` 次。
+/// 内存态，重启即清零；条目过多时整体修剪过期项。
+pub struct FixedWindowLimiter {
+    window: Duration,
+    limit: u32,
+    hits: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl FixedWindowLimiter {
+    pub fn new(window: Duration, limit: u32) -> Self {
+        Self {
+            window,
+            limit,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 返回 true 表示放行；false 表示该 key 本窗口内已超限。
+    pub fn check(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut map = match self.hits.lock() {
+            Ok(m) => m,
+            Err(p) => p.into_inner(), // 锁中毒也继续（限流非关键路径）
+        };
+        // 修剪过期项，避免 map 无限增长。
+        if map.len() > 4096 {
+            map.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+        let entry = map.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+        entry.1 += 1;
+        entry.1 <= self.limit
+    }
+
+    /// 只查不计数：该 key 本窗口内是否已经超限。用于只统计失败次数的场景（比如登录），
+    /// 超限后连正确的凭据也先拒绝，否则猜测仍然可以继续。
+    pub fn exceeded(&self, key: &str) -> bool {
+        let map = match self.hits.lock() {
+            Ok(m) => m,
+            Err(p) => p.into_inner(),
+        };
+        map.get(key).is_some_and(|(start, count)| {
+            Instant::now().duration_since(*start) < self.window && *count >= self.limit
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limiter_blocks_after_limit_per_key() {
+        let limiter = FixedWindowLimiter::new(Duration::from_secs(60), 2);
+        assert!(limiter.check("a"));
+        assert!(limiter.check("a"));
+        assert!(!limiter.check("a"));
+        assert!(limiter.check("b"));
+    }
+
+    #[test]
+    fn exceeded_peeks_without_counting() {
+        let limiter = FixedWindowLimiter::new(Duration::from_secs(60), 2);
+        assert!(!limiter.exceeded("a"));
+        limiter.check("a");
+        assert!(!limiter.exceeded("a"));
+        limiter.check("a");
+        assert!(limiter.exceeded("a"));
+        assert!(limiter.exceeded("a"));
+        assert!(!limiter.exceeded("b"));
+    }
+}
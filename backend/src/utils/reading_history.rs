@@ -0,0 +1,317 @@
+//! 解析 Goodreads 和豆瓣的读书记录导出（CSV）。
+//!
+//! Goodreads 的导出格式是固定的（Library Export）；豆瓣没有官方导出，常见的导出脚本
+//! 列名不完全一样，这里按列名的几种写法匹配。两边都统一成 `ImportedBook`，书架映射到
+//! `reading_status`，评分换成 1–5，日期统一成 `YYYY-MM-DD`。
+
+use crate::utils::error::{AppError, Result};
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySource {
+    Goodreads,
+    Douban,
+}
+
+impl HistorySource {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "goodreads" => Ok(Self::Goodreads),
+            "douban" => Ok(Self::Douban),
+            _ => Err(AppError::BadRequest(format!(
+                "Unsupported import source: {value}"
+            ))),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Goodreads => "goodreads",
+            Self::Douban => "douban",
+        }
+    }
+}
+
+/// 导出文件里的一行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedBook {
+    /// CSV 里的行号（表头是第 1 行），用于报告
+    pub line: usize,
+    pub title: String,
+    pub author: String,
+    pub reading_status: String,
+    pub rating: Option<i64>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub notes: String,
+}
+
+/// 按表头识别来源，`source` 给出时以它为准。
+pub fn parse(
+    csv: &str,
+    source: Option<HistorySource>,
+) -> Result<(HistorySource, Vec<ImportedBook>)> {
+    let mut rows = parse_csv(csv.strip_prefix('\u{feff}').unwrap_or(csv)).into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(AppError::BadRequest("The CSV file is empty".to_string()));
+    };
+    let columns = Columns::new(&header);
+    let source = match source {
+        Some(source) => source,
+        None if columns.has("Exclusive Shelf") => HistorySource::Goodreads,
+        None if columns.has("书名") || columns.has("标题") => HistorySource::Douban,
+        None => {
+            return Err(AppError::BadRequest(
+                "Could not tell whether this is a Goodreads or Douban export".to_string(),
+            ))
+        }
+    };
+    let books = rows
+        .filter(|(_, row)| row.iter().any(|cell| !cell.trim().is_empty()))
+        .map(|(line, row)| match source {
+            HistorySource::Goodreads => goodreads_row(&columns, &row, line),
+            HistorySource::Douban => douban_row(&columns, &row, line),
+        })
+        .collect();
+    Ok((source, books))
+}
+
+struct Columns(Vec<String>);
+
+impl Columns {
+    fn new(header: &[String]) -> Self {
+        Self(header.iter().map(|name| name.trim().to_string()).collect())
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.0.iter().any(|column| column == name)
+    }
+
+    /// 第一个存在的列名对应的值。
+    fn get<'r>(&self, row: &'r [String], names: &[&str]) -> &'r str {
+        names
+            .iter()
+            .find_map(|name| self.0.iter().position(|column| column == name))
+            .and_then(|index| row.get(index))
+            .map_or("", |value| value.trim())
+    }
+}
+
+fn goodreads_row(columns: &Columns, row: &[String], line: usize) -> ImportedBook {
+    let shelf = columns.get(row, &["Exclusive Shelf"]);
+    let reading_status = match shelf {
+        "read" => "finished",
+        "currently-reading" => "reading",
+        "to-read" => "want_to_read",
+        // 自定义的独占书架（did-not-finish、on-hold 等）都当作暂停
+        _ => "paused",
+    };
+    let date_read = parse_date(columns.get(row, &["Date Read"]));
+    let review = columns.get(row, &["My Review"]);
+    let private_notes = columns.get(row, &["Private Notes"]);
+    ImportedBook {
+        line,
+        title: columns.get(row, &["Title"]).to_string(),
+        author: columns.get(row, &["Author"]).to_string(),
+        reading_status: reading_status.to_string(),
+        // 0 表示没打分
+        rating: columns
+            .get(row, &["My Rating"])
+            .parse::<i64>()
+            .ok()
+            .filter(|rating| (1..=5).contains(rating)),
+        started_at: None,
+        finished_at: (reading_status == "finished")
+            .then_some(date_read)
+            .flatten(),
+        notes: join_notes(&[&strip_html(review), private_notes]),
+    }
+}
+
+fn douban_row(columns: &Columns, row: &[String], line: usize) -> ImportedBook {
+    let status = columns.get(row, &["状态", "阅读状态", "标记"]);
+    let reading_status = if status.contains("读过") || status.contains("已读") {
+        "finished"
+    } else if status.contains("在读") {
+        "reading"
+    } else if status.contains("想读") {
+        "want_to_read"
+    } else {
+        "paused"
+    };
+    let marked_at = parse_date(columns.get(row, &["标记时间", "标记日期", "创建时间", "日期"]));
+    ImportedBook {
+        line,
+        title: columns.get(row, &["书名", "标题"]).to_string(),
+        author: columns.get(row, &["作者"]).to_string(),
+        reading_status: reading_status.to_string(),
+        rating: douban_rating(columns.get(row, &["我的评分", "个人评分", "评分"])),
+        started_at: (reading_status == "reading")
+            .then(|| marked_at.clone())
+            .flatten(),
+        finished_at: (reading_status == "finished")
+            .then_some(marked_at)
+            .flatten(),
+        notes: columns.get(row, &["短评", "评论", "我的短评"]).to_string(),
+    }
+}
+
+/// 豆瓣的评分可能是星数、10 分制的数字或文字评价。
+fn douban_rating(value: &str) -> Option<i64> {
+    let stars = value.chars().filter(|c| *c == '★').count() as i64;
+    if stars > 0 {
+        return Some(stars.min(5));
+    }
+    match value {
+        "力荐" => return Some(5),
+        "推荐" => return Some(4),
+        "还行" => return Some(3),
+        "较差" => return Some(2),
+        "很差" => return Some(1),
+        _ => {}
+    }
+    let number = value.parse::<f64>().ok()?;
+    let rating = if number > 5.0 {
+        (number / 2.0).round()
+    } else {
+        number.round()
+    } as i64;
+    (1..=5).contains(&rating).then_some(rating)
+}
+
+/// Goodreads 是 `2023/05/14`，豆瓣是 `2023-05-14` 或带时间。
+fn parse_date(value: &str) -> Option<String> {
+    let date = value.get(..10).unwrap_or(value);
+    ["%Y/%m/%d", "%Y-%m-%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Goodreads 的书评里有 `<br/>`。
+fn strip_html(text: &str) -> String {
+    text.replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n")
+        .trim()
+        .to_string()
+}
+
+fn join_notes(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// RFC 4180：逗号分隔，双引号包裹的字段里可以有逗号、换行和 `""`。返回 (行号, 字段)。
+fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                row_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_csv_fields() {
+        let rows = parse_csv("a,\"b, \"\"c\"\"\",d\r\n\"multi\nline\",,x\n");
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    1,
+                    vec!["a".to_string(), "b, \"c\"".to_string(), "d".to_string()]
+                ),
+                (
+                    2,
+                    vec!["multi\nline".to_string(), String::new(), "x".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_goodreads_export() {
+        let csv = "\u{feff}Book Id,Title,Author,My Rating,Date Read,Exclusive Shelf,My Review,Private Notes\n\
+            1,\"Leviathan Wakes (The Expanse, #1)\",James S.A. Corey,4,2023/05/14,read,Great<br/>fun,\n\
+            2,Dune,Frank Herbert,0,,currently-reading,,reread\n\
+            3,Ulysses,James Joyce,0,,did-not-finish,,\n\
+            ,,,,,,,\n";
+        let (source, books) = parse(csv, None).expect("parse goodreads");
+        assert_eq!(source, HistorySource::Goodreads);
+        assert_eq!(books.len(), 3);
+        assert_eq!(books[0].title, "Leviathan Wakes (The Expanse, #1)");
+        assert_eq!(books[0].reading_status, "finished");
+        assert_eq!(books[0].rating, Some(4));
+        assert_eq!(books[0].finished_at.as_deref(), Some("2023-05-14"));
+        assert_eq!(books[0].notes, "Great\nfun");
+        assert_eq!(books[1].reading_status, "reading");
+        assert_eq!(books[1].rating, None);
+        assert_eq!(books[1].notes, "reread");
+        assert_eq!(books[2].reading_status, "paused");
+        assert_eq!(books[2].line, 4);
+    }
+
+    #[test]
+    fn maps_douban_export() {
+        let csv = "书名,作者,我的评分,状态,标记时间,短评\n\
+            三体,刘慈欣,★★★★★,读过,2021-03-02 21:10:00,好看\n\
+            活着,余华,8,在读,2024-01-05,\n\
+            百年孤独,马尔克斯,还行,想读,2024-02-01,\n";
+        let (source, books) = parse(csv, None).expect("parse douban");
+        assert_eq!(source, HistorySource::Douban);
+        assert_eq!(books[0].rating, Some(5));
+        assert_eq!(books[0].finished_at.as_deref(), Some("2021-03-02"));
+        assert_eq!(books[0].notes, "好看");
+        assert_eq!(books[1].reading_status, "reading");
+        assert_eq!(books[1].rating, Some(4));
+        assert_eq!(books[1].started_at.as_deref(), Some("2024-01-05"));
+        assert_eq!(books[2].reading_status, "want_to_read");
+        assert_eq!(books[2].rating, Some(3));
+        assert!(books[2].finished_at.is_none());
+
+        assert!(parse("a,b\n1,2\n", None).is_err());
+        assert!(parse("", Some(HistorySource::Douban)).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! 带过期时间的 HMAC-SHA256 签名链接。
+//!
+//! 签名覆盖资源标识、过期时间和（可选的）客户端 IP，链接被转发到别的网络后就失效。
+//! 签名密钥用 `derive_key` 从站点密钥按用途派生，不直接拿登录令牌的密钥来签链接。
+
+use crate::middleware::auth::secure_eq;
+use crate::utils::error::{AppError, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// `HMAC(secret, purpose)`：同一个站点密钥按用途分出互不相干的签名密钥。
+pub fn derive_key(secret: &str, purpose: &str) -> Vec<u8> {
+    hmac(secret.as_bytes(), purpose.as_bytes())
+}
+
+pub fn sign(key: &[u8], resource: &str, expires: i64, ip: Option<&str>) -> String {
+    hex::encode(hmac(
+        key,
+        format!("{resource}\n{expires}\n{}", ip.unwrap_or_default()).as_bytes(),
+    ))
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .unwrap_or_else(|_| unreachable!("HMAC-SHA256 accepts any key length"));
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// `now` 是 Unix 秒；过期或签名不符都返回 403。
+pub fn verify(
+    key: &[u8],
+    resource: &str,
+    expires: i64,
+    ip: Option<&str>,
+    signature: &str,
+    now: i64,
+) -> Result<()> {
+    if !secure_eq(signature, &sign(key, resource, expires, ip)) {
+        return Err(AppError::Forbidden("Invalid link signature".to_string()));
+    }
+    if expires < now {
+        return Err(AppError::Forbidden("This link has expired".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_bind_resource_expiry_and_ip() {
+        const RESOURCE: &str = "book-file:1:2:inline";
+        const IP: Option<&str> = Some("203.0.113.7");
+        let key = derive_key("secret", "book-file-links");
+        let signature = sign(&key, RESOURCE, 1_000, IP);
+        let check = |resource: &str, expires: i64, ip: Option<&str>, now: i64| {
+            verify(&key, resource, expires, ip, &signature, now).is_ok()
+        };
+
+        assert!(check(RESOURCE, 1_000, IP, 999));
+        assert!(check(RESOURCE, 1_000, IP, 1_000));
+        assert!(!check(RESOURCE, 1_000, IP, 1_001));
+        assert!(!check("book-file:1:2:attachment", 1_000, IP, 0));
+        assert!(!check(RESOURCE, 2_000, IP, 0));
+        assert!(!check(RESOURCE, 1_000, Some("198.51.100.1"), 0));
+        assert!(!check(RESOURCE, 1_000, None, 0));
+        assert!(verify(b"secret", RESOURCE, 1_000, IP, &signature, 0).is_err());
+        let other_purpose = derive_key("secret", "other");
+        assert!(verify(&other_purpose, RESOURCE, 1_000, IP, &signature, 0).is_err());
+    }
+}
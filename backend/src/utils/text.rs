@@ -25,6 +25,34 @@ pub fn markdown_image_urls(content: &str) -> Vec<String> {
     urls
 }
 
+const MAX_SLUG_CHARS: usize = 80;
+
+/// 把标题转成 URL slug：中文等非拉丁字符经 `slug`（deunicode）转写为拼音/拉丁字母。
+///
+/// 返回 `None` 表示标题里没有可用字符。纯数字的结果会加上 `post-` 前缀，
+/// 避免和 `/article/:id` 的数字 id 混淆。
+pub fn slugify_title(title: &str) -> Option<String> {
+    let slug = slug::slugify(title);
+    let slug = if slug.len() > MAX_SLUG_CHARS {
+        let cut = &slug[..MAX_SLUG_CHARS];
+        cut.rsplit_once('-')
+            .map_or(cut, |(head, _)| head)
+            .to_string()
+    } else {
+        slug
+    };
+    if slug.is_empty() {
+        None
+    } else if slug.bytes().all(|b| b.is_ascii_digit()) {
+        Some(format!("post-{slug}"))
+    } else {
+        Some(slug)
+    }
+}
+
+/// 文章列表里正文摘要的字符数。
+pub const SUMMARY_CHARS: usize = 200;
+
 /// 安全地截取内容生成摘要
 ///
 /// 处理 UTF-8 多字节字符（如中文），在安全的断点处截断，
@@ -123,6 +151,17 @@ mod tests {
         assert!(result.contains("。") || result.ends_with("..."));
     }
 
+    #[test]
+    fn slugs_transliterate_chinese_and_avoid_numeric_ids() {
+        assert_eq!(
+            slugify_title("Rust 异步编程").as_deref(),
+            Some("rust-yi-bu-bian-cheng")
+        );
+        assert_eq!(slugify_title("2024").as_deref(), Some("post-2024"));
+        assert_eq!(slugify_title("  !!! "), None);
+        assert!(slugify_title(&"word ".repeat(40)).is_some_and(|slug| slug.len() <= 80));
+    }
+
     #[test]
     fn extracts_unique_markdown_image_urls() {
         let content = r#"
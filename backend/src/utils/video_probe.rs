@@ -0,0 +1,174 @@
+//! 调用 ffprobe / ffmpeg 读取视频信息和截取封面帧。
+//!
+//! 两者都直接读 R2 的公开链接（只请求需要的字节范围），不把整个视频下载到本机。
+//! 只允许 http(s) 协议，避免伪装成视频的播放列表去读本地文件。
+
+use crate::utils::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+const POSTER_TIMEOUT: Duration = Duration::from_secs(120);
+const PROTOCOL_WHITELIST: &str = "http,https,tls,tcp";
+/// 封面帧最宽 1280，之后再转成 WebP。
+const POSTER_MAX_WIDTH: u32 = 1280;
+/// 不截第一帧（常常是黑屏），取视频前 10%，最多第 5 秒。
+const POSTER_MAX_OFFSET_SECS: f64 = 5.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VideoProbe {
+    pub duration: Option<f64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub codec: Option<String>,
+}
+
+impl VideoProbe {
+    pub fn has_video_stream(&self) -> bool {
+        self.width.is_some() && self.height.is_some()
+    }
+
+    /// 截封面帧的时间点（秒）。
+    pub fn poster_offset(&self) -> f64 {
+        self.duration
+            .map_or(0.0, |duration| (duration * 0.1).min(POSTER_MAX_OFFSET_SECS))
+    }
+}
+
+#[derive(Deserialize)]
+struct FfprobeOutput {
+    #[serde(default)]
+    streams: Vec<FfprobeStream>,
+    format: Option<FfprobeFormat>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<i64>,
+    height: Option<i64>,
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeFormat {
+    duration: Option<String>,
+}
+
+/// 解析 `ffprobe -print_format json -show_format -show_streams` 的输出。
+pub fn parse_ffprobe(output: &[u8]) -> Result<VideoProbe> {
+    let output: FfprobeOutput = serde_json::from_slice(output)
+        .map_err(|error| AppError::Upstream(format!("ffprobe returned invalid JSON: {error}")))?;
+    let video = output
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type.as_deref() == Some("video"));
+    let seconds = |value: Option<&String>| {
+        value
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+    };
+    Ok(VideoProbe {
+        // 容器里的时长最可靠，个别格式只有流上有
+        duration: seconds(output.format.as_ref().and_then(|f| f.duration.as_ref()))
+            .or_else(|| seconds(video.and_then(|stream| stream.duration.as_ref()))),
+        width: video.and_then(|stream| stream.width),
+        height: video.and_then(|stream| stream.height),
+        codec: video.and_then(|stream| stream.codec_name.clone()),
+    })
+}
+
+pub async fn probe(url: &str) -> Result<VideoProbe> {
+    let mut cmd = Command::new("ffprobe");
+    cmd.args(["-v", "error", "-protocol_whitelist", PROTOCOL_WHITELIST])
+        .args(["-print_format", "json", "-show_format", "-show_streams"])
+        .arg(url);
+    parse_ffprobe(&run("ffprobe", cmd, PROBE_TIMEOUT).await?)
+}
+
+/// 截取 `offset` 秒处的一帧，返回 JPEG。
+pub async fn poster_frame(url: &str, offset: f64) -> Result<Vec<u8>> {
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args(["-v", "error", "-protocol_whitelist", PROTOCOL_WHITELIST])
+        .args(["-ss", &format!("{offset:.3}")])
+        .arg("-i")
+        .arg(url)
+        .args(["-frames:v", "1", "-an"])
+        .args(["-vf", &format!("scale='min({POSTER_MAX_WIDTH},iw)':-2")])
+        .args(["-f", "image2pipe", "-c:v", "mjpeg", "-q:v", "3", "pipe:1"]);
+    let frame = run("ffmpeg", cmd, POSTER_TIMEOUT).await?;
+    if frame.is_empty() {
+        return Err(AppError::Upstream(
+            "ffmpeg did not produce a poster frame".to_string(),
+        ));
+    }
+    Ok(frame)
+}
+
+async fn run(program: &str, mut cmd: Command, timeout: Duration) -> Result<Vec<u8>> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // 超时或任务取消时 future 被丢弃，顺带杀掉子进程
+        .kill_on_drop(true);
+    let child = cmd.spawn().map_err(|error| {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            AppError::Internal(format!("{program} is not installed on the server"))
+        } else {
+            AppError::Internal(format!("Failed to start {program}: {error}"))
+        }
+    })?;
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| AppError::Upstream(format!("{program} timed out")))??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Upstream(format!(
+            "{program} failed: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ffprobe_output() {
+        let output = br#"{
+            "streams": [
+                {"index": 0, "codec_type": "audio", "codec_name": "aac", "duration": "12.000"},
+                {"index": 1, "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080, "duration": "12.012"}
+            ],
+            "format": {"format_name": "mov,mp4,m4a,3gp,3g2,mj2", "duration": "12.034000"}
+        }"#;
+        let probe = parse_ffprobe(output).expect("parse ffprobe");
+        assert_eq!(probe.duration, Some(12.034));
+        assert_eq!((probe.width, probe.height), (Some(1920), Some(1080)));
+        assert_eq!(probe.codec.as_deref(), Some("h264"));
+        assert!(probe.has_video_stream());
+        assert!((probe.poster_offset() - 1.2034).abs() < 1e-9);
+    }
+
+    #[test]
+    fn falls_back_to_stream_duration() {
+        let output = br#"{
+            "streams": [{"codec_type": "video", "codec_name": "vp9", "width": 640, "height": 360, "duration": "300.5"}],
+            "format": {"duration": "N/A"}
+        }"#;
+        let probe = parse_ffprobe(output).expect("parse ffprobe");
+        assert_eq!(probe.duration, Some(300.5));
+        assert_eq!(probe.poster_offset(), 5.0);
+
+        let audio_only = parse_ffprobe(br#"{"streams": [{"codec_type": "audio"}], "format": {}}"#)
+            .expect("parse audio");
+        assert!(!audio_only.has_video_stream());
+        assert_eq!(audio_only.poster_offset(), 0.0);
+        assert!(parse_ffprobe(b"not json").is_err());
+    }
+}
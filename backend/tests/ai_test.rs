@@ -0,0 +1,210 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use chuyi_uk_back::config::AiConfig;
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    AiAssistRequest, AiLanguage, AiStreamEvent, AiTask, CreateTagRequest, TagSuggestion,
+};
+use chuyi_uk_back::services::{AiService, TagService};
+use chuyi_uk_back::utils::error::AppError;
+use serde_json::Value;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+#[derive(Clone, Default)]
+struct MockState {
+    /// 最近一次收到的 (Authorization, 请求体)
+    last_request: Arc<Mutex<Option<(String, Value)>>>,
+    /// 按块返回的 SSE 字节；None 时返回 401
+    chunks: Option<Vec<Vec<u8>>>,
+}
+
+async fn chat_completions(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let authorization = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    *state.last_request.lock().unwrap() = Some((authorization, body));
+    match state.chunks {
+        Some(chunks) => {
+            let stream = tokio_stream::iter(
+                chunks
+                    .into_iter()
+                    .map(|chunk| Ok::<_, Infallible>(Bytes::from(chunk))),
+            );
+            (
+                [("content-type", "text/event-stream")],
+                Body::from_stream(stream),
+            )
+                .into_response()
+        }
+        None => (StatusCode::UNAUTHORIZED, "invalid api key").into_response(),
+    }
+}
+
+/// 在本机随机端口起一个 OpenAI 兼容的 `/v1/chat/completions`，返回 base URL。
+async fn spawn_mock(state: MockState) -> String {
+    let app = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind mock server");
+    let address = listener.local_addr().expect("mock address");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve mock");
+    });
+    format!("http://{address}/v1")
+}
+
+fn delta(text: &str) -> String {
+    format!(
+        "data: {}\n\n",
+        serde_json::json!({ "choices": [{ "delta": { "content": text } }] })
+    )
+}
+
+fn ai_config(url: String, key: &str) -> AiConfig {
+    AiConfig {
+        deepseek_api_key: key.to_string(),
+        deepseek_api_url: url,
+        deepseek_model: "deepseek-chat".to_string(),
+    }
+}
+
+fn request(content: &str, target_language: Option<AiLanguage>) -> AiAssistRequest {
+    AiAssistRequest {
+        title: "SQLite 与 Rust".to_string(),
+        content: content.to_string(),
+        target_language,
+    }
+}
+
+#[tokio::test]
+async fn tag_suggestions_stream_and_match_existing_tags() {
+    let database = setup_test_db().await;
+    let rust = TagService::new(database.clone())
+        .create_tag(CreateTagRequest {
+            name: "Rust".to_string(),
+        })
+        .await
+        .expect("create tag");
+
+    // 把「，」的 UTF-8 字节拆到两块里，模拟上游在字符中间分块
+    let body = [
+        ": keep-alive\n\n".to_string(),
+        delta("rust"),
+        delta("，SQL"),
+        delta("ite"),
+        "data: [DONE]\n\n".to_string(),
+    ]
+    .concat()
+    .into_bytes();
+    let split = body
+        .windows(3)
+        .position(|window| window == "，".as_bytes())
+        .expect("find comma")
+        + 1;
+    let state = MockState {
+        chunks: Some(vec![body[..split].to_vec(), body[split..].to_vec()]),
+        ..Default::default()
+    };
+    let base_url = spawn_mock(state.clone()).await;
+    let service = AiService::new(database, ai_config(base_url, "test-key"));
+
+    let mut events = service
+        .assist(AiTask::Tags, request("用 sqlx 操作 SQLite", None))
+        .await
+        .expect("start stream");
+    let mut streamed = String::new();
+    let mut done = None;
+    while let Some(event) = events.recv().await {
+        match event {
+            AiStreamEvent::Delta { text } => streamed.push_str(&text),
+            AiStreamEvent::Done { text, tags, titles } => done = Some((text, tags, titles)),
+            AiStreamEvent::Error { message } => panic!("unexpected error: {message}"),
+        }
+    }
+    assert_eq!(streamed, "rust，SQLite");
+    let (text, tags, titles) = done.expect("done event");
+    assert_eq!(text, "rust，SQLite");
+    assert!(titles.is_none());
+    assert_eq!(
+        tags.expect("tag suggestions"),
+        [
+            TagSuggestion {
+                id: Some(rust.id),
+                name: "Rust".to_string()
+            },
+            TagSuggestion {
+                id: None,
+                name: "SQLite".to_string()
+            },
+        ]
+    );
+
+    let (authorization, sent) = state.last_request.lock().unwrap().clone().expect("request");
+    assert_eq!(authorization, "Bearer test-key");
+    assert_eq!(sent["stream"], true);
+    assert_eq!(sent["model"], "deepseek-chat");
+    assert!(sent["messages"][0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("已有标签：Rust"));
+}
+
+#[tokio::test]
+async fn requests_are_rejected_before_streaming() {
+    let database = setup_test_db().await;
+
+    let unconfigured = AiService::new(
+        database.clone(),
+        ai_config("http://127.0.0.1:9".to_string(), ""),
+    );
+    assert!(matches!(
+        unconfigured
+            .assist(AiTask::Summary, request("正文", None))
+            .await,
+        Err(AppError::BadRequest(_))
+    ));
+
+    let base_url = spawn_mock(MockState::default()).await;
+    let service = AiService::new(database, ai_config(base_url, "wrong-key"));
+    assert!(matches!(
+        service
+            .assist(AiTask::Translate, request("正文", None))
+            .await,
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        service
+            .assist(AiTask::Translate, request("正文", Some(AiLanguage::En)))
+            .await,
+        Err(AppError::Upstream(_))
+    ));
+}
@@ -0,0 +1,164 @@
+use chrono::{Duration, Utc};
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreatePostRequest, PostStatus};
+use chuyi_uk_back::services::{AnalyticsService, PostService};
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+async fn published_post(database: &Database, title: &str) -> (i64, String) {
+    let service = PostService::new(
+        database.clone(),
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    let post = service
+        .create_post(CreatePostRequest {
+            title: title.to_string(),
+            cover_url: None,
+            content: "content".to_string(),
+            category_id: None,
+            status: Some(PostStatus::Published),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post");
+    (post.id, post.slug.expect("slug"))
+}
+
+#[tokio::test]
+async fn page_views_are_batched_and_counted_per_post_and_section() {
+    let database = setup_test_db().await;
+    let (post_id, slug) = published_post(&database, "Counting Views").await;
+    let analytics = AnalyticsService::new(database);
+    let now = Utc::now();
+
+    let by_slug = format!("/article/{slug}?utm_source=x");
+    let by_id = format!("/article/{post_id}");
+    assert!(analytics.record(
+        &by_slug,
+        Some("news.ycombinator.com".into()),
+        "1.1.1.1",
+        "Firefox",
+        now
+    ));
+    assert!(analytics.record(&by_id, None, "1.1.1.1", "Firefox", now));
+    assert!(analytics.record(&by_slug, None, "2.2.2.2", "Safari", now));
+    assert!(analytics.record("/", None, "2.2.2.2", "Safari", now));
+    assert!(!analytics.record("/admin/posts", None, "2.2.2.2", "Safari", now));
+
+    // 写库前查询看不到
+    assert_eq!(analytics.total_views().await.expect("total"), 0);
+    assert_eq!(analytics.flush(now).await.expect("flush"), 4);
+    assert_eq!(analytics.flush(now).await.expect("empty flush"), 0);
+
+    let top = analytics.top_posts(None, None).await.expect("top posts");
+    assert_eq!(top.len(), 1);
+    assert_eq!(
+        (top[0].post_id, top[0].views, top[0].visitors),
+        (post_id, 3, 2)
+    );
+
+    // 同一访客在下一批里不再算新访客
+    analytics.record(&by_id, None, "1.1.1.1", "Firefox", now);
+    analytics.flush(now).await.expect("second flush");
+    let series = analytics
+        .timeseries(Some(post_id), None, Some(7))
+        .await
+        .expect("post series");
+    assert_eq!(series.len(), 7);
+    assert_eq!(series[6].day, now.date_naive().to_string());
+    assert_eq!((series[6].views, series[6].visitors), (4, 2));
+    assert_eq!((series[0].views, series[0].visitors), (0, 0));
+
+    let sections = analytics.sections(None).await.expect("sections");
+    let article = sections
+        .iter()
+        .find(|s| s.section == "article")
+        .expect("article section");
+    assert_eq!((article.views, article.visitors), (4, 2));
+    assert!(sections.iter().any(|s| s.section == "home" && s.views == 1));
+    assert!(analytics
+        .timeseries(None, Some("wp-admin"), None)
+        .await
+        .is_err());
+
+    let referrers = analytics
+        .top_referrers(None, None)
+        .await
+        .expect("referrers");
+    assert_eq!(referrers.len(), 1);
+    assert_eq!(referrers[0].host, "news.ycombinator.com");
+    assert_eq!(analytics.total_views().await.expect("total"), 5);
+}
+
+#[tokio::test]
+async fn visitor_hashes_rotate_daily_and_old_ones_are_pruned() {
+    let database = setup_test_db().await;
+    let analytics = AnalyticsService::new(database.clone());
+    let today = Utc::now();
+    let yesterday = today - Duration::days(1);
+
+    analytics.record("/about", None, "1.1.1.1", "Firefox", yesterday);
+    analytics.flush(yesterday).await.expect("flush yesterday");
+    analytics.record("/about", None, "1.1.1.1", "Firefox", today);
+    analytics.flush(today).await.expect("flush today");
+
+    let series = analytics
+        .timeseries(None, Some("about"), Some(2))
+        .await
+        .expect("section series");
+    assert_eq!(
+        series.iter().map(|d| d.visitors).collect::<Vec<_>>(),
+        [1, 1]
+    );
+
+    let stale: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM analytics_visitors WHERE day < ?")
+        .bind(today.date_naive().to_string())
+        .fetch_one(database.pool())
+        .await
+        .expect("count stale hashes");
+    assert_eq!(stale, 0);
+}
+
+#[tokio::test]
+async fn failed_flush_keeps_the_batch_for_the_next_one() {
+    let database = setup_test_db().await;
+    let analytics = AnalyticsService::new(database.clone());
+    let now = Utc::now();
+    analytics.record("/", None, "1.1.1.1", "Firefox", now);
+    analytics.record("/about", None, "2.2.2.2", "Safari", now);
+
+    sqlx::query("ALTER TABLE analytics_daily RENAME TO analytics_daily_moved")
+        .execute(database.pool())
+        .await
+        .expect("move table");
+    assert!(analytics.flush(now).await.is_err());
+    analytics.record("/", None, "3.3.3.3", "Chrome", now);
+    sqlx::query("ALTER TABLE analytics_daily_moved RENAME TO analytics_daily")
+        .execute(database.pool())
+        .await
+        .expect("restore table");
+
+    assert_eq!(analytics.flush(now).await.expect("retry flush"), 3);
+    assert_eq!(analytics.total_views().await.expect("total"), 3);
+}
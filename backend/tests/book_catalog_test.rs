@@ -0,0 +1,153 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreateBookFile, CreateBookRequest};
+use chuyi_uk_back::services::BookService;
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+async fn create_book(
+    books: &BookService,
+    title: &str,
+    author: &str,
+    status: &str,
+    is_public: bool,
+) {
+    books
+        .create(CreateBookRequest {
+            title: title.to_string(),
+            author: author.to_string(),
+            description: String::new(),
+            cover_url: None,
+            reading_status: status.to_string(),
+            progress: 0,
+            rating: None,
+            notes: String::new(),
+            started_at: None,
+            finished_at: None,
+            is_public,
+            download_enabled: false,
+        })
+        .await
+        .expect("create book");
+}
+
+fn titles(books: &[chuyi_uk_back::models::Book]) -> Vec<&str> {
+    let mut titles: Vec<&str> = books
+        .iter()
+        .map(|book| book.record.title.as_str())
+        .collect();
+    titles.sort_unstable();
+    titles
+}
+
+#[tokio::test]
+async fn catalog_filters_by_visibility_status_and_search() {
+    let database = setup_test_db().await;
+    let books = BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    create_book(&books, "三体", "刘慈欣", "reading", true).await;
+    create_book(&books, "球状闪电", "刘慈欣", "finished", true).await;
+    create_book(&books, "私人日记", "我", "reading", false).await;
+    create_book(&books, "100%_Rust", "Ferris", "want_to_read", true).await;
+
+    let public = books
+        .catalog(true, None, None)
+        .await
+        .expect("public catalog");
+    assert_eq!(titles(&public), ["100%_Rust", "三体", "球状闪电"]);
+    let all = books
+        .catalog(false, None, None)
+        .await
+        .expect("full catalog");
+    assert_eq!(all.len(), 4);
+
+    let reading = books
+        .catalog(true, Some("reading"), None)
+        .await
+        .expect("reading shelf");
+    assert_eq!(titles(&reading), ["三体"]);
+    let reading = books
+        .catalog(false, Some("reading"), None)
+        .await
+        .expect("private reading shelf");
+    assert_eq!(titles(&reading), ["三体", "私人日记"]);
+
+    let by_author = books
+        .catalog(true, None, Some(" 刘慈欣 "))
+        .await
+        .expect("search author");
+    assert_eq!(titles(&by_author), ["三体", "球状闪电"]);
+    // % 和 _ 按字面匹配
+    let literal = books
+        .catalog(true, None, Some("0%_R"))
+        .await
+        .expect("search literal");
+    assert_eq!(titles(&literal), ["100%_Rust"]);
+    assert!(books
+        .catalog(true, None, Some("%"))
+        .await
+        .expect("search percent")
+        .iter()
+        .all(|book| book.record.title.contains('%')));
+
+    assert!(matches!(
+        books.catalog(true, Some("lost"), None).await,
+        Err(AppError::BadRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn public_book_list_hides_storage_locations() {
+    let database = setup_test_db().await;
+    let books = BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    create_book(&books, "公开", "作者", "reading", true).await;
+    let book_id = books.list_admin().await.expect("list")[0].record.id;
+    books
+        .add_file(CreateBookFile {
+            book_id,
+            format: "epub".to_string(),
+            file_url: format!("https://assets.example.com/books/{book_id}/book.epub"),
+            r2_key: format!("books/{book_id}/book.epub"),
+            file_name: "book.epub".to_string(),
+            file_size: 1024,
+            mime_type: "application/epub+zip".to_string(),
+        })
+        .await
+        .expect("add file");
+
+    let public =
+        serde_json::to_value(books.list_public().await.expect("list public")).expect("serialize");
+    let file = &public[0]["files"][0];
+    assert_eq!(file["file_name"], "book.epub");
+    assert_eq!(file["format"], "epub");
+    assert!(file.get("file_url").is_none());
+    assert!(file.get("r2_key").is_none());
+    assert!(!public.to_string().contains("assets.example.com"));
+}
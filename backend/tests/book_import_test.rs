@@ -0,0 +1,112 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::CreateBookRequest;
+use chuyi_uk_back::services::BookService;
+use chuyi_uk_back::utils::reading_history::{self, HistorySource};
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+const GOODREADS_CSV: &str =
+    "Book Id,Title,Author,My Rating,Date Read,Exclusive Shelf,My Review,Private Notes
+1,The  Hobbit,J.R.R. Tolkien,5,2022/08/01,read,,
+2,Dune,Frank Herbert,4,2023/01/10,read,,
+3,Dune,Frank Herbert,3,,to-read,,
+4,Snow Crash,Neal Stephenson,0,,currently-reading,,
+5,,Nobody,0,,read,,
+";
+
+#[tokio::test]
+async fn import_dedupes_against_existing_books() {
+    let database = setup_test_db().await;
+    let books = BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    let hobbit = books
+        .create(CreateBookRequest {
+            title: "the hobbit".to_string(),
+            author: "J.R.R. Tolkien".to_string(),
+            description: String::new(),
+            cover_url: None,
+            reading_status: "reading".to_string(),
+            progress: 40,
+            rating: None,
+            notes: String::new(),
+            started_at: Some("2022-07-01".to_string()),
+            finished_at: None,
+            is_public: true,
+            download_enabled: false,
+        })
+        .await
+        .expect("create book");
+
+    let (source, records) = reading_history::parse(GOODREADS_CSV, None).expect("parse csv");
+    assert_eq!(source, HistorySource::Goodreads);
+
+    let preview = books
+        .import_history(source, records.clone(), true, false)
+        .await
+        .expect("dry run");
+    assert!(preview.dry_run);
+    assert_eq!(
+        (preview.created, preview.updated, preview.skipped),
+        (2, 1, 2)
+    );
+    let actions: Vec<&str> = preview
+        .items
+        .iter()
+        .map(|item| item.action.as_str())
+        .collect();
+    assert_eq!(actions, ["update", "create", "skip", "create", "skip"]);
+    assert_eq!(preview.items[0].book_id, Some(hobbit.record.id));
+    assert_eq!(
+        preview.items[0].changes,
+        ["reading_status", "rating", "finished_at"]
+    );
+    assert!(preview.items[1].book_id.is_none());
+    assert_eq!(books.list_admin().await.expect("list").len(), 1);
+
+    let report = books
+        .import_history(source, records.clone(), false, false)
+        .await
+        .expect("import");
+    assert_eq!((report.created, report.updated, report.skipped), (2, 1, 2));
+    let all = books.list_admin().await.expect("list");
+    assert_eq!(all.len(), 3);
+    let hobbit = books.get(hobbit.record.id, false).await.expect("hobbit");
+    assert_eq!(hobbit.record.reading_status, "finished");
+    assert_eq!(hobbit.record.progress, 100);
+    assert_eq!(hobbit.record.rating, Some(5));
+    assert_eq!(hobbit.record.started_at.as_deref(), Some("2022-07-01"));
+    assert_eq!(hobbit.record.finished_at.as_deref(), Some("2022-08-01"));
+    let dune = books
+        .get(report.items[1].book_id.expect("created id"), false)
+        .await
+        .expect("dune");
+    assert_eq!(dune.record.finished_at.as_deref(), Some("2023-01-10"));
+    assert!(!dune.record.is_public);
+
+    // 再导一次什么都不变
+    let again = books
+        .import_history(source, records, false, false)
+        .await
+        .expect("import again");
+    assert_eq!((again.created, again.updated, again.skipped), (0, 0, 5));
+}
@@ -0,0 +1,185 @@
+use chuyi_uk_back::config::S3Config;
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreateBookFile, CreateBookRequest, JobKind};
+use chuyi_uk_back::services::{BookMetadataService, BookService, JobService};
+use chuyi_uk_back::utils::book_metadata::{BookMetadata, CoverImage};
+use chuyi_uk_back::utils::{FileHandler, R2Storage};
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::io::Cursor;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn book_service(database: Database) -> BookService {
+    BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+async fn create_book(books: &BookService, author: &str, cover_url: Option<&str>) -> (i64, i64) {
+    let book = books
+        .create(CreateBookRequest {
+            title: "未命名".to_string(),
+            author: author.to_string(),
+            description: String::new(),
+            cover_url: cover_url.map(str::to_string),
+            reading_status: "want_to_read".to_string(),
+            progress: 0,
+            rating: None,
+            notes: String::new(),
+            started_at: None,
+            finished_at: None,
+            is_public: true,
+            download_enabled: false,
+        })
+        .await
+        .expect("create book");
+    let file = books
+        .add_file(CreateBookFile {
+            book_id: book.record.id,
+            format: "pdf".to_string(),
+            file_url: format!("https://assets.example.com/books/{}.pdf", book.record.id),
+            r2_key: format!("books/{}/upload.pdf", book.record.id),
+            file_name: "upload.pdf".to_string(),
+            file_size: 2048,
+            mime_type: "application/pdf".to_string(),
+        })
+        .await
+        .expect("add file");
+    (book.record.id, file.id)
+}
+
+fn png_cover() -> CoverImage {
+    let mut data = Vec::new();
+    image::DynamicImage::new_rgb8(8, 12)
+        .write_to(&mut Cursor::new(&mut data), image::ImageOutputFormat::Png)
+        .expect("encode png");
+    CoverImage {
+        file_name: "cover.png".to_string(),
+        data,
+    }
+}
+
+fn metadata() -> BookMetadata {
+    BookMetadata {
+        title: Some("沉思录".to_string()),
+        author: Some("马可·奥勒留".to_string()),
+        description: Some("斯多葛学派的札记。".to_string()),
+        page_count: Some(256),
+        cover: Some(png_cover()),
+    }
+}
+
+#[tokio::test]
+async fn extracted_metadata_fills_empty_book_fields() {
+    let database = setup_test_db().await;
+    let books = book_service(database);
+    let (book_id, file_id) = create_book(&books, "", None).await;
+
+    let (book, extracted) = books
+        .apply_metadata(book_id, file_id, metadata())
+        .await
+        .expect("apply metadata");
+    // 标题只返回给前端，不自动覆盖
+    assert_eq!(book.record.title, "未命名");
+    assert_eq!(extracted.title.as_deref(), Some("沉思录"));
+    assert_eq!(book.record.author, "马可·奥勒留");
+    assert_eq!(book.record.description, "斯多葛学派的札记。");
+    let cover_url = extracted.cover_url.expect("cover saved");
+    assert!(cover_url.starts_with("/uploads/covers/") && cover_url.ends_with(".webp"));
+    assert_eq!(book.record.cover_url.as_deref(), Some(cover_url.as_str()));
+    assert_eq!(book.files[0].page_count, Some(256));
+}
+
+#[tokio::test]
+async fn extracted_metadata_keeps_existing_values() {
+    let database = setup_test_db().await;
+    let books = book_service(database);
+    let (book_id, file_id) = create_book(&books, "原作者", Some("/uploads/covers/old.webp")).await;
+
+    let (book, extracted) = books
+        .apply_metadata(book_id, file_id, metadata())
+        .await
+        .expect("apply metadata");
+    assert_eq!(book.record.author, "原作者");
+    assert_eq!(book.record.description, "斯多葛学派的札记。");
+    assert_eq!(
+        book.record.cover_url.as_deref(),
+        Some("/uploads/covers/old.webp")
+    );
+    // 书已有封面时不保存提取的封面
+    assert!(extracted.cover_url.is_none());
+
+    let (other_book, _) = create_book(&books, "", None).await;
+    assert!(books
+        .apply_metadata(other_book, file_id, BookMetadata::default())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn upload_queues_metadata_extraction_for_supported_files() {
+    let database = setup_test_db().await;
+    let books = Arc::new(book_service(database.clone()));
+    let jobs = Arc::new(JobService::new(database));
+    let storage = Arc::new(R2Storage::new(&S3Config {
+        enabled: false,
+        endpoint: String::new(),
+        bucket: String::new(),
+        access_key: String::new(),
+        secret_key: String::new(),
+        region: String::new(),
+        public_url: String::new(),
+    }));
+    let metadata = BookMetadataService::new(books.clone(), jobs.clone(), storage);
+    let (book_id, file_id) = create_book(&books, "", None).await;
+    let (_, file) = books.get_file(book_id, file_id).await.expect("file");
+
+    let job = metadata
+        .enqueue(&file)
+        .await
+        .expect("enqueue")
+        .expect("pdf is supported");
+    assert_eq!(job.kind, JobKind::ExtractBookMetadata.as_str());
+    assert_eq!(
+        job.payload,
+        json!({ "book_id": book_id, "file_id": file_id })
+    );
+
+    let mobi = books
+        .add_file(CreateBookFile {
+            book_id,
+            format: "mobi".to_string(),
+            file_url: "https://assets.example.com/books/1/upload.mobi".to_string(),
+            r2_key: "books/1/upload.mobi".to_string(),
+            file_name: "upload.mobi".to_string(),
+            file_size: 2048,
+            mime_type: "application/octet-stream".to_string(),
+        })
+        .await
+        .expect("add mobi");
+    assert!(metadata.enqueue(&mobi).await.expect("enqueue").is_none());
+    assert_eq!(
+        jobs.pending_count(JobKind::ExtractBookMetadata)
+            .await
+            .expect("pending"),
+        1
+    );
+}
@@ -0,0 +1,214 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreateBookFile, CreateBookRequest, CreateHighlightRequest, SaveReadingProgressRequest,
+    UpdateHighlightRequest,
+};
+use chuyi_uk_back::services::BookService;
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn book_service(database: Database) -> BookService {
+    BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+/// 建一本书和它的一个文件，返回 (book_id, file_id)。
+async fn create_book(books: &BookService, title: &str, format: &str) -> (i64, i64) {
+    let book = books
+        .create(CreateBookRequest {
+            title: title.to_string(),
+            author: "作者".to_string(),
+            description: String::new(),
+            cover_url: None,
+            reading_status: "want_to_read".to_string(),
+            progress: 0,
+            rating: None,
+            notes: String::new(),
+            started_at: None,
+            finished_at: None,
+            is_public: true,
+            download_enabled: false,
+        })
+        .await
+        .expect("create book");
+    let file = books
+        .add_file(CreateBookFile {
+            book_id: book.record.id,
+            format: format.to_string(),
+            file_url: format!(
+                "https://assets.example.com/books/{}.{format}",
+                book.record.id
+            ),
+            r2_key: format!("books/{}.{format}", book.record.id),
+            file_name: format!("{title}.{format}"),
+            file_size: 1024,
+            mime_type: "application/epub+zip".to_string(),
+        })
+        .await
+        .expect("add file");
+    (book.record.id, file.id)
+}
+
+fn progress(locator: &str, percentage: f64) -> SaveReadingProgressRequest {
+    SaveReadingProgressRequest {
+        locator: locator.to_string(),
+        percentage,
+    }
+}
+
+#[tokio::test]
+async fn reading_activity_updates_book_status_and_dates() {
+    let database = setup_test_db().await;
+    let books = book_service(database);
+    let (book_id, file_id) = create_book(&books, "三体", "epub").await;
+
+    let saved = books
+        .save_progress(book_id, file_id, progress("epubcfi(/6/4!/4/2)", 12.7))
+        .await
+        .expect("save progress");
+    assert_eq!(saved.locator, "epubcfi(/6/4!/4/2)");
+    let book = books.get(book_id, false).await.expect("book").record;
+    assert_eq!(book.reading_status, "reading");
+    assert_eq!(book.progress, 12);
+    let started_at = book.started_at.clone().expect("started_at is set");
+    assert!(book.finished_at.is_none());
+
+    // 同一个文件只保留最新位置
+    books
+        .save_progress(book_id, file_id, progress("epubcfi(/6/20!/4/2)", 99.4))
+        .await
+        .expect("finish book");
+    let listed = books.list_progress(book_id).await.expect("list progress");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].locator, "epubcfi(/6/20!/4/2)");
+    let book = books.get(book_id, false).await.expect("book").record;
+    assert_eq!(book.reading_status, "finished");
+    assert_eq!(book.progress, 100);
+    assert_eq!(book.started_at.as_deref(), Some(started_at.as_str()));
+    assert!(book.finished_at.is_some());
+
+    // 重读不会把已读完的书改回在读
+    books
+        .save_progress(book_id, file_id, progress("epubcfi(/6/2!/4/2)", 3.0))
+        .await
+        .expect("reread");
+    let book = books.get(book_id, false).await.expect("book").record;
+    assert_eq!(book.reading_status, "finished");
+    assert_eq!(book.progress, 100);
+
+    assert!(matches!(
+        books
+            .save_progress(book_id, file_id, progress("page:1", 120.0))
+            .await,
+        Err(AppError::BadRequest(_))
+    ));
+    assert!(matches!(
+        books
+            .save_progress(book_id, file_id, progress("  ", 10.0))
+            .await,
+        Err(AppError::BadRequest(_))
+    ));
+    let (_, other_file) = create_book(&books, "别的书", "pdf").await;
+    assert!(matches!(
+        books
+            .save_progress(book_id, other_file, progress("page:3", 10.0))
+            .await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn highlights_export_as_markdown_notes() {
+    let database = setup_test_db().await;
+    let books = book_service(database);
+    let (book_id, file_id) = create_book(&books, "沉思录", "pdf").await;
+
+    let highlight = |locator: &str, chapter: &str, text: &str, note: &str| CreateHighlightRequest {
+        file_id,
+        locator: locator.to_string(),
+        chapter: chapter.to_string(),
+        text: text.to_string(),
+        note: note.to_string(),
+        color: "yellow".to_string(),
+    };
+    let first = books
+        .create_highlight(
+            book_id,
+            highlight("page:12", "卷一", "  你有力量\n做到这一点。 ", ""),
+        )
+        .await
+        .expect("create highlight");
+    assert_eq!(first.text, "你有力量\n做到这一点。");
+    books
+        .create_highlight(book_id, highlight("page:30", "卷二", "第二段", "值得再读"))
+        .await
+        .expect("create second highlight");
+    books
+        .update_highlight(
+            first.id,
+            UpdateHighlightRequest {
+                note: Some("开篇".to_string()),
+                color: Some("green".to_string()),
+            },
+        )
+        .await
+        .expect("update highlight");
+
+    let (_, markdown) = books.export_notes(book_id).await.expect("export notes");
+    assert_eq!(
+        markdown,
+        "# 沉思录\n\n*作者*\n\n## 卷一\n\n> 你有力量\n> 做到这一点。\n>\n> — 第 12 页\n\n开篇\n\n\
+         ## 卷二\n\n> 第二段\n>\n> — 第 30 页\n\n值得再读\n\n"
+    );
+
+    let mut invalid = highlight("page:1", "", "x", "");
+    invalid.color = "red".to_string();
+    assert!(matches!(
+        books.create_highlight(book_id, invalid).await,
+        Err(AppError::BadRequest(_))
+    ));
+    assert!(matches!(
+        books
+            .create_highlight(book_id, highlight("page:1", "", "   ", ""))
+            .await,
+        Err(AppError::BadRequest(_))
+    ));
+
+    books
+        .delete_highlight(first.id)
+        .await
+        .expect("delete highlight");
+    assert_eq!(
+        books
+            .list_highlights(book_id, Some(file_id))
+            .await
+            .expect("list highlights")
+            .len(),
+        1
+    );
+    assert!(matches!(
+        books.delete_highlight(first.id).await,
+        Err(AppError::NotFound(_))
+    ));
+}
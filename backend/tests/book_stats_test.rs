@@ -0,0 +1,149 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreateBookFile, CreateBookRequest};
+use chuyi_uk_back::services::BookService;
+use chuyi_uk_back::utils::book_metadata::BookMetadata;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+async fn create_book(
+    books: &BookService,
+    title: &str,
+    status: &str,
+    rating: Option<i64>,
+    dates: (Option<&str>, Option<&str>),
+    is_public: bool,
+) -> i64 {
+    books
+        .create(CreateBookRequest {
+            title: title.to_string(),
+            author: String::new(),
+            description: String::new(),
+            cover_url: None,
+            reading_status: status.to_string(),
+            progress: 0,
+            rating,
+            notes: String::new(),
+            started_at: dates.0.map(str::to_string),
+            finished_at: dates.1.map(str::to_string),
+            is_public,
+            download_enabled: false,
+        })
+        .await
+        .expect("create book")
+        .record
+        .id
+}
+
+#[tokio::test]
+async fn stats_and_yearly_report() {
+    let database = setup_test_db().await;
+    let books = BookService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    let quick = create_book(
+        &books,
+        "一天读完",
+        "finished",
+        Some(4),
+        (Some("2024-03-02"), Some("2024-03-02")),
+        true,
+    )
+    .await;
+    let slow = create_book(
+        &books,
+        "慢慢读",
+        "finished",
+        Some(5),
+        (Some("2024-01-01"), Some("2024-03-10")),
+        true,
+    )
+    .await;
+    create_book(
+        &books,
+        "没有开始日期",
+        "finished",
+        None,
+        (None, Some("2023-12-31")),
+        true,
+    )
+    .await;
+    create_book(&books, "在读", "reading", Some(3), (None, None), true).await;
+    create_book(
+        &books,
+        "私密",
+        "finished",
+        Some(1),
+        (Some("2024-05-01"), Some("2024-05-03")),
+        false,
+    )
+    .await;
+    let file = books
+        .add_file(CreateBookFile {
+            book_id: slow,
+            format: "pdf".to_string(),
+            file_url: "https://assets.example.com/books/slow.pdf".to_string(),
+            r2_key: "books/slow.pdf".to_string(),
+            file_name: "slow.pdf".to_string(),
+            file_size: 1024,
+            mime_type: "application/pdf".to_string(),
+        })
+        .await
+        .expect("add file");
+    books
+        .apply_metadata(
+            slow,
+            file.id,
+            BookMetadata {
+                page_count: Some(320),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("page count");
+
+    let stats = books.stats(true).await.expect("public stats");
+    assert_eq!((stats.total, stats.finished, stats.reading), (4, 3, 1));
+    assert_eq!(stats.average_rating, Some(4.0));
+    // (1 + 70) / 2
+    assert_eq!(stats.average_days_to_finish, Some(35.5));
+    let years: Vec<(i32, i64)> = stats
+        .years
+        .iter()
+        .map(|count| (count.year, count.finished))
+        .collect();
+    assert_eq!(years, [(2024, 2), (2023, 1)]);
+    assert_eq!(stats.years[0].months[2], 2);
+    assert_eq!(books.stats(false).await.expect("admin stats").finished, 4);
+
+    let report = books.report(true, 2024).await.expect("report");
+    assert_eq!(report.finished, 2);
+    assert_eq!(report.months.iter().sum::<i64>(), 2);
+    assert_eq!(report.pages_read, 320);
+    assert_eq!(report.books[0].id, quick);
+    assert_eq!(report.fastest.map(|book| book.id), Some(quick));
+    assert_eq!(report.slowest.map(|book| book.id), Some(slow));
+    assert_eq!(books.report(false, 2024).await.expect("admin").finished, 3);
+
+    let empty = books.report(true, 1999).await.expect("empty report");
+    assert_eq!(empty.finished, 0);
+    assert!(empty.average_rating.is_none() && empty.fastest.is_none());
+}
@@ -0,0 +1,190 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    BanCommenterRequest, CreateCommentRequest, CreatePostRequest, GiscusExport, PostStatus,
+};
+use chuyi_uk_back::services::{CommentService, PostService};
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+async fn published_post(database: &Database, title: &str) -> i64 {
+    let service = PostService::new(
+        database.clone(),
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    service
+        .create_post(CreatePostRequest {
+            title: title.to_string(),
+            cover_url: None,
+            content: "content".to_string(),
+            category_id: None,
+            status: Some(PostStatus::Published),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post")
+        .id
+}
+
+fn comment(content: &str, email: Option<&str>, parent_id: Option<i64>) -> CreateCommentRequest {
+    CreateCommentRequest {
+        author_name: "reader".to_string(),
+        author_email: email.map(str::to_string),
+        author_url: None,
+        content: content.to_string(),
+        parent_id,
+        website: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn comments_wait_for_moderation_and_trusted_authors_skip_it() {
+    let database = setup_test_db().await;
+    let post_id = published_post(&database, "Threaded").await;
+    let service = CommentService::new(database);
+
+    let first = service
+        .submit(
+            Some(post_id),
+            comment("First!", Some("Reader@Example.com"), None),
+            "1.1.1.1",
+            None,
+        )
+        .await
+        .expect("submit comment");
+    assert_eq!(first.status, "pending");
+    assert!(service
+        .list_thread(Some(post_id))
+        .await
+        .expect("thread")
+        .is_empty());
+
+    service.approve(first.id).await.expect("approve");
+    let reply = service
+        .submit(
+            Some(post_id),
+            comment("A reply", Some("reader@example.com"), Some(first.id)),
+            "1.1.1.1",
+            None,
+        )
+        .await
+        .expect("submit reply");
+    assert_eq!(reply.status, "approved");
+
+    let thread = service.list_thread(Some(post_id)).await.expect("thread");
+    assert_eq!(thread.len(), 1);
+    assert_eq!(thread[0].replies.len(), 1);
+    assert_eq!(thread[0].replies[0].content, "A reply");
+    assert!(thread[0].avatar_url.contains("gravatar.com/avatar/"));
+
+    // 留言板是独立的线程
+    assert!(service
+        .list_thread(None)
+        .await
+        .expect("guestbook")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn spam_is_flagged_and_banned_authors_are_refused() {
+    let database = setup_test_db().await;
+    let service = CommentService::new(database);
+
+    let mut bot = comment("buy now", Some("bot@spam.io"), None);
+    bot.website = "https://spam.io".to_string();
+    let flagged = service
+        .submit(None, bot, "6.6.6.6", None)
+        .await
+        .expect("submit spam");
+    assert_eq!(flagged.status, "spam");
+
+    service
+        .ban(flagged.id, BanCommenterRequest::default())
+        .await
+        .expect("ban");
+    let refused = service
+        .submit(
+            None,
+            comment("hello again", Some("other@example.com"), None),
+            "6.6.6.6",
+            None,
+        )
+        .await;
+    assert!(matches!(refused, Err(AppError::Forbidden(_))));
+
+    let (queue, total) = service
+        .list_admin(Some("spam"), 1, 20)
+        .await
+        .expect("spam queue");
+    assert_eq!(total, 1);
+    assert_eq!(queue[0].spam_reasons, ["honeypot"]);
+}
+
+#[tokio::test]
+async fn giscus_export_imports_threads_once() {
+    let database = setup_test_db().await;
+    let post_id = published_post(&database, "Hello Giscus").await;
+    let service = CommentService::new(database);
+    let export = r#"{
+        "discussions": { "nodes": [
+            { "title": "Hello Giscus", "comments": { "nodes": [
+                { "id": "DC_1", "author": { "login": "octocat", "avatarUrl": "https://avatars.githubusercontent.com/u/1", "url": "https://github.com/octocat" },
+                  "body": "Great post", "createdAt": "2024-05-01T10:00:00Z",
+                  "replies": { "nodes": [
+                    { "id": "DC_2", "author": null, "body": "Agreed", "createdAt": "2024-05-02T10:00:00Z" }
+                  ] } },
+                { "id": "DC_3", "author": { "login": "spammer" }, "body": "spam", "createdAt": "2024-05-03T10:00:00Z", "isMinimized": true }
+            ] } },
+            { "title": "guestbook", "comments": [
+                { "id": "DC_4", "author": { "login": "visitor" }, "body": "Hi!", "createdAt": "2024-06-01T10:00:00Z" }
+            ] },
+            { "title": "Removed post", "comments": [] }
+        ] }
+    }"#;
+
+    let summary = service
+        .import_giscus(serde_json::from_str::<GiscusExport>(export).expect("parse export"))
+        .await
+        .expect("import");
+    assert_eq!(summary.imported, 3);
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(summary.unmatched_threads, ["Removed post"]);
+
+    let thread = service.list_thread(Some(post_id)).await.expect("thread");
+    assert_eq!(thread.len(), 1);
+    assert_eq!(thread[0].author_name, "octocat");
+    assert_eq!(
+        thread[0].avatar_url,
+        "https://avatars.githubusercontent.com/u/1"
+    );
+    assert_eq!(thread[0].replies[0].author_name, "ghost");
+    assert_eq!(service.list_thread(None).await.expect("guestbook").len(), 1);
+
+    let again = service
+        .import_giscus(serde_json::from_str::<GiscusExport>(export).expect("parse export"))
+        .await
+        .expect("re-import");
+    assert_eq!(again.imported, 0);
+    assert_eq!(again.skipped, 4);
+}
@@ -0,0 +1,251 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreateCategoryRequest, CreatePostRequest, CreateSeriesRequest, EpubExportRequest, PostStatus,
+    SetSeriesPostsRequest,
+};
+use chuyi_uk_back::services::{CategoryService, ExportService, PostService, SeriesService};
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+/// 临时上传目录，里面放一张封面和一张正文插图。
+fn upload_dir() -> String {
+    let dir = std::env::temp_dir().join(format!("chuyi-export-test-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("images")).expect("create upload dir");
+    std::fs::write(dir.join("images/cover.png"), b"\x89PNG\r\n\x1a\ncover").expect("write cover");
+    std::fs::write(dir.join("images/figure.jpg"), b"\xff\xd8\xff figure").expect("write figure");
+    dir.to_string_lossy().into_owned()
+}
+
+async fn create_post(
+    posts: &PostService,
+    title: &str,
+    content: &str,
+    category_id: Option<i64>,
+    status: PostStatus,
+) -> i64 {
+    posts
+        .create_post(CreatePostRequest {
+            title: title.to_string(),
+            cover_url: Some("/uploads/images/cover.png".to_string()),
+            content: content.to_string(),
+            category_id,
+            status: Some(status),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post")
+        .id
+}
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle.as_bytes())
+}
+
+#[tokio::test]
+async fn category_export_is_an_epub_with_embedded_images() {
+    let database = setup_test_db().await;
+    let file_handler = Arc::new(FileHandler::new(upload_dir(), 1_000_000, None));
+    let posts = PostService::new(database.clone(), file_handler.clone());
+    let categories = CategoryService::new(database.clone());
+    let export = ExportService::new(database, file_handler);
+    let category = categories
+        .create_category(CreateCategoryRequest {
+            name: "随笔".to_string(),
+        })
+        .await
+        .expect("create category");
+    create_post(
+        &posts,
+        "第一篇",
+        "## 小节\n\n![图](/uploads/images/figure.jpg)\n\n![外链](https://example.com/a.png)",
+        Some(category.id),
+        PostStatus::Published,
+    )
+    .await;
+    create_post(
+        &posts,
+        "第二篇",
+        "正文",
+        Some(category.id),
+        PostStatus::Published,
+    )
+    .await;
+    create_post(
+        &posts,
+        "草稿",
+        "还没写完",
+        Some(category.id),
+        PostStatus::Draft,
+    )
+    .await;
+
+    let request = EpubExportRequest {
+        category_id: Some(category.id),
+        ..Default::default()
+    };
+    let file = export
+        .export_epub(request, true)
+        .await
+        .expect("export category");
+    assert_eq!(file.title, "随笔");
+    assert_eq!(file.file_name, format!("category-{}.epub", category.id));
+    // OCF 要求 mimetype 是第一个、未压缩的条目
+    assert!(file.data.starts_with(b"PK\x03\x04"));
+    assert_eq!(&file.data[30..38], b"mimetype");
+    assert_eq!(&file.data[38..58], b"application/epub+zip");
+    for entry in [
+        "META-INF/container.xml",
+        "OEBPS/content.opf",
+        "OEBPS/nav.xhtml",
+        "OEBPS/cover.xhtml",
+        "OEBPS/chapter-001.xhtml",
+        "OEBPS/chapter-002.xhtml",
+        "OEBPS/images/cover.png",
+        "OEBPS/images/0001.jpg",
+    ] {
+        assert!(contains(&file.data, entry), "missing {entry}");
+    }
+    // 草稿不导出
+    assert!(!contains(&file.data, "OEBPS/chapter-003.xhtml"));
+
+    // 管理端可以带上草稿
+    let request = EpubExportRequest {
+        category_id: Some(category.id),
+        include_unpublished: true,
+        ..Default::default()
+    };
+    let file = export
+        .export_epub(request, false)
+        .await
+        .expect("export all");
+    assert!(contains(&file.data, "OEBPS/chapter-003.xhtml"));
+}
+
+#[tokio::test]
+async fn series_and_single_post_exports_respect_visibility() {
+    let database = setup_test_db().await;
+    let file_handler = Arc::new(FileHandler::new(upload_dir(), 1_000_000, None));
+    let posts = PostService::new(database.clone(), file_handler.clone());
+    let series = SeriesService::new(database.clone());
+    let export = ExportService::new(database, file_handler);
+    let first = create_post(&posts, "上篇", "上", None, PostStatus::Published).await;
+    let second = create_post(&posts, "下篇", "下", None, PostStatus::Published).await;
+    let draft = create_post(&posts, "草稿", "草", None, PostStatus::Draft).await;
+
+    let created = series
+        .create(CreateSeriesRequest {
+            title: "两篇连载".to_string(),
+            slug: None,
+            description: String::new(),
+        })
+        .await
+        .expect("create series");
+    series
+        .set_posts(
+            created.series.id,
+            SetSeriesPostsRequest {
+                post_ids: vec![second, first],
+            },
+        )
+        .await
+        .expect("set posts");
+    let file = export
+        .export_epub(
+            EpubExportRequest {
+                series_id: Some(created.series.id),
+                ..Default::default()
+            },
+            true,
+        )
+        .await
+        .expect("export series");
+    assert_eq!(file.title, "两篇连载");
+    assert!(contains(&file.data, "OEBPS/chapter-002.xhtml"));
+
+    let single = |id: i64| EpubExportRequest {
+        post_ids: vec![id],
+        ..Default::default()
+    };
+    let file = export
+        .export_epub(single(first), true)
+        .await
+        .expect("export post");
+    assert_eq!(file.title, "上篇");
+    assert!(matches!(
+        export.export_epub(single(draft), true).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(export.export_epub(single(draft), false).await.is_ok());
+
+    // 必须且只能指定一种选择方式
+    assert!(matches!(
+        export
+            .export_epub(EpubExportRequest::default(), false)
+            .await,
+        Err(AppError::Validation(_))
+    ));
+    let both = EpubExportRequest {
+        post_ids: vec![first],
+        series_id: Some(created.series.id),
+        ..Default::default()
+    };
+    assert!(matches!(
+        export.export_epub(both, false).await,
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn public_exports_are_rebuilt_only_after_posts_change() {
+    let database = setup_test_db().await;
+    let file_handler = Arc::new(FileHandler::new(upload_dir(), 1_000_000, None));
+    let posts = PostService::new(database.clone(), file_handler.clone());
+    let export = ExportService::new(database.clone(), file_handler);
+    let id = create_post(&posts, "缓存", "正文", None, PostStatus::Published).await;
+    let request = || EpubExportRequest {
+        post_ids: vec![id],
+        ..Default::default()
+    };
+    let first = export.export_epub(request(), true).await.expect("export");
+    assert!(!contains(&first.data, "OEBPS/images/0001.jpg"));
+
+    // 不改 updated_at 的写入看不到，说明用的是缓存
+    sqlx::query("UPDATE posts SET content = '![图](/uploads/images/figure.jpg)' WHERE id = ?")
+        .bind(id)
+        .execute(database.pool())
+        .await
+        .expect("edit content");
+    let cached = export.export_epub(request(), true).await.expect("export");
+    assert_eq!(cached.data, first.data);
+    // 管理端不走缓存
+    let fresh = export.export_epub(request(), false).await.expect("export");
+    assert!(contains(&fresh.data, "OEBPS/images/0001.jpg"));
+
+    sqlx::query("UPDATE posts SET updated_at = datetime(updated_at, '+1 second') WHERE id = ?")
+        .bind(id)
+        .execute(database.pool())
+        .await
+        .expect("touch post");
+    let rebuilt = export.export_epub(request(), true).await.expect("export");
+    assert!(contains(&rebuilt.data, "OEBPS/images/0001.jpg"));
+}
@@ -0,0 +1,126 @@
+use chuyi_uk_back::database::repositories::{CategoryRepository, TagRepository};
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreateCategoryRequest, CreatePostRequest, CreateTagRequest, PostStatus,
+};
+use chuyi_uk_back::services::PostService;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn create_request(
+    title: &str,
+    content: String,
+    status: PostStatus,
+    category_id: Option<i64>,
+    tag_ids: Option<Vec<i64>>,
+) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        cover_url: None,
+        content,
+        category_id,
+        status: Some(status),
+        post_images: None,
+        pdf_url: None,
+        publish_at: None,
+        tag_ids,
+    }
+}
+
+#[tokio::test]
+async fn feed_posts_have_full_content_and_respect_scope() {
+    let database = setup_test_db().await;
+    let service = PostService::new(
+        database.clone(),
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    );
+    let category = CategoryRepository::create(
+        database.pool(),
+        CreateCategoryRequest {
+            name: "notes".to_string(),
+        },
+    )
+    .await
+    .expect("create category");
+    let tag = TagRepository::create(
+        database.pool(),
+        CreateTagRequest {
+            name: "rust".to_string(),
+        },
+    )
+    .await
+    .expect("create tag");
+
+    let long_content = "长".repeat(1_000);
+    let in_category = service
+        .create_post(create_request(
+            "in category",
+            long_content.clone(),
+            PostStatus::Published,
+            Some(category.id),
+            Some(vec![tag.id]),
+        ))
+        .await
+        .expect("create categorized post");
+    service
+        .create_post(create_request(
+            "elsewhere",
+            "other".to_string(),
+            PostStatus::Published,
+            None,
+            None,
+        ))
+        .await
+        .expect("create uncategorized post");
+    service
+        .create_post(create_request(
+            "draft",
+            "draft".to_string(),
+            PostStatus::Draft,
+            Some(category.id),
+            Some(vec![tag.id]),
+        ))
+        .await
+        .expect("create draft");
+
+    let site = service
+        .list_feed_posts(None, None, 50)
+        .await
+        .expect("site feed");
+    assert_eq!(site.len(), 2);
+
+    let by_category = service
+        .list_feed_posts(Some(category.id), None, 50)
+        .await
+        .expect("category feed");
+    assert_eq!(by_category.len(), 1);
+    assert_eq!(by_category[0].id, in_category.id);
+    assert_eq!(by_category[0].content, long_content);
+    assert_eq!(by_category[0].category_name.as_deref(), Some("notes"));
+    assert_eq!(by_category[0].tags.len(), 1);
+
+    let by_tag = service
+        .list_feed_posts(None, Some(tag.id), 50)
+        .await
+        .expect("tag feed");
+    assert_eq!(by_tag.len(), 1);
+    assert_eq!(by_tag[0].id, in_category.id);
+}
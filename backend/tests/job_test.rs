@@ -0,0 +1,202 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    Gitbook2EpubRequest, JobKind, JOB_CANCELLED, JOB_FAILED, JOB_QUEUED, JOB_RUNNING, JOB_SUCCEEDED,
+};
+use chuyi_uk_back::services::{Gitbook2EpubService, JobService};
+use chuyi_uk_back::utils::error::AppError;
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+#[tokio::test]
+async fn claimed_job_completes_and_exposes_result() {
+    let jobs = JobService::new(setup_test_db().await);
+    let job = jobs
+        .enqueue(JobKind::OptimizeImages, serde_json::Value::Null)
+        .await
+        .unwrap();
+    assert_eq!(job.status, JOB_QUEUED);
+    assert_eq!(job.payload, json!({}));
+    assert_eq!(job.result, serde_json::Value::Null);
+
+    let claimed = jobs.claim().await.unwrap().expect("job is due");
+    assert_eq!(claimed.id, job.id);
+    assert_eq!(claimed.status, JOB_RUNNING);
+    assert_eq!(claimed.attempts, 1);
+    assert!(jobs.claim().await.unwrap().is_none(), "claimed only once");
+
+    jobs.report_progress(job.id, 0.5, "halfway").await.unwrap();
+    let polled = jobs.get_by_token(&job.token).await.unwrap();
+    assert_eq!(polled.progress, 0.5);
+    assert_eq!(polled.progress_message.as_deref(), Some("halfway"));
+
+    jobs.complete(job.id, &json!({ "converted": 3 }))
+        .await
+        .unwrap();
+    let done = jobs.get(job.id).await.unwrap();
+    assert_eq!(done.status, JOB_SUCCEEDED);
+    assert_eq!(done.progress, 1.0);
+    assert_eq!(done.result, json!({ "converted": 3 }));
+    assert!(done.finished_at.is_some());
+    assert!(matches!(
+        jobs.cancel(job.id).await,
+        Err(AppError::BadRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn failed_job_backs_off_then_fails_permanently() {
+    let database = setup_test_db().await;
+    let jobs = JobService::new(database.clone());
+    let job = jobs
+        .enqueue(JobKind::OptimizeImages, json!({}))
+        .await
+        .unwrap();
+    assert_eq!(job.max_attempts, 2);
+
+    jobs.claim().await.unwrap().unwrap();
+    assert!(jobs.fail(job.id, "disk full").await.unwrap());
+    let retrying = jobs.get(job.id).await.unwrap();
+    assert_eq!(retrying.status, JOB_QUEUED);
+    assert_eq!(retrying.error.as_deref(), Some("disk full"));
+    assert!(retrying.run_at > job.run_at);
+    assert!(
+        jobs.claim().await.unwrap().is_none(),
+        "not due before backoff"
+    );
+
+    // 跳过退避等待
+    sqlx::query("UPDATE jobs SET run_at = datetime('now', '-1 second') WHERE id = ?")
+        .bind(job.id)
+        .execute(database.pool())
+        .await
+        .unwrap();
+    let second = jobs.claim().await.unwrap().unwrap();
+    assert_eq!(second.attempts, 2);
+    assert!(!jobs.fail(job.id, "disk still full").await.unwrap());
+    let failed = jobs.get(job.id).await.unwrap();
+    assert_eq!(failed.status, JOB_FAILED);
+    assert!(failed.finished_at.is_some());
+}
+
+#[tokio::test]
+async fn queued_jobs_cancel_and_interrupted_jobs_recover() {
+    let jobs = JobService::new(setup_test_db().await);
+    let first = jobs
+        .enqueue(JobKind::OptimizeImages, json!({}))
+        .await
+        .unwrap();
+    let cancelled = jobs.cancel(first.id).await.unwrap();
+    assert_eq!(cancelled.status, JOB_CANCELLED);
+    assert!(jobs.claim().await.unwrap().is_none());
+
+    let second = jobs
+        .enqueue(JobKind::OptimizeImages, json!({}))
+        .await
+        .unwrap();
+    jobs.claim().await.unwrap().unwrap();
+    // 模拟进程在任务执行中退出
+    assert_eq!(jobs.recover().await.unwrap(), 1);
+    let requeued = jobs.claim().await.unwrap().expect("requeued after restart");
+    assert_eq!(requeued.id, second.id);
+    assert_eq!(requeued.attempts, 2);
+
+    let running = jobs.cancel(second.id).await.unwrap();
+    assert_eq!(running.status, JOB_RUNNING);
+    assert!(running.cancel_requested);
+    assert_eq!(jobs.recover().await.unwrap(), 1);
+    assert_eq!(jobs.get(second.id).await.unwrap().status, JOB_CANCELLED);
+}
+
+#[tokio::test]
+async fn identical_conversions_share_a_job_and_report_queue_position() {
+    let jobs = Arc::new(JobService::new(setup_test_db().await));
+    let gitbook2epub = Gitbook2EpubService::new(jobs.clone());
+    let request = |url: &str, include_images| Gitbook2EpubRequest {
+        url: url.to_string(),
+        include_images,
+    };
+
+    let first = gitbook2epub
+        .submit(request("https://example.com/book/", false))
+        .await
+        .unwrap();
+    let again = gitbook2epub
+        .submit(request("https://example.com/book/", false))
+        .await
+        .unwrap();
+    assert_eq!(again.token, first.token);
+    let with_images = gitbook2epub
+        .submit(request("https://example.com/book/", true))
+        .await
+        .unwrap();
+    assert_ne!(with_images.token, first.token);
+
+    let status = gitbook2epub.describe(with_images.clone()).await.unwrap();
+    assert_eq!(status.queue_position, 1);
+    assert!(status.download_url.is_none());
+    jobs.claim().await.unwrap().unwrap();
+    let first = jobs.get(first.id).await.unwrap();
+    assert_eq!(
+        gitbook2epub.describe(first).await.unwrap().queue_position,
+        0
+    );
+    let with_images = gitbook2epub.get(&with_images.token).await.unwrap();
+    assert_eq!(
+        gitbook2epub
+            .describe(with_images)
+            .await
+            .unwrap()
+            .queue_position,
+        1
+    );
+
+    // 其它种类的任务令牌查不到
+    let other = jobs
+        .enqueue(JobKind::OptimizeImages, json!({}))
+        .await
+        .unwrap();
+    assert!(matches!(
+        gitbook2epub.get(&other.token).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn busy_kinds_are_skipped_so_other_jobs_still_run() {
+    let jobs = JobService::new(setup_test_db().await);
+    let first = jobs
+        .enqueue(JobKind::Gitbook2Epub, json!({ "url": "https://a.example" }))
+        .await
+        .unwrap();
+    let second = jobs
+        .enqueue(JobKind::Gitbook2Epub, json!({ "url": "https://b.example" }))
+        .await
+        .unwrap();
+    let probe = jobs
+        .enqueue(JobKind::ProbeVideo, json!({ "video_id": 1 }))
+        .await
+        .unwrap();
+
+    assert_eq!(jobs.claim().await.unwrap().unwrap().id, first.id);
+    // 已经有一个转换在跑，第二个留在队列里，worker 去领后面的视频探测
+    assert_eq!(jobs.claim().await.unwrap().unwrap().id, probe.id);
+    assert!(jobs.claim().await.unwrap().is_none());
+    assert_eq!(jobs.running_count(JobKind::Gitbook2Epub).await.unwrap(), 1);
+
+    jobs.complete(first.id, &json!({})).await.unwrap();
+    assert_eq!(jobs.claim().await.unwrap().unwrap().id, second.id);
+}
@@ -42,6 +42,7 @@ fn create_request(title: &str, tag_ids: Option<Vec<i64>>) -> CreatePostRequest {
         status: Some(PostStatus::Published),
         post_images: None,
         pdf_url: None,
+        publish_at: None,
         tag_ids,
     }
 }
@@ -109,6 +110,8 @@ async fn update_can_clear_nullable_fields_and_save_tags_atomically() {
                 status: None,
                 post_images: NullablePatch::Null,
                 pdf_url: NullablePatch::Null,
+                publish_at: NullablePatch::Null,
+                slug: None,
                 tag_ids: Some(vec![tag.id]),
             },
         )
@@ -153,7 +156,7 @@ async fn adjacent_posts_use_stable_timestamp_and_id_ordering() {
 }
 
 #[tokio::test]
-async fn updating_content_tracks_images_and_removes_unreferenced_assets() {
+async fn updating_content_tracks_images_and_keeps_assets_held_by_revisions() {
     let database = setup_test_db().await;
     let upload_dir = format!("/tmp/chuyi-blog-tests-{}", uuid::Uuid::new_v4());
     let service = post_service_with_upload_dir(database.clone(), upload_dir.clone());
@@ -186,6 +189,8 @@ async fn updating_content_tracks_images_and_removes_unreferenced_assets() {
                 status: None,
                 post_images: NullablePatch::Missing,
                 pdf_url: NullablePatch::Missing,
+                publish_at: NullablePatch::Missing,
+                slug: None,
                 tag_ids: None,
             },
         )
@@ -193,7 +198,8 @@ async fn updating_content_tracks_images_and_removes_unreferenced_assets() {
         .expect("update post")
         .expect("post exists");
 
-    assert!(!file_path.exists());
+    // 旧图片仍被创建时的修订引用，恢复该修订时需要它。
+    assert!(file_path.exists());
     let updated = PostRepository::get_by_id(database.pool(), post.id)
         .await
         .expect("load post")
@@ -0,0 +1,31 @@
+use axum::{body::Body, http::Request, middleware, routing::get, Router};
+use chuyi_uk_back::middleware::metrics::{prometheus_handle, track_metrics};
+use tower::Service;
+
+#[tokio::test]
+async fn requests_are_counted_by_route_template_and_status_class() {
+    let handle = prometheus_handle();
+    let app = Router::new()
+        .route("/items/:id", get(|| async { "ok" }))
+        .layer(middleware::from_fn(track_metrics));
+
+    for id in ["1", "2"] {
+        // Router 总是 ready，直接 call 即可
+        let response = app
+            .clone()
+            .call(
+                Request::get(format!("/items/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    let rendered = handle.render();
+    assert!(
+        rendered.contains(r#"http_requests_total{route="/items/:id",method="GET",status="2xx"} 2"#)
+    );
+    assert!(rendered.contains(r#"http_request_duration_seconds_bucket{route="/items/:id""#));
+}
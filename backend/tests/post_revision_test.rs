@@ -0,0 +1,190 @@
+use chuyi_uk_back::database::repositories::TagRepository;
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreatePostRequest, CreateTagRequest, NullablePatch, PostStatus, UpdatePostRequest,
+};
+use chuyi_uk_back::services::PostService;
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database, upload_dir: String) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(upload_dir, 1_000_000, None)),
+    )
+}
+
+fn content_update(content: &str) -> UpdatePostRequest {
+    UpdatePostRequest {
+        title: None,
+        cover_url: NullablePatch::Missing,
+        content: Some(content.to_string()),
+        category_id: NullablePatch::Missing,
+        status: None,
+        post_images: NullablePatch::Missing,
+        pdf_url: NullablePatch::Missing,
+        publish_at: NullablePatch::Missing,
+        slug: None,
+        tag_ids: None,
+    }
+}
+
+async fn write_upload(upload_dir: &str, name: &str) -> (String, PathBuf) {
+    let file_path = PathBuf::from(upload_dir).join("images").join(name);
+    tokio::fs::create_dir_all(file_path.parent().expect("image parent"))
+        .await
+        .expect("create image directory");
+    tokio::fs::write(&file_path, b"image")
+        .await
+        .expect("write image");
+    (format!("/uploads/images/{name}"), file_path)
+}
+
+#[tokio::test]
+async fn restoring_a_revision_brings_back_content_tags_and_images() {
+    let database = setup_test_db().await;
+    let upload_dir = format!("/tmp/chuyi-blog-tests-{}", uuid::Uuid::new_v4());
+    let service = post_service(database.clone(), upload_dir.clone());
+    let (image_url, image_path) = write_upload(&upload_dir, "kept.webp").await;
+    let tag = TagRepository::create(
+        database.pool(),
+        CreateTagRequest {
+            name: "rust".to_string(),
+        },
+    )
+    .await
+    .expect("create tag");
+
+    let post = service
+        .create_post(CreatePostRequest {
+            title: "Draft one".to_string(),
+            cover_url: None,
+            content: format!("line one\nline two\n\n![img]({image_url})\n"),
+            category_id: None,
+            status: Some(PostStatus::Published),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: Some(vec![tag.id]),
+        })
+        .await
+        .expect("create post");
+    let mut bad_save = content_update("line one\nline 2\n");
+    bad_save.title = Some("Oops".to_string());
+    bad_save.tag_ids = Some(Vec::new());
+    service
+        .update_post(post.id, bad_save)
+        .await
+        .expect("update post")
+        .expect("post exists");
+    assert!(image_path.exists());
+
+    let revisions = service.list_revisions(post.id).await.expect("list");
+    assert_eq!(revisions.len(), 2);
+    let (latest, original) = (revisions[0].id, revisions[1].id);
+    assert_eq!(revisions[1].title, "Draft one");
+
+    let diff = service
+        .diff_revisions(post.id, original, latest)
+        .await
+        .expect("diff revisions");
+    assert_eq!(diff.changed_fields, vec!["title", "tag_ids"]);
+    let changes: Vec<(&str, &str)> = diff
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter(|line| line.kind != "context")
+        .map(|line| (line.kind, line.text.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("delete", "line two"),
+            ("delete", ""),
+            ("delete", &format!("![img]({image_url})")),
+            ("insert", "line 2"),
+        ]
+    );
+
+    let restored = service
+        .restore_revision(post.id, original)
+        .await
+        .expect("restore revision");
+    assert_eq!(restored.title, "Draft one");
+    assert_eq!(
+        serde_json::from_str::<Vec<String>>(restored.post_images.as_deref().expect("images"))
+            .expect("valid image list"),
+        vec![image_url]
+    );
+    let tags = service.get_post_tags(post.id).await.expect("post tags");
+    assert_eq!(tags.iter().map(|tag| tag.id).collect::<Vec<_>>(), [tag.id]);
+    assert_eq!(
+        service.list_revisions(post.id).await.expect("list").len(),
+        3
+    );
+    assert!(image_path.exists());
+}
+
+#[tokio::test]
+async fn pruning_old_revisions_deletes_assets_nothing_else_references() {
+    let database = setup_test_db().await;
+    let upload_dir = format!("/tmp/chuyi-blog-tests-{}", uuid::Uuid::new_v4());
+    let service = post_service(database.clone(), upload_dir.clone());
+    let (image_url, image_path) = write_upload(&upload_dir, "old.webp").await;
+
+    let post = service
+        .create_post(CreatePostRequest {
+            title: "Long lived".to_string(),
+            cover_url: None,
+            content: format!("![img]({image_url})"),
+            category_id: None,
+            status: Some(PostStatus::Draft),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post");
+
+    for edit in 1..50 {
+        service
+            .update_post(post.id, content_update(&format!("edit {edit}")))
+            .await
+            .expect("update post")
+            .expect("post exists");
+    }
+    assert!(image_path.exists());
+    assert_eq!(
+        service.list_revisions(post.id).await.expect("list").len(),
+        50
+    );
+
+    service
+        .update_post(post.id, content_update("edit 50"))
+        .await
+        .expect("update post")
+        .expect("post exists");
+    let revisions = service.list_revisions(post.id).await.expect("list");
+    assert_eq!(revisions.len(), 50);
+    assert!(!image_path.exists());
+
+    let missing = service.get_revision(post.id, i64::MAX).await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+}
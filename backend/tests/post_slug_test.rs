@@ -0,0 +1,132 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreatePostRequest, NullablePatch, PostStatus, UpdatePostRequest};
+use chuyi_uk_back::services::PostService;
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+fn create_request(title: &str) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        cover_url: None,
+        content: "content".to_string(),
+        category_id: None,
+        status: Some(PostStatus::Published),
+        post_images: None,
+        pdf_url: None,
+        publish_at: None,
+        tag_ids: None,
+    }
+}
+
+fn slug_update(slug: &str) -> UpdatePostRequest {
+    UpdatePostRequest {
+        title: None,
+        cover_url: NullablePatch::Missing,
+        content: None,
+        category_id: NullablePatch::Missing,
+        status: None,
+        post_images: NullablePatch::Missing,
+        pdf_url: NullablePatch::Missing,
+        publish_at: NullablePatch::Missing,
+        slug: Some(slug.to_string()),
+        tag_ids: None,
+    }
+}
+
+#[tokio::test]
+async fn slugs_are_generated_from_titles_and_kept_unique() {
+    let database = setup_test_db().await;
+    let service = post_service(database);
+
+    let first = service
+        .create_post(create_request("周记：博客重构"))
+        .await
+        .expect("create post");
+    let second = service
+        .create_post(create_request("周记 博客重构"))
+        .await
+        .expect("create post");
+    let symbols = service
+        .create_post(create_request("？！"))
+        .await
+        .expect("create post");
+
+    assert_eq!(first.slug.as_deref(), Some("zhou-ji-bo-ke-zhong-gou"));
+    assert_eq!(second.slug.as_deref(), Some("zhou-ji-bo-ke-zhong-gou-2"));
+    assert_eq!(symbols.slug, Some(format!("post-{}", symbols.id)));
+}
+
+#[tokio::test]
+async fn renamed_slugs_still_resolve_to_the_post() {
+    let database = setup_test_db().await;
+    let service = post_service(database);
+    let post = service
+        .create_post(create_request("Hello World"))
+        .await
+        .expect("create post");
+    let other = service
+        .create_post(create_request("Other"))
+        .await
+        .expect("create post");
+
+    let renamed = service
+        .update_post(post.id, slug_update("Hello Rust"))
+        .await
+        .expect("rename slug")
+        .expect("post exists");
+    assert_eq!(renamed.slug.as_deref(), Some("hello-rust"));
+
+    for slug in ["hello-world", "hello-rust"] {
+        let found = service
+            .get_post_by_slug(slug)
+            .await
+            .expect("lookup")
+            .expect("post found");
+        assert_eq!(found.id, post.id);
+        assert_eq!(found.slug.as_deref(), Some("hello-rust"));
+    }
+
+    let taken = service
+        .update_post(other.id, slug_update("hello-rust"))
+        .await;
+    assert!(matches!(taken, Err(AppError::BadRequest(_))));
+
+    // 旧 slug 可以被别的文章重新使用，此后它指向新主人。
+    service
+        .update_post(other.id, slug_update("hello-world"))
+        .await
+        .expect("reuse old slug")
+        .expect("post exists");
+    let found = service
+        .get_post_by_slug("hello-world")
+        .await
+        .expect("lookup")
+        .expect("post found");
+    assert_eq!(found.id, other.id);
+}
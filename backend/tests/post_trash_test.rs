@@ -0,0 +1,151 @@
+use chrono::{Duration, Utc};
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreatePostRequest, PostStatus};
+use chuyi_uk_back::services::post_service::TRASH_RETENTION_DAYS;
+use chuyi_uk_back::services::PostService;
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+async fn post_with_image(service: &PostService, upload_dir: &str, title: &str) -> (i64, PathBuf) {
+    let name = format!("{}.webp", uuid::Uuid::new_v4());
+    let file_path = PathBuf::from(upload_dir).join("images").join(&name);
+    tokio::fs::create_dir_all(file_path.parent().expect("image parent"))
+        .await
+        .expect("create image directory");
+    tokio::fs::write(&file_path, b"image")
+        .await
+        .expect("write image");
+
+    let post = service
+        .create_post(CreatePostRequest {
+            title: title.to_string(),
+            cover_url: None,
+            content: format!("![img](/uploads/images/{name})"),
+            category_id: None,
+            status: Some(PostStatus::Private),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post");
+    (post.id, file_path)
+}
+
+#[tokio::test]
+async fn trashed_posts_keep_assets_and_restore_their_previous_status() {
+    let database = setup_test_db().await;
+    let upload_dir = format!("/tmp/chuyi-blog-tests-{}", uuid::Uuid::new_v4());
+    let service = PostService::new(
+        database,
+        Arc::new(FileHandler::new(upload_dir.clone(), 1_000_000, None)),
+    );
+    let (post_id, image_path) = post_with_image(&service, &upload_dir, "Trash me").await;
+
+    assert!(service.delete_post(post_id).await.expect("delete post"));
+    assert!(!service.delete_post(post_id).await.expect("delete again"));
+    assert!(image_path.exists());
+    assert!(service
+        .get_post_detail(post_id)
+        .await
+        .expect("load")
+        .is_none());
+
+    let (trash, total) = service.list_trash(1, 20).await.expect("list trash");
+    assert_eq!(total, 1);
+    assert_eq!(trash[0].id, post_id);
+    assert_eq!(trash[0].previous_status, Some(PostStatus::Private as i32));
+    assert_eq!(
+        trash[0].purge_at - trash[0].deleted_at,
+        Duration::days(TRASH_RETENTION_DAYS)
+    );
+
+    let restored = service
+        .restore_from_trash(post_id)
+        .await
+        .expect("restore post");
+    assert_eq!(restored.status, PostStatus::Private as i32);
+    assert_eq!(service.list_trash(1, 20).await.expect("list").1, 0);
+    assert!(matches!(
+        service.restore_from_trash(post_id).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn purging_removes_the_post_its_assets_and_frees_its_slug() {
+    let database = setup_test_db().await;
+    let upload_dir = format!("/tmp/chuyi-blog-tests-{}", uuid::Uuid::new_v4());
+    let service = PostService::new(
+        database,
+        Arc::new(FileHandler::new(upload_dir.clone(), 1_000_000, None)),
+    );
+    let (manual, manual_image) = post_with_image(&service, &upload_dir, "Gone").await;
+    let (expired, expired_image) = post_with_image(&service, &upload_dir, "Old").await;
+
+    assert!(matches!(
+        service.purge_post(manual).await,
+        Err(AppError::NotFound(_))
+    ));
+    for id in [manual, expired] {
+        service.delete_post(id).await.expect("delete post");
+    }
+
+    service.purge_post(manual).await.expect("purge post");
+    assert!(!manual_image.exists());
+    assert!(service
+        .get_post_by_slug("gone")
+        .await
+        .expect("lookup")
+        .is_none());
+
+    assert_eq!(
+        service
+            .purge_expired_trash(Utc::now())
+            .await
+            .expect("sweep trash"),
+        0
+    );
+    assert!(expired_image.exists());
+    // 模拟保留期已过：以保留期之后的时间运行清理。
+    let purged = service
+        .purge_expired_trash(Utc::now() + Duration::days(TRASH_RETENTION_DAYS) + Duration::hours(1))
+        .await
+        .expect("sweep trash");
+    assert_eq!(purged, 1);
+    assert!(!expired_image.exists());
+    assert_eq!(service.list_trash(1, 20).await.expect("list").1, 0);
+
+    let reused = service
+        .create_post(CreatePostRequest {
+            title: "Gone".to_string(),
+            cover_url: None,
+            content: String::new(),
+            category_id: None,
+            status: None,
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post");
+    assert_eq!(reused.slug.as_deref(), Some("gone"));
+}
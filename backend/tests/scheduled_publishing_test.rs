@@ -0,0 +1,168 @@
+use chrono::{Duration, Utc};
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreateChangelogRequest, CreatePostRequest, NullablePatch, PostStatus, UpdatePostRequest,
+};
+use chuyi_uk_back::services::{ChangelogService, PostService};
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+fn scheduled_request(title: &str, publish_at: Option<chrono::DateTime<Utc>>) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        cover_url: None,
+        content: "content".to_string(),
+        category_id: None,
+        status: Some(PostStatus::Scheduled),
+        post_images: None,
+        pdf_url: None,
+        publish_at,
+        tag_ids: None,
+    }
+}
+
+#[tokio::test]
+async fn scheduled_posts_go_live_once_due() {
+    let database = setup_test_db().await;
+    let service = post_service(database);
+    let publish_at = Utc::now() + Duration::hours(2);
+
+    let post = service
+        .create_post(scheduled_request("Tomorrow's post", Some(publish_at)))
+        .await
+        .expect("create scheduled post");
+    assert_eq!(post.status, PostStatus::Scheduled as i32);
+    assert_eq!(post.publish_at, Some(publish_at));
+
+    let published = service
+        .publish_due_posts(Utc::now())
+        .await
+        .expect("publish due posts");
+    assert!(published.is_empty());
+
+    let published = service
+        .publish_due_posts(publish_at + Duration::seconds(1))
+        .await
+        .expect("publish due posts");
+    assert_eq!(published, vec![post.id]);
+
+    let live = service
+        .get_post_detail(post.id)
+        .await
+        .expect("load post")
+        .expect("post exists");
+    assert_eq!(live.status, PostStatus::Published as i32);
+    assert_eq!(live.publish_at, None);
+    assert_eq!(live.created_at.timestamp(), publish_at.timestamp());
+    assert_eq!(
+        service.list_revisions(post.id).await.expect("revisions")[0].status,
+        PostStatus::Published as i32
+    );
+}
+
+#[tokio::test]
+async fn scheduling_requires_a_publish_time_and_past_times_publish_immediately() {
+    let database = setup_test_db().await;
+    let service = post_service(database);
+
+    let missing = service
+        .create_post(scheduled_request("No time", None))
+        .await;
+    assert!(matches!(missing, Err(AppError::BadRequest(_))));
+
+    let past = service
+        .create_post(scheduled_request(
+            "Already due",
+            Some(Utc::now() - Duration::minutes(5)),
+        ))
+        .await
+        .expect("create post");
+    assert_eq!(past.status, PostStatus::Published as i32);
+    assert_eq!(past.publish_at, None);
+
+    let scheduled = service
+        .create_post(scheduled_request(
+            "Back to draft",
+            Some(Utc::now() + Duration::days(1)),
+        ))
+        .await
+        .expect("create post");
+    let draft = service
+        .update_post(
+            scheduled.id,
+            UpdatePostRequest {
+                title: None,
+                cover_url: NullablePatch::Missing,
+                content: None,
+                category_id: NullablePatch::Missing,
+                status: Some(PostStatus::Draft),
+                post_images: NullablePatch::Missing,
+                pdf_url: NullablePatch::Missing,
+                publish_at: NullablePatch::Missing,
+                slug: None,
+                tag_ids: None,
+            },
+        )
+        .await
+        .expect("update post")
+        .expect("post exists");
+    assert_eq!(draft.status, PostStatus::Draft as i32);
+    assert_eq!(draft.publish_at, None);
+}
+
+#[tokio::test]
+async fn public_changelog_hides_entries_published_in_the_future() {
+    let database = setup_test_db().await;
+    let changelog = ChangelogService::new(database);
+    for (title, published_at) in [
+        ("Shipped", Utc::now() - Duration::days(1)),
+        ("Upcoming", Utc::now() + Duration::days(1)),
+    ] {
+        changelog
+            .create(CreateChangelogRequest {
+                version: String::new(),
+                title: title.to_string(),
+                content: "notes".to_string(),
+                published_at: Some(published_at),
+                status: 1,
+            })
+            .await
+            .expect("create changelog entry");
+    }
+
+    let public: Vec<String> = changelog
+        .list(true)
+        .await
+        .expect("public list")
+        .into_iter()
+        .map(|entry| entry.title)
+        .collect();
+    assert_eq!(public, vec!["Shipped"]);
+    assert_eq!(changelog.list(false).await.expect("admin list").len(), 2);
+}
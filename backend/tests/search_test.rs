@@ -0,0 +1,152 @@
+use chuyi_uk_back::database::repositories::TagRepository;
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreatePostRequest, CreateTagRequest, PostStatus, UpdateTagRequest};
+use chuyi_uk_back::services::{PostService, SearchService};
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+fn create_request(
+    title: &str,
+    content: &str,
+    status: PostStatus,
+    tag_ids: Option<Vec<i64>>,
+) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        cover_url: None,
+        content: content.to_string(),
+        category_id: None,
+        status: Some(status),
+        post_images: None,
+        pdf_url: None,
+        publish_at: None,
+        tag_ids,
+    }
+}
+
+#[tokio::test]
+async fn search_ranks_title_hits_first_and_hides_unpublished_posts() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let search = SearchService::new(database);
+
+    let body_hit = posts
+        .create_post(create_request(
+            "Weekly notes",
+            "Some thoughts about tokio runtimes.",
+            PostStatus::Published,
+            None,
+        ))
+        .await
+        .expect("body hit");
+    let title_hit = posts
+        .create_post(create_request(
+            "Understanding tokio",
+            "A deep dive.",
+            PostStatus::Published,
+            None,
+        ))
+        .await
+        .expect("title hit");
+    for status in [PostStatus::Draft, PostStatus::Private, PostStatus::Deleted] {
+        posts
+            .create_post(create_request("tokio draft", "tokio", status, None))
+            .await
+            .expect("hidden post");
+    }
+
+    let (hits, total) = search
+        .search_published("tokio", 1, 10)
+        .await
+        .expect("search");
+
+    assert_eq!(total, 2);
+    assert_eq!(
+        hits.iter().map(|hit| hit.id).collect::<Vec<_>>(),
+        vec![title_hit.id, body_hit.id]
+    );
+    assert_eq!(hits[0].title_highlight, "Understanding <mark>tokio</mark>");
+    assert!(hits[1].snippet.contains("<mark>tokio</mark>"));
+}
+
+#[tokio::test]
+async fn search_matches_cjk_text_and_follows_tag_renames() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let search = SearchService::new(database.clone());
+    let tag = TagRepository::create(
+        database.pool(),
+        CreateTagRequest {
+            name: "后端".to_string(),
+        },
+    )
+    .await
+    .expect("create tag");
+
+    let post = posts
+        .create_post(create_request(
+            "周记",
+            "这周把博客的全文检索迁移到了数据库。",
+            PostStatus::Published,
+            Some(vec![tag.id]),
+        ))
+        .await
+        .expect("create post");
+
+    let (hits, _) = search
+        .search_published("全文检索", 1, 10)
+        .await
+        .expect("search long CJK term");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, post.id);
+    assert_eq!(hits[0].tags, vec!["后端"]);
+
+    let (hits, _) = search
+        .search_published("周记", 1, 10)
+        .await
+        .expect("search short CJK term");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].title_highlight, "<mark>周记</mark>");
+
+    TagRepository::update(
+        database.pool(),
+        tag.id,
+        UpdateTagRequest {
+            name: "服务端开发".to_string(),
+        },
+    )
+    .await
+    .expect("rename tag")
+    .expect("tag exists");
+    let (hits, _) = search
+        .search_published("服务端开发", 1, 10)
+        .await
+        .expect("search renamed tag");
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].snippet.contains("<mark>服务端开发</mark>"));
+}
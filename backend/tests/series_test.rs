@@ -0,0 +1,214 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreatePostRequest, CreateSeriesRequest, PostStatus, SetSeriesPostsRequest, UpdateSeriesRequest,
+};
+use chuyi_uk_back::services::{PostService, SeriesService};
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+async fn create_post(posts: &PostService, title: &str, status: PostStatus) -> i64 {
+    posts
+        .create_post(CreatePostRequest {
+            title: title.to_string(),
+            cover_url: None,
+            content: "content".to_string(),
+            category_id: None,
+            status: Some(status),
+            post_images: None,
+            pdf_url: None,
+            publish_at: None,
+            tag_ids: None,
+        })
+        .await
+        .expect("create post")
+        .id
+}
+
+fn series_request(title: &str, slug: Option<&str>) -> CreateSeriesRequest {
+    CreateSeriesRequest {
+        title: title.to_string(),
+        slug: slug.map(str::to_string),
+        description: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn series_parts_are_ordered_and_drafts_are_skipped_publicly() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let series = SeriesService::new(database);
+    let first = create_post(&posts, "第一篇", PostStatus::Published).await;
+    let draft = create_post(&posts, "还没写完", PostStatus::Draft).await;
+    let third = create_post(&posts, "第三篇", PostStatus::Published).await;
+    let outside = create_post(&posts, "不在系列里", PostStatus::Published).await;
+
+    let created = series
+        .create(series_request("Rust 入门", None))
+        .await
+        .expect("create series");
+    assert_eq!(created.series.slug, "rust-ru-men");
+    // 空系列不公开
+    assert!(series.list(true).await.expect("list").is_empty());
+    assert!(matches!(
+        series.get("rust-ru-men", true).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    let admin = series
+        .set_posts(
+            created.series.id,
+            SetSeriesPostsRequest {
+                post_ids: vec![third, draft, first],
+            },
+        )
+        .await
+        .expect("set posts");
+    let order: Vec<i64> = admin.parts.iter().map(|part| part.id).collect();
+    assert_eq!(order, [third, draft, first]);
+    assert_eq!(admin.series.part_count, 3);
+
+    let public = series
+        .get("rust-ru-men", true)
+        .await
+        .expect("public series");
+    let parts: Vec<(i64, i64)> = public
+        .parts
+        .iter()
+        .map(|part| (part.part, part.id))
+        .collect();
+    assert_eq!(parts, [(1, third), (2, first)]);
+    assert_eq!(public.series.part_count, 2);
+    assert_eq!(series.list(true).await.expect("list")[0].part_count, 2);
+
+    let navigation = series
+        .navigation(first)
+        .await
+        .expect("navigation")
+        .expect("first is in a series");
+    assert_eq!((navigation.series.part, navigation.series.total), (2, 2));
+    assert_eq!(navigation.previous.map(|post| post.id), Some(third));
+    assert!(navigation.next.is_none());
+    assert!(series.navigation(draft).await.expect("draft").is_none());
+    assert!(series.navigation(outside).await.expect("outside").is_none());
+
+    assert!(matches!(
+        series
+            .set_posts(
+                created.series.id,
+                SetSeriesPostsRequest {
+                    post_ids: vec![first, first],
+                },
+            )
+            .await,
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        series
+            .set_posts(
+                created.series.id,
+                SetSeriesPostsRequest {
+                    post_ids: vec![first, outside + 100],
+                },
+            )
+            .await,
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn posts_move_between_series_and_slugs_stay_unique() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let series = SeriesService::new(database);
+    let post = create_post(&posts, "共享的文章", PostStatus::Published).await;
+
+    let first = series
+        .create(series_request("Tutorial", None))
+        .await
+        .expect("create series");
+    let second = series
+        .create(series_request("Tutorial", None))
+        .await
+        .expect("create series");
+    assert_eq!(second.series.slug, "tutorial-2");
+    assert!(matches!(
+        series
+            .create(series_request("Other", Some("tutorial")))
+            .await,
+        Err(AppError::BadRequest(_))
+    ));
+
+    for target in [first.series.id, second.series.id] {
+        series
+            .set_posts(
+                target,
+                SetSeriesPostsRequest {
+                    post_ids: vec![post],
+                },
+            )
+            .await
+            .expect("set posts");
+    }
+    assert!(series
+        .get(&first.series.id.to_string(), false)
+        .await
+        .expect("first series")
+        .parts
+        .is_empty());
+    assert_eq!(
+        series
+            .navigation(post)
+            .await
+            .expect("navigation")
+            .expect("in second series")
+            .series
+            .id,
+        second.series.id
+    );
+
+    let renamed = series
+        .update(
+            second.series.id,
+            UpdateSeriesRequest {
+                slug: Some("advanced".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("rename series");
+    assert_eq!(renamed.series.slug, "advanced");
+    assert_eq!(renamed.series.title, "Tutorial");
+
+    series
+        .delete(second.series.id)
+        .await
+        .expect("delete series");
+    assert!(series.navigation(post).await.expect("navigation").is_none());
+    assert!(posts.get_post_detail(post).await.expect("post").is_some());
+}
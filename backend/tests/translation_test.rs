@@ -0,0 +1,198 @@
+use axum::extract::Query;
+use axum::http::Uri;
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{
+    CreatePostRequest, LangQuery, Locale, PostListQuery, PostStatus, UpsertTranslationRequest,
+    DEFAULT_LOCALE,
+};
+use chuyi_uk_back::services::{PostService, TranslationService};
+use chuyi_uk_back::utils::error::AppError;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+fn post_service(database: Database) -> PostService {
+    PostService::new(
+        database,
+        Arc::new(FileHandler::new(
+            "/tmp/chuyi-blog-tests".to_string(),
+            1_000_000,
+            None,
+        )),
+    )
+}
+
+fn create_request(title: &str, status: PostStatus) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        cover_url: None,
+        content: "中文正文".to_string(),
+        category_id: None,
+        status: Some(status),
+        post_images: None,
+        pdf_url: None,
+        publish_at: None,
+        tag_ids: None,
+    }
+}
+
+fn translation(title: &str, content: &str) -> UpsertTranslationRequest {
+    UpsertTranslationRequest {
+        title: title.to_string(),
+        content: content.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn translations_are_upserted_validated_and_deleted() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let translations = TranslationService::new(database);
+    let post = posts
+        .create_post(create_request("你好世界", PostStatus::Published))
+        .await
+        .expect("create post");
+
+    assert!(matches!(
+        translations
+            .upsert(post.id, Locale::Zh, translation("你好", "正文"))
+            .await,
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        translations
+            .upsert(post.id, Locale::En, translation("  ", "body"))
+            .await,
+        Err(AppError::Validation(_))
+    ));
+    assert!(matches!(
+        translations
+            .upsert(post.id + 1, Locale::En, translation("Hello", "body"))
+            .await,
+        Err(AppError::NotFound(_))
+    ));
+
+    let created = translations
+        .upsert(
+            post.id,
+            Locale::En,
+            translation(" Hello world ", "First draft"),
+        )
+        .await
+        .expect("create translation");
+    assert_eq!(created.title, "Hello world");
+    assert_eq!(created.locale, "en");
+    let updated = translations
+        .upsert(post.id, Locale::En, translation("Hello world", "Final"))
+        .await
+        .expect("update translation");
+    assert_eq!(updated.content, "Final");
+    assert_eq!(updated.created_at, created.created_at);
+    assert_eq!(
+        translations.list(post.id).await.expect("list").len(),
+        1,
+        "upsert must not create a second row"
+    );
+    assert_eq!(
+        translations.locales(post.id).await.expect("locales"),
+        [Locale::Zh, Locale::En]
+    );
+
+    translations
+        .delete(post.id, Locale::En)
+        .await
+        .expect("delete translation");
+    assert!(matches!(
+        translations.get(post.id, Locale::En).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(matches!(
+        translations.delete(post.id, Locale::En).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert_eq!(
+        translations.locales(post.id).await.expect("locales"),
+        [Locale::Zh]
+    );
+}
+
+#[tokio::test]
+async fn lists_are_localized_and_sitemap_sees_only_published_translations() {
+    let database = setup_test_db().await;
+    let posts = post_service(database.clone());
+    let translations = TranslationService::new(database);
+    let translated = posts
+        .create_post(create_request("有译文", PostStatus::Published))
+        .await
+        .expect("create post");
+    let untranslated = posts
+        .create_post(create_request("没有译文", PostStatus::Published))
+        .await
+        .expect("create post");
+    let draft = posts
+        .create_post(create_request("草稿", PostStatus::Draft))
+        .await
+        .expect("create post");
+    let english_body = "English body. ".repeat(40);
+    for id in [translated.id, draft.id] {
+        translations
+            .upsert(id, Locale::En, translation("Translated", &english_body))
+            .await
+            .expect("create translation");
+    }
+
+    let (mut list, _) = posts
+        .list_posts(PostListQuery {
+            status: Some(PostStatus::Published),
+            ..Default::default()
+        })
+        .await
+        .expect("list posts");
+    translations
+        .localize(list.iter_mut().collect(), Locale::En)
+        .await
+        .expect("localize");
+    let by_id = |id: i64| list.iter().find(|post| post.id == id).expect("post");
+    assert_eq!(by_id(translated.id).title, "Translated");
+    // 列表和原文一样只返回摘要，不带整篇译文
+    let summary = &by_id(translated.id).content;
+    assert!(summary.starts_with("English body."));
+    assert!(summary.chars().count() < english_body.chars().count());
+    assert!(summary.ends_with("..."));
+    assert_eq!(by_id(untranslated.id).title, "没有译文");
+
+    let sitemap = translations
+        .published_locales()
+        .await
+        .expect("published locales");
+    assert_eq!(sitemap.len(), 1);
+    assert_eq!(sitemap[&translated.id][0].0, Locale::En);
+}
+
+#[test]
+fn unknown_lang_falls_back_to_default_locale() {
+    let query = |uri: &str| {
+        Query::<LangQuery>::try_from_uri(&uri.parse::<Uri>().expect("uri"))
+            .expect("lang query")
+            .0
+            .locale()
+    };
+    assert_eq!(query("/api/posts?lang=en"), Locale::En);
+    assert_eq!(query("/api/posts?lang=EN"), Locale::En);
+    assert_eq!(query("/api/posts?lang=fr"), DEFAULT_LOCALE);
+    assert_eq!(query("/api/posts?lang=zh-CN"), DEFAULT_LOCALE);
+    assert_eq!(query("/api/posts"), DEFAULT_LOCALE);
+}
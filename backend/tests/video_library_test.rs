@@ -0,0 +1,127 @@
+use chuyi_uk_back::database::Database;
+use chuyi_uk_back::models::{CreateVideo, JobKind, UpdateVideoRequest, PROBE_PENDING, PROBE_READY};
+use chuyi_uk_back::services::{JobService, ResourceService, VideoService};
+use chuyi_uk_back::utils::video_probe::VideoProbe;
+use chuyi_uk_back::utils::FileHandler;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+async fn setup_test_db() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create test database");
+    let database = Database {
+        pool: Arc::new(pool),
+    };
+    database.migrate().await.expect("run migrations");
+    database
+}
+
+#[tokio::test]
+async fn uploaded_videos_are_tracked_and_linked_to_posts() {
+    let database = setup_test_db().await;
+    let file_handler = Arc::new(FileHandler::new(
+        "/tmp/chuyi-blog-tests".to_string(),
+        1_000_000,
+        None,
+    ));
+    let jobs = Arc::new(JobService::new(database.clone()));
+    let videos = VideoService::new(database.clone(), file_handler.clone(), jobs.clone());
+    let resources = ResourceService::new(
+        database.clone(),
+        file_handler,
+        "/tmp/chuyi-blog-tests".to_string(),
+    );
+
+    let key = "videos/2026/10/0b6c1f4e-2f0a-4d7e-9c55-6a3f1c2d9e10.mp4";
+    let video = videos
+        .create(CreateVideo {
+            title: "  ".to_string(),
+            file_name: "trip to kyoto.mp4".to_string(),
+            r2_key: key.to_string(),
+            public_url: format!("https://assets.example.com/{key}"),
+            content_type: "video/mp4".to_string(),
+            file_size: 4096,
+        })
+        .await
+        .expect("create video");
+    assert_eq!(video.title, "trip to kyoto");
+    assert_eq!(video.probe_status, PROBE_PENDING);
+    assert!(video.duration.is_none());
+    assert_eq!(
+        jobs.pending_count(JobKind::ProbeVideo)
+            .await
+            .expect("pending jobs"),
+        1
+    );
+
+    let probed = videos
+        .apply_probe(
+            video.id,
+            &VideoProbe {
+                duration: Some(42.5),
+                width: Some(1920),
+                height: Some(1080),
+                codec: Some("h264".to_string()),
+            },
+            Some("/uploads/video_posters/frame.webp".to_string()),
+        )
+        .await
+        .expect("apply probe");
+    assert_eq!(probed.probe_status, PROBE_READY);
+    assert_eq!((probed.width, probed.height), (Some(1920), Some(1080)));
+    assert_eq!(
+        probed.poster_url.as_deref(),
+        Some("/uploads/video_posters/frame.webp")
+    );
+
+    let renamed = videos
+        .update(
+            video.id,
+            UpdateVideoRequest {
+                title: Some("京都".to_string()),
+                poster_url: None,
+            },
+        )
+        .await
+        .expect("rename");
+    assert_eq!(renamed.title, "京都");
+    assert!(renamed.poster_url.is_some());
+    assert!(videos
+        .update(
+            video.id,
+            UpdateVideoRequest {
+                title: Some(String::new()),
+                poster_url: None,
+            },
+        )
+        .await
+        .is_err());
+
+    assert!(!resources
+        .video_usage()
+        .await
+        .expect("usage")
+        .contains_key(&video.id));
+    let post_id = sqlx::query(
+        "INSERT INTO posts (title, content, status) VALUES ('游记', ?, 1), ('无关', 'no video here', 1)",
+    )
+    .bind(format!(
+        "<video controls src=\"https://cdn.example.com/{key}\"></video>"
+    ))
+    .execute(database.pool())
+    .await
+    .expect("insert posts")
+    .last_insert_rowid()
+        - 1;
+    let usage = resources.video_usage().await.expect("usage");
+    let used_by = &usage[&video.id];
+    assert_eq!(used_by.len(), 1);
+    assert_eq!(used_by[0].ref_id, post_id);
+    assert_eq!(used_by[0].ref_title, "游记");
+
+    videos.delete(video.id).await.expect("delete video");
+    assert!(videos.list().await.expect("list").is_empty());
+}
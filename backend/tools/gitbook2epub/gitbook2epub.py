@@ -66,6 +66,12 @@ def find_chrome() -> str | None:
     return None
 
 
+def report(args, fraction: float, message: str) -> None:
+    """--progress 时向 stderr 写一行机器可读的进度（供后端任务轮询解析）。"""
+    if getattr(args, "progress", False):
+        print(f"@@PROGRESS {fraction:.3f} {message}", file=sys.stderr, flush=True)
+
+
 def _run_live(cmd: list[str], desc: str) -> bool:
     """执行命令并把输出直接透传给用户（用于展示下载进度）。"""
     print(f"· {desc} …")
@@ -378,6 +384,7 @@ def build(args) -> int:
         return h
 
     print(f"· 抓取目录页 {base}")
+    report(args, 0.02, "抓取目录页")
     index_html = smart_fetch(base, need_toc=True)
     if index_html is None:
         print("错误：无法获取目录页。", file=sys.stderr)
@@ -393,6 +400,7 @@ def build(args) -> int:
     if args.limit:
         links = links[: args.limit]
     print(f"· 共 {len(links)} 个章节页面")
+    report(args, 0.05, f"下载页面 0/{len(links)}")
 
     # 1) 下载所有页面（带缓存）
     def get_page(u: str) -> tuple[str, str | None]:
@@ -411,9 +419,10 @@ def build(args) -> int:
 
     pages: dict[str, str] = {}
     with cf.ThreadPoolExecutor(max_workers=page_workers) as ex:
-        for u, h in ex.map(get_page, links):
+        for done, (u, h) in enumerate(ex.map(get_page, links), 1):
             if h is not None:
                 pages[u] = h
+            report(args, 0.05 + 0.65 * done / len(links), f"下载页面 {done}/{len(links)}")
     failed_pages = [u for u in links if u not in pages]
     print(f"· 下载成功 {len(pages)}/{len(links)} 页"
           + (f"，失败 {len(failed_pages)}" if failed_pages else ""))
@@ -441,6 +450,7 @@ def build(args) -> int:
     if not args.no_images and url_to_local:
         wanted = list(url_to_local.keys())
         print(f"· 下载 {len(wanted)} 张图片 …")
+        report(args, 0.75, f"下载 {len(wanted)} 张图片")
         got = download_images(wanted, img_dir, args.workers)
         missing = len(wanted) - len(got)
         if missing:
@@ -479,6 +489,7 @@ def build(args) -> int:
     if args.cover and os.path.exists(args.cover):
         cmd += ["--epub-cover-image", args.cover]
     print("· 运行 pandoc 打包 …")
+    report(args, 0.9, "打包 EPUB")
     r = subprocess.run(cmd, capture_output=True, text=True)
     if r.returncode != 0:
         print(r.stderr, file=sys.stderr)
@@ -526,6 +537,8 @@ def main() -> int:
     p.add_argument("--refresh", action="store_true", help="忽略页面缓存，重新下载 HTML")
     p.add_argument("--keep-cache", action="store_true", help="结束时打印缓存目录位置")
     p.add_argument("--clean", action="store_true", help="开始前清空该书缓存")
+    p.add_argument("--progress", action="store_true",
+                   help="向 stderr 输出 `@@PROGRESS <0-1> <说明>` 进度行（后端调用时用）")
     args = p.parse_args()
 
     if args.setup:
@@ -16,6 +16,7 @@ const Guestbook = lazy(() => import('@/pages/Guestbook'))
 const Books = lazy(() => import('@/pages/Books'))
 const BookReader = lazy(() => import('@/pages/BookReader'))
 const Changelog = lazy(() => import('@/pages/Changelog'))
+const Series = lazy(() => import('@/pages/Series'))
 const NotFound = lazy(() => import('@/pages/NotFound'))
 const AdminLogin = lazy(() => import('@/pages/admin/Login'))
 const AdminLayout = lazy(() => import('@/pages/admin/AdminLayout'))
@@ -75,6 +76,7 @@ export default function App() {
           <Route path="/tools/mailbox" element={<Mailbox />} />
           <Route path="/tools/quant" element={<Quant />} />
           <Route path="/article/:id" element={<ArticleDetail />} />
+          <Route path="/series/:slug" element={<Series />} />
           <Route path="/about" element={<About />} />
           <Route path="/guestbook" element={<Guestbook />} />
           <Route path="/books" element={<Books />} />
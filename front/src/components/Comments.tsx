@@ -0,0 +1,167 @@
+import { useEffect, useState, type FormEvent } from 'react'
+import { Loader2, Reply } from 'lucide-react'
+import { Button } from '@/components/ui/button'
+import { Input } from '@/components/ui/input'
+import { Textarea } from '@/components/ui/textarea'
+import { listComments, submitComment, type CommentItem, type CommentThread } from '@/services/api'
+
+interface CommentsProps {
+  thread: CommentThread
+  title?: string
+  description?: string
+}
+
+const AUTHOR_KEY = 'comment-author'
+
+interface SavedAuthor {
+  name: string
+  email: string
+  url: string
+}
+
+function loadAuthor(): SavedAuthor {
+  try {
+    return { name: '', email: '', url: '', ...JSON.parse(localStorage.getItem(AUTHOR_KEY) || '{}') }
+  } catch {
+    return { name: '', email: '', url: '' }
+  }
+}
+
+function formatDate(value: string): string {
+  return new Date(value).toLocaleDateString('en-US', { year: 'numeric', month: 'short', day: 'numeric' })
+}
+
+export function Comments({
+  thread,
+  title = 'Discussion',
+  description = 'Comments are reviewed before they appear.',
+}: CommentsProps) {
+  const threadKey = thread === 'guestbook' ? 'guestbook' : thread.postId
+  const [comments, setComments] = useState<CommentItem[] | null>(null)
+  const [error, setError] = useState('')
+  const [replyTo, setReplyTo] = useState<CommentItem | null>(null)
+  const [author, setAuthor] = useState<SavedAuthor>(loadAuthor)
+  const [content, setContent] = useState('')
+  const [honeypot, setHoneypot] = useState('')
+  const [sending, setSending] = useState(false)
+  const [notice, setNotice] = useState('')
+
+  // threadKey, not the thread object, so a new object literal per render doesn't refetch
+  useEffect(() => {
+    setComments(null)
+    listComments(thread)
+      .then(setComments)
+      .catch((loadError) => setError((loadError as Error).message))
+  }, [threadKey])
+
+  async function onSubmit(event: FormEvent) {
+    event.preventDefault()
+    setSending(true)
+    setNotice('')
+    try {
+      const result = await submitComment(thread, {
+        author_name: author.name,
+        author_email: author.email || undefined,
+        author_url: author.url || undefined,
+        content,
+        parent_id: replyTo?.id,
+        website: honeypot,
+      })
+      localStorage.setItem(AUTHOR_KEY, JSON.stringify(author))
+      setContent('')
+      setReplyTo(null)
+      if (result.status === 'approved') {
+        setComments(await listComments(thread))
+        setNotice('Thanks! Your comment is live.')
+      } else {
+        setNotice('Thanks! Your comment will appear once it has been reviewed.')
+      }
+    } catch (submitError) {
+      setNotice((submitError as Error).message)
+    } finally {
+      setSending(false)
+    }
+  }
+
+  return (
+    <section className="space-y-8" aria-labelledby="comments-title">
+      <header>
+        <h2 id="comments-title" className="text-lg font-semibold tracking-tight">{title}</h2>
+        <p className="mt-1 text-sm text-muted-foreground">{description}</p>
+      </header>
+
+      <form onSubmit={onSubmit} className="space-y-3">
+        {replyTo && (
+          <p className="flex items-center gap-2 text-xs text-muted-foreground">
+            Replying to {replyTo.author_name}
+            <button type="button" className="underline" onClick={() => setReplyTo(null)}>cancel</button>
+          </p>
+        )}
+        <div className="grid gap-3 sm:grid-cols-3">
+          <Input required maxLength={50} placeholder="Name" value={author.name} onChange={(e) => setAuthor({ ...author, name: e.target.value })} />
+          <Input type="email" placeholder="Email (optional, for avatar)" value={author.email} onChange={(e) => setAuthor({ ...author, email: e.target.value })} />
+          <Input type="url" placeholder="Website (optional)" value={author.url} onChange={(e) => setAuthor({ ...author, url: e.target.value })} />
+        </div>
+        <input
+          type="text"
+          name="website"
+          tabIndex={-1}
+          autoComplete="off"
+          aria-hidden="true"
+          className="hidden"
+          value={honeypot}
+          onChange={(e) => setHoneypot(e.target.value)}
+        />
+        <Textarea required maxLength={5000} rows={4} placeholder="Leave a comment…" value={content} onChange={(e) => setContent(e.target.value)} />
+        <div className="flex items-center gap-3">
+          <Button type="submit" size="sm" disabled={sending}>
+            {sending && <Loader2 className="animate-spin" />} Post
+          </Button>
+          {notice && <p className="text-xs text-muted-foreground">{notice}</p>}
+        </div>
+      </form>
+
+      {!comments && !error && (
+        <div className="flex items-center gap-2 text-sm text-muted-foreground"><Loader2 className="size-4 animate-spin" /> Loading comments…</div>
+      )}
+      {error && <p className="text-sm text-destructive">Could not load comments: {error}</p>}
+      {comments?.length === 0 && <p className="text-sm text-muted-foreground">No comments yet.</p>}
+      <ol className="space-y-6">
+        {comments?.map((comment) => <CommentNode key={comment.id} comment={comment} onReply={setReplyTo} />)}
+      </ol>
+    </section>
+  )
+}
+
+function CommentNode({ comment, onReply }: { comment: CommentItem; onReply: (comment: CommentItem) => void }) {
+  return (
+    <li className="flex gap-3">
+      <img src={comment.avatar_url} alt="" loading="lazy" className="size-8 shrink-0 rounded-full bg-secondary" />
+      <div className="min-w-0 flex-1">
+        <div className="flex flex-wrap items-center gap-x-2 text-sm">
+          {comment.author_url ? (
+            <a href={comment.author_url} rel="nofollow noopener noreferrer" target="_blank" className="font-medium hover:underline">
+              {comment.author_name}
+            </a>
+          ) : (
+            <span className="font-medium">{comment.author_name}</span>
+          )}
+          <time className="text-xs text-muted-foreground">{formatDate(comment.created_at)}</time>
+        </div>
+        <p className="mt-1 whitespace-pre-wrap break-words text-sm leading-6">{comment.content}</p>
+        <button
+          type="button"
+          onClick={() => onReply(comment)}
+          className="mt-1 inline-flex items-center gap-1 text-xs text-muted-foreground hover:text-foreground"
+        >
+          <Reply className="size-3" /> Reply
+        </button>
+        {comment.replies.length > 0 && (
+          <ol className="mt-4 space-y-4">
+            {comment.replies.map((reply) => <CommentNode key={reply.id} comment={reply} onReply={onReply} />)}
+          </ol>
+        )}
+      </div>
+    </li>
+  )
+}
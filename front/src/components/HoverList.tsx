@@ -2,7 +2,7 @@ import { useState } from 'react'
 import { createPortal } from 'react-dom'
 import { Link } from 'react-router-dom'
 import { cn } from '@/lib/utils'
-import type { Article } from '@/services/api'
+import { articlePath, type Article } from '@/services/api'
 
 // cai.im's springy easing.
 const SPRING = 'cubic-bezier(.25,1.22,.45,1.04)'
@@ -64,7 +64,7 @@ export function HoverList({ articles }: { articles: Article[] }) {
       {articles.map((a) => (
         <Link
           key={a.id}
-          to={`/article/${a.id}`}
+          to={articlePath(a)}
           onMouseEnter={(e) => enter(e, a)}
           className="group relative flex w-fit max-w-full flex-col gap-0.5 rounded-xl px-3 py-2.5"
         >
@@ -70,6 +70,8 @@ export function SEO({
       <meta name="robots" content={noIndex ? 'noindex,nofollow' : 'index,follow'} />
       <link rel="canonical" href={canonical} />
       <link rel="alternate" type="application/rss+xml" title={SITE_NAME} href={`${SITE}/rss.xml`} />
+      <link rel="alternate" type="application/atom+xml" title={SITE_NAME} href={`${SITE}/atom.xml`} />
+      <link rel="alternate" type="application/feed+json" title={SITE_NAME} href={`${SITE}/feed.json`} />
       <meta property="og:type" content={type} />
       <meta property="og:site_name" content={SITE_NAME} />
       <meta property="og:title" content={fullTitle} />
@@ -0,0 +1,127 @@
+import { useRef, useState } from 'react'
+import { Copy, Loader2, Sparkles, Square } from 'lucide-react'
+import { toast } from 'sonner'
+import { Button } from '@/components/ui/button'
+import { Label } from '@/components/ui/label'
+import { streamAi, type AiResult, type AiTagSuggestion, type AiTask } from '@/services/admin'
+
+interface AiAssistantProps {
+  title: string
+  content: string
+  onApplyTitle: (title: string) => void
+  onApplyContent: (content: string) => void
+  onApplyTags: (tags: AiTagSuggestion[]) => void
+}
+
+type Action = { task: AiTask; label: string; target?: 'zh' | 'en' }
+
+const ACTIONS: Action[] = [
+  { task: 'summary', label: '摘要' },
+  { task: 'seo-description', label: 'SEO 描述' },
+  { task: 'tags', label: '推荐标签' },
+  { task: 'title', label: '候选标题' },
+  { task: 'translate', label: '译为英文', target: 'en' },
+  { task: 'translate', label: '译为中文', target: 'zh' },
+]
+
+export function AiAssistant({ title, content, onApplyTitle, onApplyContent, onApplyTags }: AiAssistantProps) {
+  const [running, setRunning] = useState<Action | null>(null)
+  const [last, setLast] = useState<Action | null>(null)
+  const [output, setOutput] = useState('')
+  const [result, setResult] = useState<AiResult | null>(null)
+  const abortRef = useRef<AbortController | null>(null)
+
+  async function run(action: Action) {
+    if (!content.trim()) {
+      toast.error('正文为空')
+      return
+    }
+    abortRef.current?.abort()
+    const controller = new AbortController()
+    abortRef.current = controller
+    setRunning(action)
+    setLast(action)
+    setOutput('')
+    setResult(null)
+    try {
+      const done = await streamAi(
+        action.task,
+        { title, content, target_language: action.target },
+        (text) => setOutput((current) => current + text),
+        controller.signal,
+      )
+      setOutput(done.text)
+      setResult(done)
+    } catch (aiError) {
+      if (!controller.signal.aborted) toast.error('AI 生成失败', { description: (aiError as Error).message })
+    } finally {
+      if (abortRef.current === controller) setRunning(null)
+    }
+  }
+
+  function stop() {
+    abortRef.current?.abort()
+    setRunning(null)
+  }
+
+  return (
+    <div className="space-y-2">
+      <Label className="flex items-center gap-1.5"><Sparkles className="size-4" /> AI 助手</Label>
+      <div className="grid grid-cols-2 gap-2">
+        {ACTIONS.map((action) => (
+          <Button
+            key={action.label}
+            variant="outline"
+            size="sm"
+            disabled={running !== null}
+            onClick={() => void run(action)}
+          >
+            {running === action && <Loader2 className="animate-spin" />} {action.label}
+          </Button>
+        ))}
+      </div>
+
+      {(output || running) && (
+        <div className="space-y-2 rounded-md border p-3">
+          <p className="max-h-60 overflow-y-auto whitespace-pre-wrap break-words text-sm">
+            {output || '生成中…'}
+          </p>
+          <div className="flex flex-wrap gap-2">
+            {running && (
+              <Button variant="ghost" size="sm" onClick={stop}><Square /> 停止</Button>
+            )}
+            {result && (
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => void navigator.clipboard.writeText(result.text).then(() => toast.success('已复制'))}
+              >
+                <Copy /> 复制
+              </Button>
+            )}
+            {result?.tags && result.tags.length > 0 && (
+              <Button variant="secondary" size="sm" onClick={() => onApplyTags(result.tags ?? [])}>
+                添加这些标签
+              </Button>
+            )}
+            {result && last?.task === 'translate' && (
+              <Button variant="secondary" size="sm" onClick={() => onApplyContent(result.text)}>
+                替换正文
+              </Button>
+            )}
+          </div>
+          {result?.titles?.map((candidate) => (
+            <button
+              key={candidate}
+              type="button"
+              className="block w-full rounded px-2 py-1 text-left text-sm hover:bg-accent"
+              onClick={() => onApplyTitle(candidate)}
+            >
+              {candidate}
+            </button>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
@@ -1,10 +1,11 @@
 import { useEffect, useRef, useState } from 'react'
-import { ChevronLeft, ChevronRight, List, Loader2, X } from 'lucide-react'
+import { ChevronLeft, ChevronRight, Highlighter, List, Loader2, X } from 'lucide-react'
 import type { Book as EpubBook, Contents, Location, NavItem, Rendition } from 'epubjs'
 import { Button } from '@/components/ui/button'
-import { loadReaderProgress, saveReaderProgress } from '@/lib/book-progress'
+import { isReaderOwner, loadReaderProgress, loadSyncedProgress, syncReaderProgress } from '@/lib/book-progress'
 import { bindReaderGestures, bindReaderKeyboard } from '@/lib/reader-gestures'
-import { bookFileContentUrl, type BookFile } from '@/services/api'
+import { createHighlight, listHighlights, type BookHighlight } from '@/services/admin'
+import { getBookFileLink, type BookFile } from '@/services/api'
 
 export type ReaderTheme = 'paper' | 'night'
 export type ReaderFlow = 'paginated' | 'scrolled'
@@ -75,6 +76,32 @@ function applyRenditionAppearance(rendition: Rendition, theme: ReaderTheme, font
   visibleContents(rendition).forEach((contents) => applyContentAppearance(contents, theme))
 }
 
+const HIGHLIGHT_FILL: Record<BookHighlight['color'], string> = {
+  yellow: '#f5d76e',
+  green: '#9fd8a4',
+  blue: '#9cc7f0',
+  pink: '#f3a6c4',
+  purple: '#c7a6f0',
+}
+
+function showHighlight(rendition: Rendition, highlight: Pick<BookHighlight, 'locator' | 'color'>): void {
+  rendition.annotations.highlight(highlight.locator, {}, () => undefined, 'reader-highlight', {
+    fill: HIGHLIGHT_FILL[highlight.color],
+    'fill-opacity': '0.35',
+  })
+}
+
+/** Label of the table-of-contents entry for the section being read, used to group exported notes. */
+function chapterLabel(items: NavItem[], href: string): string {
+  const path = href.split('#')[0]
+  for (const item of items) {
+    if (item.href.split('#')[0] === path) return item.label.trim()
+    const nested = chapterLabel(item.subitems ?? [], href)
+    if (nested) return nested
+  }
+  return ''
+}
+
 function TableOfContents({ items, onSelect }: { items: NavItem[]; onSelect: (href: string) => void }) {
   return (
     <ol>
@@ -95,7 +122,10 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
   const themeRef = useRef(theme)
   fontSizeRef.current = fontSize
   themeRef.current = theme
+  const bookRef = useRef<EpubBook | null>(null)
   const [navigation, setNavigation] = useState<NavItem[]>([])
+  const [owner, setOwner] = useState(false)
+  const [selection, setSelection] = useState<string | null>(null)
   const [position, setPosition] = useState<ReadingPosition>(EMPTY_POSITION)
   const [tocOpen, setTocOpen] = useState(false)
   const [loading, setLoading] = useState(true)
@@ -109,11 +139,12 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
       setLoading(true)
       setError('')
       try {
-        const response = await fetch(bookFileContentUrl(bookId, file.id))
+        const response = await fetch(await getBookFileLink(bookId, file.id))
         if (!response.ok) throw new Error(`The book file returned ${response.status}.`)
         const { default: createEpub } = await import('epubjs')
         if (disposed || !viewportRef.current) return
         activeBook = createEpub(await response.arrayBuffer())
+        bookRef.current = activeBook
         const rendition = activeBook.renderTo(viewportRef.current, {
           width: '100%',
           height: '100%',
@@ -170,12 +201,20 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
             atEnd: nextLocation.atEnd,
             atStart: nextLocation.atStart,
           })
-          saveReaderProgress(bookId, file.id, { kind: 'epub', cfi: nextLocation.start.cfi, percent })
+          syncReaderProgress(bookId, file.id, { kind: 'epub', cfi: nextLocation.start.cfi, percent })
         })
-        const saved = loadReaderProgress(bookId, file.id)
+        rendition.on('selected', (cfiRange: string) => setSelection(cfiRange))
+        const saved = (await loadSyncedProgress(bookId, file.id)) ?? loadReaderProgress(bookId, file.id)
+        if (disposed) return
         await rendition.display(saved?.kind === 'epub' ? saved.cfi : undefined).catch(() => rendition.display())
         bindVisibleContents()
         setLoading(false)
+        if (await isReaderOwner()) {
+          if (disposed) return
+          setOwner(true)
+          const highlights = await listHighlights(bookId, file.id).catch(() => [])
+          if (!disposed) highlights.forEach((highlight) => showHighlight(rendition, highlight))
+        }
         void activeBook.locations.generate(1600).then(() => rendition.reportLocation()).catch(() => undefined)
       } catch (openError) {
         if (!disposed) {
@@ -188,11 +227,13 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
     return () => {
       disposed = true
       renditionRef.current = null
+      bookRef.current = null
+      setSelection(null)
       interactionCleanups.forEach((cleanup) => cleanup())
       interactionCleanups.clear()
       activeBook?.destroy()
     }
-  }, [bookId, file.id, file.file_url, flow, onTopHoverChange, onToggleUi])
+  }, [bookId, file.id, flow, onTopHoverChange, onToggleUi])
 
   useEffect(() => {
     themeRef.current = theme
@@ -213,6 +254,30 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
     onPrevious: () => void renditionRef.current?.prev(),
   }), [flow])
 
+  const saveSelection = async () => {
+    const rendition = renditionRef.current
+    const book = bookRef.current
+    if (!rendition || !book || !selection) return
+    const text = (await book.getRange(selection))?.toString().trim()
+    if (!text) return setSelection(null)
+    const note = window.prompt('Add a note to this highlight (optional)') ?? ''
+    const location = rendition.currentLocation() as unknown as Location
+    try {
+      const highlight = await createHighlight(bookId, {
+        file_id: file.id,
+        locator: selection,
+        text,
+        note,
+        chapter: chapterLabel(navigation, location.start.href),
+      })
+      showHighlight(rendition, highlight)
+      visibleContents(rendition).forEach((contents) => contents.window.getSelection()?.removeAllRanges())
+      setSelection(null)
+    } catch (saveError) {
+      window.alert((saveError as Error).message || 'The highlight could not be saved.')
+    }
+  }
+
   const displayChapter = (href: string) => {
     void renditionRef.current?.display(href)
     setTocOpen(false)
@@ -234,6 +299,9 @@ export function EpubReader({ bookId, file, flow, fontSize, onTopHoverChange, onT
         <Button size="icon" variant="ghost" onClick={() => setTocOpen(true)} aria-label="Open table of contents"><List /></Button>
         <Button size="icon" variant="ghost" disabled={position.atStart} onClick={() => void renditionRef.current?.prev()} aria-label="Previous page"><ChevronLeft /></Button>
         <Button size="icon" variant="ghost" disabled={position.atEnd} onClick={() => void renditionRef.current?.next()} aria-label="Next page"><ChevronRight /></Button>
+        {owner && (
+          <Button size="icon" variant="ghost" disabled={!selection} onClick={() => void saveSelection()} aria-label="Highlight selection" title="Highlight selection"><Highlighter /></Button>
+        )}
       </div>
     </div>
   )
@@ -1,11 +1,12 @@
 import { useEffect, useRef, useState } from 'react'
-import { ChevronLeft, ChevronRight, Loader2, Minus, Plus } from 'lucide-react'
+import { ChevronLeft, ChevronRight, Loader2, Minus, NotebookPen, Plus } from 'lucide-react'
 import type { PDFDocumentLoadingTask, PDFDocumentProxy, RenderTask } from 'pdfjs-dist/types/src/display/api'
 import pdfWorkerUrl from 'pdfjs-dist/build/pdf.worker.min.mjs?url'
 import { Button } from '@/components/ui/button'
-import { loadReaderProgress, saveReaderProgress } from '@/lib/book-progress'
+import { isReaderOwner, loadReaderProgress, loadSyncedProgress, syncReaderProgress } from '@/lib/book-progress'
 import { bindReaderGestures, bindReaderKeyboard } from '@/lib/reader-gestures'
-import { bookFileContentUrl, type BookFile } from '@/services/api'
+import { createHighlight } from '@/services/admin'
+import { getBookFileLink, type BookFile } from '@/services/api'
 
 interface PdfReaderProps {
   bookId: number
@@ -26,6 +27,7 @@ export function PdfReader({ bookId, file, onTopHoverChange, onToggleUi }: PdfRea
   const [page, setPage] = useState(() => restoredPage(bookId, file.id))
   const [pages, setPages] = useState(0)
   const [zoom, setZoom] = useState(1)
+  const [owner, setOwner] = useState(false)
   const [frameWidth, setFrameWidth] = useState(0)
   const [loading, setLoading] = useState(true)
   const [error, setError] = useState('')
@@ -37,12 +39,16 @@ export function PdfReader({ bookId, file, onTopHoverChange, onToggleUi }: PdfRea
       setLoading(true)
       setError('')
       try {
-        const pdfjs = await import('pdfjs-dist')
+        const [pdfjs, url] = await Promise.all([import('pdfjs-dist'), getBookFileLink(bookId, file.id)])
+        if (disposed) return
         pdfjs.GlobalWorkerOptions.workerSrc = pdfWorkerUrl
-        loadingTask = pdfjs.getDocument({ url: bookFileContentUrl(bookId, file.id) })
+        loadingTask = pdfjs.getDocument({ url })
         const loaded = await loadingTask.promise
         if (disposed) return void loaded.destroy()
-        const initialPage = Math.min(loaded.numPages, Math.max(1, restoredPage(bookId, file.id)))
+        const synced = await loadSyncedProgress(bookId, file.id)
+        if (disposed) return void loaded.destroy()
+        const savedPage = synced?.kind === 'pdf' ? synced.page : restoredPage(bookId, file.id)
+        const initialPage = Math.min(loaded.numPages, Math.max(1, savedPage))
         setDocument(loaded)
         setPages(loaded.numPages)
         setPage(initialPage)
@@ -60,7 +66,7 @@ export function PdfReader({ bookId, file, onTopHoverChange, onToggleUi }: PdfRea
       setDocument(null)
       void loadingTask?.destroy()
     }
-  }, [bookId, file.id, file.file_url])
+  }, [bookId, file.id])
 
   useEffect(() => {
     if (!frameRef.current) return
@@ -100,9 +106,26 @@ export function PdfReader({ bookId, file, onTopHoverChange, onToggleUi }: PdfRea
   }, [document, frameWidth, page, zoom])
 
   useEffect(() => {
-    if (pages) saveReaderProgress(bookId, file.id, { kind: 'pdf', page, pages })
+    if (pages) syncReaderProgress(bookId, file.id, { kind: 'pdf', page, pages })
   }, [bookId, file.id, page, pages])
 
+  useEffect(() => {
+    let active = true
+    void isReaderOwner().then((isOwner) => { if (active) setOwner(isOwner) })
+    return () => { active = false }
+  }, [])
+
+  // The PDF is drawn on a canvas without a text layer, so page notes take the quoted passage by hand.
+  const addPageNote = async () => {
+    const text = window.prompt(`Passage or thought from page ${page}`)?.trim()
+    if (!text) return
+    try {
+      await createHighlight(bookId, { file_id: file.id, locator: `page:${page}`, text })
+    } catch (saveError) {
+      window.alert((saveError as Error).message || 'The note could not be saved.')
+    }
+  }
+
   useEffect(() => {
     const frame = frameRef.current
     if (!frame) return
@@ -139,6 +162,12 @@ export function PdfReader({ bookId, file, onTopHoverChange, onToggleUi }: PdfRea
         <Button size="icon" variant="ghost" disabled={zoom <= 0.7} onClick={() => setZoom((current) => Math.max(0.7, current - 0.1))} aria-label="Zoom out"><Minus /></Button>
         <small>{Math.round(zoom * 100)}%</small>
         <Button size="icon" variant="ghost" disabled={zoom >= 1.8} onClick={() => setZoom((current) => Math.min(1.8, current + 0.1))} aria-label="Zoom in"><Plus /></Button>
+        {owner && (
+          <>
+            <span className="reader-control-divider" />
+            <Button size="icon" variant="ghost" disabled={!pages} onClick={() => void addPageNote()} aria-label="Add a note for this page" title="Add a note for this page"><NotebookPen /></Button>
+          </>
+        )}
       </div>
     </div>
   )
@@ -1,7 +1,7 @@
 import { Link } from 'react-router-dom'
 import type { LucideIcon } from 'lucide-react'
 import { cn } from '@/lib/utils'
-import type { Article } from '@/services/api'
+import { articlePath, type Article } from '@/services/api'
 
 /** Minimal stat: label + big number + icon, no box. */
 export function StatCard({
@@ -60,7 +60,7 @@ export function ListCard({
 export function ArticleRow({ article, index }: { article: Article; index: number }) {
   return (
     <Link
-      to={`/article/${article.id}`}
+      to={articlePath(article)}
       className="flex items-center gap-3 rounded-md px-3 py-2.5 transition-colors hover:bg-accent"
     >
       <span className="w-6 shrink-0 text-right text-sm font-medium tabular-nums text-muted-foreground/70">
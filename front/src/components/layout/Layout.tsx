@@ -1,8 +1,18 @@
-import { Outlet } from 'react-router-dom'
+import { useEffect, useRef } from 'react'
+import { Outlet, useLocation } from 'react-router-dom'
 import { Dock } from '@/components/Dock'
 import { RouteSEO } from '@/components/SEO'
+import { trackPageView } from '@/services/api'
 
 export function Layout() {
+  const { pathname } = useLocation()
+  const landed = useRef(false)
+
+  useEffect(() => {
+    trackPageView(pathname, landed.current ? undefined : document.referrer)
+    landed.current = true
+  }, [pathname])
+
   return (
     <div className="min-h-dvh pb-28">
       <RouteSEO />
@@ -1,7 +1,11 @@
+import { getAdminSession, listReadingProgress, saveReadingProgress } from '@/services/admin'
+
 export type ReaderProgress =
   | { kind: 'epub'; cfi: string; percent: number }
   | { kind: 'pdf'; page: number; pages: number }
 
+const SYNC_DELAY_MS = 1500
+
 function progressKey(bookId: number, fileId: number): string {
   return `book-reader:${bookId}:${fileId}`
 }
@@ -22,3 +26,47 @@ export function saveReaderProgress(bookId: number, fileId: number, progress: Rea
     // Reading still works when storage is unavailable (private mode or quota limits).
   }
 }
+
+let ownerCheck: Promise<boolean> | null = null
+
+/** Only the signed-in owner syncs progress and highlights; visitors keep progress in localStorage. */
+export function isReaderOwner(): Promise<boolean> {
+  ownerCheck ??= getAdminSession().then((session) => session !== null).catch(() => false)
+  return ownerCheck
+}
+
+function toLocator(progress: ReaderProgress): { locator: string; percentage: number } {
+  if (progress.kind === 'epub') return { locator: progress.cfi, percentage: progress.percent }
+  const percentage = progress.pages ? (progress.page / progress.pages) * 100 : 0
+  return { locator: `page:${progress.page}`, percentage: Math.min(100, Math.max(0, percentage)) }
+}
+
+/** The owner's last position from the server, so reading continues across devices. */
+export async function loadSyncedProgress(bookId: number, fileId: number): Promise<ReaderProgress | null> {
+  if (!(await isReaderOwner())) return null
+  try {
+    const saved = (await listReadingProgress(bookId)).find((item) => item.book_file_id === fileId)
+    if (!saved) return null
+    const page = saved.locator.match(/^page:(\d+)$/)
+    return page
+      ? { kind: 'pdf', page: Number(page[1]), pages: 0 }
+      : { kind: 'epub', cfi: saved.locator, percent: Math.round(saved.percentage) }
+  } catch {
+    return null
+  }
+}
+
+const pendingSyncs = new Map<string, number>()
+
+/** Stores progress locally right away and, for the owner, pushes it to the server once paging settles. */
+export function syncReaderProgress(bookId: number, fileId: number, progress: ReaderProgress): void {
+  saveReaderProgress(bookId, fileId, progress)
+  const key = progressKey(bookId, fileId)
+  window.clearTimeout(pendingSyncs.get(key))
+  pendingSyncs.set(key, window.setTimeout(() => {
+    pendingSyncs.delete(key)
+    void isReaderOwner().then((owner) => {
+      if (owner) return saveReadingProgress(bookId, fileId, toLocator(progress)).then(() => undefined)
+    }).catch(() => undefined)
+  }, SYNC_DELAY_MS))
+}
@@ -1,6 +1,6 @@
 import { useEffect, useLayoutEffect, useMemo, useRef, useState } from 'react'
 import { useReducedMotion } from 'framer-motion'
-import { useNavigate, useParams } from 'react-router-dom'
+import { Link, useNavigate, useParams, useSearchParams } from 'react-router-dom'
 import PhotoSwipeLightbox from 'photoswipe/lightbox'
 import {
   ArrowLeft,
@@ -8,12 +8,15 @@ import {
   ChevronLeft,
   ChevronRight,
   Clock3,
+  Download,
   Focus,
   Loader2,
   AlertCircle,
   Share2,
 } from 'lucide-react'
 import {
+  articleEpubUrl,
+  articlePath,
   getAdjacentArticles,
   getArticle,
   stripMarkdown,
@@ -26,7 +29,7 @@ import { Button } from '@/components/ui/button'
 import { Badge } from '@/components/ui/badge'
 import { Separator } from '@/components/ui/separator'
 import { MagneticBackButton } from '@/components/MagneticBackButton'
-import { GiscusComments } from '@/components/GiscusComments'
+import { Comments } from '@/components/Comments'
 import { useSiteUI } from '@/lib/site-ui'
 import { cn } from '@/lib/utils'
 
@@ -41,6 +44,8 @@ const TOC_TEXT_HIGHLIGHT_DELAY_MS = 340
 
 export default function ArticleDetail() {
   const { id } = useParams<{ id: string }>()
+  const [searchParams] = useSearchParams()
+  const lang = searchParams.get('lang') === 'en' ? 'en' : undefined
   const navigate = useNavigate()
   const { zen, toggleZen, exitZen } = useSiteUI()
   const [article, setArticle] = useState<Article | null>(null)
@@ -85,13 +90,17 @@ export default function ArticleDetail() {
     setError(null)
     setCoverFailed(false)
     setCoverSize({ width: 0, height: 0 })
-    Promise.all([
-      getArticle(id, controller.signal),
-      getAdjacentArticles(id, controller.signal).catch((error: unknown) => {
-        if (error instanceof DOMException && error.name === 'AbortError') throw error
-        return {}
-      }),
-    ])
+    // The route param may be a slug; adjacent posts are looked up by the numeric id.
+    getArticle(id, controller.signal, lang)
+      .then((nextArticle) =>
+        Promise.all([
+          nextArticle,
+          getAdjacentArticles(nextArticle.id, controller.signal).catch((error: unknown) => {
+            if (error instanceof DOMException && error.name === 'AbortError') throw error
+            return {}
+          }),
+        ]),
+      )
       .then(([nextArticle, nextAdjacent]) => {
         setArticle(nextArticle)
         setAdjacent(nextAdjacent)
@@ -102,7 +111,7 @@ export default function ArticleDetail() {
       })
 
     return () => controller.abort()
-  }, [id])
+  }, [id, lang])
 
   useLayoutEffect(() => {
     const main = mainRef.current
@@ -331,8 +340,8 @@ export default function ArticleDetail() {
       const target = event.target as HTMLElement | null
       if (target?.closest('input, textarea, select, [contenteditable="true"]')) return
       if (event.metaKey || event.ctrlKey || event.altKey) return
-      if (event.key.toLowerCase() === 'j' && adjacent.older) navigate(`/article/${adjacent.older.id}`)
-      if (event.key.toLowerCase() === 'k' && adjacent.newer) navigate(`/article/${adjacent.newer.id}`)
+      if (event.key.toLowerCase() === 'j' && adjacent.older) navigate(articlePath(adjacent.older))
+      if (event.key.toLowerCase() === 'k' && adjacent.newer) navigate(articlePath(adjacent.newer))
     }
     window.addEventListener('keydown', onKeyDown)
     return () => window.removeEventListener('keydown', onKeyDown)
@@ -416,7 +425,7 @@ export default function ArticleDetail() {
       <SEO
         title={article.title}
         description={stripMarkdown(article.content, 150)}
-        path={`/article/${article.id}`}
+        path={article.locale === 'en' ? `/article/${article.id}?lang=en` : `/article/${article.id}`}
         image={article.coverImage}
         type="article"
         publishedAt={article.rawDate}
@@ -470,6 +479,11 @@ export default function ArticleDetail() {
               >
                 <Focus />
               </Button>
+              <Button asChild variant="ghost" size="icon" className="h-8 w-8 rounded-full" title="Download EPUB">
+                <a href={articleEpubUrl(article.id)} download aria-label="Download as EPUB">
+                  <Download />
+                </a>
+              </Button>
             </div>
           </div>
 
@@ -480,10 +494,32 @@ export default function ArticleDetail() {
             <span className="inline-flex items-center gap-1">
               <Clock3 className="size-3.5" /> {readMinutes} min read
             </span>
+            {article.availableLocales && article.availableLocales.length > 1 && (
+              <span className="inline-flex items-center gap-1.5">
+                {article.availableLocales.map((locale) =>
+                  locale === (article.locale ?? 'zh') ? (
+                    <strong key={locale}>{locale === 'en' ? 'EN' : '中文'}</strong>
+                  ) : (
+                    <Link key={locale} to={{ search: locale === 'en' ? '?lang=en' : '' }} hrefLang={locale === 'en' ? 'en' : 'zh-CN'}>
+                      {locale === 'en' ? 'EN' : '中文'}
+                    </Link>
+                  ),
+                )}
+              </span>
+            )}
           </div>
 
           <h1 className="article-title article-reveal">{article.title}</h1>
 
+          {adjacent.series && (
+            <p className="article-reveal mt-3 text-sm text-muted-foreground">
+              Part {adjacent.series.part} of {adjacent.series.total} in{' '}
+              <Link to={`/series/${adjacent.series.slug}`} className="font-medium text-foreground underline-offset-4 hover:underline">
+                {adjacent.series.title}
+              </Link>
+            </p>
+          )}
+
           {article.tags.length > 0 && (
             <div className="article-tags article-reveal mt-5 flex flex-wrap gap-2">
               {article.tags.map((t) => (
@@ -534,11 +570,11 @@ export default function ArticleDetail() {
               {adjacent.older ? (
                 <button
                   type="button"
-                  onClick={() => navigate(`/article/${adjacent.older!.id}`)}
+                  onClick={() => navigate(articlePath(adjacent.older!))}
                   className="group rounded-xl p-4 text-left transition-colors"
                 >
                   <span className="flex items-center gap-1 text-xs text-muted-foreground">
-                    <ChevronLeft className="size-3.5" /> Older · J
+                    <ChevronLeft className="size-3.5" /> {adjacent.series ? 'Previous part' : 'Older'} · J
                   </span>
                   <span className="mt-1 block truncate text-sm font-medium">{adjacent.older.title}</span>
                 </button>
@@ -548,11 +584,11 @@ export default function ArticleDetail() {
               {adjacent.newer && (
                 <button
                   type="button"
-                  onClick={() => navigate(`/article/${adjacent.newer!.id}`)}
+                  onClick={() => navigate(articlePath(adjacent.newer!))}
                   className="group rounded-xl p-4 text-right transition-colors"
                 >
                   <span className="flex items-center justify-end gap-1 text-xs text-muted-foreground">
-                    K · Newer <ChevronRight className="size-3.5" />
+                    K · {adjacent.series ? 'Next part' : 'Newer'} <ChevronRight className="size-3.5" />
                   </span>
                   <span className="mt-1 block truncate text-sm font-medium">{adjacent.newer.title}</span>
                 </button>
@@ -561,7 +597,7 @@ export default function ArticleDetail() {
           )}
 
           <div className="mt-12">
-            <GiscusComments term={article.title} />
+            <Comments thread={{ postId: article.id }} />
           </div>
         </article>
 
@@ -1,7 +1,7 @@
 import { useEffect, useMemo, useState } from 'react'
 import { Link } from 'react-router-dom'
 import { Helmet } from 'react-helmet-async'
-import { listArticles, getCategories, getTags, type Article, type Category, type Tag } from '@/services/api'
+import { articlePath, listArticles, getCategories, getTags, type Article, type Category, type Tag } from '@/services/api'
 import { Skeleton } from '@/components/ui/skeleton'
 import { cn } from '@/lib/utils'
 
@@ -125,7 +125,7 @@ export default function Articles() {
                 {g.items.map((a) => (
                   <li key={a.id}>
                     <Link
-                      to={`/article/${a.id}`}
+                      to={articlePath(a)}
                       className="group flex items-baseline gap-4 rounded-lg px-2 py-2 transition-colors hover:bg-accent/60"
                     >
                       <span className="w-12 shrink-0 text-xs tabular-nums text-muted-foreground/70">
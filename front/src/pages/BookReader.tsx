@@ -1,13 +1,15 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react'
-import { Maximize2, Minimize2, Minus, Plus } from 'lucide-react'
+import { Download, FileText, Maximize2, Minimize2, Minus, Plus } from 'lucide-react'
 import { Link, useNavigate, useParams, useSearchParams } from 'react-router-dom'
 import { EpubReader, type ReaderFlow, type ReaderTheme } from '@/components/books/EpubReader'
 import { PdfReader } from '@/components/books/PdfReader'
 import { MagneticBackButton } from '@/components/MagneticBackButton'
 import { SEO } from '@/components/SEO'
 import { Button } from '@/components/ui/button'
+import { isReaderOwner } from '@/lib/book-progress'
 import { isReaderHoverDevice } from '@/lib/reader-gestures'
-import { listBooks, type Book, type BookFile } from '@/services/api'
+import { bookNotesUrl } from '@/services/admin'
+import { getBookFileLink, listBooks, type Book, type BookFile } from '@/services/api'
 import './BookReader.css'
 
 function isReadable(file: BookFile): boolean {
@@ -107,9 +109,11 @@ export default function BookReader() {
   const [flow, setFlow] = useState<ReaderFlow>(initialReaderFlow)
   const [fullscreen, setFullscreen] = useState(false)
   const [uiVisible, setUiVisible] = useState(false)
+  const [owner, setOwner] = useState(false)
 
   useEffect(() => {
     listBooks().then(setBooks).catch((loadError) => setError((loadError as Error).message))
+    void isReaderOwner().then(setOwner)
   }, [])
 
   useEffect(() => {
@@ -172,6 +176,26 @@ export default function BookReader() {
         </label>
       )}
       <div className="book-reader-settings">
+        {owner && (
+          <Button asChild variant="ghost" size="icon" title="Export notes">
+            <a href={bookNotesUrl(book.id)} download aria-label="Export highlights and notes as Markdown"><FileText /></a>
+          </Button>
+        )}
+        {book.download_enabled && (
+          <Button
+            variant="ghost"
+            size="icon"
+            aria-label={`Download ${file.format.toUpperCase()}`}
+            title="Download"
+            onClick={() => {
+              getBookFileLink(book.id, file.id, { download: true })
+                .then((url) => window.location.assign(url))
+                .catch(() => undefined)
+            }}
+          >
+            <Download />
+          </Button>
+        )}
         <ReaderPreferences
           format={format}
           flow={flow}
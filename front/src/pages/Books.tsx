@@ -3,10 +3,14 @@ import { BookOpen, LibraryBig, Loader2 } from 'lucide-react'
 import { Link } from 'react-router-dom'
 import { SEO } from '@/components/SEO'
 import {
+  getBookStats,
+  getReadingReport,
   imageUrl,
   listBooks,
   type Book,
   type BookFile,
+  type BookStats,
+  type ReadingReport,
   type ReadingStatus,
 } from '@/services/api'
 import './Books.css'
@@ -62,6 +66,67 @@ function BookCard({ book }: { book: Book }) {
   )
 }
 
+const MONTHS = ['Jan', 'Feb', 'Mar', 'Apr', 'May', 'Jun', 'Jul', 'Aug', 'Sep', 'Oct', 'Nov', 'Dec']
+
+function ReadingTimeline({ stats }: { stats: BookStats }) {
+  const [year, setYear] = useState(stats.years[0]?.year)
+  const [report, setReport] = useState<ReadingReport | null>(null)
+  const peak = Math.max(1, ...stats.years.flatMap((entry) => entry.months))
+
+  useEffect(() => {
+    if (year === undefined) return
+    let cancelled = false
+    setReport(null)
+    getReadingReport(year)
+      .then((next) => { if (!cancelled) setReport(next) })
+      .catch(() => undefined)
+    return () => { cancelled = true }
+  }, [year])
+
+  return (
+    <section className="library-stats" aria-label="Reading statistics">
+      <dl className="library-stats-summary">
+        <div><dt>Finished</dt><dd>{stats.finished}</dd></div>
+        <div><dt>Reading</dt><dd>{stats.reading}</dd></div>
+        {stats.average_rating !== null && <div><dt>Avg rating</dt><dd>{stats.average_rating.toFixed(1)}</dd></div>}
+        {stats.average_days_to_finish !== null && <div><dt>Avg days</dt><dd>{Math.round(stats.average_days_to_finish)}</dd></div>}
+      </dl>
+      {stats.years.length > 0 && (
+        <div className="library-timeline">
+          {stats.years.map((entry) => (
+            <button
+              type="button"
+              key={entry.year}
+              className={entry.year === year ? 'is-active' : undefined}
+              onClick={() => setYear(entry.year)}
+              aria-pressed={entry.year === year}
+            >
+              <span className="library-timeline-year">{entry.year}</span>
+              <span className="library-timeline-months" aria-hidden="true">
+                {entry.months.map((count, index) => (
+                  <i key={MONTHS[index]} title={`${MONTHS[index]}: ${count}`} style={{ height: `${(count / peak) * 100}%` }} />
+                ))}
+              </span>
+              <span className="library-timeline-count">{entry.finished}</span>
+            </button>
+          ))}
+        </div>
+      )}
+      {report && report.books.length > 0 && (
+        <ol className="library-report">
+          {report.books.map((book) => (
+            <li key={book.id}>
+              <time dateTime={book.finished_at}>{MONTHS[Number(book.finished_at.slice(5, 7)) - 1]} {Number(book.finished_at.slice(8, 10))}</time>
+              <span>{book.title}{book.author && <small> · {book.author}</small>}</span>
+              {book.rating !== null && <span className="library-report-rating" aria-label={`${book.rating} out of 5`}>{'★'.repeat(book.rating)}</span>}
+            </li>
+          ))}
+        </ol>
+      )}
+    </section>
+  )
+}
+
 function EmptyBookshelf() {
   return (
     <div className="library-empty">
@@ -74,10 +139,12 @@ function EmptyBookshelf() {
 
 export default function Books() {
   const [books, setBooks] = useState<Book[] | null>(null)
+  const [stats, setStats] = useState<BookStats | null>(null)
   const [error, setError] = useState('')
 
   useEffect(() => {
     listBooks().then(setBooks).catch((loadError) => setError((loadError as Error).message))
+    getBookStats().then(setStats).catch(() => undefined)
   }, [])
 
   const grouped = useMemo(() => {
@@ -100,6 +167,7 @@ export default function Books() {
       {!books && !error && <div className="library-loading"><Loader2 /> Loading shelf…</div>}
       {error && <p className="py-8 text-sm text-destructive">Could not load the shelf: {error}</p>}
       {books?.length === 0 && <EmptyBookshelf />}
+      {stats && stats.finished > 0 && <ReadingTimeline stats={stats} />}
 
       <div className="library-groups">
         {(['reading', 'finished', 'want_to_read', 'paused'] as ReadingStatus[]).map((status) => {
@@ -1,4 +1,4 @@
-import { GiscusComments } from '@/components/GiscusComments'
+import { Comments } from '@/components/Comments'
 import { SEO } from '@/components/SEO'
 
 export default function Guestbook() {
@@ -13,13 +13,13 @@ export default function Guestbook() {
         <p className="text-xs font-semibold uppercase tracking-[0.2em] text-muted-foreground">Community</p>
         <h1 className="mt-3 text-3xl font-bold tracking-tight">Guestbook</h1>
         <p className="mt-4 max-w-xl text-sm leading-7 text-muted-foreground">
-          Leave a thought, suggestion, question, or link. Powered by GitHub Discussions.
+          Leave a thought, suggestion, question, or link. No account needed.
         </p>
       </header>
-      <GiscusComments
-        term="guestbook"
+      <Comments
+        thread="guestbook"
         title="Leave a note"
-        description="Sign in with GitHub to post a message or react to other notes."
+        description="Notes are reviewed before they appear."
       />
     </div>
   )
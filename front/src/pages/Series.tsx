@@ -0,0 +1,73 @@
+import { useEffect, useState } from 'react'
+import { Link, useParams } from 'react-router-dom'
+import { Loader2 } from 'lucide-react'
+import { SEO } from '@/components/SEO'
+import { articlePath, getSeries, seriesEpubUrl, type SeriesDetail } from '@/services/api'
+
+function formatDate(value: string): string {
+  return new Date(value).toLocaleDateString('en-US', { year: 'numeric', month: 'long', day: 'numeric' })
+}
+
+export default function Series() {
+  const { slug } = useParams<{ slug: string }>()
+  const [series, setSeries] = useState<SeriesDetail | null>(null)
+  const [error, setError] = useState('')
+
+  useEffect(() => {
+    if (!slug) return
+    const controller = new AbortController()
+    setSeries(null)
+    setError('')
+    getSeries(slug, controller.signal)
+      .then(setSeries)
+      .catch((loadError: unknown) => {
+        if (loadError instanceof DOMException && loadError.name === 'AbortError') return
+        setError((loadError as Error).message)
+      })
+    return () => controller.abort()
+  }, [slug])
+
+  return (
+    <div className="mx-auto max-w-2xl px-6 py-16 sm:py-20">
+      {series && (
+        <SEO
+          title={series.title}
+          description={series.description || `${series.parts.length} parts`}
+          path={`/series/${series.slug}`}
+        />
+      )}
+      {!series && !error && <div className="flex items-center gap-2 py-12 text-sm text-muted-foreground"><Loader2 className="size-4 animate-spin" /> Loading series…</div>}
+      {error && <p className="py-8 text-sm text-destructive">Could not load this series: {error}</p>}
+      {series && (
+        <>
+          <header className="mb-12">
+            <p className="text-xs font-semibold uppercase tracking-[0.2em] text-muted-foreground">Series · {series.parts.length} parts</p>
+            <h1 className="mt-3 text-3xl font-bold tracking-tight">{series.title}</h1>
+            {series.description && <p className="mt-4 text-sm leading-7 text-muted-foreground">{series.description}</p>}
+            <a
+              href={seriesEpubUrl(series.slug)}
+              download
+              className="mt-4 inline-block text-sm font-medium underline-offset-4 hover:underline"
+            >
+              Download as EPUB
+            </a>
+          </header>
+          <ol className="space-y-2">
+            {series.parts.map((part) => (
+              <li key={part.id}>
+                <Link
+                  to={articlePath({ id: String(part.id), slug: part.slug ?? undefined })}
+                  className="flex items-baseline gap-4 rounded-xl p-3 transition-colors hover:bg-accent"
+                >
+                  <span className="w-6 shrink-0 text-right text-sm tabular-nums text-muted-foreground">{part.part}</span>
+                  <span className="min-w-0 flex-1 truncate font-medium">{part.title}</span>
+                  <time className="shrink-0 text-xs text-muted-foreground">{formatDate(part.created_at)}</time>
+                </Link>
+              </li>
+            ))}
+          </ol>
+        </>
+      )}
+    </div>
+  )
+}
@@ -1,5 +1,5 @@
 import { useEffect, useMemo, useRef, useState } from 'react'
-import { BookOpen, FileUp, Loader2, MoreHorizontal, Pencil, Plus, Trash2, X } from 'lucide-react'
+import { BookOpen, FileUp, Import, Loader2, MoreHorizontal, Pencil, Plus, Trash2, X } from 'lucide-react'
 import { toast } from 'sonner'
 import { Button } from '@/components/ui/button'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
@@ -13,8 +13,11 @@ import {
   createBook,
   deleteBook,
   deleteBookFile,
+  importBookHistory,
   updateBook,
   uploadImage,
+  waitForBookMetadata,
+  type BookImportReport,
   type BookPayload,
 } from '@/services/admin'
 import { uploadBookFileDirect } from '@/services/book-upload'
@@ -42,6 +45,12 @@ const STATUS: Record<ReadingStatus, string> = {
   paused: '暂停',
 }
 
+const IMPORT_ACTION: Record<BookImportReport['items'][number]['action'], string> = {
+  create: '新建',
+  update: '更新',
+  skip: '跳过',
+}
+
 function payloadFromBook(book: Book): BookPayload {
   return {
     title: book.title,
@@ -115,7 +124,11 @@ export default function BooksManager() {
   const [uploadProgress, setUploadProgress] = useState(0)
   const [selectedEpub, setSelectedEpub] = useState<File | null>(null)
   const [embeddedCover, setEmbeddedCover] = useState<File | null>(null)
+  const [importFile, setImportFile] = useState<File | null>(null)
+  const [importPreview, setImportPreview] = useState<BookImportReport | null>(null)
+  const [importing, setImporting] = useState(false)
   const epubInputRef = useRef<HTMLInputElement>(null)
+  const importInputRef = useRef<HTMLInputElement>(null)
   const embeddedCoverUrl = useMemo(
     () => embeddedCover ? URL.createObjectURL(embeddedCover) : null,
     [embeddedCover],
@@ -238,9 +251,29 @@ export default function BooksManager() {
     setUploadingBookId(book.id)
     setUploadProgress(0)
     try {
-      await uploadBookFileDirect(book.id, file, (progress) => setUploadProgress(progress.percent), controller.signal)
+      const { metadata_job } = await uploadBookFileDirect(book.id, file, (progress) => setUploadProgress(progress.percent), controller.signal)
       await refresh()
-      toast.success(`${file.name} 已上传`)
+      const metadata = metadata_job
+        ? await waitForBookMetadata(metadata_job, controller.signal).catch(() => null)
+        : null
+      // 作者、简介、封面已在后台补上
+      if (metadata) await refresh()
+      const extractedTitle = metadata?.title?.trim()
+      if (extractedTitle && extractedTitle !== book.title) {
+        toast.success(`${file.name} 已上传`, {
+          description: `文件里的书名是“${extractedTitle}”`,
+          action: {
+            label: '使用此书名',
+            onClick: () => {
+              updateBook(book.id, { title: extractedTitle })
+                .then(refresh)
+                .catch((error) => toast.error('书名更新失败', { description: (error as Error).message }))
+            },
+          },
+        })
+      } else {
+        toast.success(`${file.name} 已上传`)
+      }
     } catch (error) {
       toast.error('EPUB 上传失败', { description: (error as Error).message })
     } finally {
@@ -249,6 +282,38 @@ export default function BooksManager() {
     }
   }
 
+  const previewImport = async (file: File) => {
+    setImporting(true)
+    try {
+      setImportPreview(await importBookHistory(file, { dryRun: true }))
+      setImportFile(file)
+    } catch (error) {
+      toast.error('无法读取导入文件', { description: (error as Error).message })
+    } finally {
+      setImporting(false)
+    }
+  }
+
+  const closeImport = () => {
+    setImportFile(null)
+    setImportPreview(null)
+  }
+
+  const runImport = async () => {
+    if (!importFile) return
+    setImporting(true)
+    try {
+      const report = await importBookHistory(importFile, { source: importPreview?.source })
+      await refresh()
+      closeImport()
+      toast.success(`已导入：新建 ${report.created} 本，更新 ${report.updated} 本，跳过 ${report.skipped} 本`)
+    } catch (error) {
+      toast.error('导入失败', { description: (error as Error).message })
+    } finally {
+      setImporting(false)
+    }
+  }
+
   const removeBook = async (book: Book) => {
     if (!window.confirm(`确定删除“${book.title}”及其电子书文件吗？`)) return
     try {
@@ -273,7 +338,15 @@ export default function BooksManager() {
 
   return (
     <div>
-      <div className="mb-4 flex justify-end">
+      <div className="mb-4 flex justify-end gap-2">
+        <input ref={importInputRef} type="file" accept=".csv,text/csv" className="hidden" onChange={(event) => {
+          const file = event.target.files?.[0]
+          if (file) void previewImport(file)
+          event.target.value = ''
+        }} />
+        <Button variant="outline" size="sm" disabled={importing} onClick={() => importInputRef.current?.click()}>
+          {importing && !importPreview ? <Loader2 className="animate-spin" /> : <Import />} 导入 Goodreads / 豆瓣
+        </Button>
         <Button onClick={openCreate} size="sm"><Plus /> 添加书籍</Button>
       </div>
 
@@ -320,7 +393,7 @@ export default function BooksManager() {
                 {book.files.map((file) => (
                   <div key={file.id} className="flex items-center gap-2 rounded-md border px-3 py-2 text-xs">
                     <span className="font-medium">{file.format.toUpperCase()}</span>
-                    <span className="min-w-0 flex-1 truncate text-muted-foreground">{file.file_name} · {formatBytes(file.file_size)}</span>
+                    <span className="min-w-0 flex-1 truncate text-muted-foreground">{file.file_name} · {formatBytes(file.file_size)}{file.page_count ? ` · ${file.page_count} 页` : ''}</span>
                     <Button variant="ghost" size="icon" className="size-7" onClick={() => void removeFile(file.id)}><X /></Button>
                   </div>
                 ))}
@@ -343,6 +416,32 @@ export default function BooksManager() {
         )}
       </div>
 
+      <Dialog open={importPreview !== null} onOpenChange={(open) => !open && closeImport()}>
+        <DialogContent className="max-h-[90vh] max-w-2xl overflow-y-auto">
+          <DialogHeader>
+            <DialogTitle>导入读书记录</DialogTitle>
+            <DialogDescription>
+              {importPreview && `${importPreview.source === 'goodreads' ? 'Goodreads' : '豆瓣'}导出：将新建 ${importPreview.created} 本，更新 ${importPreview.updated} 本，跳过 ${importPreview.skipped} 本。新建的书默认不公开。`}
+            </DialogDescription>
+          </DialogHeader>
+          <div className="space-y-1 text-xs">
+            {importPreview?.items.map((item) => (
+              <div key={item.line} className="flex items-center gap-2 rounded-md border px-3 py-2">
+                <span className="w-8 shrink-0 text-muted-foreground">{IMPORT_ACTION[item.action]}</span>
+                <span className="min-w-0 flex-1 truncate">{item.title || '（无书名）'}{item.author && <span className="text-muted-foreground"> · {item.author}</span>}</span>
+                <span className="shrink-0 text-muted-foreground">{item.reason ?? item.changes.join(', ')}</span>
+              </div>
+            ))}
+          </div>
+          <DialogFooter>
+            <Button variant="outline" onClick={closeImport}>取消</Button>
+            <Button disabled={importing || !importPreview || importPreview.created + importPreview.updated === 0} onClick={() => void runImport()}>
+              {importing && <Loader2 className="animate-spin" />} 确认导入
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+
       <Dialog open={editing !== null} onOpenChange={(open) => !open && closeEditor()}>
         <DialogContent className="max-h-[90vh] max-w-3xl overflow-y-auto">
           <DialogHeader><DialogTitle>{editing === 'new' ? '添加书籍' : '编辑书籍'}</DialogTitle><DialogDescription>选择 EPUB 后会自动读取标题、作者和封面。</DialogDescription></DialogHeader>
@@ -1,7 +1,7 @@
 import { useEffect, useState } from 'react'
 import { Link } from 'react-router-dom'
-import { AlertCircle, FileText, FolderTree, Tags, Plus } from 'lucide-react'
-import { getDashboard, STATUS_NAME, type DashboardStats } from '@/services/admin'
+import { AlertCircle, Eye, FileText, FolderTree, Tags, Plus } from 'lucide-react'
+import { getDashboard, getTopPosts, STATUS_NAME, type DashboardStats, type TopPost } from '@/services/admin'
 import { Button } from '@/components/ui/button'
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert'
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card'
@@ -28,12 +28,16 @@ function Stat({ icon: Icon, label, value }: { icon: React.ElementType; label: st
 
 export default function Dashboard() {
   const [stats, setStats] = useState<DashboardStats | null>(null)
+  const [topPosts, setTopPosts] = useState<TopPost[]>([])
   const [err, setErr] = useState('')
 
   useEffect(() => {
     getDashboard()
       .then(setStats)
       .catch((e) => setErr(String(e.message || e)))
+    getTopPosts(30, 5)
+      .then(setTopPosts)
+      .catch(() => setTopPosts([]))
   }, [])
 
   return (
@@ -55,14 +59,15 @@ export default function Dashboard() {
       )}
 
       {!stats && !err && (
-        <div className="grid grid-cols-2 gap-3 sm:grid-cols-3">
-          {[0, 1, 2].map((item) => <Skeleton key={item} className="h-32 rounded-xl" />)}
+        <div className="grid grid-cols-2 gap-3 sm:grid-cols-4">
+          {[0, 1, 2, 3].map((item) => <Skeleton key={item} className="h-32 rounded-xl" />)}
         </div>
       )}
 
       {stats && (
         <>
-          <div className="grid grid-cols-2 gap-3 sm:grid-cols-3">
+          <div className="grid grid-cols-2 gap-3 sm:grid-cols-4">
+            <Stat icon={Eye} label="浏览" value={stats.total_views ?? 0} />
             <Stat icon={FileText} label="文章" value={stats.total_posts} />
             <Stat icon={FolderTree} label="分类" value={stats.total_categories} />
             <Stat icon={Tags} label="标签" value={stats.total_tags} />
@@ -86,6 +91,26 @@ export default function Dashboard() {
             </Card>
           )}
 
+          {topPosts.length > 0 && (
+            <Card className="mt-6">
+              <CardHeader className="p-4"><CardTitle className="text-base">近 30 天热门</CardTitle></CardHeader>
+              <CardContent className="divide-y divide-border p-0">
+                {topPosts.map((p) => (
+                  <Link
+                    key={p.post_id}
+                    to={`/admin/posts/${p.post_id}`}
+                    className="flex items-center justify-between gap-3 px-4 py-3 text-sm transition-colors hover:bg-accent/50"
+                  >
+                    <span className="truncate">{p.title || '(无标题)'}</span>
+                    <span className="shrink-0 text-xs tabular-nums text-muted-foreground">
+                      {p.views} 浏览 · {p.visitors} 访客
+                    </span>
+                  </Link>
+                ))}
+              </CardContent>
+            </Card>
+          )}
+
           {stats.system_info && (
             <p className="mt-8 text-xs text-muted-foreground">
               运行 {stats.system_info.uptime} · 内存 {stats.system_info.memory_usage} · 磁盘 {stats.system_info.disk_usage}
@@ -2,6 +2,7 @@ import { useEffect, useRef, useState } from 'react'
 import { useNavigate, useParams } from 'react-router-dom'
 import { AlertCircle, ArrowLeft, ChevronDown, ImagePlus, Loader2, Settings2, Tags, X } from 'lucide-react'
 import { toast } from 'sonner'
+import { AiAssistant } from '@/components/admin/AiAssistant'
 import { MarkdownEditor } from '@/components/admin/MarkdownEditor'
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert'
 import { Button } from '@/components/ui/button'
@@ -27,6 +28,7 @@ import {
   replacePostCover,
   updatePost,
   uploadImage,
+  type AiTagSuggestion,
 } from '@/services/admin'
 import { imageUrl, type Category, type Tag } from '@/services/api'
 
@@ -123,6 +125,26 @@ export default function PostEditor() {
     }
   }
 
+  async function applyTagSuggestions(suggestions: AiTagSuggestion[]) {
+    try {
+      const ids: number[] = []
+      for (const suggestion of suggestions) {
+        const existing = suggestion.id ?? tagsList.find((tag) => tag.name === suggestion.name)?.id
+        if (existing != null) {
+          ids.push(Number(existing))
+        } else {
+          const created = await createTag(suggestion.name)
+          setTagsList((current) => [...current, { id: String(created.id), name: created.name, count: 0 }])
+          ids.push(created.id)
+        }
+      }
+      setTagIds((current) => Array.from(new Set([...current, ...ids])))
+      toast.success(`已添加 ${ids.length} 个标签`)
+    } catch (tagError) {
+      toast.error('添加标签失败', { description: (tagError as Error).message })
+    }
+  }
+
   function toggleTag(tagId: number) {
     setTagIds((current) => (
       current.includes(tagId) ? current.filter((value) => value !== tagId) : [...current, tagId]
@@ -321,6 +343,16 @@ export default function PostEditor() {
             </div>
           </div>
 
+          <div className="border-t pt-5">
+            <AiAssistant
+              title={title}
+              content={content}
+              onApplyTitle={setTitle}
+              onApplyContent={setContent}
+              onApplyTags={(suggestions) => void applyTagSuggestions(suggestions)}
+            />
+          </div>
+
           <div className="border-t pt-5">
             <Button variant="outline" className="w-full" disabled={saving} onClick={() => void save()}>
               {saving && <Loader2 className="animate-spin" />} 保存当前状态
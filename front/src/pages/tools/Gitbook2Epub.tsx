@@ -1,46 +1,87 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { Helmet } from 'react-helmet-async'
-import { Link } from 'react-router-dom'
+import { Link, useSearchParams } from 'react-router-dom'
 import { ArrowLeft, BookOpen, Download, Loader2, CheckCircle2, AlertCircle } from 'lucide-react'
-import { gitbook2epub } from '@/services/api'
+import { conversionDownloadUrl, getGitbook2epubJob, gitbook2epub, type ConversionJob } from '@/services/api'
 import { Button } from '@/components/ui/button'
 import { Input } from '@/components/ui/input'
 
 type Status =
   | { kind: 'idle' }
-  | { kind: 'loading' }
-  | { kind: 'done'; filename: string }
+  | { kind: 'submitting' }
+  | { kind: 'working'; job: ConversionJob }
+  | { kind: 'done'; job: ConversionJob }
   | { kind: 'error'; message: string }
 
+const POLL_MS = 2000
+
+function isActive(job: ConversionJob) {
+  return job.status === 'queued' || job.status === 'running'
+}
+
+function statusOf(job: ConversionJob): Status {
+  if (isActive(job)) return { kind: 'working', job }
+  if (job.status === 'succeeded') return { kind: 'done', job }
+  return { kind: 'error', message: job.error || `Conversion ${job.status}.` }
+}
+
+function describeProgress(job: ConversionJob) {
+  if (job.status === 'queued') {
+    return job.queue_position > 0
+      ? `Waiting in line — ${job.queue_position} conversion${job.queue_position > 1 ? 's' : ''} ahead.`
+      : 'Waiting to start…'
+  }
+  const percent = Math.round(job.progress * 100)
+  return `${job.progress_message || 'Converting'} · ${percent}%`
+}
+
 export default function Gitbook2Epub() {
   const [url, setUrl] = useState('')
   const [includeImages, setIncludeImages] = useState(false)
   const [status, setStatus] = useState<Status>({ kind: 'idle' })
+  // The job id lives in the URL so a reload keeps following the same conversion.
+  const [searchParams, setSearchParams] = useSearchParams()
+  const jobId = searchParams.get('job')
 
-  const loading = status.kind === 'loading'
+  const loading = status.kind === 'submitting' || status.kind === 'working'
+
+  useEffect(() => {
+    if (!jobId) return
+    const controller = new AbortController()
+    let timer: ReturnType<typeof setTimeout> | undefined
+    const poll = async () => {
+      try {
+        const job = await getGitbook2epubJob(jobId, controller.signal)
+        setStatus(statusOf(job))
+        if (isActive(job)) timer = setTimeout(poll, POLL_MS)
+      } catch (err) {
+        if (controller.signal.aborted) return
+        setStatus({ kind: 'error', message: err instanceof Error ? err.message : String(err) })
+      }
+    }
+    poll()
+    return () => {
+      controller.abort()
+      clearTimeout(timer)
+    }
+  }, [jobId])
 
   async function onSubmit(e: React.FormEvent) {
     e.preventDefault()
     const target = url.trim()
     if (!target || loading) return
-    setStatus({ kind: 'loading' })
+    setStatus({ kind: 'submitting' })
     try {
-      const { blob, filename } = await gitbook2epub(target, includeImages)
-      // Trigger a browser download from the returned blob.
-      const href = URL.createObjectURL(blob)
-      const a = document.createElement('a')
-      a.href = href
-      a.download = filename
-      document.body.appendChild(a)
-      a.click()
-      a.remove()
-      URL.revokeObjectURL(href)
-      setStatus({ kind: 'done', filename })
+      const job = await gitbook2epub(target, includeImages)
+      setStatus(statusOf(job))
+      setSearchParams({ job: job.job_id }, { replace: true })
     } catch (err) {
       setStatus({ kind: 'error', message: err instanceof Error ? err.message : String(err) })
     }
   }
 
+  const downloadUrl = status.kind === 'done' ? conversionDownloadUrl(status.job) : undefined
+
   return (
     <div className="mx-auto max-w-2xl px-6 py-16 sm:px-8">
       <Helmet>
@@ -103,17 +144,34 @@ export default function Gitbook2Epub() {
 
       {/* Status line */}
       <div className="mt-4 min-h-5 text-sm">
-        {status.kind === 'loading' && (
-          <p className="text-muted-foreground">
-            Crawling the book and building the EPUB — this can take up to ~90&nbsp;seconds for larger
-            books. Keep this tab open.
-          </p>
-        )}
-        {status.kind === 'done' && (
-          <p className="inline-flex items-center gap-1.5 text-emerald-500">
-            <CheckCircle2 className="size-4" /> Done — <span className="font-medium">{status.filename}</span> downloaded.
-          </p>
+        {status.kind === 'submitting' && <p className="text-muted-foreground">Submitting…</p>}
+        {status.kind === 'working' && (
+          <div className="space-y-2">
+            <p className="text-muted-foreground">{describeProgress(status.job)}</p>
+            <div className="h-1.5 overflow-hidden rounded-full bg-secondary">
+              <div
+                className="h-full rounded-full bg-foreground transition-[width] duration-500"
+                style={{ width: `${Math.round(status.job.progress * 100)}%` }}
+              />
+            </div>
+            <p className="text-xs text-muted-foreground/70">
+              Larger books can take a few minutes. You can leave this page and come back with the same link.
+            </p>
+          </div>
         )}
+        {status.kind === 'done' &&
+          (downloadUrl ? (
+            <p className="inline-flex items-center gap-1.5 text-emerald-500">
+              <CheckCircle2 className="size-4" /> Done —{' '}
+              <a href={downloadUrl} download className="font-medium underline underline-offset-4">
+                download {status.job.result?.file_name ?? 'book.epub'}
+              </a>
+            </p>
+          ) : (
+            <p className="inline-flex items-center gap-1.5 text-muted-foreground">
+              <AlertCircle className="size-4" /> This result has expired — convert the book again.
+            </p>
+          ))}
         {status.kind === 'error' && (
           <p className="inline-flex items-start gap-1.5 text-destructive">
             <AlertCircle className="mt-0.5 size-4 shrink-0" /> {status.message}
@@ -124,7 +182,8 @@ export default function Gitbook2Epub() {
       <div className="mt-12 space-y-2 border-t border-border/60 pt-6 text-xs leading-relaxed text-muted-foreground">
         <p>
           <span className="font-medium text-foreground/80">Tips.</span> Works best with static online
-          books. One conversion runs at a time; if it's busy, try again in a moment.
+          books. Conversions run one at a time in a queue, and finished EPUBs stay downloadable for a
+          few hours.
         </p>
         <p>The link must be a public http/https address. Private and internal addresses are rejected.</p>
       </div>
@@ -128,6 +128,79 @@ export async function getDashboard(): Promise<DashboardStats> {
   return (await res.json()) as DashboardStats
 }
 
+// ---------- AI writing assistant ----------
+export type AiTask = 'summary' | 'seo-description' | 'tags' | 'title' | 'translate'
+
+export interface AiTagSuggestion {
+  /** null = not an existing tag yet */
+  id: number | null
+  name: string
+}
+
+export interface AiResult {
+  text: string
+  tags?: AiTagSuggestion[]
+  titles?: string[]
+}
+
+/**
+ * Run an assistant task. The server streams SSE (`delta`…, then `done` or
+ * `error`); EventSource can't POST, so the body is read by hand.
+ */
+export async function streamAi(
+  task: AiTask,
+  payload: { title: string; content: string; target_language?: 'zh' | 'en' },
+  onDelta: (text: string) => void,
+  signal?: AbortSignal,
+): Promise<AiResult> {
+  const res = await fetch(`${API_BASE}${PREFIX}/admin/ai/${task}`, {
+    method: 'POST',
+    credentials: 'include',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(payload),
+    signal,
+  })
+  if (res.status === 401) authExpired('未授权，请重新登录')
+  if (!res.ok || !res.body) {
+    const body = (await res.json().catch(() => ({}))) as Partial<Envelope<null>>
+    throw new Error(body.message || `请求失败 (${res.status})`)
+  }
+  const reader = res.body.pipeThrough(new TextDecoderStream()).getReader()
+  let buffer = ''
+  for (;;) {
+    const { value, done } = await reader.read()
+    if (done) break
+    buffer += value
+    let boundary: number
+    while ((boundary = buffer.indexOf('\n\n')) >= 0) {
+      const block = buffer.slice(0, boundary)
+      buffer = buffer.slice(boundary + 2)
+      const data = block.split('\n').filter((line) => line.startsWith('data:')).map((line) => line.slice(5).trim()).join('\n')
+      if (!data) continue
+      const event = JSON.parse(data) as { type: string; text?: string; message?: string } & AiResult
+      if (event.type === 'delta') onDelta(event.text ?? '')
+      else if (event.type === 'error') throw new Error(event.message || 'AI 生成失败')
+      else if (event.type === 'done') return event
+    }
+  }
+  throw new Error('AI 输出意外中断')
+}
+
+// ---------- analytics ----------
+export interface TopPost {
+  post_id: number
+  title: string
+  slug?: string | null
+  views: number
+  /** Sum of daily unique visitors (hashes rotate daily). */
+  visitors: number
+}
+
+export async function getTopPosts(days = 30, limit = 10): Promise<TopPost[]> {
+  const env = await req<TopPost[]>(`/admin/analytics/top-posts?days=${days}&limit=${limit}`)
+  return env.data || []
+}
+
 // ---------- posts ----------
 interface RawDetail {
   post: AdminPost
@@ -222,19 +295,56 @@ export async function beginVideoUpload(file: File, contentType: string): Promise
   return env.data
 }
 
+/** A video in the library; duration, size, codec and poster are filled in by a background probe. */
+export interface Video {
+  id: number
+  title: string
+  file_name: string
+  r2_key: string
+  public_url: string
+  content_type: string
+  file_size: number
+  /** Seconds. */
+  duration: number | null
+  width: number | null
+  height: number | null
+  codec: string | null
+  poster_url: string | null
+  probe_status: 'pending' | 'ready' | 'failed'
+  probe_error: string | null
+  created_at: string
+  updated_at: string
+}
+
+export interface VideoUsageRef {
+  ref_type: string
+  ref_id: number
+  ref_title: string
+}
+
+/** Library entry with the posts that embed the video. */
+export interface VideoEntry extends Video {
+  used_by: VideoUsageRef[]
+}
+
 export async function completeVideoUpload(
+  file: File,
+  contentType: string,
   session: VideoMultipartSession,
   parts: CompletedVideoPart[],
-): Promise<string> {
-  const env = await req<{ public_url: string }>('/admin/videos/multipart/complete', {
+): Promise<Video> {
+  const env = await req<{ public_url: string; video: Video }>('/admin/videos/multipart/complete', {
     method: 'POST',
     body: JSON.stringify({
       key: session.key,
       upload_id: session.upload_id,
       parts,
+      file_name: file.name,
+      content_type: contentType,
+      file_size: file.size,
     }),
   })
-  return env.data.public_url
+  return env.data.video
 }
 
 export async function abortVideoUpload(session: VideoMultipartSession): Promise<void> {
@@ -244,6 +354,29 @@ export async function abortVideoUpload(session: VideoMultipartSession): Promise<
   })
 }
 
+export async function adminListVideos(): Promise<VideoEntry[]> {
+  const env = await req<VideoEntry[]>('/admin/videos')
+  return env.data || []
+}
+
+export async function updateVideo(
+  id: number,
+  payload: { title?: string; poster_url?: string | null },
+): Promise<Video> {
+  const env = await req<Video>(`/admin/videos/${id}`, { method: 'PUT', body: JSON.stringify(payload) })
+  return env.data
+}
+
+/** Fails while a post still embeds the video. */
+export async function deleteVideo(id: number): Promise<void> {
+  await req(`/admin/videos/${id}`, { method: 'DELETE' })
+}
+
+/** Queue another metadata/poster probe, e.g. after ffmpeg was installed on the server. */
+export async function reprobeVideo(id: number): Promise<void> {
+  await req(`/admin/videos/${id}/probe`, { method: 'POST' })
+}
+
 // ---------- books ----------
 export interface BookPayload {
   title: string
@@ -291,13 +424,48 @@ export async function beginBookUpload(bookId: number, file: File): Promise<Video
   return env.data
 }
 
+/** Metadata read from the uploaded EPUB/PDF; empty author, description and cover were filled from it. */
+export interface ExtractedBookMetadata {
+  title: string | null
+  author: string | null
+  description: string | null
+  page_count: number | null
+  cover_url: string | null
+}
+
+export interface CompletedBookUpload {
+  file: BookFile
+  book: Book
+  /** Token of the background job reading metadata from the file; null for formats it can't read. */
+  metadata_job: string | null
+}
+
+interface MetadataJob {
+  status: 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled'
+  result: ExtractedBookMetadata | null
+  error: string | null
+}
+
+const METADATA_POLL_MS = 1500
+
+/** Wait for the metadata job queued by `completeBookUpload`. Resolves to null if nothing could be read. */
+export async function waitForBookMetadata(token: string, signal?: AbortSignal): Promise<ExtractedBookMetadata | null> {
+  for (;;) {
+    const { data: job } = await req<MetadataJob>(`/jobs/${encodeURIComponent(token)}`, { signal })
+    if (job.status === 'succeeded') return job.result
+    if (job.status === 'failed' || job.status === 'cancelled') throw new Error(job.error || '元数据提取失败')
+    await new Promise((resolve) => setTimeout(resolve, METADATA_POLL_MS))
+    signal?.throwIfAborted()
+  }
+}
+
 export async function completeBookUpload(
   bookId: number,
   file: File,
   session: VideoMultipartSession,
   parts: CompletedVideoPart[],
-): Promise<BookFile> {
-  const env = await req<{ file: BookFile }>(`/admin/books/${bookId}/files/multipart/complete`, {
+): Promise<CompletedBookUpload> {
+  const env = await req<CompletedBookUpload>(`/admin/books/${bookId}/files/multipart/complete`, {
     method: 'POST',
     body: JSON.stringify({
       key: session.key,
@@ -308,7 +476,7 @@ export async function completeBookUpload(
       file_size: file.size,
     }),
   })
-  return env.data.file
+  return env.data
 }
 
 export async function abortBookUpload(session: VideoMultipartSession): Promise<void> {
@@ -322,6 +490,118 @@ export async function deleteBookFile(fileId: number): Promise<void> {
   await req(`/admin/books/files/${fileId}`, { method: 'DELETE' })
 }
 
+// ---------- Goodreads / Douban import ----------
+export type BookImportSource = 'goodreads' | 'douban'
+
+export interface BookImportItem {
+  /** CSV line number (the header is line 1). */
+  line: number
+  action: 'create' | 'update' | 'skip'
+  title: string
+  author: string
+  book_id: number | null
+  changes: string[]
+  reason: string | null
+}
+
+export interface BookImportReport {
+  dry_run: boolean
+  source: BookImportSource
+  created: number
+  updated: number
+  skipped: number
+  items: BookImportItem[]
+}
+
+/** Import a Goodreads/Douban CSV export; `dryRun` only reports what would change. */
+export async function importBookHistory(
+  file: File,
+  opts: { dryRun?: boolean; source?: BookImportSource; isPublic?: boolean } = {},
+): Promise<BookImportReport> {
+  const params = new URLSearchParams()
+  if (opts.dryRun) params.set('dry_run', 'true')
+  if (opts.source) params.set('source', opts.source)
+  if (opts.isPublic) params.set('is_public', 'true')
+  const query = params.toString()
+  const env = await upload<BookImportReport>(`/admin/books/import${query ? `?${query}` : ''}`, file)
+  return env.data
+}
+
+// ---------- reading progress & highlights ----------
+export interface ReadingProgress {
+  book_file_id: number
+  book_id: number
+  /** `epubcfi(...)` for EPUB, `page:N` for PDF. */
+  locator: string
+  /** 0–100 */
+  percentage: number
+  updated_at: string
+}
+
+export type HighlightColor = 'yellow' | 'green' | 'blue' | 'pink' | 'purple'
+
+export interface BookHighlight {
+  id: number
+  book_id: number
+  book_file_id: number
+  locator: string
+  chapter: string
+  text: string
+  note: string
+  color: HighlightColor
+  created_at: string
+  updated_at: string
+}
+
+export async function listReadingProgress(bookId: number): Promise<ReadingProgress[]> {
+  const env = await req<ReadingProgress[]>(`/admin/books/${bookId}/progress`)
+  return env.data || []
+}
+
+/** Also moves the book to reading/finished and fills in its start and finish dates. */
+export async function saveReadingProgress(
+  bookId: number,
+  fileId: number,
+  progress: { locator: string; percentage: number },
+): Promise<ReadingProgress> {
+  const env = await req<ReadingProgress>(`/admin/books/${bookId}/files/${fileId}/progress`, {
+    method: 'PUT',
+    body: JSON.stringify(progress),
+  })
+  return env.data
+}
+
+export async function listHighlights(bookId: number, fileId?: number): Promise<BookHighlight[]> {
+  const query = fileId ? `?file_id=${fileId}` : ''
+  const env = await req<BookHighlight[]>(`/admin/books/${bookId}/highlights${query}`)
+  return env.data || []
+}
+
+export async function createHighlight(
+  bookId: number,
+  payload: { file_id: number; locator: string; text: string; chapter?: string; note?: string; color?: HighlightColor },
+): Promise<BookHighlight> {
+  const env = await req<BookHighlight>(`/admin/books/${bookId}/highlights`, {
+    method: 'POST',
+    body: JSON.stringify(payload),
+  })
+  return env.data
+}
+
+export async function updateHighlight(id: number, payload: { note?: string; color?: HighlightColor }): Promise<BookHighlight> {
+  const env = await req<BookHighlight>(`/admin/books/highlights/${id}`, { method: 'PUT', body: JSON.stringify(payload) })
+  return env.data
+}
+
+export async function deleteHighlight(id: number): Promise<void> {
+  await req(`/admin/books/highlights/${id}`, { method: 'DELETE' })
+}
+
+/** Markdown export of every highlight and note in a book (cookie-authenticated download). */
+export function bookNotesUrl(bookId: number): string {
+  return `${API_BASE}${PREFIX}/admin/books/${bookId}/notes.md`
+}
+
 function bookContentType(file: File): string {
   if (file.type) return file.type
   const extension = file.name.split('.').pop()?.toLowerCase()
@@ -5,6 +5,7 @@ const PREFIX = '/api'
 
 export interface Article {
   id: string
+  slug?: string
   title: string
   excerpt: string
   content: string
@@ -15,14 +16,43 @@ export interface Article {
   tags: string[]
   coverImage?: string
   pdfUrl?: string
+  /** Language actually served; falls back to the original when a translation is missing. */
+  locale?: Locale
+  availableLocales?: Locale[]
 }
+export type Locale = 'zh' | 'en'
 export interface AdjacentArticle {
   id: string
+  slug?: string
   title: string
 }
 export interface AdjacentArticles {
   newer?: AdjacentArticle
   older?: AdjacentArticle
+  /** Present when the article is part of a series; newer/older are then the next/previous part. */
+  series?: ArticleSeries
+}
+export interface ArticleSeries {
+  id: number
+  slug: string
+  title: string
+  part: number
+  total: number
+}
+export interface SeriesPart {
+  part: number
+  id: number
+  slug?: string | null
+  title: string
+  created_at: string
+}
+export interface SeriesDetail {
+  id: number
+  slug: string
+  title: string
+  description: string
+  part_count: number
+  parts: SeriesPart[]
 }
 export interface Category {
   id: string
@@ -55,11 +85,11 @@ export interface BookFile {
   id: number
   book_id: number
   format: string
-  file_url: string
-  r2_key: string
   file_name: string
   file_size: number
   mime_type: string
+  /** Read from the PDF page tree when the upload completed; null for EPUB. */
+  page_count: number | null
   created_at: string
 }
 
@@ -102,6 +132,7 @@ interface Envelope<T> {
 
 interface RawPost {
   id: number
+  slug?: string | null
   title: string
   content?: string
   excerpt?: string
@@ -112,6 +143,8 @@ interface RawPost {
   created_at: string
   updated_at?: string
   pdf_url?: string
+  locale?: Locale
+  available_locales?: Locale[]
   // tags live inside `post` and may be objects or plain strings
   tags?: Array<{ id?: number; name: string } | string>
 }
@@ -177,6 +210,7 @@ function toArticle(post: RawPost): Article {
   const content = post.content || ''
   return {
     id: String(post.id),
+    slug: post.slug ?? undefined,
     title: post.title,
     content,
     excerpt: cleanExcerpt(post.excerpt) || stripMarkdown(content, 140),
@@ -187,6 +221,8 @@ function toArticle(post: RawPost): Article {
     tags: tagNames(post.tags),
     coverImage: imageUrl(post.cover_url),
     pdfUrl: post.pdf_url,
+    locale: post.locale,
+    availableLocales: post.available_locales,
   }
 }
 
@@ -209,13 +245,21 @@ export async function listArticles(params: {
   }
 }
 
-export async function getArticle(id: string, signal?: AbortSignal): Promise<Article> {
-  const env = await req<RawPost>(`/post/get/${id}`, { signal })
+/** Canonical article URL: the slug when the post has one, the numeric id otherwise. */
+export function articlePath(article: { id: string; slug?: string }): string {
+  return `/article/${article.slug ?? article.id}`
+}
+
+/** `key` is the `/article/:id` route param — a numeric id or a (possibly renamed) slug. */
+export async function getArticle(key: string, signal?: AbortSignal, lang?: Locale): Promise<Article> {
+  const base = /^\d+$/.test(key) ? `/post/get/${key}` : `/post/by-slug/${encodeURIComponent(key)}`
+  const path = lang ? `${base}?lang=${lang}` : base
+  const env = await req<RawPost>(path, { signal })
   const article = toArticle(env.data)
   // If the post payload didn't include tags, fetch them separately.
   if (article.tags.length === 0) {
     try {
-      const t = await req<RawTag[]>(`/post/${id}/tags`, { signal })
+      const t = await req<RawTag[]>(`/post/${article.id}/tags`, { signal })
       article.tags = (t.data || []).map((x) => x.name)
     } catch (error) {
       if (error instanceof DOMException && error.name === 'AbortError') throw error
@@ -227,12 +271,18 @@ export async function getArticle(id: string, signal?: AbortSignal): Promise<Arti
 
 export async function getAdjacentArticles(id: string, signal?: AbortSignal): Promise<AdjacentArticles> {
   const env = await req<{
-    newer?: { id: number; title: string } | null
-    older?: { id: number; title: string } | null
+    newer?: { id: number; slug?: string | null; title: string } | null
+    older?: { id: number; slug?: string | null; title: string } | null
+    series?: ArticleSeries
   }>(`/post/adjacent/${id}`, { signal })
   return {
-    newer: env.data.newer ? { ...env.data.newer, id: String(env.data.newer.id) } : undefined,
-    older: env.data.older ? { ...env.data.older, id: String(env.data.older.id) } : undefined,
+    series: env.data.series,
+    newer: env.data.newer
+      ? { id: String(env.data.newer.id), slug: env.data.newer.slug ?? undefined, title: env.data.newer.title }
+      : undefined,
+    older: env.data.older
+      ? { id: String(env.data.older.id), slug: env.data.older.slug ?? undefined, title: env.data.older.title }
+      : undefined,
   }
 }
 
@@ -257,30 +307,45 @@ export async function getAbout(): Promise<About> {
 }
 
 /** Convert an online GitBook/bookdown book to EPUB. Returns the file blob. */
-export async function gitbook2epub(
-  url: string,
-  includeImages = false,
-): Promise<{ blob: Blob; filename: string }> {
-  const res = await fetch(`${API_BASE}${PREFIX}/tools/gitbook2epub`, {
-    method: 'POST',
+export type ConversionStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled'
+export interface ConversionJob {
+  job_id: string
+  status: ConversionStatus
+  /** Conversions ahead of this one; 0 once it has started. */
+  queue_position: number
+  /** 0..1 */
+  progress: number
+  progress_message?: string | null
+  error?: string | null
+  result?: { file_name: string; size: number; expires_at: string } | null
+  /** Set while the finished EPUB is still kept on the server. */
+  download_url?: string | null
+}
+
+async function conversionReq(path: string, init?: RequestInit): Promise<ConversionJob> {
+  const res = await fetch(`${API_BASE}${PREFIX}${path}`, {
     headers: { 'Content-Type': 'application/json' },
+    ...init,
+  })
+  const env = (await res.json().catch(() => null)) as Envelope<ConversionJob> | null
+  if (!res.ok || !env?.data) throw new Error(env?.message || `Request failed: ${res.status}`)
+  return env.data
+}
+
+/** Queue a conversion; identical url + options reuse a running or recently finished job. */
+export function gitbook2epub(url: string, includeImages = false): Promise<ConversionJob> {
+  return conversionReq('/tools/gitbook2epub', {
+    method: 'POST',
     body: JSON.stringify({ url, include_images: includeImages }),
   })
-  if (!res.ok) {
-    let message = `Request failed: ${res.status}`
-    try {
-      const body = (await res.json()) as { message?: string }
-      if (body?.message) message = body.message
-    } catch {
-      /* non-JSON error */
-    }
-    throw new Error(message)
-  }
-  const blob = await res.blob()
-  // Prefer the server-provided filename, fall back to a sane default.
-  const cd = res.headers.get('Content-Disposition') || ''
-  const match = /filename="?([^"]+)"?/.exec(cd)
-  return { blob, filename: match?.[1] || 'book.epub' }
+}
+
+export function getGitbook2epubJob(jobId: string, signal?: AbortSignal): Promise<ConversionJob> {
+  return conversionReq(`/tools/gitbook2epub/${encodeURIComponent(jobId)}`, { signal })
+}
+
+export function conversionDownloadUrl(job: ConversionJob): string | undefined {
+  return job.download_url ? `${API_BASE}${job.download_url}` : undefined
 }
 
 export async function getHealth(): Promise<HealthStatus> {
@@ -295,11 +360,223 @@ export async function listBooks(): Promise<Book[]> {
   return env.data || []
 }
 
-export function bookFileContentUrl(bookId: number, fileId: number): string {
-  return `${API_BASE}${PREFIX}/books/${bookId}/files/${fileId}/content`
+export interface YearlyReadingCount {
+  year: number
+  finished: number
+  /** Books finished in each month, January first. */
+  months: number[]
+}
+
+export interface BookStats {
+  total: number
+  want_to_read: number
+  reading: number
+  finished: number
+  paused: number
+  average_rating: number | null
+  /** Counting the start day, so a book finished the same day took 1 day. */
+  average_days_to_finish: number | null
+  /** Newest year first. */
+  years: YearlyReadingCount[]
+}
+
+export interface ReportBook {
+  id: number
+  title: string
+  author: string
+  cover_url: string | null
+  rating: number | null
+  started_at: string | null
+  finished_at: string
+  days_to_finish: number | null
+  page_count: number | null
+}
+
+export interface ReadingReport {
+  year: number
+  finished: number
+  months: number[]
+  average_rating: number | null
+  average_days_to_finish: number | null
+  pages_read: number
+  fastest: ReportBook | null
+  slowest: ReportBook | null
+  books: ReportBook[]
+}
+
+export async function getBookStats(): Promise<BookStats> {
+  const env = await req<BookStats>('/books/stats')
+  return env.data
+}
+
+export async function getReadingReport(year: number): Promise<ReadingReport> {
+  const env = await req<ReadingReport>(`/books/reports/${year}`)
+  return env.data
+}
+
+/** SVG badge with the number of finished public books, optionally for one year. */
+export function bookStatsBadgeUrl(year?: number): string {
+  return `${API_BASE}${PREFIX}/books/stats/badge.svg${year ? `?year=${year}` : ''}`
+}
+
+// ---------- chapter-level EPUB reader ----------
+// URLs in these payloads (spine `url`, chapter `next`/`previous`, stylesheets and the
+// `src`/`href` attributes inside chapter HTML) are root-relative API paths; pass them
+// through `imageUrl()` when the API lives on another origin.
+
+export interface EpubSpineItem {
+  index: number
+  /** Path inside the EPUB, e.g. `OEBPS/Text/ch1.xhtml`. */
+  path: string
+  /** `false` for `linear="no"` items such as footnote pages. */
+  linear: boolean
+  url: string
+}
+
+export interface EpubTocItem {
+  title: string
+  path: string
+  fragment: string | null
+  children: EpubTocItem[]
+}
+
+export interface EpubContents {
+  title: string | null
+  spine: EpubSpineItem[]
+  toc: EpubTocItem[]
+}
+
+export interface EpubChapter {
+  index: number
+  path: string
+  title: string | null
+  /** Sanitized `<body>` markup; links to other chapters point at the chapter endpoint. */
+  html: string
+  stylesheets: string[]
+  previous: string | null
+  next: string | null
+}
+
+function epubPath(bookId: number, fileId: number): string {
+  return `/books/${bookId}/files/${fileId}/epub`
+}
+
+/**
+ * The chapter endpoints accept the signature of a reading link from `getBookFileLink`.
+ * Private books get R2 links instead; admins read those with their session cookie.
+ */
+function epubQuery(fileLink: string): string {
+  const url = new URL(fileLink, window.location.origin)
+  if (!url.pathname.endsWith('/content')) return ''
+  url.searchParams.delete('download')
+  return `?${url.searchParams}`
+}
+
+/** `fileLink` is a reading link from `getBookFileLink`; chapter URLs in the result carry its signature. */
+export async function getEpubContents(bookId: number, fileId: number, fileLink: string): Promise<EpubContents> {
+  const env = await req<EpubContents>(`${epubPath(bookId, fileId)}${epubQuery(fileLink)}`, { credentials: 'include' })
+  return env.data
+}
+
+export async function getEpubChapter(bookId: number, fileId: number, path: string, fileLink: string): Promise<EpubChapter> {
+  const encoded = path.split('/').map(encodeURIComponent).join('/')
+  const env = await req<EpubChapter>(`${epubPath(bookId, fileId)}/chapters/${encoded}${epubQuery(fileLink)}`, {
+    credentials: 'include',
+  })
+  return env.data
+}
+
+/**
+ * Book files are served through short-lived signed links. Reading links last a few hours,
+ * download links only a few minutes; downloads are refused unless the book allows them.
+ */
+export async function getBookFileLink(
+  bookId: number,
+  fileId: number,
+  options: { download?: boolean; bindIp?: boolean } = {},
+): Promise<string> {
+  const env = await req<{ url: string; expires_at: string }>(`/books/${bookId}/files/${fileId}/link`, {
+    method: 'POST',
+    credentials: 'include',
+    body: JSON.stringify({ download: options.download ?? false, bind_ip: options.bindIp ?? false }),
+  })
+  return imageUrl(env.data.url) as string
+}
+
+/** EPUB download of a single published article. */
+export function articleEpubUrl(id: string): string {
+  return `${API_BASE}${PREFIX}/post/${encodeURIComponent(id)}/export.epub`
+}
+
+/** EPUB download of every published part of a series, in series order. */
+export function seriesEpubUrl(key: string): string {
+  return `${API_BASE}${PREFIX}/series/${encodeURIComponent(key)}/export.epub`
+}
+
+/** `key` is the series slug or numeric id; only published parts are returned. */
+export async function getSeries(key: string, signal?: AbortSignal): Promise<SeriesDetail> {
+  const env = await req<SeriesDetail>(`/series/${encodeURIComponent(key)}`, { signal })
+  return env.data
 }
 
 export async function listChangelog(): Promise<ChangelogEntry[]> {
   const env = await req<ChangelogEntry[]>('/changelog')
   return env.data || []
 }
+
+export interface CommentItem {
+  id: number
+  parent_id?: number | null
+  author_name: string
+  author_url?: string | null
+  avatar_url: string
+  content: string
+  created_at: string
+  replies: CommentItem[]
+}
+
+/** A post's comment thread, or the site-wide guestbook. */
+export type CommentThread = { postId: string } | 'guestbook'
+
+export interface NewComment {
+  author_name: string
+  author_email?: string
+  author_url?: string
+  content: string
+  parent_id?: number
+  /** Honeypot — stays empty for humans. */
+  website?: string
+}
+
+function commentsPath(thread: CommentThread): string {
+  return thread === 'guestbook' ? '/comments/guestbook' : `/comments/post/${thread.postId}`
+}
+
+export async function listComments(thread: CommentThread): Promise<CommentItem[]> {
+  const env = await req<CommentItem[]>(commentsPath(thread))
+  return env.data || []
+}
+
+/** Returns the new comment's status: `pending` means it is waiting for moderation. */
+export async function submitComment(thread: CommentThread, comment: NewComment): Promise<{ id: number; status: string }> {
+  const res = await fetch(`${API_BASE}${PREFIX}${commentsPath(thread)}`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(comment),
+  })
+  const env = (await res.json().catch(() => null)) as Envelope<{ id: number; status: string }> | null
+  if (!res.ok || !env?.data) throw new Error(env?.message || `Request failed: ${res.status}`)
+  return env.data
+}
+
+// ---------- analytics ----------
+/**
+ * Report a page view. Cookie-free; sent as text/plain so the beacon needs no
+ * CORS preflight. Only the landing page carries the external referrer.
+ */
+export function trackPageView(path: string, referrer?: string): void {
+  const body = JSON.stringify({ path, referrer: referrer || undefined })
+  const url = `${API_BASE}${PREFIX}/analytics/collect`
+  if (navigator.sendBeacon?.(url, body)) return
+  fetch(url, { method: 'POST', body, keepalive: true }).catch(() => {})
+}
@@ -2,20 +2,20 @@ import {
   abortBookUpload,
   beginBookUpload,
   completeBookUpload,
+  type CompletedBookUpload,
   type VideoMultipartSession,
 } from '@/services/admin'
 import {
   uploadMultipartParts,
   type MultipartUploadProgress,
 } from '@/services/video-upload'
-import type { BookFile } from '@/services/api'
 
 export async function uploadBookFileDirect(
   bookId: number,
   file: File,
   onProgress: (progress: MultipartUploadProgress) => void,
   signal: AbortSignal,
-): Promise<BookFile> {
+): Promise<CompletedBookUpload> {
   let session: VideoMultipartSession | null = null
   try {
     session = await beginBookUpload(bookId, file)
@@ -34,9 +34,9 @@ export async function uploadVideoDirect(
   try {
     session = await beginVideoUpload(file, contentType)
     const parts = await uploadMultipartParts(file, session, onProgress, signal)
-    const url = await completeVideoUpload(session, parts)
+    const video = await completeVideoUpload(file, contentType, session, parts)
     onProgress({ uploadedBytes: file.size, totalBytes: file.size, percent: 100 })
-    return { url, title: file.name.replace(/\.[^.]+$/, '') }
+    return { url: video.public_url, title: video.title }
   } catch (error) {
     if (session) await abortVideoUpload(session).catch(() => undefined)
     throw error
@@ -3,7 +3,7 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { apiService } from '../../../services/api';
 import { API_BASE_URL } from '../../../services/api/base';
-import type { StaticResource, ResourceStats } from '../../../services/api';
+import type { StaticResource, ResourceStats, OptimizeResult } from '../../../services/api';
 import AdminLayout from '../../../components/adminLayout/AdminLayout';
 import { ProTable } from '@ant-design/pro-components';
 import type { ActionType, ProColumns } from '@ant-design/pro-components';
@@ -154,8 +154,20 @@ const ResourceManagement: React.FC = () => {
         try {
           setOptimizing(true);
           const response = await apiService.optimizeAllImages();
-          if (response.success && response.data) {
-            const result = response.data;
+          if (!response.success || !response.data) {
+            throw new Error(response.error || 'Failed to optimize images');
+          }
+          let job = response.data;
+          while (job.status === 'queued' || job.status === 'running') {
+            await new Promise((resolve) => setTimeout(resolve, 2000));
+            const poll = await apiService.getJob<OptimizeResult>(job.token);
+            if (!poll.success || !poll.data) {
+              throw new Error(poll.error || 'Failed to fetch optimization status');
+            }
+            job = poll.data;
+          }
+          if (job.status === 'succeeded' && job.result) {
+            const result = job.result;
             const savedSize = result.original_size - result.optimized_size;
             const savedPercent = result.original_size > 0
               ? ((savedSize / result.original_size) * 100).toFixed(1)
@@ -175,7 +187,7 @@ const ResourceManagement: React.FC = () => {
             actionRef.current?.reload();
             loadStats();
           } else {
-            throw new Error(response.error || 'Failed to optimize images');
+            throw new Error(job.error || `Image optimization ${job.status}`);
           }
         } catch (err) {
           message.error(err instanceof Error ? err.message : 'Failed to optimize images');
@@ -220,6 +220,10 @@ class ApiService {
     optimizeAllImages() {
         return this.resources.optimizeAllImages();
     }
+
+    getJob<T>(token: string) {
+        return this.resources.getJob<T>(token);
+    }
 }
 
 // Export singleton instance
@@ -246,5 +250,7 @@ export type {
     UsageRef,
     ResourceStats,
     TypeStats,
-    OptimizeResult
+    OptimizeResult,
+    BackgroundJob,
+    JobStatus
 } from './resources';
@@ -46,6 +46,23 @@ export interface OptimizeResult {
   optimized_size: number;
 }
 
+export type JobStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+
+// Background job as returned by /jobs/:token
+export interface BackgroundJob<T> {
+  token: string;
+  kind: string;
+  status: JobStatus;
+  progress: number;
+  progress_message: string | null;
+  result: T | null;
+  error: string | null;
+  attempts: number;
+  created_at: string;
+  started_at: string | null;
+  finished_at: string | null;
+}
+
 export class ResourceApiService extends BaseApiService {
   // List all resources
   async listResources(fileType?: string, used?: boolean): Promise<ApiResponse<StaticResource[]>> {
@@ -68,10 +85,15 @@ export class ResourceApiService extends BaseApiService {
     });
   }
 
-  // Optimize all images (batch convert to WebP)
-  async optimizeAllImages(): Promise<ApiResponse<OptimizeResult>> {
-    return this.request<OptimizeResult>('/admin/resources/optimize', {
+  // Optimize all images (batch convert to WebP); runs as a background job
+  async optimizeAllImages(): Promise<ApiResponse<BackgroundJob<OptimizeResult>>> {
+    return this.request<BackgroundJob<OptimizeResult>>('/admin/resources/optimize', {
       method: 'POST',
     });
   }
+
+  // Poll a background job by its token
+  async getJob<T>(token: string): Promise<ApiResponse<BackgroundJob<T>>> {
+    return this.request<BackgroundJob<T>>(`/jobs/${token}`);
+  }
 }